@@ -0,0 +1,76 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use libscd::wire::common::crc8_verify_chunked_3;
+use libscd::wire::crc::crc8;
+
+const CRC8_POLY: u8 = 0x31;
+const CRC8_INITIAL: u8 = 0xFF;
+
+// A table driven CRC-8, as a candidate replacement of the bitwise one. It
+// trades 256 bytes of flash for the inner loop over the bits
+static CRC8_TABLE: [u8; 256] = crc8_table();
+
+const fn crc8_table() -> [u8; 256] {
+    let mut table = [0u8; 256];
+
+    let mut idx = 0;
+    while idx < table.len() {
+        let mut crc = idx as u8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ CRC8_POLY
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+
+        table[idx] = crc;
+        idx += 1;
+    }
+
+    table
+}
+
+fn crc8_lut(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(CRC8_INITIAL, |crc, &b| CRC8_TABLE[(crc ^ b) as usize])
+}
+
+// The longest response of the supported sensors: the SCD30 measurement
+fn measurement_frame() -> [u8; 18] {
+    let mut frame = [0u8; 18];
+    for (idx, chunk) in frame.chunks_exact_mut(3).enumerate() {
+        chunk[0] = 0x40 + idx as u8;
+        chunk[1] = 0x13 * idx as u8;
+        chunk[2] = crc8(&chunk[..2]);
+    }
+    frame
+}
+
+fn bench_crc8(c: &mut Criterion) {
+    let word = [0xBE, 0xEF];
+    for byte in 0..=u8::MAX {
+        assert_eq!(crc8(&[byte, !byte]), crc8_lut(&[byte, !byte]));
+    }
+
+    let mut group = c.benchmark_group("crc8");
+    group.throughput(Throughput::Bytes(word.len() as u64));
+    group.bench_function("bitwise", |b| b.iter(|| crc8(black_box(&word))));
+    group.bench_function("lut", |b| b.iter(|| crc8_lut(black_box(&word))));
+    group.finish();
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let frame = measurement_frame();
+
+    let mut group = c.benchmark_group("crc8_verify_chunked_3");
+    group.throughput(Throughput::Bytes(frame.len() as u64));
+    group.bench_function("scd30_measurement", |b| {
+        b.iter(|| crc8_verify_chunked_3(black_box(&frame)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_crc8, bench_verify);
+criterion_main!(benches);
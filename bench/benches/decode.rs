@@ -0,0 +1,46 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use libscd::wire::crc::crc8;
+use libscd::wire::{scd30, scd4x};
+
+fn frame<const N: usize>(words: &[u16]) -> [u8; N] {
+    let mut frame = [0u8; N];
+    for (chunk, word) in frame.chunks_exact_mut(3).zip(words) {
+        chunk[..2].copy_from_slice(&word.to_be_bytes());
+        chunk[2] = crc8(&chunk[..2]);
+    }
+    frame
+}
+
+fn bench_scd30(c: &mut Criterion) {
+    // 800 ppm, 22.5 C, 50 %RH as big endian floats
+    let co2 = 800.0f32.to_bits();
+    let temperature = 22.5f32.to_bits();
+    let humidity = 50.0f32.to_bits();
+    let buf: [u8; 18] = frame(&[
+        (co2 >> 16) as u16,
+        co2 as u16,
+        (temperature >> 16) as u16,
+        temperature as u16,
+        (humidity >> 16) as u16,
+        humidity as u16,
+    ]);
+
+    c.bench_function("scd30::decode_measurement_data", |b| {
+        b.iter(|| scd30::decode_measurement_data(black_box(buf)))
+    });
+}
+
+fn bench_scd4x(c: &mut Criterion) {
+    let measurement: [u8; 9] = frame(&[0x01F4, 0x6667, 0x5EB9]);
+    let serial: [u8; 9] = frame(&[0xF896, 0x9F90, 0x7BB1]);
+
+    c.bench_function("scd4x::decode_measurement", |b| {
+        b.iter(|| scd4x::decode_measurement(black_box(measurement)))
+    });
+    c.bench_function("scd4x::decode_serial_number", |b| {
+        b.iter(|| scd4x::decode_serial_number(black_box(serial)))
+    });
+}
+
+criterion_group!(benches, bench_scd30, bench_scd4x);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use libscd::encoding::bthome::{write_bthome, BTHOME_MAX_LEN};
+use libscd::encoding::csv::write_csv_row;
+use libscd::encoding::influxdb::write_line_protocol;
+use libscd::encoding::senml::write_senml_json;
+use libscd::measurement::{Measurement, TimestampedMeasurement};
+
+fn row() -> TimestampedMeasurement {
+    TimestampedMeasurement {
+        timestamp: 1_700_000_000,
+        measurement: Measurement {
+            temperature: 22.46,
+            humidity: 45.13,
+            co2: 800,
+        },
+    }
+}
+
+fn bench_packed(c: &mut Criterion) {
+    let row = row();
+    let mut buf = [0u8; BTHOME_MAX_LEN];
+
+    c.bench_function("bthome", |b| {
+        b.iter(|| write_bthome(black_box(&row.measurement), 1, &mut buf))
+    });
+}
+
+fn bench_text(c: &mut Criterion) {
+    let row = row();
+    let mut out = String::with_capacity(256);
+    let mut buf = [0u8; 128];
+
+    c.bench_function("json", |b| {
+        b.iter(|| black_box(&row.measurement).to_json(&mut buf))
+    });
+
+    c.bench_function("csv", |b| {
+        b.iter(|| {
+            out.clear();
+            write_csv_row(black_box(&row), &mut out)
+        })
+    });
+    c.bench_function("influxdb", |b| {
+        b.iter(|| {
+            out.clear();
+            write_line_protocol(&mut out, "co2", &[("room", "office")], black_box(&row))
+        })
+    });
+    c.bench_function("senml", |b| {
+        b.iter(|| {
+            out.clear();
+            write_senml_json(&mut out, "urn:dev:scd41:", black_box(&row))
+        })
+    });
+}
+
+criterion_group!(benches, bench_packed, bench_text);
+criterion_main!(benches);
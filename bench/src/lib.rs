@@ -0,0 +1,2 @@
+//! Host-side benchmarks of the wire format and the encoders. Run with
+//! `cargo bench` from this directory.
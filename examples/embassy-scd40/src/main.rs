@@ -38,9 +38,11 @@ async fn main(_spawner: Spawner) {
     let mut scd = Scd40::new(i2c, Delay);
 
     // When re-programming, the controller will be restarted,
-    // but not the sensor. We try to stop it in order to
-    // prevent the rest of the commands failing.
-    _ = scd.stop_periodic_measurement();
+    // but not the sensor. We bring it back to a known idle state
+    // in order to prevent the rest of the commands failing.
+    if let Err(e) = scd.reset_to_known_state() {
+        defmt::panic!("Failed to reset the sensor: {:?}", e);
+    }
 
     info!("Sensor serial number: {:?}", scd.serial_number());
     if let Err(e) = scd.start_periodic_measurement() {
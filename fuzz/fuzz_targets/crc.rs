@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libscd::wire::common::{crc8_verify_chunked_3, opcode_with_data_into_payload};
+use libscd::wire::crc::crc8;
+
+fuzz_target!(|data: &[u8]| {
+    // Arbitrary bus data must never panic, regardless of its length
+    let _ = crc8_verify_chunked_3(data);
+
+    // Data words with a freshly computed CRC must always pass the verification
+    let mut framed = Vec::with_capacity(data.len() / 2 * 3);
+    for word in data.chunks_exact(2) {
+        framed.extend_from_slice(word);
+        framed.push(crc8(word));
+    }
+    assert_eq!(Ok(()), crc8_verify_chunked_3(&framed));
+
+    // A corrupted CRC must be reported for the first corrupted word
+    if !framed.is_empty() {
+        framed[2] ^= 0xFF;
+        let err = crc8_verify_chunked_3(&framed).unwrap_err();
+        assert_eq!(0, err.word);
+        assert_eq!(framed[2], err.received);
+    }
+
+    // The payloads written to the sensor must always carry a valid CRC
+    if let [op_hi, op_lo, d_hi, d_lo, ..] = *data {
+        let opcode = u16::from_be_bytes([op_hi, op_lo]);
+        let value = u16::from_be_bytes([d_hi, d_lo]);
+        let payload = opcode_with_data_into_payload(opcode, value);
+        assert_eq!(Ok(()), crc8_verify_chunked_3(&payload[2..]));
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libscd::wire::scd30::decode_measurement_data;
+
+fuzz_target!(|buf: [u8; 18]| {
+    let m = decode_measurement_data(buf);
+
+    // The CRC bytes must not influence the decoded values
+    let mut corrupted = buf;
+    for crc in corrupted.iter_mut().skip(2).step_by(3) {
+        *crc = !*crc;
+    }
+    let c = decode_measurement_data(corrupted);
+
+    assert_eq!(m.co2, c.co2);
+    assert_eq!(m.temperature.to_bits(), c.temperature.to_bits());
+    assert_eq!(m.humidity.to_bits(), c.humidity.to_bits());
+});
@@ -0,0 +1,57 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use libscd::error::Error;
+use libscd::wire::scd4x::{
+    decode_frc_status, decode_measurement, decode_sensor_variant, decode_serial_number,
+    decode_temperature_offset, encode_temperature_offset,
+};
+
+// Section 3.6.1 of the datasheet: T = -45 + 175 * word / (2^16 - 1)
+const TEMPERATURE_LSB: f32 = 175.0 / 65535.0;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    measurement: [u8; 9],
+    serial_number: [u8; 9],
+    word: [u8; 3],
+    offset: f32,
+}
+
+fuzz_target!(|input: Input| {
+    let m = decode_measurement(input.measurement);
+    assert!((-45.0..=130.0).contains(&m.temperature));
+    assert!((0.0..=100.0).contains(&m.humidity));
+
+    let sn = decode_serial_number(input.serial_number);
+    assert!(sn < 1 << 48);
+
+    let _ = decode_sensor_variant::<()>(input.word);
+
+    match decode_frc_status(input.word) {
+        None => assert_eq!([0xFF, 0xFF], [input.word[0], input.word[1]]),
+        Some(correction) => {
+            let raw = u16::from_be_bytes([input.word[0], input.word[1]]);
+            assert_eq!(raw as i32 - 0x8000, correction as i32);
+        }
+    }
+
+    let decoded = decode_temperature_offset(input.word);
+    assert!((0.0..=175.0).contains(&decoded));
+
+    // Round-trip: encoding truncates to the nearest lower LSB
+    match encode_temperature_offset::<()>(input.offset) {
+        Err(Error::InvalidInput) => {
+            assert!(!input.offset.is_finite() || input.offset.is_sign_negative())
+        }
+        Err(e) => panic!("unexpected error: {e:?}"),
+        Ok(raw) if input.offset <= 175.0 => {
+            let [hi, lo] = raw.to_be_bytes();
+            let decoded = decode_temperature_offset([hi, lo, 0]);
+            assert!(decoded <= input.offset + TEMPERATURE_LSB);
+            assert!(input.offset - decoded <= TEMPERATURE_LSB);
+        }
+        Ok(_) => {}
+    }
+});
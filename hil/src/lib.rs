@@ -0,0 +1,50 @@
+#![no_std]
+
+//! Board support shared by the hardware-in-the-loop tests. The panic
+//! handler is provided by `embedded-test`
+
+use defmt_rtt as _;
+use embassy_stm32::dma::NoDma;
+use embassy_stm32::i2c::{self, I2c};
+use embassy_stm32::peripherals::{self, I2C2};
+use embassy_stm32::time::Hertz;
+use embassy_stm32::{bind_interrupts, Peripherals};
+
+pub use embassy_time::Delay;
+
+bind_interrupts!(struct Irqs {
+    I2C2_EV => i2c::EventInterruptHandler<peripherals::I2C2>;
+    I2C2_ER => i2c::ErrorInterruptHandler<peripherals::I2C2>;
+});
+
+/// The I2C bus the sensor under test is attached to
+pub type Bus = I2c<'static, I2C2, NoDma, NoDma>;
+
+/// Initialize the board and return the I2C bus of the sensor
+pub fn init() -> Bus {
+    let p: Peripherals = embassy_stm32::init(Default::default());
+
+    I2c::new(
+        p.I2C2,
+        p.PB10,
+        p.PB3,
+        Irqs,
+        NoDma,
+        NoDma,
+        Hertz(100_000),
+        Default::default(),
+    )
+}
+
+/// Poll `ready` every 100ms until it returns `true`. Panics if it does
+/// not happen within `timeout_ms`
+pub fn wait_until(timeout_ms: u32, mut ready: impl FnMut() -> bool) {
+    use embedded_hal::delay::DelayNs;
+
+    let mut elapsed = 0;
+    while !ready() {
+        assert!(elapsed < timeout_ms, "Timed out after {}ms", timeout_ms);
+        Delay.delay_ms(100);
+        elapsed += 100;
+    }
+}
@@ -0,0 +1,91 @@
+#![no_std]
+#![no_main]
+
+#[cfg(test)]
+#[embedded_test::tests]
+mod tests {
+    use libscd::error::Error;
+    use libscd::synchronous::scd30::Scd30;
+    use libscd::synchronous::transport::I2cTransport;
+    use libscd_hil::{wait_until, Bus, Delay};
+
+    const MEASUREMENT_INTERVAL_S: u16 = 2;
+    const DATA_READY_TIMEOUT_MS: u32 = 5_000;
+
+    #[init]
+    fn init() -> Scd30<I2cTransport<Bus, Delay>> {
+        let mut sensor = Scd30::new(libscd_hil::init(), Delay);
+
+        // Only the MCU is reset between the test cases, so the sensor might
+        // still be measuring. Unlike the SCD4x, the SCD30 accepts all commands
+        // while measuring, so there is no need to stop the measurement.
+        sensor
+            .set_measurement_interval(MEASUREMENT_INTERVAL_S)
+            .unwrap();
+        sensor
+    }
+
+    #[test]
+    fn firmware_version(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        let (major, minor) = sensor.read_firmware_version().unwrap();
+        defmt::info!("Firmware version: {}.{}", major, minor);
+        assert_ne!((0, 0), (major, minor));
+    }
+
+    #[test]
+    fn measurement_interval_round_trip(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        sensor.set_measurement_interval(5).unwrap();
+        assert_eq!(5, sensor.get_measurement_interval().unwrap());
+    }
+
+    #[test]
+    fn temperature_offset_round_trip(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        let offset = sensor.get_temperature_offset().unwrap();
+        sensor.set_temperature_offset(offset).unwrap();
+        assert_eq!(offset, sensor.get_temperature_offset().unwrap());
+    }
+
+    #[test]
+    fn altitude_compensation_round_trip(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        sensor.set_altitude_compensation(650).unwrap();
+        assert_eq!(650, sensor.get_altitude_compensation().unwrap());
+    }
+
+    #[test]
+    fn automatic_self_calibration_round_trip(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        sensor.enable_automatic_self_calibration(false).unwrap();
+        assert!(!sensor.get_automatic_self_calibration().unwrap());
+    }
+
+    #[test]
+    fn invalid_input_is_rejected(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        assert_eq!(Err(Error::InvalidInput), sensor.set_measurement_interval(1));
+        assert_eq!(
+            Err(Error::InvalidInput),
+            sensor.start_continuous_measurement(1)
+        );
+    }
+
+    #[test]
+    #[timeout(20)]
+    fn continuous_measurement(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        sensor.start_continuous_measurement(1013).unwrap();
+
+        wait_until(DATA_READY_TIMEOUT_MS, || sensor.data_ready().unwrap());
+        let m = sensor.read_measurement().unwrap();
+        defmt::info!("CO2: {}, T: {}, RH: {}", m.co2, m.temperature, m.humidity);
+
+        assert!((400..=5_000).contains(&m.co2));
+        assert!((0.0..=50.0).contains(&m.temperature));
+        assert!((0.0..=100.0).contains(&m.humidity));
+
+        sensor.stop_continuous_measurement().unwrap();
+    }
+
+    #[test]
+    #[timeout(20)]
+    fn soft_reset(mut sensor: Scd30<I2cTransport<Bus, Delay>>) {
+        sensor.soft_reset().unwrap();
+        sensor.read_firmware_version().unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+#![no_std]
+#![no_main]
+
+#[cfg(test)]
+#[embedded_test::tests]
+mod tests {
+    use libscd::error::Error;
+    use libscd::synchronous::transport::I2cTransport;
+    use libscd::variant::SensorVariant;
+    use libscd_hil::{wait_until, Bus, Delay};
+
+    #[cfg(not(feature = "scd41"))]
+    use libscd::synchronous::scd4x::Scd40 as Sensor;
+    #[cfg(feature = "scd41")]
+    use libscd::synchronous::scd4x::Scd41 as Sensor;
+
+    #[cfg(not(feature = "scd41"))]
+    const VARIANT: SensorVariant = SensorVariant::Scd40;
+    #[cfg(feature = "scd41")]
+    const VARIANT: SensorVariant = SensorVariant::Scd41;
+
+    // Section 3.6.1: the first measurement is available after 5 seconds
+    const DATA_READY_TIMEOUT_MS: u32 = 6_000;
+
+    #[init]
+    fn init() -> Sensor<I2cTransport<Bus, Delay>> {
+        let mut sensor = Sensor::new(libscd_hil::init(), Delay);
+
+        // Only the MCU is reset between the test cases, so the sensor might
+        // still be measuring or have a modified configuration in RAM
+        _ = sensor.stop_periodic_measurement();
+        sensor.reinit().unwrap();
+        sensor
+    }
+
+    #[test]
+    fn identification(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        let serial = sensor.serial_number().unwrap();
+        defmt::info!("Serial number: {=u64:X}", serial);
+        assert_ne!(0, serial);
+        assert!(serial < 1 << 48);
+
+        assert_eq!(VARIANT, sensor.get_sensor_variant().unwrap());
+    }
+
+    #[test]
+    fn temperature_offset_round_trip(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.set_temperature_offset(5.5).unwrap();
+        let offset = sensor.get_temperature_offset().unwrap();
+        assert!((5.5 - offset).abs() < 0.01);
+    }
+
+    #[test]
+    fn sensor_altitude_round_trip(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.set_sensor_altitude(650).unwrap();
+        assert_eq!(650, sensor.get_sensor_altitude().unwrap());
+    }
+
+    #[test]
+    fn automatic_self_calibration_round_trip(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.enable_automatic_self_calibration(false).unwrap();
+        assert!(!sensor.get_automatic_self_calibration().unwrap());
+
+        sensor.set_automatic_self_calibration_target(420).unwrap();
+        assert_eq!(420, sensor.get_automatic_self_calibration_target().unwrap());
+    }
+
+    #[test]
+    fn reinit_discards_unsaved_settings(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        let altitude = sensor.get_sensor_altitude().unwrap();
+        sensor.set_sensor_altitude(altitude + 100).unwrap();
+        sensor.reinit().unwrap();
+        assert_eq!(altitude, sensor.get_sensor_altitude().unwrap());
+    }
+
+    #[test]
+    #[timeout(20)]
+    fn periodic_measurement(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.start_periodic_measurement().unwrap();
+        sensor.set_ambient_pressure(1013).unwrap();
+
+        wait_until(DATA_READY_TIMEOUT_MS, || sensor.data_ready().unwrap());
+        let m = sensor.read_measurement().unwrap();
+        defmt::info!("CO2: {}, T: {}, RH: {}", m.co2, m.temperature, m.humidity);
+
+        assert!((400..=5_000).contains(&m.co2));
+        assert!((0.0..=50.0).contains(&m.temperature));
+        assert!((0.0..=100.0).contains(&m.humidity));
+
+        sensor.stop_periodic_measurement().unwrap();
+    }
+
+    #[test]
+    #[timeout(20)]
+    fn commands_are_rejected_while_measuring(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.start_periodic_measurement().unwrap();
+        assert_eq!(Err(Error::NotAllowed), sensor.serial_number());
+        assert_eq!(Err(Error::NotAllowed), sensor.reinit());
+
+        // Stopping the measurement must bring the sensor back into a
+        // state where it accepts all commands
+        sensor.stop_periodic_measurement().unwrap();
+        sensor.serial_number().unwrap();
+    }
+
+    #[test]
+    #[timeout(20)]
+    fn self_test(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.perform_self_test_strict().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "scd41")]
+    #[timeout(20)]
+    fn single_shot_measurement(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.measure_single_shot().unwrap();
+        assert!(sensor.data_ready().unwrap());
+        let m = sensor.read_measurement().unwrap();
+        assert!((400..=5_000).contains(&m.co2));
+    }
+
+    #[test]
+    #[cfg(feature = "scd41")]
+    fn power_down_and_wake_up(mut sensor: Sensor<I2cTransport<Bus, Delay>>) {
+        sensor.power_down().unwrap();
+
+        // The sensor does not acknowledge the wake-up command
+        _ = sensor.wake_up();
+        sensor.serial_number().unwrap();
+    }
+}
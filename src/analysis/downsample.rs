@@ -0,0 +1,167 @@
+use crate::measurement::Measurement;
+
+/// The measurements within one period aggregated by a [Decimator]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AggregatedMeasurement {
+    /// The start of the period in milliseconds
+    pub timestamp_ms: u64,
+
+    /// The number of aggregated measurements
+    pub samples: u32,
+
+    /// The mean CO2 concentration in PPM, rounded to the nearest integer
+    pub co2_mean: u16,
+
+    /// The lowest CO2 concentration in PPM
+    pub co2_min: u16,
+
+    /// The highest CO2 concentration in PPM
+    pub co2_max: u16,
+
+    /// The last measured temperature in Celsius
+    pub temperature: f32,
+
+    /// The last measured humidity (RH%)
+    pub humidity: f32,
+}
+
+/// Aggregates the measurements taken at the native interval of the sensor,
+/// e.g. every 5 seconds, into records over longer periods, e.g. 1 or 5
+/// minutes, suitable for storage and uplink.
+///
+/// The periods are aligned to multiples of their length since the epoch of
+/// the timestamps, so the records of several devices with synchronized
+/// clocks line up. Periods without measurements produce no records.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct Decimator {
+    period_ms: u64,
+    start: u64,
+    samples: u32,
+    co2_sum: u64,
+    co2_min: u16,
+    co2_max: u16,
+    temperature: f32,
+    humidity: f32,
+}
+
+impl Decimator {
+    /// One minute in milliseconds
+    pub const ONE_MINUTE: u64 = 60 * 1_000;
+
+    /// Five minutes in milliseconds
+    pub const FIVE_MINUTES: u64 = 5 * 60 * 1_000;
+
+    /// Create a new decimator aggregating the measurements over periods of
+    /// `period_ms` milliseconds
+    pub fn new(period_ms: u64) -> Self {
+        Self {
+            period_ms: period_ms.max(1),
+            start: 0,
+            samples: 0,
+            co2_sum: 0,
+            co2_min: u16::MAX,
+            co2_max: 0,
+            temperature: 0.0,
+            humidity: 0.0,
+        }
+    }
+
+    /// Add a measurement taken at `timestamp_ms` milliseconds. Returns the
+    /// record of the previous period when the measurement belongs to a
+    /// later one. The timestamps must not decrease
+    pub fn push(
+        &mut self,
+        timestamp_ms: u64,
+        measurement: &Measurement,
+    ) -> Option<AggregatedMeasurement> {
+        let start = timestamp_ms - timestamp_ms % self.period_ms;
+
+        let mut record = None;
+        if start != self.start {
+            record = self.flush();
+            self.start = start;
+        }
+
+        self.samples += 1;
+        self.co2_sum += u64::from(measurement.co2);
+        self.co2_min = self.co2_min.min(measurement.co2);
+        self.co2_max = self.co2_max.max(measurement.co2);
+        self.temperature = measurement.temperature;
+        self.humidity = measurement.humidity;
+
+        record
+    }
+
+    /// Emit the record of the current period, even if the period has not
+    /// ended yet, e.g. before the device goes to sleep. `None` if there are
+    /// no measurements
+    pub fn flush(&mut self) -> Option<AggregatedMeasurement> {
+        if self.samples == 0 {
+            return None;
+        }
+
+        let samples = u64::from(self.samples);
+        let record = AggregatedMeasurement {
+            timestamp_ms: self.start,
+            samples: self.samples,
+            co2_mean: ((self.co2_sum + samples / 2) / samples) as u16,
+            co2_min: self.co2_min,
+            co2_max: self.co2_max,
+            temperature: self.temperature,
+            humidity: self.humidity,
+        };
+
+        self.samples = 0;
+        self.co2_sum = 0;
+        self.co2_min = u16::MAX;
+        self.co2_max = 0;
+
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(co2: u16, temperature: f32) -> Measurement {
+        Measurement {
+            temperature,
+            humidity: 40.0,
+            co2,
+        }
+    }
+
+    #[test]
+    fn test_decimation() {
+        let mut decimator = Decimator::new(Decimator::ONE_MINUTE);
+        assert_eq!(None, decimator.flush());
+
+        // 5 seconds interval, starting in the middle of a minute
+        for idx in 0..6 {
+            let m = measurement(600 + idx as u16, 20.0 + idx as f32);
+            assert_eq!(None, decimator.push(90_000 + idx * 5_000, &m));
+        }
+
+        let record = decimator.push(120_000, &measurement(900, 30.0)).unwrap();
+        assert_eq!(60_000, record.timestamp_ms);
+        assert_eq!(6, record.samples);
+        assert_eq!(603, record.co2_mean);
+        assert_eq!(600, record.co2_min);
+        assert_eq!(605, record.co2_max);
+        assert_eq!(25.0, record.temperature);
+
+        // A gap of several minutes
+        let record = decimator.push(400_000, &measurement(800, 30.0)).unwrap();
+        assert_eq!(120_000, record.timestamp_ms);
+        assert_eq!(1, record.samples);
+        assert_eq!(900, record.co2_mean);
+
+        let record = decimator.flush().unwrap();
+        assert_eq!(360_000, record.timestamp_ms);
+        assert_eq!(800, record.co2_max);
+        assert_eq!(None, decimator.flush());
+    }
+}
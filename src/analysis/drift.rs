@@ -0,0 +1,203 @@
+/// Configuration of a [DriftMonitor]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DriftConfig {
+    /// The length of a window in milliseconds. The lowest concentration
+    /// within a window is considered its baseline
+    pub window_ms: u64,
+
+    /// The expected baseline in PPM, i.e. the target of the automatic
+    /// self-calibration (ASC) configured on the sensor
+    pub target_ppm: u16,
+
+    /// The maximal tolerated deviation in PPM of the baseline from the target
+    pub threshold_ppm: u16,
+
+    /// The number of consecutive windows whose baseline deviates from the
+    /// target by more than the threshold before a calibration is recommended
+    pub windows: u8,
+}
+
+impl Default for DriftConfig {
+    /// Weekly windows against the default ASC target of 400 PPM. A
+    /// calibration is recommended after two weeks with a deviation of more
+    /// than 50 PPM
+    fn default() -> Self {
+        Self {
+            window_ms: 7 * 24 * 60 * 60 * 1_000,
+            target_ppm: 400,
+            threshold_ppm: 50,
+            windows: 2,
+        }
+    }
+}
+
+/// The outcome of a completed window of a [DriftMonitor]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DriftReport {
+    /// The lowest concentration in PPM measured within the window
+    pub baseline_ppm: u16,
+
+    /// The deviation in PPM of the baseline from the target
+    pub deviation_ppm: i32,
+
+    /// The number of consecutive windows, including this one, whose baseline
+    /// deviates from the target by more than the threshold
+    pub drifted_windows: u8,
+
+    /// Whether a forced recalibration of the sensor is recommended
+    pub calibration_recommended: bool,
+}
+
+/// Monitors the long-term drift of a sensor by comparing the baseline of
+/// the CO2 concentration over long windows, e.g. weeks, against the target
+/// of the automatic self-calibration (ASC).
+///
+/// The ASC assumes that the sensor is exposed to fresh air regularly, e.g.
+/// when the building is empty. A baseline that stays away from the target
+/// means that the ASC does not keep up with the drift of the sensor, or
+/// that the assumption does not hold for its location, so a forced
+/// recalibration is recommended.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct DriftMonitor {
+    config: DriftConfig,
+    window_start: Option<u64>,
+    baseline: Option<u16>,
+    drifted_windows: u8,
+}
+
+impl DriftMonitor {
+    /// Create a new monitor with the given configuration
+    pub fn new(config: DriftConfig) -> Self {
+        Self {
+            config,
+            window_start: None,
+            baseline: None,
+            drifted_windows: 0,
+        }
+    }
+
+    /// Add a CO2 measurement taken at `timestamp_ms` milliseconds. Returns
+    /// the report of the previous window when the measurement starts a new
+    /// one. Windows without measurements are skipped, i.e. they neither
+    /// count as drifted nor break a series of drifted windows
+    pub fn push(&mut self, timestamp_ms: u64, co2: u16) -> Option<DriftReport> {
+        let start = *self.window_start.get_or_insert(timestamp_ms);
+
+        let mut report = None;
+        if timestamp_ms.saturating_sub(start) >= self.config.window_ms {
+            report = self.close_window();
+
+            let elapsed = timestamp_ms - start;
+            let window_ms = self.config.window_ms.max(1);
+            self.window_start = Some(start + elapsed / window_ms * window_ms);
+        }
+
+        self.baseline = Some(self.baseline.map_or(co2, |baseline| baseline.min(co2)));
+        report
+    }
+
+    /// The lowest concentration in PPM measured in the current window
+    pub fn current_baseline(&self) -> Option<u16> {
+        self.baseline
+    }
+
+    /// Forget the measurements and the drifted windows, e.g. after the
+    /// sensor was recalibrated
+    pub fn reset(&mut self) {
+        self.window_start = None;
+        self.baseline = None;
+        self.drifted_windows = 0;
+    }
+
+    fn close_window(&mut self) -> Option<DriftReport> {
+        let baseline_ppm = self.baseline.take()?;
+        let deviation_ppm = i32::from(baseline_ppm) - i32::from(self.config.target_ppm);
+
+        if deviation_ppm.unsigned_abs() > u32::from(self.config.threshold_ppm) {
+            self.drifted_windows = self.drifted_windows.saturating_add(1);
+        } else {
+            self.drifted_windows = 0;
+        }
+
+        Some(DriftReport {
+            baseline_ppm,
+            deviation_ppm,
+            drifted_windows: self.drifted_windows,
+            calibration_recommended: self.drifted_windows >= self.config.windows.max(1),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_MS: u64 = 24 * 60 * 60 * 1_000;
+
+    // Feeds a week of hourly measurements with the given baseline
+    fn week(monitor: &mut DriftMonitor, week: u64, baseline: u16) -> Option<DriftReport> {
+        let mut report = None;
+        for hour in 0..7 * 24 {
+            let co2 = baseline + if hour % 24 < 8 { 0 } else { 400 };
+            let timestamp = week * 7 * DAY_MS + hour * 60 * 60 * 1_000;
+            report = report.or(monitor.push(timestamp, co2));
+        }
+        report
+    }
+
+    #[test]
+    fn test_calibration_recommended() {
+        let mut monitor = DriftMonitor::new(DriftConfig::default());
+        assert_eq!(None, week(&mut monitor, 0, 420));
+        assert_eq!(Some(420), monitor.current_baseline());
+
+        let report = week(&mut monitor, 1, 480).unwrap();
+        assert_eq!(420, report.baseline_ppm);
+        assert_eq!(20, report.deviation_ppm);
+        assert!(!report.calibration_recommended);
+
+        let report = week(&mut monitor, 2, 470).unwrap();
+        assert_eq!(80, report.deviation_ppm);
+        assert_eq!(1, report.drifted_windows);
+        assert!(!report.calibration_recommended);
+
+        let report = week(&mut monitor, 3, 400).unwrap();
+        assert_eq!(70, report.deviation_ppm);
+        assert_eq!(2, report.drifted_windows);
+        assert!(report.calibration_recommended);
+
+        // The drift is gone
+        let report = week(&mut monitor, 4, 400).unwrap();
+        assert_eq!(0, report.deviation_ppm);
+        assert_eq!(0, report.drifted_windows);
+        assert!(!report.calibration_recommended);
+    }
+
+    #[test]
+    fn test_skips_empty_windows() {
+        let config = DriftConfig {
+            window_ms: DAY_MS,
+            target_ppm: 400,
+            threshold_ppm: 50,
+            windows: 2,
+        };
+
+        let mut monitor = DriftMonitor::new(config);
+        assert_eq!(None, monitor.push(0, 300));
+
+        let report = monitor.push(5 * DAY_MS + 1_000, 320).unwrap();
+        assert_eq!(-100, report.deviation_ppm);
+        assert_eq!(1, report.drifted_windows);
+
+        let report = monitor.push(6 * DAY_MS, 400).unwrap();
+        assert_eq!(320, report.baseline_ppm);
+        assert!(report.calibration_recommended);
+
+        monitor.reset();
+        assert_eq!(None, monitor.current_baseline());
+        assert_eq!(None, monitor.push(7 * DAY_MS, 400));
+    }
+}
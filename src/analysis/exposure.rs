@@ -0,0 +1,361 @@
+/// The length of the window of the occupational exposure limits, 8 hours in
+/// milliseconds
+pub const TWA_WINDOW_MS: u64 = 8 * 60 * 60 * 1_000;
+
+/// The length of a day in milliseconds
+pub const DAY_MS: u64 = 24 * 60 * 60 * 1_000;
+
+/// The time spent above each threshold of an [ExcursionCounter] during a
+/// completed day
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ExcursionReport<const N: usize> {
+    /// The number of the day since the epoch of the timestamps
+    pub day: u64,
+
+    /// The time in milliseconds spent above each threshold, in the order of
+    /// the thresholds
+    pub above_ms: [u64; N],
+
+    /// The measured time of the day in milliseconds
+    pub covered_ms: u64,
+}
+
+/// Accumulates the time per day the CO2 concentration spends above a set of
+/// thresholds, e.g. 1000 and 1400 PPM, as used by building certifications
+/// such as RESET or WELL.
+///
+/// Days are aligned to multiples of [DAY_MS] since the epoch of the
+/// timestamps, i.e. UTC midnight for UNIX timestamps. Each measurement is
+/// held until the next one, and the intervals between measurements longer
+/// than the configured maximal gap are not counted. A timestamp earlier
+/// than the previous one discards the current day.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct ExcursionCounter<const N: usize> {
+    thresholds: [u16; N],
+    max_gap_ms: u64,
+    day: Option<u64>,
+    above_ms: [u64; N],
+    covered_ms: u64,
+    last: Option<(u64, u16)>,
+}
+
+impl<const N: usize> ExcursionCounter<N> {
+    /// Create a new counter for the given thresholds in PPM, which ignores
+    /// the intervals between two measurements longer than `max_gap_ms`
+    /// milliseconds
+    pub fn new(thresholds: [u16; N], max_gap_ms: u64) -> Self {
+        Self {
+            thresholds,
+            max_gap_ms,
+            day: None,
+            above_ms: [0; N],
+            covered_ms: 0,
+            last: None,
+        }
+    }
+
+    /// The thresholds in PPM
+    pub fn thresholds(&self) -> &[u16; N] {
+        &self.thresholds
+    }
+
+    /// Add a CO2 measurement taken at `timestamp_ms` milliseconds. Returns
+    /// the report of the previous day when the measurement starts a new one
+    pub fn push(&mut self, timestamp_ms: u64, co2: u16) -> Option<ExcursionReport<N>> {
+        let mut report = None;
+
+        if let Some((last_ms, last_co2)) = self.last {
+            if timestamp_ms < last_ms {
+                self.reset();
+            } else if timestamp_ms - last_ms <= self.max_gap_ms {
+                report = self.accumulate(last_ms, timestamp_ms, last_co2);
+            }
+        }
+
+        let day = timestamp_ms / DAY_MS;
+        if self.day.is_some_and(|d| d != day) {
+            report = report.or(self.report());
+            self.start_day(day);
+        }
+
+        self.day = Some(day);
+        self.last = Some((timestamp_ms, co2));
+        report
+    }
+
+    /// The time spent above each threshold during the current day so far
+    pub fn today(&self) -> Option<ExcursionReport<N>> {
+        self.report()
+    }
+
+    /// The time in milliseconds spent above the threshold at `index` during
+    /// the current day so far
+    pub fn time_above_ms(&self, index: usize) -> u64 {
+        self.above_ms.get(index).copied().unwrap_or(0)
+    }
+
+    /// Discard the current day and the last measurement
+    pub fn reset(&mut self) {
+        self.day = None;
+        self.above_ms = [0; N];
+        self.covered_ms = 0;
+        self.last = None;
+    }
+
+    fn report(&self) -> Option<ExcursionReport<N>> {
+        self.day.map(|day| ExcursionReport {
+            day,
+            above_ms: self.above_ms,
+            covered_ms: self.covered_ms,
+        })
+    }
+
+    fn start_day(&mut self, day: u64) {
+        self.day = Some(day);
+        self.above_ms = [0; N];
+        self.covered_ms = 0;
+    }
+
+    // Splits the interval at midnight and returns the report of the
+    // completed day, if any
+    fn accumulate(&mut self, from_ms: u64, to_ms: u64, co2: u16) -> Option<ExcursionReport<N>> {
+        let mut report = None;
+        let mut from_ms = from_ms;
+
+        while from_ms < to_ms {
+            let day = from_ms / DAY_MS;
+            if self.day != Some(day) {
+                report = report.or(self.report());
+                self.start_day(day);
+            }
+
+            let end_ms = to_ms.min((day + 1) * DAY_MS);
+            let duration = end_ms - from_ms;
+
+            self.covered_ms += duration;
+            for (above, &threshold) in self.above_ms.iter_mut().zip(&self.thresholds) {
+                if co2 > threshold {
+                    *above += duration;
+                }
+            }
+
+            from_ms = end_ms;
+        }
+
+        report
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Default, Copy, Clone)]
+struct Bucket {
+    id: u64,
+    integral: u64,
+    covered_ms: u64,
+}
+
+/// Computes the 8-hour time-weighted average (TWA) of the CO2 concentration
+/// over a rolling window, as required for occupational exposure reporting.
+///
+/// The window is split into `N` buckets, e.g. 96 buckets of 5 minutes, and
+/// rolls by whole buckets, so its length is accurate to one bucket. Each
+/// measurement is held until the next one. Intervals between measurements
+/// longer than the configured maximal gap, e.g. while the sensor was off,
+/// are excluded from the average instead of being interpolated, and are
+/// reported by [TwaAccumulator::coverage]. A timestamp earlier than the
+/// previous one, e.g. after a restart of a clock that is not persistent,
+/// clears the accumulator.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct TwaAccumulator<const N: usize> {
+    buckets: [Bucket; N],
+    max_gap_ms: u64,
+    last: Option<(u64, u16)>,
+}
+
+impl<const N: usize> TwaAccumulator<N> {
+    /// Create a new accumulator, which excludes the intervals between two
+    /// measurements longer than `max_gap_ms` milliseconds
+    pub fn new(max_gap_ms: u64) -> Self {
+        Self {
+            buckets: [Bucket::default(); N],
+            max_gap_ms,
+            last: None,
+        }
+    }
+
+    /// Add a CO2 measurement taken at `timestamp_ms` milliseconds
+    pub fn push(&mut self, timestamp_ms: u64, co2: u16) {
+        if let Some((last_ms, last_co2)) = self.last {
+            if timestamp_ms < last_ms {
+                self.clear();
+            } else if timestamp_ms - last_ms <= self.max_gap_ms {
+                self.integrate(last_ms, timestamp_ms, last_co2);
+            }
+        }
+
+        self.last = Some((timestamp_ms, co2));
+    }
+
+    /// Remove all measurements
+    pub fn clear(&mut self) {
+        self.buckets = [Bucket::default(); N];
+        self.last = None;
+    }
+
+    /// The time-weighted average CO2 concentration in PPM over the measured
+    /// part of the window ending at the last measurement. `None` if no
+    /// interval was measured
+    pub fn twa(&self) -> Option<f32> {
+        let (integral, covered_ms) = self
+            .window()
+            .fold((0, 0), |(i, c), b| (i + b.integral, c + b.covered_ms));
+
+        if covered_ms == 0 {
+            return None;
+        }
+
+        Some(integral as f32 / covered_ms as f32)
+    }
+
+    /// The measured part of the window, between 0 and 1. Reports typically
+    /// require a minimal coverage for the TWA to be valid
+    pub fn coverage(&self) -> f32 {
+        let covered_ms: u64 = self.window().map(|b| b.covered_ms).sum();
+        (covered_ms as f32 / TWA_WINDOW_MS as f32).min(1.0)
+    }
+
+    fn bucket_ms() -> u64 {
+        (TWA_WINDOW_MS / N as u64).max(1)
+    }
+
+    // The buckets within the window ending at the last measurement
+    fn window(&self) -> impl Iterator<Item = &Bucket> {
+        let last_id = self
+            .last
+            .map_or(0, |(ms, _)| ms.saturating_sub(1) / Self::bucket_ms());
+        let first_id = (last_id + 1).saturating_sub(N as u64);
+
+        self.buckets
+            .iter()
+            .filter(move |b| b.covered_ms > 0 && (first_id..=last_id).contains(&b.id))
+    }
+
+    fn integrate(&mut self, mut from_ms: u64, to_ms: u64, co2: u16) {
+        if N == 0 {
+            return;
+        }
+
+        let bucket_ms = Self::bucket_ms();
+        while from_ms < to_ms {
+            let id = from_ms / bucket_ms;
+            let end_ms = to_ms.min((id + 1) * bucket_ms);
+
+            let bucket = &mut self.buckets[(id % N as u64) as usize];
+            if bucket.id != id {
+                *bucket = Bucket {
+                    id,
+                    ..Bucket::default()
+                };
+            }
+
+            let duration = end_ms - from_ms;
+            bucket.integral += u64::from(co2) * duration;
+            bucket.covered_ms += duration;
+            from_ms = end_ms;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: u64 = 60 * 1_000;
+    const HOUR: u64 = 60 * MINUTE;
+
+    #[test]
+    fn test_twa() {
+        let mut twa = TwaAccumulator::<96>::new(10 * MINUTE);
+        assert_eq!(None, twa.twa());
+
+        // 4 hours at 1000 PPM, then 4 hours at 600 PPM
+        for minute in 0..=8 * 60 {
+            let co2 = if minute < 4 * 60 { 1_000 } else { 600 };
+            twa.push(minute * MINUTE, co2);
+        }
+        assert!((800.0 - twa.twa().unwrap()).abs() < 0.01);
+        assert!((1.0 - twa.coverage()).abs() < 1e-6);
+
+        // The first 2 hours roll out of the window
+        for minute in 8 * 60 + 1..=10 * 60 {
+            twa.push(minute * MINUTE, 600);
+        }
+        assert!((700.0 - twa.twa().unwrap()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_gaps_and_restarts() {
+        let mut twa = TwaAccumulator::<96>::new(10 * MINUTE);
+        twa.push(0, 1_000);
+        twa.push(HOUR, 1_000);
+        assert_eq!(None, twa.twa());
+
+        // The sensor was off for an hour
+        twa.push(HOUR + 5 * MINUTE, 500);
+        twa.push(3 * HOUR, 500);
+        twa.push(3 * HOUR + 5 * MINUTE, 500);
+        assert!((750.0 - twa.twa().unwrap()).abs() < 0.01);
+        assert!((10.0 / 480.0 - twa.coverage()).abs() < 1e-6);
+
+        // The clock restarted
+        twa.push(MINUTE, 800);
+        assert_eq!(None, twa.twa());
+        twa.push(2 * MINUTE, 800);
+        assert!((800.0 - twa.twa().unwrap()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_excursions() {
+        let mut counter = ExcursionCounter::new([1_000, 1_400], HOUR);
+        assert_eq!(None, counter.today());
+
+        let start = DAY_MS - 4 * HOUR;
+        assert_eq!(None, counter.push(start, 1_200));
+        assert_eq!(None, counter.push(start + HOUR, 1_500));
+        assert_eq!(HOUR, counter.time_above_ms(0));
+        assert_eq!(0, counter.time_above_ms(1));
+
+        // The sensor was off, so the interval is not counted
+        assert_eq!(None, counter.push(start + 150 * MINUTE, 1_500));
+        assert_eq!(None, counter.push(start + 3 * HOUR, 900));
+        assert_eq!(None, counter.push(start + 210 * MINUTE, 900));
+        assert_eq!(90 * MINUTE, counter.time_above_ms(0));
+        assert_eq!(30 * MINUTE, counter.time_above_ms(1));
+
+        // The interval across midnight is split between the two days
+        let report = counter.push(DAY_MS + 20 * MINUTE, 900).unwrap();
+        assert_eq!(
+            ExcursionReport {
+                day: 0,
+                above_ms: [90 * MINUTE, 30 * MINUTE],
+                covered_ms: 150 * MINUTE,
+            },
+            report
+        );
+        assert_eq!(
+            Some(ExcursionReport {
+                day: 1,
+                above_ms: [0, 0],
+                covered_ms: 20 * MINUTE,
+            }),
+            counter.today()
+        );
+
+        counter.reset();
+        assert_eq!(None, counter.today());
+        assert_eq!(0, counter.time_above_ms(0));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::analysis::median;
+use crate::measurement::{Measurement, MeasurementBatch};
+
+/// The largest deviations from the median of a batch that are tolerated
+/// before a unit is flagged as an outlier
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutlierLimits {
+    /// The largest deviation of the CO2 concentration in PPM
+    pub co2: f32,
+
+    /// The largest deviation of the temperature in Celsius
+    pub temperature: f32,
+
+    /// The largest deviation of the relative humidity in RH%
+    pub humidity: f32,
+}
+
+impl Default for OutlierLimits {
+    /// Roughly twice the combined accuracy of two SCD4x sensors at indoor
+    /// concentrations, so that only units that are clearly off are rejected
+    fn default() -> Self {
+        Self {
+            co2: 200.0,
+            temperature: 3.0,
+            humidity: 15.0,
+        }
+    }
+}
+
+/// The minimum, the maximum and the mean of a field across the sensors
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldStats {
+    /// The lowest value
+    pub min: f32,
+
+    /// The highest value
+    pub max: f32,
+
+    /// The arithmetic mean of the values
+    pub mean: f32,
+}
+
+impl FieldStats {
+    /// The difference between the highest and the lowest value
+    pub fn spread(&self) -> f32 {
+        self.max - self.min
+    }
+
+    fn of(values: impl Iterator<Item = f32>) -> Option<Self> {
+        let mut count = 0;
+        let mut stats = Self {
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            mean: 0.0,
+        };
+
+        for value in values {
+            count += 1;
+            stats.min = stats.min.min(value);
+            stats.max = stats.max.max(value);
+            stats.mean += value;
+        }
+
+        stats.mean /= count as f32;
+        (count > 0).then_some(stats)
+    }
+}
+
+/// The statistics of a batch of measurements of several sensors, e.g. the
+/// rooms of a building, after rejecting the outlier units
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FleetStats<const N: usize> {
+    /// The statistics of the CO2 concentration in PPM
+    pub co2: FieldStats,
+
+    /// The statistics of the temperature in Celsius
+    pub temperature: FieldStats,
+
+    /// The statistics of the relative humidity in RH%
+    pub humidity: FieldStats,
+
+    /// The number of measurements the statistics are computed from
+    pub sensors: usize,
+
+    /// The units flagged as outliers, in the order of the sensors. Their
+    /// measurements are not included in the statistics
+    pub outliers: [bool; N],
+}
+
+/// Aggregates the batches of measurements of a group of sensors into
+/// building-level statistics, rejecting the units whose measurements
+/// deviate from the median of the batch by more than the limits.
+///
+/// The median is not affected by a single faulty unit, unlike the mean. The
+/// outliers can only be told apart with at least 3 measurements in a batch,
+/// so smaller batches are aggregated as they are. When every unit deviates,
+/// e.g. two equally sized clusters, none of them is rejected.
+///
+/// The aggregator also counts the consecutive batches in which each unit
+/// was an outlier, which tells a faulty unit from a momentary local event,
+/// such as a window opened next to one of the sensors.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct FleetAggregator<const N: usize> {
+    limits: OutlierLimits,
+    streaks: [u16; N],
+}
+
+impl<const N: usize> FleetAggregator<N> {
+    /// The smallest batch in which the outliers are rejected
+    pub const MIN_SENSORS_FOR_REJECTION: usize = 3;
+
+    /// Create a new aggregator with the given outlier limits
+    pub fn new(limits: OutlierLimits) -> Self {
+        Self {
+            limits,
+            streaks: [0; N],
+        }
+    }
+
+    /// Aggregate a batch of measurements. Returns `None` if the batch does
+    /// not contain any measurement, in which case the outlier streaks are
+    /// not changed. A unit without a measurement breaks its streak
+    pub fn push(&mut self, batch: &MeasurementBatch<N>) -> Option<FleetStats<N>> {
+        let stats = self.aggregate(batch)?;
+        for (streak, outlier) in self.streaks.iter_mut().zip(stats.outliers) {
+            *streak = if outlier { streak.saturating_add(1) } else { 0 };
+        }
+
+        Some(stats)
+    }
+
+    /// Aggregate a batch of measurements without updating the outlier
+    /// streaks
+    pub fn aggregate(&self, batch: &MeasurementBatch<N>) -> Option<FleetStats<N>> {
+        let outliers = self.outliers(batch);
+        let inliers = || {
+            batch
+                .measurements
+                .iter()
+                .zip(outliers)
+                .filter(|(_, outlier)| !outlier)
+                .filter_map(|(measurement, _)| measurement.as_ref())
+        };
+
+        Some(FleetStats {
+            co2: FieldStats::of(inliers().map(|m| m.co2 as f32))?,
+            temperature: FieldStats::of(inliers().map(|m| m.temperature))?,
+            humidity: FieldStats::of(inliers().map(|m| m.humidity))?,
+            sensors: inliers().count(),
+            outliers,
+        })
+    }
+
+    /// The number of consecutive batches in which each unit was flagged as
+    /// an outlier, in the order of the sensors
+    pub fn outlier_streaks(&self) -> &[u16; N] {
+        &self.streaks
+    }
+
+    /// Forget the outlier streaks, e.g. after a faulty unit was replaced
+    pub fn reset(&mut self) {
+        self.streaks = [0; N];
+    }
+
+    fn outliers(&self, batch: &MeasurementBatch<N>) -> [bool; N] {
+        let mut outliers = [false; N];
+        if batch.iter().count() < Self::MIN_SENSORS_FOR_REJECTION {
+            return outliers;
+        }
+
+        let co2 = median_of(batch, |m| m.co2 as f32);
+        let temperature = median_of(batch, |m| m.temperature);
+        let humidity = median_of(batch, |m| m.humidity);
+
+        for (outlier, measurement) in outliers.iter_mut().zip(&batch.measurements) {
+            *outlier = measurement.as_ref().is_some_and(|m| {
+                (m.co2 as f32 - co2).abs() > self.limits.co2
+                    || (m.temperature - temperature).abs() > self.limits.temperature
+                    || (m.humidity - humidity).abs() > self.limits.humidity
+            });
+        }
+
+        let every_unit_deviates = batch
+            .measurements
+            .iter()
+            .zip(outliers)
+            .filter(|(measurement, _)| measurement.is_some())
+            .all(|(_, outlier)| outlier);
+        if every_unit_deviates {
+            return [false; N];
+        }
+
+        outliers
+    }
+}
+
+// The median of a field of the measurements present in the batch
+fn median_of<const N: usize>(batch: &MeasurementBatch<N>, field: fn(&Measurement) -> f32) -> f32 {
+    let mut values = [0.0; N];
+    let mut len = 0;
+    for measurement in batch.iter() {
+        values[len] = field(measurement);
+        len += 1;
+    }
+
+    median(&mut values[..len]).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch<const N: usize>(co2: [Option<u16>; N]) -> MeasurementBatch<N> {
+        MeasurementBatch {
+            measurements: co2.map(|co2| {
+                co2.map(|co2| Measurement {
+                    temperature: 21.0,
+                    humidity: 45.0,
+                    co2,
+                })
+            }),
+            spread_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_rejects_outliers() {
+        let mut fleet = FleetAggregator::new(OutlierLimits::default());
+        let stats = fleet
+            .push(&batch([Some(600), Some(700), Some(2_000), None, Some(650)]))
+            .unwrap();
+
+        assert_eq!([false, false, true, false, false], stats.outliers);
+        assert_eq!(3, stats.sensors);
+        assert_eq!(600.0, stats.co2.min);
+        assert_eq!(700.0, stats.co2.max);
+        assert_eq!(650.0, stats.co2.mean);
+        assert_eq!(100.0, stats.co2.spread());
+        assert_eq!(0.0, stats.temperature.spread());
+        assert_eq!(&[0, 0, 1, 0, 0], fleet.outlier_streaks());
+
+        fleet.push(&batch([Some(600), Some(700), Some(2_000), None, Some(650)]));
+        assert_eq!(&[0, 0, 2, 0, 0], fleet.outlier_streaks());
+
+        // The unit recovered
+        fleet.push(&batch([Some(600), Some(700), Some(680), None, Some(650)]));
+        assert_eq!(&[0; 5], fleet.outlier_streaks());
+    }
+
+    #[test]
+    fn test_small_batches_are_not_rejected() {
+        let fleet = FleetAggregator::new(OutlierLimits::default());
+        let stats = fleet
+            .aggregate(&batch([Some(400), None, Some(2_000)]))
+            .unwrap();
+
+        assert_eq!([false; 3], stats.outliers);
+        assert_eq!(1_200.0, stats.co2.mean);
+    }
+
+    #[test]
+    fn test_no_rejection_when_every_unit_deviates() {
+        let fleet = FleetAggregator::new(OutlierLimits::default());
+        let stats = fleet
+            .aggregate(&batch([Some(400), Some(400), Some(1_000), Some(1_000)]))
+            .unwrap();
+
+        assert_eq!([false; 4], stats.outliers);
+        assert_eq!(4, stats.sensors);
+
+        // The missing units are not taken into account
+        let fleet = FleetAggregator::new(OutlierLimits::default());
+        let stats = fleet
+            .aggregate(&batch([
+                None,
+                Some(400),
+                Some(400),
+                Some(1_000),
+                Some(1_000),
+            ]))
+            .unwrap();
+
+        assert_eq!([false; 5], stats.outliers);
+        assert_eq!(4, stats.sensors);
+    }
+
+    #[test]
+    fn test_empty_batch() {
+        let mut fleet = FleetAggregator::new(OutlierLimits::default());
+        assert_eq!(None, fleet.push(&batch::<2>([None, None])));
+    }
+}
@@ -0,0 +1,50 @@
+//! Analysis of series of measurements, e.g. to control the ventilation of
+//! a room. The helpers keep their state in fixed-size buffers and don't
+//! allocate
+
+/// Aggregation of the measurements over longer periods
+pub mod downsample;
+
+/// Monitoring of the long-term drift of the sensor against the ASC target
+pub mod drift;
+
+/// Occupational and building exposure metrics
+pub mod exposure;
+
+/// Building-level statistics of a group of sensors with outlier rejection
+pub mod fleet;
+
+/// The rate of change of the CO2 concentration and detection of sudden drops
+pub mod trend;
+
+/// Estimation of the ventilation rate from the decay of the CO2
+/// concentration
+pub mod ventilation;
+
+// Median of the values, reordering them. `None` if there are no values
+pub(crate) fn median(values: &mut [f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_unstable_by(f32::total_cmp);
+
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        assert_eq!(None, median(&mut []));
+        assert_eq!(Some(2.0), median(&mut [3.0, 1.0, 2.0]));
+        assert_eq!(Some(2.5), median(&mut [4.0, 1.0, 3.0, 2.0]));
+    }
+}
@@ -0,0 +1,315 @@
+use crate::analysis::median;
+
+/// The direction of the CO2 concentration over the analysed window
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Trend {
+    /// The concentration increases faster than the threshold
+    Rising,
+
+    /// The concentration decreases faster than the threshold
+    Falling,
+
+    /// The concentration changes slower than the threshold
+    Steady,
+}
+
+/// Estimates the rate of change of the CO2 concentration over a sliding
+/// window of the last `N` measurements.
+///
+/// The slope is estimated with the repeated median regression, which
+/// tolerates up to half of the samples being outliers, e.g. a person
+/// breathing next to the sensor, unlike the least squares regression.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct TrendDetector<const N: usize> {
+    samples: [(u64, u16); N],
+    len: usize,
+    next: usize,
+    threshold: f32,
+}
+
+impl<const N: usize> TrendDetector<N> {
+    /// Create a new detector classifying the rates of change above
+    /// `threshold_ppm_per_min` (in absolute value) as rising or falling
+    pub fn new(threshold_ppm_per_min: f32) -> Self {
+        Self {
+            samples: [(0, 0); N],
+            len: 0,
+            next: 0,
+            threshold: threshold_ppm_per_min,
+        }
+    }
+
+    /// Add a CO2 measurement taken at `timestamp_ms` milliseconds, replacing
+    /// the oldest one if the window is full. The timestamps must not
+    /// decrease
+    pub fn push(&mut self, timestamp_ms: u64, co2: u16) {
+        if N == 0 {
+            return;
+        }
+
+        self.samples[self.next] = (timestamp_ms, co2);
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// Remove all measurements, e.g. after the sensor was restarted
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+
+    /// The rate of change of the CO2 concentration in PPM per minute.
+    /// `None` if the window contains less than 3 measurements, or if
+    /// they were taken at the same time
+    pub fn slope(&self) -> Option<f32> {
+        if self.len < 3 {
+            return None;
+        }
+
+        let samples = &self.samples[..self.len];
+        let origin = samples.iter().map(|&(t, _)| t).min().unwrap_or(0);
+        let point = |(t, co2): (u64, u16)| ((t - origin) as f32 / 60_000.0, co2 as f32);
+
+        let mut medians = [0.0; N];
+        let mut count = 0;
+
+        for &a in samples {
+            let (ta, ya) = point(a);
+
+            let mut slopes = [0.0; N];
+            let mut len = 0;
+            for &b in samples {
+                let (tb, yb) = point(b);
+                if tb != ta {
+                    slopes[len] = (yb - ya) / (tb - ta);
+                    len += 1;
+                }
+            }
+
+            if let Some(slope) = median(&mut slopes[..len]) {
+                medians[count] = slope;
+                count += 1;
+            }
+        }
+
+        median(&mut medians[..count])
+    }
+
+    /// Classify the rate of change against the threshold. `None` if the
+    /// slope is not available yet
+    pub fn trend(&self) -> Option<Trend> {
+        let slope = self.slope()?;
+
+        Some(if slope > self.threshold {
+            Trend::Rising
+        } else if slope < -self.threshold {
+            Trend::Falling
+        } else {
+            Trend::Steady
+        })
+    }
+}
+
+/// An event of a [DropDetector]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DropEvent {
+    /// The concentration started falling faster than the start threshold,
+    /// e.g. a window was opened
+    Started {
+        /// The timestamp in milliseconds of the measurement that detected
+        /// the drop
+        timestamp_ms: u64,
+
+        /// The rate of change in PPM per minute
+        slope: f32,
+    },
+
+    /// The concentration stopped falling faster than the end threshold
+    Ended {
+        /// The timestamp in milliseconds of the measurement that ended the
+        /// drop
+        timestamp_ms: u64,
+
+        /// The duration of the drop in milliseconds
+        duration_ms: u64,
+
+        /// The decrease of the concentration in PPM during the drop
+        drop_ppm: u16,
+    },
+}
+
+/// Detects rapid drops of the CO2 concentration, e.g. when a window or a
+/// door is opened, or the ventilation is boosted. HVAC controllers can use
+/// it to suppress heating while a window is open.
+///
+/// A drop starts when the slope estimated by a [TrendDetector] falls below
+/// the negated start threshold, and ends when it rises above the negated
+/// end threshold. The end threshold should be lower than the start one to
+/// avoid flapping.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct DropDetector<const N: usize> {
+    trend: TrendDetector<N>,
+    start_threshold: f32,
+    end_threshold: f32,
+    active: Option<(u64, u16)>,
+}
+
+impl<const N: usize> DropDetector<N> {
+    /// Create a new detector. The thresholds are rates of decrease in PPM
+    /// per minute, i.e. positive values
+    pub fn new(start_ppm_per_min: f32, end_ppm_per_min: f32) -> Self {
+        Self {
+            trend: TrendDetector::new(start_ppm_per_min),
+            start_threshold: start_ppm_per_min,
+            end_threshold: end_ppm_per_min,
+            active: None,
+        }
+    }
+
+    /// Add a CO2 measurement taken at `timestamp_ms` milliseconds. Returns
+    /// an event when a drop starts or ends. The timestamps must not decrease
+    pub fn push(&mut self, timestamp_ms: u64, co2: u16) -> Option<DropEvent> {
+        // The concentration before the drop is the highest one in the window
+        let peak = self.trend.samples[..self.trend.len]
+            .iter()
+            .map(|&(_, co2)| co2)
+            .max()
+            .unwrap_or(co2)
+            .max(co2);
+
+        self.trend.push(timestamp_ms, co2);
+        let slope = self.trend.slope()?;
+
+        match self.active {
+            None if slope < -self.start_threshold => {
+                self.active = Some((timestamp_ms, peak));
+                Some(DropEvent::Started {
+                    timestamp_ms,
+                    slope,
+                })
+            }
+            Some((start_ms, peak)) if slope > -self.end_threshold => {
+                self.active = None;
+                Some(DropEvent::Ended {
+                    timestamp_ms,
+                    duration_ms: timestamp_ms - start_ms,
+                    drop_ppm: peak.saturating_sub(co2),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether a drop is in progress
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// The underlying trend detector
+    pub fn trend(&self) -> &TrendDetector<N> {
+        &self.trend
+    }
+
+    /// Remove all measurements and end the drop in progress without an
+    /// event, e.g. after the sensor was restarted
+    pub fn clear(&mut self) {
+        self.trend.clear();
+        self.active = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_enough_samples() {
+        let mut detector = TrendDetector::<8>::new(5.0);
+        assert_eq!(None, detector.slope());
+
+        detector.push(0, 400);
+        detector.push(5_000, 410);
+        assert_eq!(None, detector.trend());
+
+        let mut detector = TrendDetector::<8>::new(5.0);
+        for _ in 0..3 {
+            detector.push(1_000, 400);
+        }
+        assert_eq!(None, detector.slope());
+    }
+
+    #[test]
+    fn test_rising_with_outliers() {
+        let mut detector = TrendDetector::<12>::new(5.0);
+        for idx in 0..12u16 {
+            // 20 PPM per minute, sampled every 30 seconds
+            let mut co2 = 600 + idx * 10;
+            if idx == 4 || idx == 9 {
+                co2 += 400;
+            }
+            detector.push(idx as u64 * 30_000, co2);
+        }
+
+        let slope = detector.slope().unwrap();
+        assert!((20.0 - slope).abs() < 0.01, "{slope}");
+        assert_eq!(Some(Trend::Rising), detector.trend());
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let mut detector = TrendDetector::<4>::new(5.0);
+        for idx in 0..8u16 {
+            // Rising first, then falling by 12 PPM per minute
+            let co2 = if idx < 4 { 800 + idx * 50 } else { 1_000 - idx };
+            detector.push(idx as u64 * 5_000, co2);
+        }
+
+        let slope = detector.slope().unwrap();
+        assert!((-12.0 - slope).abs() < 0.01, "{slope}");
+        assert_eq!(Some(Trend::Falling), detector.trend());
+
+        detector.clear();
+        for idx in 0..4 {
+            detector.push(idx * 5_000, 800);
+        }
+        assert_eq!(Some(Trend::Steady), detector.trend());
+    }
+
+    #[test]
+    fn test_drop() {
+        let mut detector = DropDetector::<4>::new(20.0, 5.0);
+        let mut events = [None; 16];
+
+        // Steady, then falling by 50 PPM every 30 seconds, then steady again
+        for (idx, event) in events.iter_mut().enumerate() {
+            let co2 = match idx {
+                0..4 => 1_200,
+                4..8 => 1_200 - 50 * (idx as u16 - 3),
+                _ => 1_000,
+            };
+            *event = detector.push(idx as u64 * 30_000, co2);
+        }
+
+        assert_eq!(
+            Some(DropEvent::Started {
+                timestamp_ms: 150_000,
+                slope: -100.0,
+            }),
+            events[5]
+        );
+        assert_eq!(
+            Some(DropEvent::Ended {
+                timestamp_ms: 270_000,
+                duration_ms: 120_000,
+                drop_ppm: 200,
+            }),
+            events[9]
+        );
+        assert_eq!(2, events.iter().flatten().count());
+        assert!(!detector.is_active());
+    }
+}
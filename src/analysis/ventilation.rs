@@ -0,0 +1,126 @@
+use crate::math::ln;
+
+/// Samples whose CO2 concentration exceeds the outdoor baseline by less
+/// than this are ignored, as their decay is dominated by the sensor noise
+pub const MIN_EXCESS_PPM: u16 = 50;
+
+/// The air change rate estimated from the decay of the CO2 concentration
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AirChangeEstimate {
+    /// The air changes per hour (ACH)
+    pub air_changes_per_hour: f32,
+
+    /// The coefficient of determination of the fit, between 0 and 1. Low
+    /// values indicate that the space was not empty or that the ventilation
+    /// changed during the decay
+    pub r_squared: f32,
+
+    /// The number of samples used for the fit
+    pub samples: u32,
+}
+
+/// Estimate the air change rate of an unoccupied space from the decay of
+/// the CO2 concentration, e.g. after the occupants have left a meeting room.
+///
+/// Without CO2 sources the excess concentration over the outdoor baseline
+/// decays exponentially: `C(t) - C_out = (C(0) - C_out) * e^(-ACH * t)`.
+/// The rate is fitted by a least squares regression of the logarithm of
+/// the excess concentration over time. The samples are `(timestamp_ms, co2)`
+/// pairs, where the timestamps are in milliseconds.
+///
+/// Returns `None` if less than 3 samples exceed the baseline by at least
+/// [MIN_EXCESS_PPM] or if the concentration does not decay.
+pub fn estimate_air_change_rate(
+    samples: impl IntoIterator<Item = (u64, u16)>,
+    outdoor_ppm: u16,
+) -> Option<AirChangeEstimate> {
+    let mut origin = None;
+    let mut n = 0.0;
+    let (mut sum_t, mut sum_y, mut sum_tt, mut sum_ty, mut sum_yy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+
+    for (timestamp_ms, co2) in samples {
+        let excess = co2.saturating_sub(outdoor_ppm);
+        if excess < MIN_EXCESS_PPM {
+            continue;
+        }
+
+        // Relative to the first sample to preserve the precision
+        let origin = *origin.get_or_insert(timestamp_ms);
+        let t = timestamp_ms.saturating_sub(origin) as f32 / 3_600_000.0;
+        let y = ln(excess as f32)?;
+
+        n += 1.0;
+        sum_t += t;
+        sum_y += y;
+        sum_tt += t * t;
+        sum_ty += t * y;
+        sum_yy += y * y;
+    }
+
+    if n < 3.0 {
+        return None;
+    }
+
+    let var_t = n * sum_tt - sum_t * sum_t;
+    let var_y = n * sum_yy - sum_y * sum_y;
+    let cov = n * sum_ty - sum_t * sum_y;
+    if var_t <= 0.0 {
+        return None;
+    }
+
+    let slope = cov / var_t;
+    if slope >= 0.0 {
+        return None;
+    }
+
+    let r_squared = if var_y > 0.0 {
+        (cov * cov / (var_t * var_y)).min(1.0)
+    } else {
+        1.0
+    };
+
+    Some(AirChangeEstimate {
+        air_changes_per_hour: -slope,
+        r_squared,
+        samples: n as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::exp;
+
+    #[test]
+    fn test_exponential_decay() {
+        // 2 air changes per hour, sampled every 5 minutes for an hour
+        let samples = (0..13u64).map(|idx| {
+            let hours = idx as f32 / 12.0;
+            let co2 = 420.0 + 1_080.0 * exp(-2.0 * hours);
+            (1_000_000 + idx * 300_000, (co2 + 0.5) as u16)
+        });
+
+        let estimate = estimate_air_change_rate(samples, 420).unwrap();
+        assert!((2.0 - estimate.air_changes_per_hour).abs() < 0.01);
+        assert!(estimate.r_squared > 0.999);
+        assert_eq!(13, estimate.samples);
+    }
+
+    #[test]
+    fn test_ignores_samples_near_the_baseline() {
+        let samples = [
+            (0, 1_000),
+            (600_000, 800),
+            (1_200_000, 460),
+            (1_800_000, 430),
+        ];
+        assert_eq!(None, estimate_air_change_rate(samples, 420));
+    }
+
+    #[test]
+    fn test_rising_concentration() {
+        let samples = [(0, 600), (600_000, 700), (1_200_000, 800)];
+        assert_eq!(None, estimate_air_change_rate(samples, 420));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::asynchronous::sensor::{Co2Sensor, Reattach};
+use crate::error::Error;
+use crate::hotplug::{HotPlugEvent, Presence};
+use crate::measurement::Measurement;
+use embedded_hal_async::i2c::{Error as I2cError, ErrorKind, NoAcknowledgeSource};
+
+/// A driver that detects when its sensor is detached from the bus, and
+/// re-initializes the sensor when it's attached again, e.g. a field-swappable
+/// sensor module.
+///
+/// The sensor is considered detached after
+/// [DETACH_THRESHOLD](crate::hotplug::DETACH_THRESHOLD) consecutive transfers
+/// whose address was not acknowledged, which tells a missing sensor from one
+/// that is momentarily busy. The operations of a detached sensor return
+/// `Error::NotAllowed` without accessing the bus, while [Self::poll] probes
+/// for its return. Once the sensor responds, it is identified and its
+/// configuration is restored via [Reattach].
+///
+/// The changes are reported by [Self::poll], which should be called
+/// periodically, e.g. before each measurement. The commands sent directly to
+/// the driver via [Self::sensor] are not tracked.
+#[derive(Debug)]
+pub struct HotPlugSensor<S> {
+    sensor: S,
+    presence: Presence,
+}
+
+impl<S, E> HotPlugSensor<S>
+where
+    S: Reattach<Error = E>,
+    E: I2cError,
+{
+    /// Wrap the driver of a sensor, which is assumed to be attached
+    pub fn new(sensor: S) -> Self {
+        Self {
+            sensor,
+            presence: Presence::new(),
+        }
+    }
+
+    /// Set the number of consecutive transfers that were not acknowledged,
+    /// after which the sensor is considered detached. A threshold of `0` is
+    /// treated as `1`
+    pub fn set_detach_threshold(&mut self, threshold: u8) {
+        self.presence.set_threshold(threshold);
+    }
+
+    /// Whether the sensor is attached, as far as the driver can tell
+    pub fn is_attached(&self) -> bool {
+        self.presence.is_attached()
+    }
+
+    /// The wrapped driver, e.g. to send commands not covered by [Co2Sensor]
+    pub fn sensor(&mut self) -> &mut S {
+        &mut self.sensor
+    }
+
+    /// Release the driver
+    pub fn release(self) -> S {
+        self.sensor
+    }
+
+    /// Report the detachment of the sensor detected by the previous
+    /// operations, or probe for a detached sensor and re-initialize it once
+    /// it responds again. Returns `None` if nothing changed.
+    ///
+    /// Errors other than a missing acknowledgement, e.g. a failed restore
+    /// of the configuration, are returned and the sensor stays detached, so
+    /// that the next call retries the re-initialization
+    pub async fn poll(&mut self) -> Result<Option<HotPlugEvent>, Error<E>> {
+        if let Some(event) = self.presence.take_event() {
+            return Ok(Some(event));
+        }
+
+        if self.presence.is_attached() {
+            return Ok(None);
+        }
+
+        match self.sensor.reattach().await {
+            Ok(()) => {
+                self.presence.attach();
+                Ok(self.presence.take_event())
+            }
+            Err(e) if is_absent(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn track<T>(&mut self, result: Result<T, Error<E>>) -> Result<T, Error<E>> {
+        self.presence
+            .record(matches!(&result, Err(e) if is_absent(e)));
+        result
+    }
+}
+
+impl<S, E> Co2Sensor for HotPlugSensor<S>
+where
+    S: Reattach<Error = E>,
+    E: I2cError,
+{
+    type Error = E;
+
+    async fn start_measurement(&mut self) -> Result<(), Error<E>> {
+        self.presence.check()?;
+        let result = self.sensor.start_measurement().await;
+        self.track(result)
+    }
+
+    async fn stop_measurement(&mut self) -> Result<(), Error<E>> {
+        self.presence.check()?;
+        let result = self.sensor.stop_measurement().await;
+        self.track(result)
+    }
+
+    async fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        self.presence.check()?;
+        let result = self.sensor.data_ready().await;
+        self.track(result)
+    }
+
+    async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.presence.check()?;
+        let result = self.sensor.read_measurement().await;
+        self.track(result)
+    }
+}
+
+// Some buses do not tell which part of the transfer was not acknowledged
+fn is_absent<E: I2cError>(error: &Error<E>) -> bool {
+    match error {
+        Error::I2C(e) => matches!(
+            e.kind(),
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address | NoAcknowledgeSource::Unknown)
+        ),
+        _ => false,
+    }
+}
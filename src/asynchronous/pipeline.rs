@@ -0,0 +1,68 @@
+use crate::asynchronous::sensor::Co2Sensor;
+use crate::error::Error;
+use crate::measurement::Measurement;
+use crate::pipeline::MeasurementProcessor;
+
+/// A driver whose measurements pass through a [MeasurementProcessor], e.g.
+/// a chain of filters and corrections built with
+/// [MeasurementProcessor::then]
+#[derive(Debug)]
+pub struct ProcessedSensor<S, P> {
+    sensor: S,
+    processor: P,
+}
+
+impl<S, P> ProcessedSensor<S, P>
+where
+    S: Co2Sensor,
+    P: MeasurementProcessor,
+{
+    /// Wrap the driver of a sensor
+    pub fn new(sensor: S, processor: P) -> Self {
+        Self { sensor, processor }
+    }
+
+    /// The wrapped driver, e.g. to send commands not covered by [Co2Sensor]
+    pub fn sensor(&mut self) -> &mut S {
+        &mut self.sensor
+    }
+
+    /// The processor of the measurements
+    pub fn processor(&mut self) -> &mut P {
+        &mut self.processor
+    }
+
+    /// Release the driver and the processor
+    pub fn release(self) -> (S, P) {
+        (self.sensor, self.processor)
+    }
+
+    /// Start the periodic measurement and reset the processor, so that e.g.
+    /// the warm-up starts over
+    pub async fn start_measurement(&mut self) -> Result<(), Error<S::Error>> {
+        self.sensor.start_measurement().await?;
+        self.processor.reset();
+        Ok(())
+    }
+
+    /// Stop the periodic measurement
+    pub async fn stop_measurement(&mut self) -> Result<(), Error<S::Error>> {
+        self.sensor.stop_measurement().await
+    }
+
+    /// Check whether a new measurement can be read
+    pub async fn data_ready(&mut self) -> Result<bool, Error<S::Error>> {
+        self.sensor.data_ready().await
+    }
+
+    /// Read the latest measurement and pass it through the processor.
+    /// Returns `Ok(None)` if the processor discarded it, or if the sensor
+    /// responded with a frame of zeros instead of a measurement
+    pub async fn read_measurement(&mut self) -> Result<Option<Measurement>, Error<S::Error>> {
+        let measurement = match self.sensor.read_measurement().await {
+            Err(Error::NoData) => return Ok(None),
+            result => result?,
+        };
+        Ok(self.processor.process(measurement))
+    }
+}
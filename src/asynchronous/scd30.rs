@@ -1,59 +1,260 @@
-pub use crate::internal::scd30::I2C_ADDRESS;
-
-use crate::asynchronous::i2c::{i2c_read, i2c_write};
-use crate::error::Error;
+pub use crate::wire::scd30::I2C_ADDRESS;
+
+use crate::asynchronous::sensor::{Co2Sensor, Reattach};
+use crate::asynchronous::transport::{read_frame, write_frame, I2cTransport, Transport};
+use crate::calibration::{MeasurementCorrection, ReferenceTemperature, TemperatureOffsetReport};
+use crate::commands::{SensorCommand, MAX_RESPONSE_WORDS};
+use crate::config::{Compensation, ConfigurationReport, ConfigurationSnapshot, Scd30Settings};
+use crate::diagnostics::{DiagnosticCounters, Diagnostics, MeasurementMode};
+use crate::error::{Error, ErrorKind};
 use crate::measurement::Measurement;
+use crate::variant::{FirmwareVersion, SensorVariant};
+use core::num::NonZeroU16;
+use core::time::Duration;
 use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::i2c::I2c;
 
-use crate::internal::scd30::{
-    decode_measurement_data, Command, AMBIENT_PRESSURE_DISABLE_COMPENSATION,
-    AMBIENT_PRESSURE_RANGE_HPA, BOOT_DELAY_MILLIS, FRC_PPM_RANGE, GET_DATA_READY_STATUS,
-    GET_SET_ALTITUDE_COMPENSATION, GET_SET_MEASUREMENT_INTERVAL, GET_SET_TEMPERATURE_OFFSET,
-    MANAGE_AUTOMATIC_SELF_CALIBRATION, MEASUREMENT_INTERVAL_RANGE, READ_FIRMWARE_VERSION,
-    READ_MEASUREMENT, SET_FORCED_RECALIBRATION_VALUE, SOFT_RESET, START_CONTINUOUS_MEASUREMENT,
-    STOP_CONTINUOUS_MEASUREMENT, WRITE_DELAY_MILLIS,
+use crate::wire::common::{is_all_zero, opcode_with_data_into_payload, words_from_chunks};
+use crate::wire::scd30::{
+    decode_measurement_data, encode_measurement_interval, Command,
+    AMBIENT_PRESSURE_DISABLE_COMPENSATION, AMBIENT_PRESSURE_RANGE_HPA, BOOT_DELAY_MILLIS,
+    FRC_PPM_RANGE, GET_DATA_READY_STATUS, GET_SET_ALTITUDE_COMPENSATION,
+    GET_SET_MEASUREMENT_INTERVAL, GET_SET_TEMPERATURE_OFFSET, MANAGE_AUTOMATIC_SELF_CALIBRATION,
+    MEASUREMENT_INTERVAL_RANGE, POWER_ON_ATTEMPTS, POWER_ON_RETRY_DELAY_MILLIS,
+    READ_FIRMWARE_VERSION, READ_MEASUREMENT, SET_FORCED_RECALIBRATION_VALUE, SOFT_RESET,
+    START_CONTINUOUS_MEASUREMENT, STOP_CONTINUOUS_MEASUREMENT, WRITE_DELAY_MILLIS,
 };
 
+const DATA_READY_POLL_MILLIS: u32 = 250;
+
 /// Driver implementation for the SCD30 CO2 sensor.
 ///
 /// This sensor needs to be enabled via the `scd30` feature flag
-pub struct Scd30<I2C, D> {
-    i2c: I2C,
-    delay: D,
+pub struct Scd30<T> {
+    transport: T,
+    measurement_started: bool,
+    state_check: bool,
+    compensation_check: bool,
+    correction: Option<MeasurementCorrection>,
+    settings: Scd30Settings,
+    verify_crc: bool,
+    crc_failures: u8,
+    crc_recovery_threshold: u8,
+    counters: DiagnosticCounters,
+    last_error: Option<ErrorKind>,
+}
+
+/// An [Scd30] on an I2C bus, waiting with `embassy_time::Delay`, so that
+/// applications embedding the driver only need to name the bus type
+///
+/// This alias needs to be enabled via the `embassy` feature flag
+#[cfg(feature = "embassy")]
+pub type EmbassyScd30<I2C> = Scd30<I2cTransport<I2C, embassy_time::Delay>>;
+
+#[cfg(feature = "embassy")]
+impl<I2C> EmbassyScd30<I2C>
+where
+    I2C: I2c,
+{
+    /// Create a new sensor using the provided I2C bus and the Embassy delay
+    pub fn new_embassy(i2c: I2C) -> Self {
+        Self::new(i2c, embassy_time::Delay)
+    }
 }
 
-impl<I2C, D, E> Scd30<I2C, D>
+impl<I2C, D> Scd30<I2cTransport<I2C, D>>
 where
-    I2C: I2c<Error = E>,
+    I2C: I2c,
     D: DelayNs,
 {
     /// Create a new SCD30 sensor using the provided I2C and delay implementations
     pub fn new(i2c: I2C, delay: D) -> Self {
-        Self { i2c, delay }
+        Self::with_transport(I2cTransport::new(i2c, delay, I2C_ADDRESS))
+    }
+
+    /// Create a new SCD30 sensor right after the sensor has been powered on.
+    ///
+    /// Waits for the sensor to boot and then reads its firmware version to
+    /// verify the communication, retrying while the sensor is not ready to
+    /// respond yet. The last error is returned if the sensor does not
+    /// respond after [POWER_ON_ATTEMPTS] attempts.
+    pub async fn new_after_power_on(i2c: I2C, delay: D) -> Result<Self, Error<I2C::Error>> {
+        let mut sensor = Self::new(i2c, delay);
+        sensor.wait_until_booted().await?;
+        Ok(sensor)
     }
 
     /// Release the I2C bus held by this sensor
     pub fn release(self) -> I2C {
-        self.i2c
+        self.transport.release().0
+    }
+}
+
+impl<T, E> Scd30<T>
+where
+    T: Transport<Error = E>,
+{
+    /// Create a new SCD30 sensor communicating over a custom transport
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            transport,
+            measurement_started: false,
+            state_check: true,
+            compensation_check: false,
+            correction: None,
+            settings: Scd30Settings::default(),
+            verify_crc: true,
+            crc_failures: 0,
+            crc_recovery_threshold: 0,
+            counters: DiagnosticCounters::default(),
+            last_error: None,
+        }
+    }
+
+    /// Release the transport held by this sensor
+    pub fn release_transport(self) -> T {
+        self.transport
+    }
+
+    /// Enable the automatic recovery of the sensor after `threshold`
+    /// consecutive CRC failures. The recovery performs a soft reset, writes
+    /// the settings written by this driver again, and resumes the continuous
+    /// measurement with the last ambient pressure if it was active. As the
+    /// responses can't be trusted at that point, the settings are written
+    /// without reading them back first. When the recovery
+    /// succeeds, the failed operation returns `Error::Recovered` and can
+    /// be retried.
+    ///
+    /// A threshold of `0` disables the automatic recovery (default).
+    pub fn set_crc_recovery_threshold(&mut self, threshold: u8) {
+        self.crc_recovery_threshold = threshold;
+        self.crc_failures = 0;
+    }
+
+    /// Disable the CRC verification of the responses received from the
+    /// sensor. The commands sent to the sensor still carry a CRC.
+    ///
+    /// **DANGER**: Corrupted responses are returned as valid data. Only use
+    /// this if the bus layer already guarantees the integrity of the data.
+    #[cfg(feature = "danger-skip-crc")]
+    pub fn dangerously_skip_crc_verification(&mut self, skip: bool) {
+        self.verify_crc = !skip;
+    }
+
+    fn check_is_measuring(&self) -> Result<(), Error<E>> {
+        if self.state_check && !self.measurement_started {
+            return Err(Error::NotAllowed);
+        }
+
+        Ok(())
     }
 
     async fn read_response(&mut self, read_buf: &mut [u8]) -> Result<(), Error<E>> {
-        i2c_read(&mut self.i2c, I2C_ADDRESS, read_buf).await
+        let result = read_frame(&mut self.transport, read_buf, self.verify_crc).await;
+        if let Err(e) = &result {
+            self.record_error(e);
+        }
+
+        match &result {
+            Ok(()) => self.crc_failures = 0,
+            Err(Error::CRC(_)) => self.on_crc_failure().await?,
+            Err(_) => {}
+        }
+
+        result
     }
 
-    async fn write_command(&mut self, cmd: Command) -> Result<(), Error<E>> {
-        i2c_write(&mut self.i2c, I2C_ADDRESS, &cmd.prepare()).await?;
-        self.delay.delay_ms(WRITE_DELAY_MILLIS).await;
+    async fn on_crc_failure(&mut self) -> Result<(), Error<E>> {
+        self.crc_failures = self.crc_failures.saturating_add(1);
+        if self.crc_recovery_threshold == 0 || self.crc_failures < self.crc_recovery_threshold {
+            return Ok(());
+        }
+
+        warn!(
+            "{} consecutive CRC failures, recovering the sensor",
+            self.crc_failures
+        );
+        self.crc_failures = 0;
+        self.counters.recoveries = self.counters.recoveries.saturating_add(1);
+
+        let was_running = self.measurement_started;
+        self.soft_reset().await?;
+
+        let check = core::mem::replace(&mut self.compensation_check, false);
+        let result = self.rewrite_settings(was_running).await;
+        self.compensation_check = check;
+        result?;
+
+        Err(Error::Recovered)
+    }
+
+    // The responses of the sensor can't be trusted after repeated CRC
+    // failures, so unlike `restore_settings()` the settings are written
+    // without reading them back first
+    async fn rewrite_settings(&mut self, was_running: bool) -> Result<(), Error<E>> {
+        let settings = self.settings;
+
+        if let Some(interval) = settings.measurement_interval {
+            self.set_measurement_interval(interval).await?;
+        }
+        if let Some(offset) = settings.temperature_offset {
+            self.set_temperature_offset(offset).await?;
+        }
+        if let Some(altitude) = settings.altitude {
+            self.set_altitude_compensation(altitude).await?;
+        }
+        if let Some(enabled) = settings.automatic_self_calibration {
+            self.enable_automatic_self_calibration(enabled).await?;
+        }
+
+        if was_running {
+            let pressure = settings
+                .ambient_pressure
+                .unwrap_or(AMBIENT_PRESSURE_DISABLE_COMPENSATION);
+            self.start_continuous_measurement(pressure).await?;
+        }
+
         Ok(())
     }
 
-    async fn write_command_with_data(&mut self, cmd: Command, data: u16) -> Result<(), Error<E>> {
-        i2c_write(&mut self.i2c, I2C_ADDRESS, &cmd.prepare_with_data(data)).await?;
-        self.delay.delay_ms(WRITE_DELAY_MILLIS).await;
+    fn record_error(&mut self, error: &Error<E>) {
+        self.last_error = Some(self.counters.record(error));
+    }
+
+    async fn configuration_snapshot(&mut self) -> Result<ConfigurationSnapshot, Error<E>> {
+        Ok(ConfigurationSnapshot {
+            temperature_offset: self.get_temperature_offset().await? as f32 / 100.0,
+            altitude: self.get_altitude_compensation().await?,
+            ambient_pressure: self
+                .settings
+                .ambient_pressure
+                .filter(|&pressure| pressure != AMBIENT_PRESSURE_DISABLE_COMPENSATION),
+            automatic_self_calibration: self.get_automatic_self_calibration().await?,
+            automatic_self_calibration_target: None,
+            measurement_interval: Some(self.get_measurement_interval().await?),
+        })
+    }
+
+    // Every command must be followed by a delay before the next transfer,
+    // otherwise back-to-back commands may not be acknowledged
+    async fn send(&mut self, frame: &[u8]) -> Result<(), Error<E>> {
+        if let Err(e) = write_frame(&mut self.transport, frame).await {
+            self.record_error(&e);
+            return Err(e);
+        }
+
+        self.transport.delay_ms(WRITE_DELAY_MILLIS).await;
         Ok(())
     }
 
+    async fn write_command(&mut self, cmd: Command) -> Result<(), Error<E>> {
+        self.send(&cmd.prepare()).await
+    }
+
+    async fn write_command_with_data(&mut self, cmd: Command, data: u16) -> Result<(), Error<E>> {
+        self.send(&cmd.prepare_with_data(data)).await
+    }
+
     async fn command_with_response(
         &mut self,
         cmd: Command,
@@ -63,6 +264,58 @@ where
         self.read_response(read_buf).await
     }
 
+    /// Enable or disable the validation of the commands against the state
+    /// of the continuous measurement tracked by the driver. The forced
+    /// recalibration and the temperature offset tuning require a running
+    /// measurement and return `Error::NotAllowed` otherwise.
+    ///
+    /// The SCD30 resumes the continuous measurement after a power-cycle, so
+    /// the driver also considers the measurement as running once the sensor
+    /// reports ready data. The check can be disabled if the measurement is
+    /// known to run, but the driver has not observed it yet.
+    ///
+    /// The check is enabled by default.
+    pub fn set_state_check(&mut self, enabled: bool) {
+        self.state_check = enabled;
+    }
+
+    /// The source of the pressure compensation set through the driver. A
+    /// non-zero ambient pressure the continuous measurement was started
+    /// with takes precedence over the altitude, because the sensor ignores
+    /// the altitude then
+    pub fn active_compensation(&self) -> Compensation {
+        self.settings.compensation()
+    }
+
+    /// Enable or disable returning `Error::CompensationConflict` when both
+    /// the altitude and a non-zero ambient pressure compensation are set
+    /// through the driver. A conflict is always logged as a warning, but is
+    /// otherwise accepted when the check is disabled.
+    ///
+    /// The check is disabled by default.
+    pub fn set_compensation_conflict_check(&mut self, enabled: bool) {
+        self.compensation_check = enabled;
+    }
+
+    /// Set a correction applied to each measurement before it is returned,
+    /// e.g. one derived by characterizing the unit against a reference rig,
+    /// when the single-point forced recalibration is not sufficient. `None`
+    /// disables the correction, which is the default.
+    pub fn set_measurement_correction(&mut self, correction: Option<MeasurementCorrection>) {
+        self.correction = correction;
+    }
+
+    /// The correction applied to the measurements, if any
+    pub fn measurement_correction(&self) -> Option<&MeasurementCorrection> {
+        self.correction.as_ref()
+    }
+
+    /// Check if the continuous measurement is running according to the state
+    /// tracked by the driver
+    pub fn is_measuring(&self) -> bool {
+        self.measurement_started
+    }
+
     /// Starts continuous measurement of the SCD30 to measure CO2 concentration, humidity and temperature. Measurement data
     /// which is not read from the sensor will be overwritten. The measurement interval is adjustable via the command documented in
     /// chapter 1.4.3, initial measurement rate is 2s.
@@ -86,13 +339,35 @@ where
             return Err(Error::InvalidInput);
         }
 
+        let conflict = ambient_pressure_hpa != AMBIENT_PRESSURE_DISABLE_COMPENSATION
+            && self.settings.altitude.is_some();
+        self.check_compensation_conflict(conflict)?;
+
         self.write_command_with_data(START_CONTINUOUS_MEASUREMENT, ambient_pressure_hpa)
-            .await
+            .await?;
+        self.measurement_started = true;
+        self.settings.ambient_pressure = Some(ambient_pressure_hpa);
+        Ok(())
+    }
+
+    /// Same as [Self::start_continuous_measurement], but `None` disables the
+    /// pressure compensation instead of the magic `0` value. The valid range
+    /// for the ambient pressure is `700..=1400` HPa.
+    pub async fn start_continuous_measurement_with_pressure(
+        &mut self,
+        ambient_pressure_hpa: Option<NonZeroU16>,
+    ) -> Result<(), Error<E>> {
+        self.start_continuous_measurement(
+            ambient_pressure_hpa.map_or(AMBIENT_PRESSURE_DISABLE_COMPENSATION, NonZeroU16::get),
+        )
+        .await
     }
 
     /// Stops the continuous measurement of the SCD30.
     pub async fn stop_continuous_measurement(&mut self) -> Result<(), Error<E>> {
-        self.write_command(STOP_CONTINUOUS_MEASUREMENT).await
+        self.write_command(STOP_CONTINUOUS_MEASUREMENT).await?;
+        self.measurement_started = false;
+        Ok(())
     }
 
     /// Sets the interval used by the SCD30 sensor to measure in continuous
@@ -111,7 +386,9 @@ where
         }
 
         self.write_command_with_data(GET_SET_MEASUREMENT_INTERVAL, interval_seconds)
-            .await
+            .await?;
+        self.settings.measurement_interval = Some(interval_seconds);
+        Ok(())
     }
 
     /// Retrieve the configured measurement interval
@@ -123,6 +400,23 @@ where
         Ok(u16::from_be_bytes([buf[0], buf[1]]))
     }
 
+    /// Set the measurement interval like [Self::set_measurement_interval].
+    /// The interval must be a whole number of seconds in the range
+    /// `2..=1800` seconds.
+    pub async fn set_measurement_interval_duration(
+        &mut self,
+        interval: Duration,
+    ) -> Result<(), Error<E>> {
+        let seconds = encode_measurement_interval(interval)?;
+        self.set_measurement_interval(seconds).await
+    }
+
+    /// Retrieve the configured measurement interval as a [Duration]
+    pub async fn get_measurement_interval_duration(&mut self) -> Result<Duration, Error<E>> {
+        let seconds = self.get_measurement_interval().await?;
+        Ok(Duration::from_secs(seconds.into()))
+    }
+
     /// Data ready command is used to determine if a measurement can be read
     /// from the sensor’s buffer. Whenever there is a measurement available
     /// from the internal buffer this command returns `true` and `false`
@@ -138,8 +432,9 @@ where
         self.command_with_response(GET_DATA_READY_STATUS, &mut buf)
             .await?;
 
-        let val = u16::from_be_bytes([buf[0], buf[1]]);
-        Ok(val == 1)
+        let ready = u16::from_be_bytes([buf[0], buf[1]]) == 1;
+        self.measurement_started |= ready;
+        Ok(ready)
     }
 
     /// When new measurement data is available it can be read out with the
@@ -147,12 +442,34 @@ where
     /// delay of > 3ms following the write sequence. Make sure that the
     /// measurement is completed by reading the data ready status bit
     /// before read out.
+    ///
+    /// Returns `Error::NoData` if the sensor responds with a frame of zeros,
+    /// e.g. right after a change of its operating mode.
     pub async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
-        let mut buf = [0; 18];
-        self.command_with_response(READ_MEASUREMENT, &mut buf)
-            .await?;
+        self.read_measurement_with_buffer(&mut [0; 18]).await
+    }
 
-        Ok(decode_measurement_data(buf))
+    /// Same as [Self::read_measurement], but the response is received into
+    /// the caller-provided buffer instead of a buffer on the stack, e.g. for
+    /// HALs that require the DMA buffers to be placed in specific memory
+    /// regions.
+    pub async fn read_measurement_with_buffer(
+        &mut self,
+        buf: &mut [u8; 18],
+    ) -> Result<Measurement, Error<E>> {
+        self.command_with_response(READ_MEASUREMENT, buf).await?;
+        if is_all_zero(buf) {
+            debug!("the sensor responded with a measurement frame of zeros");
+            return Err(Error::NoData);
+        }
+
+        self.counters.measurements = self.counters.measurements.saturating_add(1);
+
+        let measurement = decode_measurement_data(*buf);
+        Ok(match &self.correction {
+            Some(correction) => correction.apply(measurement),
+            None => measurement,
+        })
     }
 
     /// Continuous automatic self-calibration can be (de-)activated with the
@@ -185,7 +502,9 @@ where
         enable: bool,
     ) -> Result<(), Error<E>> {
         self.write_command_with_data(MANAGE_AUTOMATIC_SELF_CALIBRATION, enable as u16)
-            .await
+            .await?;
+        self.settings.automatic_self_calibration = Some(enable);
+        Ok(())
     }
 
     /// Check if the automatic self calibration algorithm is enabled
@@ -215,7 +534,11 @@ where
     ///
     ///  After repowering the sensor, the command will return the standard
     /// reference value of 400 ppm.
+    ///
+    /// Returns `Error::NotAllowed` if the continuous measurement is not
+    /// running.
     pub async fn set_forced_recalibration_value(&mut self, ppm: u16) -> Result<(), Error<E>> {
+        self.check_is_measuring()?;
         if !FRC_PPM_RANGE.contains(&ppm) {
             return Err(Error::InvalidInput);
         }
@@ -238,7 +561,9 @@ where
     /// Unit: C * 100 => one tick corresponds to 0.01 degrees Celsius
     pub async fn set_temperature_offset(&mut self, offset: u16) -> Result<(), Error<E>> {
         self.write_command_with_data(GET_SET_TEMPERATURE_OFFSET, offset)
-            .await
+            .await?;
+        self.settings.temperature_offset = Some(offset);
+        Ok(())
     }
 
     /// Retrieve the configured temperature offset
@@ -250,6 +575,94 @@ where
         Ok(u16::from_be_bytes([buf[0], buf[1]]))
     }
 
+    /// Adjust the temperature offset by `delta` degrees Celsius, e.g. while
+    /// tuning it iteratively in the field. The current offset is read from
+    /// the sensor, and the new one is rounded to ticks of 0.01 degrees
+    /// Celsius and clamped to the range supported by the sensor.
+    ///
+    /// Returns the new offset in degrees Celsius, or `Error::InvalidInput`
+    /// if `delta` is not finite.
+    pub async fn adjust_temperature_offset(&mut self, delta: f32) -> Result<f32, Error<E>> {
+        if !delta.is_finite() {
+            return Err(Error::InvalidInput);
+        }
+
+        let ticks = self.get_temperature_offset().await? as f32 + delta * 100.0;
+        let ticks = (ticks.clamp(0.0, u16::MAX as f32) + 0.5) as u16;
+        self.set_temperature_offset(ticks).await?;
+
+        Ok(ticks as f32 / 100.0)
+    }
+
+    /// Determine and apply the temperature offset compensating the
+    /// self-heating of the sensor in its final enclosure:
+    ///
+    /// `offset = T_scd30 - T_reference + offset_previous`
+    ///
+    /// The reported temperature already accounts for the currently active
+    /// offset, which is read back from the sensor and added to the result.
+    /// The sensor and the reference are sampled `samples` times and the
+    /// means are used for the calculation. The new offset is rounded to
+    /// ticks of 0.01 degrees Celsius and is saved in non-volatile memory.
+    ///
+    /// The continuous measurement must be running and the sensor should be
+    /// in thermal equilibrium before the tuning. Returns `Error::NotAllowed`
+    /// if the measurement is not running, `Error::InvalidInput` if `samples`
+    /// is zero and `Error::CalibrationFailed` if the calculated offset is out
+    /// of the range supported by the sensor.
+    pub async fn tune_temperature_offset<R: ReferenceTemperature>(
+        &mut self,
+        reference: &mut R,
+        samples: u16,
+    ) -> Result<TemperatureOffsetReport, Error<E>> {
+        self.check_is_measuring()?;
+        if samples == 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let previous_offset = self.get_temperature_offset().await? as f32 / 100.0;
+
+        let mut sensor_sum = 0.0;
+        let mut reference_sum = 0.0;
+        for _ in 0..samples {
+            sensor_sum += self.wait_for_measurement().await?.temperature;
+            reference_sum += reference.temperature();
+        }
+
+        let sensor_temperature = sensor_sum / samples as f32;
+        let reference_temperature = reference_sum / samples as f32;
+        let new_offset = sensor_temperature - reference_temperature + previous_offset;
+
+        let ticks = new_offset * 100.0;
+        if !(0.0..=u16::MAX as f32).contains(&ticks) {
+            return Err(Error::CalibrationFailed);
+        }
+
+        let ticks = (ticks + 0.5) as u16;
+        self.set_temperature_offset(ticks).await?;
+
+        Ok(TemperatureOffsetReport {
+            previous_offset,
+            new_offset: ticks as f32 / 100.0,
+            sensor_temperature,
+            reference_temperature,
+        })
+    }
+
+    async fn wait_for_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        loop {
+            while !self.data_ready().await? {
+                self.transport.delay_ms(DATA_READY_POLL_MILLIS).await;
+            }
+
+            // The sensor may report a frame of zeros right after the start
+            match self.read_measurement().await {
+                Err(Error::NoData) => self.transport.delay_ms(DATA_READY_POLL_MILLIS).await,
+                result => return result,
+            }
+        }
+    }
+
     /// Measurements of CO2 concentration based on the NDIR principle are
     /// influenced by altitude. SCD30 offers to compensate deviations due to
     /// altitude by using the following command. Setting altitude is
@@ -259,8 +672,11 @@ where
     ///  Altitude value is saved in non-volatile memory. The last set value
     /// will be used for altitude compensation after repowering.
     pub async fn set_altitude_compensation(&mut self, altitude: u16) -> Result<(), Error<E>> {
+        self.check_compensation_conflict(self.settings.has_ambient_pressure())?;
         self.write_command_with_data(GET_SET_ALTITUDE_COMPENSATION, altitude)
-            .await
+            .await?;
+        self.settings.altitude = Some(altitude);
+        Ok(())
     }
 
     // Read the configured altitude compensation value
@@ -271,6 +687,32 @@ where
         Ok(u16::from_be_bytes([buf[0], buf[1]]))
     }
 
+    /// Read the configuration of the sensor and check it for inconsistent
+    /// or suspicious values, e.g. a zero temperature offset of a sensor
+    /// mounted in an enclosure, or a measurement interval other than the
+    /// 2 seconds the ASC is designed for. `enclosed` tells if the sensor is
+    /// mounted in an enclosure.
+    ///
+    /// The ambient pressure is taken from the last start of the continuous
+    /// measurement through the driver, because the sensor does not report it.
+    pub async fn validate_configuration(
+        &mut self,
+        enclosed: bool,
+    ) -> Result<ConfigurationReport, Error<E>> {
+        let snapshot = self.configuration_snapshot().await?;
+
+        Ok(snapshot.validate(enclosed))
+    }
+
+    /// Read the firmware version of the SCD30 module like
+    /// [Self::read_firmware_version], but as a [FirmwareVersion], which
+    /// can be compared and displayed
+    pub async fn get_firmware_version(&mut self) -> Result<FirmwareVersion, Error<E>> {
+        self.read_firmware_version()
+            .await
+            .map(FirmwareVersion::from)
+    }
+
     /// Following command can be used to read out the firmware version of
     /// SCD30 module. The returned value is in the format `(Major, Minor)`
     pub async fn read_firmware_version(&mut self) -> Result<(u8, u8), Error<E>> {
@@ -281,6 +723,62 @@ where
         Ok((buf[0], buf[1]))
     }
 
+    /// Collect the details of the sensor and the counters of the driver into
+    /// one [Diagnostics] dump, e.g. to attach it to a support ticket. The
+    /// continuous measurement is not interrupted.
+    pub async fn diagnostics(&mut self) -> Diagnostics {
+        let mode = match self.measurement_started {
+            true => MeasurementMode::Periodic,
+            false => MeasurementMode::Idle,
+        };
+
+        let mut diagnostics = Diagnostics::new(mode, self.counters, self.last_error);
+        diagnostics.variant = Some(SensorVariant::Scd30);
+        diagnostics.firmware_version = self.get_firmware_version().await.ok();
+        diagnostics.settings = self.configuration_snapshot().await.ok();
+        diagnostics
+    }
+
+    /// Execute a [SensorCommand], e.g. one added by a newer firmware of the
+    /// sensor, and decode its response. The command is sent like the
+    /// built-in ones, and the CRC of the response is verified. Returns
+    /// `Error::InvalidInput` if the command expects more than
+    /// [MAX_RESPONSE_WORDS] response words.
+    pub async fn execute<C: SensorCommand + ?Sized>(
+        &mut self,
+        command: &C,
+    ) -> Result<C::Response, Error<E>> {
+        let len = command.response_words();
+        if len > MAX_RESPONSE_WORDS {
+            return Err(Error::InvalidInput);
+        }
+
+        match command.argument() {
+            Some(data) => {
+                self.send(&opcode_with_data_into_payload(command.op_code(), data))
+                    .await?
+            }
+            None => self.send(&command.op_code().to_be_bytes()).await?,
+        }
+
+        // The delay after the command was already waited for by `send()`
+        let remaining_us = command
+            .exec_time_us()
+            .saturating_sub(WRITE_DELAY_MILLIS * 1_000);
+        if remaining_us > 0 {
+            self.transport.delay_us(remaining_us).await;
+        }
+
+        let mut words = [0; MAX_RESPONSE_WORDS];
+        if len > 0 {
+            let mut buf = [0; MAX_RESPONSE_WORDS * 3];
+            self.read_response(&mut buf[..len * 3]).await?;
+            words_from_chunks(&buf, &mut words[..len]);
+        }
+
+        Ok(command.decode(&words[..len]))
+    }
+
     /// The SCD30 provides a soft reset mechanism that forces the sensor into
     /// the same state as after powering up without the need for removing the
     /// power-supply. It does so by restarting its system controller.
@@ -295,7 +793,151 @@ where
     /// its internal state.
     pub async fn soft_reset(&mut self) -> Result<(), Error<E>> {
         self.write_command(SOFT_RESET).await?;
-        self.delay.delay_ms(BOOT_DELAY_MILLIS).await;
+        self.transport.delay_ms(BOOT_DELAY_MILLIS).await;
+        Ok(())
+    }
+
+    /// Perform a soft reset and restore the configuration written by this
+    /// driver: the measurement interval, the temperature offset, the altitude
+    /// and the ASC state are read back after the boot delay and written again
+    /// if the sensor reports different values. The continuous measurement is
+    /// restarted with the last ambient pressure if it was running.
+    ///
+    /// The settings not written by this driver are left as stored by the
+    /// sensor. See [Self::settings].
+    pub async fn soft_reset_and_restore(&mut self) -> Result<(), Error<E>> {
+        let was_running = self.measurement_started;
+        self.soft_reset().await?;
+        self.restore_accepted_settings(was_running).await
+    }
+
+    // The settings were accepted when they were first written
+    async fn restore_accepted_settings(&mut self, was_running: bool) -> Result<(), Error<E>> {
+        let check = core::mem::replace(&mut self.compensation_check, false);
+        let result = self.restore_and_resume(was_running).await;
+        self.compensation_check = check;
+        result
+    }
+
+    async fn restore_and_resume(&mut self, was_running: bool) -> Result<(), Error<E>> {
+        self.restore_settings().await?;
+
+        if was_running {
+            let pressure = self
+                .settings
+                .ambient_pressure
+                .unwrap_or(AMBIENT_PRESSURE_DISABLE_COMPENSATION);
+            self.start_continuous_measurement(pressure).await?;
+        }
+
         Ok(())
     }
+
+    // The sensor ignores the altitude once an ambient pressure is given, so
+    // setting both is most likely a configuration mistake
+    fn check_compensation_conflict(&self, conflict: bool) -> Result<(), Error<E>> {
+        if conflict {
+            warn!("both the altitude and the ambient pressure compensation are set");
+            if self.compensation_check {
+                return Err(Error::CompensationConflict);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The settings written to the sensor by this driver, which are
+    /// verified and restored by [Self::soft_reset_and_restore]
+    pub fn settings(&self) -> &Scd30Settings {
+        &self.settings
+    }
+
+    // The settings are stored in the non-volatile memory of the sensor, so
+    // they are written only when they differ in order to spare it
+    async fn restore_settings(&mut self) -> Result<(), Error<E>> {
+        let settings = self.settings;
+
+        if let Some(interval) = settings.measurement_interval {
+            if self.get_measurement_interval().await? != interval {
+                self.set_measurement_interval(interval).await?;
+            }
+        }
+
+        if let Some(offset) = settings.temperature_offset {
+            if self.get_temperature_offset().await? != offset {
+                self.set_temperature_offset(offset).await?;
+            }
+        }
+
+        if let Some(altitude) = settings.altitude {
+            if self.get_altitude_compensation().await? != altitude {
+                self.set_altitude_compensation(altitude).await?;
+            }
+        }
+
+        if let Some(enabled) = settings.automatic_self_calibration {
+            if self.get_automatic_self_calibration().await? != enabled {
+                self.enable_automatic_self_calibration(enabled).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn wait_until_booted(&mut self) -> Result<(), Error<E>> {
+        self.transport.delay_ms(BOOT_DELAY_MILLIS).await;
+
+        let mut attempt = 1;
+        loop {
+            match self.read_firmware_version().await {
+                Ok(_) => return Ok(()),
+                Err(e) if attempt >= POWER_ON_ATTEMPTS => return Err(e),
+                Err(_) => {
+                    attempt += 1;
+                    self.transport.delay_ms(POWER_ON_RETRY_DELAY_MILLIS).await;
+                }
+            }
+        }
+    }
+}
+
+/// Starts the continuous measurement without ambient pressure compensation
+impl<T, E> Co2Sensor for Scd30<T>
+where
+    T: Transport<Error = E>,
+{
+    type Error = E;
+
+    async fn start_measurement(&mut self) -> Result<(), Error<E>> {
+        self.start_continuous_measurement(AMBIENT_PRESSURE_DISABLE_COMPENSATION)
+            .await
+    }
+
+    async fn stop_measurement(&mut self) -> Result<(), Error<E>> {
+        self.stop_continuous_measurement().await
+    }
+
+    async fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        Scd30::data_ready(self).await
+    }
+
+    async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        Scd30::read_measurement(self).await
+    }
+}
+
+/// Identifies the sensor by its firmware version and restores the settings
+/// like [Scd30::soft_reset_and_restore]
+impl<T, E> Reattach for Scd30<T>
+where
+    T: Transport<Error = E>,
+{
+    async fn reattach(&mut self) -> Result<(), Error<E>> {
+        // Identify the sensor before writing to it
+        self.read_firmware_version().await?;
+        self.crc_failures = 0;
+
+        let was_running = self.measurement_started;
+        self.restore_accepted_settings(was_running).await
+    }
 }
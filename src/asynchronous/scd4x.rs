@@ -1,17 +1,36 @@
-pub use crate::internal::scd4x::I2C_ADDRESS;
-
-use crate::asynchronous::i2c::{i2c_read, i2c_write};
-use crate::error::Error;
+pub use crate::wire::scd4x::I2C_ADDRESS;
+
+use crate::asynchronous::sensor::{Co2Sensor, Reattach};
+use crate::asynchronous::transport::{read_frame, write_frame, I2cTransport, Transport};
+use crate::calibration::{
+    AltitudeSource, AscScheduler, CalibrationPolicy, CalibrationRecord, FieldCalibrationConfig,
+    FieldCalibrationReport, FrcReport, MeasurementCorrection, ReferenceTemperature, SelfTestRecord,
+    SelfTestScheduler, Statistics, TemperatureOffsetReport, TimeSource,
+};
+use crate::commands::{SensorCommand, MAX_RESPONSE_WORDS};
+use crate::config::{
+    Compensation, ConfigurationReport, ConfigurationSnapshot, DriverState, Scd4xSetting,
+    VolatileSettings,
+};
+use crate::diagnostics::{DiagnosticCounters, Diagnostics, MeasurementMode};
+use crate::error::{Error, ErrorKind};
 use crate::measurement::Measurement;
+use crate::progress::{Progress, ProgressCallback, PROGRESS_INTERVAL_MILLIS};
+use crate::variant::{SensorVariant, SensorVariantInfo};
+use core::time::Duration;
 use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::i2c::I2c;
 
-use crate::internal::scd4x::{
-    decode_frc_status, decode_measurement, decode_serial_number, decode_temperature_offset,
-    encode_temperature_offset, Command, AMBIENT_PRESSURE_RANGE_HPA, GET_AMBIENT_PRESSURE,
-    GET_AUTOMATIC_SELF_CALIBRATION_ENABLED, GET_AUTOMATIC_SELF_CALIBRATION_TARGET,
-    GET_DATA_READY_STATUS, GET_SENSOR_ALTITUDE, GET_SERIAL_NUMBER, GET_TEMPERATURE_OFFSET,
-    MAX_ALTITUDE, PERFORM_FACTORY_RESET, PERFORM_FORCED_RECALIBRATION, PERFORM_SELF_TEST,
+use crate::wire::common::{is_all_zero, words_from_chunks};
+use crate::wire::scd4x::{
+    decode_frc_status, decode_measurement, decode_sensor_variant_info, decode_serial_number,
+    decode_temperature_offset, encode_ambient_pressure, encode_temperature_offset,
+    temperature_offset_from_ticks, Command, AMBIENT_PRESSURE_RANGE_HPA, FRC_MIN_OPERATION_MILLIS,
+    GET_AMBIENT_PRESSURE, GET_AUTOMATIC_SELF_CALIBRATION_ENABLED,
+    GET_AUTOMATIC_SELF_CALIBRATION_TARGET, GET_DATA_READY_STATUS, GET_SENSOR_ALTITUDE,
+    GET_SENSOR_VARIANT, GET_SERIAL_NUMBER, GET_TEMPERATURE_OFFSET,
+    LOW_POWER_PERIODIC_MEASUREMENT_INTERVAL_MILLIS, MAX_ALTITUDE, PERFORM_FACTORY_RESET,
+    PERFORM_FORCED_RECALIBRATION, PERFORM_SELF_TEST, PERIODIC_MEASUREMENT_INTERVAL_MILLIS,
     PERSIST_SETTINGS, READ_MEASUREMENT, REINIT, SET_AMBIENT_PRESSURE,
     SET_AUTOMATIC_SELF_CALIBRATION_ENABLED, SET_AUTOMATIC_SELF_CALIBRATION_TARGET,
     SET_SENSOR_ALTITUDE, SET_TEMPERATURE_OFFSET, START_LOW_POWER_PERIODIC_MEASUREMENT,
@@ -19,38 +38,242 @@ use crate::internal::scd4x::{
 };
 
 #[cfg(feature = "scd41")]
-use crate::internal::scd4x::{
+use crate::wire::scd4x::{
     GET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD, GET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD,
     MEASURE_SINGLE_SHOT, MEASURE_SINGLE_SHOT_RHT_ONLY, POWER_DOWN,
     SET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD, SET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD,
     WAKE_UP,
 };
 
+// The interval for polling the data ready status while waiting for a
+// measurement in the calibration procedures
+const DATA_READY_POLL_MILLIS: u32 = 250;
+
+const OPERATING_HOUR_MILLIS: u64 = 60 * 60 * 1_000;
+
 /// Driver implementation for the SCD40 CO2 sensor. This driver is compatible
 /// with SCD41 devices, though it does not expose the SCD41 additional APIs
 ///
 /// This sensor needs to be enabled via the `scd40` feature flag
 #[cfg(feature = "scd40")]
-pub struct Scd40<I2C, D> {
-    inner: Scd4x<I2C, D>,
+pub struct Scd40<T> {
+    inner: Scd4x<T>,
+}
+
+/// An [Scd40] on an I2C bus, waiting with `embassy_time::Delay`, so that
+/// applications embedding the driver only need to name the bus type
+///
+/// This alias needs to be enabled via the `embassy` feature flag
+#[cfg(all(feature = "scd40", feature = "embassy"))]
+pub type EmbassyScd40<I2C> = Scd40<I2cTransport<I2C, embassy_time::Delay>>;
+
+#[cfg(all(feature = "scd40", feature = "embassy"))]
+impl<I2C> EmbassyScd40<I2C>
+where
+    I2C: I2c,
+{
+    /// Create a new sensor using the provided I2C bus and the Embassy delay
+    pub fn new_embassy(i2c: I2C) -> Self {
+        Self::new(i2c, embassy_time::Delay)
+    }
 }
 
 #[cfg(feature = "scd40")]
-impl<I2C, D, E> Scd40<I2C, D>
+impl<I2C, D> Scd40<I2cTransport<I2C, D>>
 where
-    I2C: I2c<Error = E>,
+    I2C: I2c,
     D: DelayNs,
 {
     /// Create a new sensor using the provided I2C bus and delay implementation
     pub fn new(i2c: I2C, delay: D) -> Self {
-        Self {
-            inner: Scd4x::new(i2c, delay),
-        }
+        Self::new_at(i2c, delay, I2C_ADDRESS)
+    }
+
+    fn new_at(i2c: I2C, delay: D, address: u8) -> Self {
+        Self::with_transport(I2cTransport::new(i2c, delay, address))
+    }
+
+    /// Create a new sensor like [Self::new], but verify that the connected
+    /// device is an SCD40. Returns `Error::WrongDevice` for the other SCD4x
+    /// variants, e.g. when the firmware must distinguish the sensor models
+    /// in a mixed inventory.
+    ///
+    /// The sensor must not be in periodic measurement mode, because it does
+    /// not report its variant then.
+    pub async fn new_checked(i2c: I2C, delay: D) -> Result<Self, Error<I2C::Error>> {
+        let mut sensor = Self::new(i2c, delay);
+        sensor.inner.expect_variant(SensorVariant::Scd40).await?;
+        Ok(sensor)
+    }
+
+    /// Capture the state of the driver, e.g. right before the MCU enters
+    /// deep sleep. The sensor is not affected and may keep measuring. See
+    /// [DriverState]
+    pub fn suspend(&self) -> DriverState {
+        self.inner.state(self.inner.transport.address())
+    }
+
+    /// Recreate the driver from the state captured by [Self::suspend],
+    /// e.g. after the MCU woke up from deep sleep. The sensor is not
+    /// accessed, so a running measurement is not interrupted
+    pub fn resume(i2c: I2C, delay: D, state: DriverState) -> Self {
+        let mut sensor = Self::new_at(i2c, delay, state.address);
+        sensor.inner.restore_state(&state);
+        sensor
     }
 
     /// Release the I2C bus held by this sensor
     pub fn release(self) -> I2C {
-        self.inner.release()
+        self.inner.transport.release().0
+    }
+}
+
+#[cfg(feature = "scd40")]
+impl<T, E> Scd40<T>
+where
+    T: Transport<Error = E>,
+{
+    /// Create a new sensor communicating over a custom transport
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            inner: Scd4x::new(transport),
+        }
+    }
+
+    /// Release the transport held by this sensor
+    pub fn release_transport(self) -> T {
+        self.inner.transport
+    }
+
+    /// Enable the automatic recovery of the sensor after `threshold`
+    /// consecutive CRC failures. The recovery stops the periodic
+    /// measurement, re-initializes the sensor and resumes the periodic
+    /// measurement if it was active. When the recovery succeeds, the
+    /// failed operation returns `Error::Recovered` and can be retried.
+    ///
+    /// A threshold of `0` disables the automatic recovery (default).
+    pub fn set_crc_recovery_threshold(&mut self, threshold: u8) {
+        self.inner.set_crc_recovery_threshold(threshold)
+    }
+
+    /// Keep track of the settings written to the sensor that have not been
+    /// persisted, i.e. the ambient pressure and any changes made since the
+    /// last [Self::persists_settings], so that they can be restored when the
+    /// sensor restarts unexpectedly, e.g. after a brown-out of its supply.
+    ///
+    /// When enabled, a transport error makes the next [Self::data_ready]
+    /// call check whether the sensor has restarted, as if by calling
+    /// [Self::check_sensor_reset]. Disabled by default.
+    pub fn set_volatile_settings_cache(&mut self, enabled: bool) {
+        self.inner.set_volatile_settings_cache(enabled)
+    }
+
+    /// The settings that would be restored after a restart of the sensor.
+    /// `None` if the cache is disabled.
+    pub fn volatile_settings(&self) -> Option<&VolatileSettings> {
+        self.inner.volatile.as_ref()
+    }
+
+    /// Write the cached volatile settings to the sensor again. The periodic
+    /// measurement is stopped while the settings are written and resumed
+    /// afterwards.
+    pub async fn reapply_volatile_settings(&mut self) -> Result<(), Error<E>> {
+        self.inner.reapply_volatile_settings().await
+    }
+
+    /// Write the setting to the sensor if it's allowed in the current mode,
+    /// or queue it otherwise, e.g. the sensor altitude during the periodic
+    /// measurement. The queued settings are written automatically at the
+    /// next opportunity, i.e. when the periodic measurement is stopped, or
+    /// by [Self::apply_pending_settings]. A queued setting replaces a
+    /// previously queued one of the same kind. The settings that fail to be
+    /// written when the measurement is stopped stay queued.
+    ///
+    /// Returns whether the setting was written immediately. Returns
+    /// `Error::InvalidInput` if the value is not accepted by the sensor.
+    pub async fn defer_setting(&mut self, setting: Scd4xSetting) -> Result<bool, Error<E>> {
+        self.inner.defer_setting(setting).await
+    }
+
+    /// The settings waiting for the sensor to become idle
+    pub fn pending_settings(&self) -> &VolatileSettings {
+        &self.inner.pending
+    }
+
+    /// Write the queued settings now. The periodic measurement is stopped
+    /// while the settings are written and resumed afterwards. The settings
+    /// that failed to be written stay queued.
+    pub async fn apply_pending_settings(&mut self) -> Result<(), Error<E>> {
+        self.inner.apply_pending_settings().await
+    }
+
+    /// Check whether the sensor has restarted since the driver configured
+    /// it, in which case the cached volatile settings are restored and the
+    /// periodic measurement is resumed if it was running. Returns whether
+    /// a restart was detected.
+    ///
+    /// During the periodic measurement a restart is detected by the sensor
+    /// accepting the serial number command, which it rejects while it's
+    /// measuring. Otherwise it is detected by comparing the cached settings
+    /// with the ones reported by the sensor.
+    pub async fn check_sensor_reset(&mut self) -> Result<bool, Error<E>> {
+        self.inner.check_sensor_reset().await
+    }
+
+    /// Disable the CRC verification of the responses received from the
+    /// sensor. The commands sent to the sensor still carry a CRC.
+    ///
+    /// **DANGER**: Corrupted responses are returned as valid data. Only use
+    /// this if the bus layer already guarantees the integrity of the data.
+    #[cfg(feature = "danger-skip-crc")]
+    pub fn dangerously_skip_crc_verification(&mut self, skip: bool) {
+        self.inner.verify_crc = !skip;
+    }
+
+    /// Enable or disable the check that the sensor was operated in periodic
+    /// measurement mode for at least 3 minutes before a forced recalibration.
+    /// The runtime is tracked by the driver from the delays it performs and
+    /// from the number of measurements read since the periodic measurement
+    /// was last started. Time spent outside the driver is not accounted for,
+    /// so the check can be disabled if the measurement is known to have run
+    /// long enough, e.g. when it was started before the driver was created.
+    ///
+    /// The check is enabled by default.
+    pub fn set_frc_runtime_check(&mut self, enabled: bool) {
+        self.inner.frc_runtime_check = enabled;
+    }
+
+    /// The source of the pressure compensation set through the driver. The
+    /// ambient pressure takes precedence over the altitude, because the
+    /// sensor ignores the altitude once an ambient pressure is given. The
+    /// ambient pressure is forgotten when the sensor is re-initialized or
+    /// powered down, and both are forgotten after a factory reset
+    pub fn active_compensation(&self) -> Compensation {
+        self.inner.active_compensation()
+    }
+
+    /// Enable or disable returning `Error::CompensationConflict` when both
+    /// the altitude and the ambient pressure compensation are set through
+    /// the driver. A conflict is always logged as a warning, but is
+    /// otherwise accepted when the check is disabled.
+    ///
+    /// The check is disabled by default.
+    pub fn set_compensation_conflict_check(&mut self, enabled: bool) {
+        self.inner.compensation_check = enabled;
+    }
+
+    /// Set a correction applied to each measurement before it is returned,
+    /// e.g. one derived by characterizing the unit against a reference rig,
+    /// when the single-point forced recalibration is not sufficient. `None`
+    /// disables the correction, which is the default. The correction is
+    /// part of the [DriverState].
+    pub fn set_measurement_correction(&mut self, correction: Option<MeasurementCorrection>) {
+        self.inner.correction = correction;
+    }
+
+    /// The correction applied to the measurements, if any
+    pub fn measurement_correction(&self) -> Option<&MeasurementCorrection> {
+        self.inner.correction.as_ref()
     }
 
     /// Start periodic measurement mode. The signal update interval is 5 seconds.
@@ -66,12 +289,92 @@ where
         self.inner.stop_periodic_measurement().await
     }
 
+    /// Send the stop periodic measurement command regardless of the state
+    /// tracked by the driver and wait until the sensor is idle. Useful when
+    /// the sensor may still be measuring after the MCU has been reset or
+    /// reflashed. A sensor that does not acknowledge the command is
+    /// tolerated, as it is most likely idle already.
+    pub async fn force_stop(&mut self) {
+        self.inner.force_stop().await;
+    }
+
+    /// Stop the periodic measurement, if it was started by this driver.
+    /// Returns whether the measurement was running, so that the caller can
+    /// resume it after changing the configuration of the sensor.
+    pub async fn ensure_idle(&mut self) -> Result<bool, Error<E>> {
+        self.inner.ensure_idle().await
+    }
+
+    /// Bring the sensor to a known idle state, regardless of what it was
+    /// doing before the MCU started: stop the periodic measurement, wait
+    /// until the sensor is idle and reload the user settings from the
+    /// EEPROM. Intended to be called once, right after creating the driver.
+    pub async fn reset_to_known_state(&mut self) -> Result<(), Error<E>> {
+        self.inner.reset_to_known_state().await
+    }
+
     /// Start low power periodic measurement mode, signal update interval
     /// is approximately 30 seconds.
     pub async fn start_low_power_periodic_measurement(&mut self) -> Result<(), Error<E>> {
         self.inner.start_low_power_periodic_measurement().await
     }
 
+    /// Discard the first `samples` measurements in
+    /// [Self::wait_for_first_measurement], e.g. to give the sensor time to
+    /// settle after it has been started. Defaults to `0`.
+    pub fn set_warm_up_samples(&mut self, samples: u8) {
+        self.inner.warm_up_samples = samples;
+    }
+
+    /// Wait for the first measurement after the periodic measurement has
+    /// been started, skipping the configured warm-up samples. The first
+    /// sample is available only after a full measurement interval, i.e.
+    /// 5 seconds in periodic mode and 30 seconds in low power periodic
+    /// mode, so this method blocks for at least that long.
+    ///
+    /// Returns `Error::NotAllowed` if the periodic measurement is not
+    /// running.
+    pub async fn wait_for_first_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.inner.wait_for_first_measurement().await
+    }
+
+    /// Account the time elapsed since the previous call towards the
+    /// operating time of the sensor, if the periodic measurement has been
+    /// running since then. `now_ms` is the time in milliseconds of a
+    /// monotonic clock of the application. The time between the last call
+    /// and a stop of the measurement is not accounted, so this should be
+    /// called regularly, e.g. after each measurement. Single-shot
+    /// measurements are accounted by their execution time.
+    ///
+    /// Returns `true` when another full hour of operating time has been
+    /// completed since the previous call, as a hint to persist the
+    /// [Self::operating_time] in the storage of the application.
+    pub fn update_operating_time(&mut self, now_ms: u64) -> bool {
+        self.inner.update_operating_time(now_ms)
+    }
+
+    /// The cumulative operating time of the sensor, as accounted by
+    /// [Self::update_operating_time]. Sensirion specifies the ASC and the
+    /// aging of the sensor in operating hours
+    pub fn operating_time(&self) -> Duration {
+        Duration::from_millis(self.inner.operating_ms)
+    }
+
+    /// Restore the operating time persisted by the application, e.g. after
+    /// a restart of the MCU
+    pub fn set_operating_time(&mut self, operating_time: Duration) {
+        self.inner.set_operating_time(operating_time)
+    }
+
+    /// The nominal interval between two measurements in the active
+    /// measurement mode: 5 seconds in periodic mode and 30 seconds in low
+    /// power periodic mode. `None` if the sensor is idle.
+    pub fn expected_measurement_interval(&self) -> Option<Duration> {
+        self.inner
+            .measurement_interval_ms()
+            .map(|ms| Duration::from_millis(ms.into()))
+    }
+
     /// Check if there is a measurement data ready to be read
     pub async fn data_ready(&mut self) -> Result<bool, Error<E>> {
         self.inner.data_ready().await
@@ -84,20 +387,71 @@ where
     /// available in the buffer, the sensor returns a NACK. To avoid a
     /// NACK response, the `data_ready()` method can be issued to check
     /// data status.
+    ///
+    /// Returns `Error::NoData` if the sensor responds with a frame of zeros,
+    /// e.g. right after a change of its operating mode.
     pub async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
         self.inner.read_measurement().await
     }
 
-    /// Configure the temperature offset
+    /// Same as [Self::read_measurement], but the response is received into
+    /// the caller-provided buffer instead of a buffer on the stack, e.g. for
+    /// HALs that require the DMA buffers to be placed in specific memory
+    /// regions.
+    pub async fn read_measurement_with_buffer(
+        &mut self,
+        buf: &mut [u8; 9],
+    ) -> Result<Measurement, Error<E>> {
+        self.inner.read_measurement_with_buffer(buf).await
+    }
+
+    /// Configure the temperature offset in degrees Celsius. Returns
+    /// `Error::InvalidInput` if the offset is outside of the range of
+    /// 0 - 20 degrees Celsius recommended by the datasheet, e.g. when it's
+    /// given in raw ticks or in Fahrenheit by mistake.
     pub async fn set_temperature_offset(&mut self, offset: f32) -> Result<(), Error<E>> {
         self.inner.set_temperature_offset(offset).await
     }
 
+    /// Configure the temperature offset in degrees Celsius without checking
+    /// the range recommended by the datasheet, e.g. for enclosures with an
+    /// unusually high self-heating. Returns `Error::InvalidInput` only for
+    /// negative or non-finite offsets.
+    pub async fn set_temperature_offset_unchecked(&mut self, offset: f32) -> Result<(), Error<E>> {
+        self.inner.set_temperature_offset_unchecked(offset).await
+    }
+
     /// Retrieve the configured temperature offset
     pub async fn get_temperature_offset(&mut self) -> Result<f32, Error<E>> {
         self.inner.get_temperature_offset().await
     }
 
+    /// Configure the temperature offset in raw ticks of 175 / (2^16 - 1)
+    /// degrees Celsius. The value is sent to the sensor as is, so unlike
+    /// [Self::set_temperature_offset] it can be round-tripped losslessly
+    /// with [Self::get_temperature_offset_ticks].
+    pub async fn set_temperature_offset_ticks(&mut self, ticks: u16) -> Result<(), Error<E>> {
+        self.inner.set_temperature_offset_ticks(ticks).await
+    }
+
+    /// Retrieve the configured temperature offset in raw ticks of
+    /// 175 / (2^16 - 1) degrees Celsius
+    pub async fn get_temperature_offset_ticks(&mut self) -> Result<u16, Error<E>> {
+        self.inner.get_temperature_offset_ticks().await
+    }
+
+    /// Adjust the temperature offset by `delta` degrees Celsius, e.g. while
+    /// tuning it iteratively in the field. The current offset is read from
+    /// the sensor and the new one is clamped to non-negative values. Must be
+    /// called while the sensor is in idle mode.
+    ///
+    /// Returns the new offset in degrees Celsius, or `Error::InvalidInput`
+    /// if `delta` is not finite or the new offset exceeds the range
+    /// recommended by the datasheet.
+    pub async fn adjust_temperature_offset(&mut self, delta: f32) -> Result<f32, Error<E>> {
+        self.inner.adjust_temperature_offset(delta).await
+    }
+
     /// Reading and writing the sensor altitude must be done while the SCD4x
     /// is in idle mode. Typically, the sensor altitude is set once after
     /// device installation. To save the setting to the EEPROM, the
@@ -116,6 +470,21 @@ where
         self.inner.get_sensor_altitude().await
     }
 
+    /// Read the altitude from the given source and write it to the sensor.
+    /// When `persist` is set, the altitude is also stored in the EEPROM.
+    /// Must be called while the sensor is in idle mode.
+    ///
+    /// Returns the applied altitude or `None` if the source has no altitude
+    /// available. Returns `Error::InvalidInput` if the altitude is outside
+    /// of the valid range of 0 – 3’000 m.
+    pub async fn apply_altitude_from<S: AltitudeSource>(
+        &mut self,
+        source: &mut S,
+        persist: bool,
+    ) -> Result<Option<u16>, Error<E>> {
+        self.inner.apply_altitude_from(source, persist).await
+    }
+
     /// The `set_ambient_pressure()` command can be sent during periodic
     /// measurements to enable continuous pressure compensation. Note that
     /// setting an ambient pressure overrides any pressure compensation
@@ -127,6 +496,15 @@ where
         self.inner.set_ambient_pressure(pressure).await
     }
 
+    /// Set the ambient pressure from a value in hPa, e.g. as reported by a
+    /// barometer. The pressure is rounded to the nearest hPa, which is the
+    /// resolution of the sensor. Returns `Error::InvalidInput` if the
+    /// pressure is not finite or is outside of the valid range of
+    /// 700-1200 HPa after the rounding.
+    pub async fn set_ambient_pressure_f32(&mut self, pressure: f32) -> Result<(), Error<E>> {
+        self.inner.set_ambient_pressure_f32(pressure).await
+    }
+
     /// The `get_ambient_pressure` command can be sent during periodic
     /// measurements to read out the previously  saved ambient pressure value
     /// set by the `set_ambient_pressure` command.
@@ -175,6 +553,9 @@ where
     ///
     /// An `Ok(Some(_))` value indicates that the FRC was applied. It contains
     /// the magnitude of the correction
+    ///
+    /// Returns `Error::InsufficientRuntime` if the periodic measurement has
+    /// not been running for 3 minutes (see [Self::set_frc_runtime_check]).
     pub async fn perform_forced_recalibration(
         &mut self,
         ppm_co2: u16,
@@ -187,6 +568,126 @@ where
         self.inner.get_automatic_self_calibration().await
     }
 
+    /// Run the forced recalibration (FRC) procedure described in the
+    /// datasheet: operate the sensor in periodic mode for 3 minutes, stop
+    /// the periodic measurement, perform the FRC with the given reference
+    /// and take a measurement after the recalibration. The sensor must be
+    /// exposed to a homogenous and constant CO2 concentration of
+    /// `reference_ppm` for the whole duration of the procedure.
+    ///
+    /// The periodic measurement is started if needed and is left in the
+    /// same state as before the call, including the low power periodic mode,
+    /// also when the procedure fails. Returns `Error::CalibrationFailed` if
+    /// the sensor rejected the recalibration.
+    pub async fn run_frc_procedure(&mut self, reference_ppm: u16) -> Result<FrcReport, Error<E>> {
+        self.inner.run_frc_procedure(reference_ppm).await
+    }
+
+    /// Determine and apply the temperature offset compensating the
+    /// self-heating of the sensor in its final enclosure (Section 3.7.1):
+    ///
+    /// `offset = T_scd4x - T_reference + offset_previous`
+    ///
+    /// The sensor and the reference are sampled `samples` times in periodic
+    /// mode and the means are used for the calculation. The sensor should
+    /// be in thermal equilibrium before the tuning. When `persist` is set,
+    /// the new offset is stored in the EEPROM.
+    ///
+    /// The periodic measurement is left in the same state as before the
+    /// call, including the low power periodic mode, also when the tuning
+    /// fails. Returns `Error::InvalidInput` if `samples` is zero and
+    /// `Error::CalibrationFailed` if the calculated offset is outside of the
+    /// range of 0 - 20 degrees Celsius recommended by the datasheet.
+    pub async fn tune_temperature_offset<R: ReferenceTemperature>(
+        &mut self,
+        reference: &mut R,
+        samples: u16,
+        persist: bool,
+    ) -> Result<TemperatureOffsetReport, Error<E>> {
+        self.inner
+            .tune_temperature_offset(reference, samples, persist)
+            .await
+    }
+
+    /// Perform a forced recalibration, handling the automatic self-calibration
+    /// according to the given [CalibrationPolicy]. Returns
+    /// `Error::NotAllowed` if the ASC is currently enabled and the policy does
+    /// not allow disabling it. An ASC correction that already happened is not
+    /// detected. The rest is the same as [Self::perform_forced_recalibration].
+    pub async fn perform_forced_recalibration_with_policy(
+        &mut self,
+        ppm_co2: u16,
+        policy: CalibrationPolicy,
+    ) -> Result<Option<i16>, Error<E>> {
+        self.inner
+            .perform_forced_recalibration_with_policy(ppm_co2, policy)
+            .await
+    }
+
+    /// Create a [CalibrationRecord] for the forced recalibration described
+    /// by `report`, e.g. as returned by [Self::run_frc_procedure]. The serial
+    /// number and the ASC state are read from the sensor, so the periodic
+    /// measurement must be stopped. The `timestamp` is stored as is.
+    pub async fn calibration_record(
+        &mut self,
+        report: &FrcReport,
+        timestamp: u64,
+    ) -> Result<CalibrationRecord, Error<E>> {
+        self.inner.calibration_record(report, timestamp).await
+    }
+
+    /// Perform a field calibration in an environment with a known and
+    /// stable CO2 concentration. Collects `config.samples` measurements in
+    /// periodic mode and performs a forced recalibration only if their
+    /// standard deviation does not exceed `config.max_stddev_ppm`. Otherwise
+    /// the run is rejected and the report contains no correction.
+    ///
+    /// The periodic measurement is left in the same state as before the call,
+    /// including the low power periodic mode, also when the run fails.
+    /// Returns `Error::InvalidInput` if the samples do not cover the 3 minutes
+    /// of operation required before a forced recalibration and
+    /// `Error::CalibrationFailed` if the sensor rejects the recalibration.
+    pub async fn run_field_calibration(
+        &mut self,
+        reference_ppm: u16,
+        config: FieldCalibrationConfig,
+    ) -> Result<FieldCalibrationReport, Error<E>> {
+        self.inner
+            .run_field_calibration(reference_ppm, config)
+            .await
+    }
+
+    /// Enable or disable the automatic self-calibration according to the
+    /// given [AscScheduler] and the current time. The sensor is reconfigured
+    /// only if the state differs from the one last applied by the scheduler,
+    /// in which case a running periodic measurement is stopped and restarted.
+    /// Returns whether the ASC is enabled.
+    ///
+    /// Should be called periodically, e.g. after each measurement.
+    pub async fn update_asc_schedule<C: TimeSource>(
+        &mut self,
+        scheduler: &mut AscScheduler<'_>,
+        clock: &mut C,
+    ) -> Result<bool, Error<E>> {
+        self.inner.update_asc_schedule(scheduler, clock).await
+    }
+
+    /// Apply the given settings back-to-back as one batch. A running periodic
+    /// measurement is stopped once before the batch and restarted after it.
+    /// When `persist` is set, the settings are stored in the EEPROM at the
+    /// end of the batch.
+    ///
+    /// All settings are validated before any of them is sent to the sensor.
+    /// Returns `Error::InvalidInput` if any of them is invalid. If a command
+    /// fails, the remaining settings are not applied.
+    pub async fn apply_settings(
+        &mut self,
+        settings: &[Scd4xSetting],
+        persist: bool,
+    ) -> Result<(), Error<E>> {
+        self.inner.apply_settings(settings, persist).await
+    }
+
     /// Configuration settings such as the temperature offset, sensor altitude
     /// and the ASC enabled/disabled parameters are by default stored in the
     /// volatile memory (RAM) only and will be lost after a power-cycle.
@@ -199,18 +700,92 @@ where
         self.inner.persists_settings().await
     }
 
+    /// Read the configuration of the sensor and check it for inconsistent
+    /// or suspicious values, e.g. a zero temperature offset of a sensor
+    /// mounted in an enclosure, or an ASC target far from the 400 PPM of the
+    /// fresh outdoor air. `enclosed` tells if the sensor is mounted in an
+    /// enclosure. Can be sent only when the sensor is in idle mode.
+    ///
+    /// The ambient pressure is taken from the value set through the driver,
+    /// because the sensor reports the default pressure when none was set.
+    pub async fn validate_configuration(
+        &mut self,
+        enclosed: bool,
+    ) -> Result<ConfigurationReport, Error<E>> {
+        self.inner.validate_configuration(enclosed).await
+    }
+
     /// Reading out the serial number can be used to identify the chip
     /// and to verify the presence of the sensor.
     pub async fn serial_number(&mut self) -> Result<u64, Error<E>> {
         self.inner.serial_number().await
     }
 
+    /// Read the sensor variant. This can be used to verify that the device
+    /// connected to the bus is the expected SCD4x sensor. Returns
+    /// `Error::WrongDevice` if the response does not match a known variant.
+    pub async fn get_sensor_variant(&mut self) -> Result<SensorVariant, Error<E>> {
+        self.inner.get_sensor_variant().await
+    }
+
+    /// Read the raw sensor variant word, which also carries the firmware
+    /// revision of the sensor, e.g. to enable workarounds for specific
+    /// firmware versions.
+    pub async fn get_sensor_variant_info(&mut self) -> Result<SensorVariantInfo, Error<E>> {
+        self.inner.get_sensor_variant_info().await
+    }
+
+    /// Collect the details of the sensor and the counters of the driver into
+    /// one [Diagnostics] dump, e.g. to attach it to a support ticket. The
+    /// periodic measurement is not interrupted, so the details that can be
+    /// read only in idle mode are missing while it's running.
+    pub async fn diagnostics(&mut self) -> Diagnostics {
+        self.inner.diagnostics().await
+    }
+
+    /// Execute a [SensorCommand], e.g. one added by a newer firmware of the
+    /// sensor, and decode its response. The command is sent like the
+    /// built-in ones, so it's rejected with `Error::NotAllowed` during the
+    /// periodic measurement unless it allows it, and the CRC of the response
+    /// is verified. Returns `Error::InvalidInput` if the command expects more
+    /// than [MAX_RESPONSE_WORDS] response words.
+    pub async fn execute<C: SensorCommand + ?Sized>(
+        &mut self,
+        command: &C,
+    ) -> Result<C::Response, Error<E>> {
+        self.inner.execute(command).await
+    }
+
     /// The `perform_self_test()` command can be used as an end-of-line
     /// test to check the sensor functionality.
     pub async fn perform_self_test(&mut self) -> Result<bool, Error<E>> {
         self.inner.perform_self_test().await
     }
 
+    /// Same as `perform_self_test()`, but a detected malfunction is reported
+    /// as `Error::SelfTestFailed` carrying the raw status word returned by
+    /// the sensor.
+    pub async fn perform_self_test_strict(&mut self) -> Result<(), Error<E>> {
+        self.inner.perform_self_test_strict().await
+    }
+
+    /// Run the self-test if the application declares the sensor `idle`, i.e.
+    /// no measurements are needed for the next 10 seconds, and the given
+    /// [SelfTestScheduler] considers it due at `now_ms`. A running periodic
+    /// measurement is stopped for the self-test and restarted after it.
+    ///
+    /// Returns the record of the self-test, or `None` if it was not run.
+    pub async fn run_scheduled_self_test(
+        &mut self,
+        scheduler: &mut SelfTestScheduler,
+        now_ms: u64,
+        idle: bool,
+    ) -> Result<Option<SelfTestRecord>, Error<E>> {
+        self.inner
+            .run_scheduled_self_test(scheduler, now_ms, idle)
+            .await
+    }
+
     /// The perform_factory_reset command resets all configuration
     /// settings stored in the EEPROM and erases the FRC and ASC
     /// algorithm history.
@@ -218,6 +793,43 @@ where
         self.inner.perform_factory_reset().await
     }
 
+    /// Same as `perform_self_test()`, but the given callback receives the
+    /// progress of the 10 seconds self-test every
+    /// [PROGRESS_INTERVAL_MILLIS] and may abandon it with `Error::Aborted`
+    pub async fn perform_self_test_with_progress<P: ProgressCallback>(
+        &mut self,
+        mut progress: P,
+    ) -> Result<bool, Error<E>> {
+        self.inner
+            .perform_self_test_with_progress(&mut progress)
+            .await
+    }
+
+    /// Same as `perform_factory_reset()`, but the given callback receives
+    /// the progress of the reset every [PROGRESS_INTERVAL_MILLIS] and may
+    /// abandon it with `Error::Aborted`
+    pub async fn perform_factory_reset_with_progress<P: ProgressCallback>(
+        &mut self,
+        mut progress: P,
+    ) -> Result<(), Error<E>> {
+        self.inner
+            .perform_factory_reset_with_progress(&mut progress)
+            .await
+    }
+
+    /// Same as `perform_forced_recalibration()`, but the given callback
+    /// receives the progress of the recalibration every
+    /// [PROGRESS_INTERVAL_MILLIS] and may abandon it with `Error::Aborted`
+    pub async fn perform_forced_recalibration_with_progress<P: ProgressCallback>(
+        &mut self,
+        ppm_co2: u16,
+        mut progress: P,
+    ) -> Result<Option<i16>, Error<E>> {
+        self.inner
+            .perform_forced_recalibration_with_progress(ppm_co2, &mut progress)
+            .await
+    }
+
     /// The reinit command reinitializes the sensor by reloading user
     /// settings from EEPROM. Before sending the reinit command, the
     /// `stop_periodic_measurement()` command must be issued.
@@ -229,81 +841,450 @@ where
     }
 }
 
+#[cfg(feature = "scd40")]
+impl<T, E> Co2Sensor for Scd40<T>
+where
+    T: Transport<Error = E>,
+{
+    type Error = E;
+
+    async fn start_measurement(&mut self) -> Result<(), Error<E>> {
+        self.start_periodic_measurement().await
+    }
+
+    async fn stop_measurement(&mut self) -> Result<(), Error<E>> {
+        self.stop_periodic_measurement().await
+    }
+
+    async fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        self.inner.data_ready().await
+    }
+
+    async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.inner.read_measurement().await
+    }
+}
+
+/// Identifies the sensor by its serial number. Only the settings kept by the
+/// volatile settings cache are restored, see
+/// [Scd40::set_volatile_settings_cache]
+#[cfg(feature = "scd40")]
+impl<T, E> Reattach for Scd40<T>
+where
+    T: Transport<Error = E>,
+{
+    async fn reattach(&mut self) -> Result<(), Error<E>> {
+        self.inner.reattach().await
+    }
+}
+
 /// Driver implementation for the SCD41 CO2 sensor. This driver is compatible
 /// with SCD40 devices, though it exposes operations that are not available on
 /// SCD40
 ///
 /// This sensor needs to be enabled via the `scd41` feature flag
 #[cfg(feature = "scd41")]
-pub struct Scd41<I2C, D> {
-    inner: Scd4x<I2C, D>,
+pub struct Scd41<T> {
+    inner: Scd4x<T>,
+}
+
+/// An [Scd41] on an I2C bus, waiting with `embassy_time::Delay`, so that
+/// applications embedding the driver only need to name the bus type
+///
+/// This alias needs to be enabled via the `embassy` feature flag
+#[cfg(all(feature = "scd41", feature = "embassy"))]
+pub type EmbassyScd41<I2C> = Scd41<I2cTransport<I2C, embassy_time::Delay>>;
+
+#[cfg(all(feature = "scd41", feature = "embassy"))]
+impl<I2C> EmbassyScd41<I2C>
+where
+    I2C: I2c,
+{
+    /// Create a new sensor using the provided I2C bus and the Embassy delay
+    pub fn new_embassy(i2c: I2C) -> Self {
+        Self::new(i2c, embassy_time::Delay)
+    }
 }
 
 #[cfg(feature = "scd41")]
-impl<I2C, D, E> Scd41<I2C, D>
+impl<I2C, D> Scd41<I2cTransport<I2C, D>>
 where
-    I2C: I2c<Error = E>,
+    I2C: I2c,
     D: DelayNs,
 {
     /// Create a new sensor using the provided I2C bus and delay implementation
     pub fn new(i2c: I2C, delay: D) -> Self {
-        Self {
-            inner: Scd4x::new(i2c, delay),
-        }
+        Self::new_at(i2c, delay, I2C_ADDRESS)
     }
 
-    /// Release the I2C bus held by this sensor
-    pub fn release(self) -> I2C {
-        self.inner.release()
+    fn new_at(i2c: I2C, delay: D, address: u8) -> Self {
+        Self::with_transport(I2cTransport::new(i2c, delay, address))
     }
 
-    /// Start periodic measurement mode. The signal update interval is 5 seconds.
-    pub async fn start_periodic_measurement(&mut self) -> Result<(), Error<E>> {
-        self.inner.start_periodic_measurement().await
+    /// Create a new sensor like [Self::new], but verify that the connected
+    /// device is an SCD41. Returns `Error::WrongDevice` for the other SCD4x
+    /// variants, so that no SCD41-only commands are issued to them.
+    ///
+    /// The sensor must not be in periodic measurement mode, because it does
+    /// not report its variant then.
+    pub async fn new_checked(i2c: I2C, delay: D) -> Result<Self, Error<I2C::Error>> {
+        let mut sensor = Self::new(i2c, delay);
+        sensor.inner.expect_variant(SensorVariant::Scd41).await?;
+        Ok(sensor)
     }
 
-    /// Stop periodic measurement mode to change the sensor configuration or
-    /// to save power. Note that the sensor will only respond to other
-    /// commands 500 ms after the `stop_periodic_measurement()` command
-    /// has been issued.
-    pub async fn stop_periodic_measurement(&mut self) -> Result<(), Error<E>> {
-        self.inner.stop_periodic_measurement().await
+    /// Capture the state of the driver, e.g. right before the MCU enters
+    /// deep sleep. The sensor is not affected and may keep measuring. See
+    /// [DriverState]
+    pub fn suspend(&self) -> DriverState {
+        self.inner.state(self.inner.transport.address())
     }
 
-    /// Start low power periodic measurement mode, signal update interval
-    /// is approximately 30 seconds.
-    pub async fn start_low_power_periodic_measurement(&mut self) -> Result<(), Error<E>> {
-        self.inner.start_low_power_periodic_measurement().await
+    /// Recreate the driver from the state captured by [Self::suspend],
+    /// e.g. after the MCU woke up from deep sleep. The sensor is not
+    /// accessed, so a running measurement is not interrupted
+    pub fn resume(i2c: I2C, delay: D, state: DriverState) -> Self {
+        let mut sensor = Self::new_at(i2c, delay, state.address);
+        sensor.inner.restore_state(&state);
+        sensor
     }
 
-    /// Check if there is a measurement data ready to be read
-    pub async fn data_ready(&mut self) -> Result<bool, Error<E>> {
-        self.inner.data_ready().await
+    /// Release the I2C bus held by this sensor
+    pub fn release(self) -> I2C {
+        self.inner.transport.release().0
     }
+}
 
-    /// Read sensor output.
-    ///
-    /// The measurement data can only be read out  once per signal update
-    /// interval as the buffer is emptied upon read-out. If no data is
-    /// available in the buffer, the sensor returns a NACK. To avoid a
-    /// NACK response, the `data_ready()` method can be issued to check
-    /// data status.
-    pub async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
-        self.inner.read_measurement().await
+#[cfg(feature = "scd41")]
+impl<T, E> Scd41<T>
+where
+    T: Transport<Error = E>,
+{
+    /// Create a new sensor communicating over a custom transport
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            inner: Scd4x::new(transport),
+        }
     }
 
-    /// Configure the temperature offset
-    pub async fn set_temperature_offset(&mut self, offset: f32) -> Result<(), Error<E>> {
-        self.inner.set_temperature_offset(offset).await
+    /// Release the transport held by this sensor
+    pub fn release_transport(self) -> T {
+        self.inner.transport
     }
 
-    /// Retrieve the configured temperature offset
-    pub async fn get_temperature_offset(&mut self) -> Result<f32, Error<E>> {
-        self.inner.get_temperature_offset().await
+    /// Enable the automatic recovery of the sensor after `threshold`
+    /// consecutive CRC failures. The recovery stops the periodic
+    /// measurement, re-initializes the sensor and resumes the periodic
+    /// measurement if it was active. When the recovery succeeds, the
+    /// failed operation returns `Error::Recovered` and can be retried.
+    ///
+    /// A threshold of `0` disables the automatic recovery (default).
+    pub fn set_crc_recovery_threshold(&mut self, threshold: u8) {
+        self.inner.set_crc_recovery_threshold(threshold)
     }
 
-    /// Reading and writing the sensor altitude must be done while the SCD4x
-    /// is in idle mode. Typically, the sensor altitude is set once after
+    /// Keep track of the settings written to the sensor that have not been
+    /// persisted, i.e. the ambient pressure and any changes made since the
+    /// last [Self::persists_settings], so that they can be restored when the
+    /// sensor restarts unexpectedly, e.g. after a brown-out of its supply.
+    ///
+    /// When enabled, a transport error makes the next [Self::data_ready]
+    /// call check whether the sensor has restarted, as if by calling
+    /// [Self::check_sensor_reset]. Disabled by default.
+    pub fn set_volatile_settings_cache(&mut self, enabled: bool) {
+        self.inner.set_volatile_settings_cache(enabled)
+    }
+
+    /// The settings that would be restored after a restart of the sensor.
+    /// `None` if the cache is disabled.
+    pub fn volatile_settings(&self) -> Option<&VolatileSettings> {
+        self.inner.volatile.as_ref()
+    }
+
+    /// Write the cached volatile settings to the sensor again. The periodic
+    /// measurement is stopped while the settings are written and resumed
+    /// afterwards.
+    pub async fn reapply_volatile_settings(&mut self) -> Result<(), Error<E>> {
+        self.inner.reapply_volatile_settings().await
+    }
+
+    /// Write the setting to the sensor if it's allowed in the current mode,
+    /// or queue it otherwise, e.g. the sensor altitude during the periodic
+    /// measurement. The queued settings are written automatically at the
+    /// next opportunity, i.e. when the periodic measurement is stopped, or
+    /// by [Self::apply_pending_settings]. A queued setting replaces a
+    /// previously queued one of the same kind. The settings that fail to be
+    /// written when the measurement is stopped stay queued.
+    ///
+    /// Returns whether the setting was written immediately. Returns
+    /// `Error::InvalidInput` if the value is not accepted by the sensor.
+    pub async fn defer_setting(&mut self, setting: Scd4xSetting) -> Result<bool, Error<E>> {
+        self.inner.defer_setting(setting).await
+    }
+
+    /// The settings waiting for the sensor to become idle
+    pub fn pending_settings(&self) -> &VolatileSettings {
+        &self.inner.pending
+    }
+
+    /// Write the queued settings now. The periodic measurement is stopped
+    /// while the settings are written and resumed afterwards. The settings
+    /// that failed to be written stay queued.
+    pub async fn apply_pending_settings(&mut self) -> Result<(), Error<E>> {
+        self.inner.apply_pending_settings().await
+    }
+
+    /// Check whether the sensor has restarted since the driver configured
+    /// it, in which case the cached volatile settings are restored and the
+    /// periodic measurement is resumed if it was running. Returns whether
+    /// a restart was detected.
+    ///
+    /// During the periodic measurement a restart is detected by the sensor
+    /// accepting the serial number command, which it rejects while it's
+    /// measuring. Otherwise it is detected by comparing the cached settings
+    /// with the ones reported by the sensor.
+    pub async fn check_sensor_reset(&mut self) -> Result<bool, Error<E>> {
+        self.inner.check_sensor_reset().await
+    }
+
+    /// Disable the CRC verification of the responses received from the
+    /// sensor. The commands sent to the sensor still carry a CRC.
+    ///
+    /// **DANGER**: Corrupted responses are returned as valid data. Only use
+    /// this if the bus layer already guarantees the integrity of the data.
+    #[cfg(feature = "danger-skip-crc")]
+    pub fn dangerously_skip_crc_verification(&mut self, skip: bool) {
+        self.inner.verify_crc = !skip;
+    }
+
+    /// Enable or disable the check that the sensor was operated in periodic
+    /// measurement mode for at least 3 minutes before a forced recalibration.
+    /// The runtime is tracked by the driver from the delays it performs and
+    /// from the number of measurements read since the periodic measurement
+    /// was last started. Time spent outside the driver is not accounted for,
+    /// so the check can be disabled if the measurement is known to have run
+    /// long enough, e.g. when it was started before the driver was created.
+    ///
+    /// The check is enabled by default.
+    pub fn set_frc_runtime_check(&mut self, enabled: bool) {
+        self.inner.frc_runtime_check = enabled;
+    }
+
+    /// The source of the pressure compensation set through the driver. The
+    /// ambient pressure takes precedence over the altitude, because the
+    /// sensor ignores the altitude once an ambient pressure is given. The
+    /// ambient pressure is forgotten when the sensor is re-initialized or
+    /// powered down, and both are forgotten after a factory reset
+    pub fn active_compensation(&self) -> Compensation {
+        self.inner.active_compensation()
+    }
+
+    /// Enable or disable returning `Error::CompensationConflict` when both
+    /// the altitude and the ambient pressure compensation are set through
+    /// the driver. A conflict is always logged as a warning, but is
+    /// otherwise accepted when the check is disabled.
+    ///
+    /// The check is disabled by default.
+    pub fn set_compensation_conflict_check(&mut self, enabled: bool) {
+        self.inner.compensation_check = enabled;
+    }
+
+    /// Set a correction applied to each measurement before it is returned,
+    /// e.g. one derived by characterizing the unit against a reference rig,
+    /// when the single-point forced recalibration is not sufficient. `None`
+    /// disables the correction, which is the default. The correction is
+    /// part of the [DriverState].
+    pub fn set_measurement_correction(&mut self, correction: Option<MeasurementCorrection>) {
+        self.inner.correction = correction;
+    }
+
+    /// The correction applied to the measurements, if any
+    pub fn measurement_correction(&self) -> Option<&MeasurementCorrection> {
+        self.inner.correction.as_ref()
+    }
+
+    /// Start periodic measurement mode. The signal update interval is 5 seconds.
+    pub async fn start_periodic_measurement(&mut self) -> Result<(), Error<E>> {
+        self.inner.start_periodic_measurement().await
+    }
+
+    /// Stop periodic measurement mode to change the sensor configuration or
+    /// to save power. Note that the sensor will only respond to other
+    /// commands 500 ms after the `stop_periodic_measurement()` command
+    /// has been issued.
+    pub async fn stop_periodic_measurement(&mut self) -> Result<(), Error<E>> {
+        self.inner.stop_periodic_measurement().await
+    }
+
+    /// Send the stop periodic measurement command regardless of the state
+    /// tracked by the driver and wait until the sensor is idle. Useful when
+    /// the sensor may still be measuring after the MCU has been reset or
+    /// reflashed. A sensor that does not acknowledge the command is
+    /// tolerated, as it is most likely idle already.
+    pub async fn force_stop(&mut self) {
+        self.inner.force_stop().await;
+    }
+
+    /// Stop the periodic measurement, if it was started by this driver.
+    /// Returns whether the measurement was running, so that the caller can
+    /// resume it after changing the configuration of the sensor.
+    pub async fn ensure_idle(&mut self) -> Result<bool, Error<E>> {
+        self.inner.ensure_idle().await
+    }
+
+    /// Bring the sensor to a known idle state, regardless of what it was
+    /// doing before the MCU started: wake it up in case it was powered
+    /// down, stop the periodic measurement, wait until the sensor is idle
+    /// and reload the user settings from the EEPROM. Intended to be called
+    /// once, right after creating the driver.
+    pub async fn reset_to_known_state(&mut self) -> Result<(), Error<E>> {
+        self.inner.wake_up_unacknowledged().await;
+        self.inner.reset_to_known_state().await
+    }
+
+    /// Start low power periodic measurement mode, signal update interval
+    /// is approximately 30 seconds.
+    pub async fn start_low_power_periodic_measurement(&mut self) -> Result<(), Error<E>> {
+        self.inner.start_low_power_periodic_measurement().await
+    }
+
+    /// Discard the first `samples` measurements in
+    /// [Self::wait_for_first_measurement], e.g. to give the sensor time to
+    /// settle after it has been started. Defaults to `0`.
+    pub fn set_warm_up_samples(&mut self, samples: u8) {
+        self.inner.warm_up_samples = samples;
+    }
+
+    /// Wait for the first measurement after the periodic measurement has
+    /// been started, skipping the configured warm-up samples. The first
+    /// sample is available only after a full measurement interval, i.e.
+    /// 5 seconds in periodic mode and 30 seconds in low power periodic
+    /// mode, so this method blocks for at least that long.
+    ///
+    /// Returns `Error::NotAllowed` if the periodic measurement is not
+    /// running.
+    pub async fn wait_for_first_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.inner.wait_for_first_measurement().await
+    }
+
+    /// Account the time elapsed since the previous call towards the
+    /// operating time of the sensor, if the periodic measurement has been
+    /// running since then. `now_ms` is the time in milliseconds of a
+    /// monotonic clock of the application. The time between the last call
+    /// and a stop of the measurement is not accounted, so this should be
+    /// called regularly, e.g. after each measurement. Single-shot
+    /// measurements are accounted by their execution time.
+    ///
+    /// Returns `true` when another full hour of operating time has been
+    /// completed since the previous call, as a hint to persist the
+    /// [Self::operating_time] in the storage of the application.
+    pub fn update_operating_time(&mut self, now_ms: u64) -> bool {
+        self.inner.update_operating_time(now_ms)
+    }
+
+    /// The cumulative operating time of the sensor, as accounted by
+    /// [Self::update_operating_time]. Sensirion specifies the ASC and the
+    /// aging of the sensor in operating hours
+    pub fn operating_time(&self) -> Duration {
+        Duration::from_millis(self.inner.operating_ms)
+    }
+
+    /// Restore the operating time persisted by the application, e.g. after
+    /// a restart of the MCU
+    pub fn set_operating_time(&mut self, operating_time: Duration) {
+        self.inner.set_operating_time(operating_time)
+    }
+
+    /// The nominal interval between two measurements in the active
+    /// measurement mode: 5 seconds in periodic mode and 30 seconds in low
+    /// power periodic mode. After a single-shot measurement it's the
+    /// execution time of the single-shot command. `None` if the sensor is
+    /// idle.
+    pub fn expected_measurement_interval(&self) -> Option<Duration> {
+        self.inner
+            .measurement_interval_ms()
+            .map(|ms| Duration::from_millis(ms.into()))
+    }
+
+    /// Check if there is a measurement data ready to be read
+    pub async fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        self.inner.data_ready().await
+    }
+
+    /// Read sensor output.
+    ///
+    /// The measurement data can only be read out  once per signal update
+    /// interval as the buffer is emptied upon read-out. If no data is
+    /// available in the buffer, the sensor returns a NACK. To avoid a
+    /// NACK response, the `data_ready()` method can be issued to check
+    /// data status.
+    ///
+    /// Returns `Error::NoData` if the sensor responds with a frame of zeros,
+    /// e.g. right after a change of its operating mode.
+    pub async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.inner.read_measurement().await
+    }
+
+    /// Same as [Self::read_measurement], but the response is received into
+    /// the caller-provided buffer instead of a buffer on the stack, e.g. for
+    /// HALs that require the DMA buffers to be placed in specific memory
+    /// regions.
+    pub async fn read_measurement_with_buffer(
+        &mut self,
+        buf: &mut [u8; 9],
+    ) -> Result<Measurement, Error<E>> {
+        self.inner.read_measurement_with_buffer(buf).await
+    }
+
+    /// Configure the temperature offset in degrees Celsius. Returns
+    /// `Error::InvalidInput` if the offset is outside of the range of
+    /// 0 - 20 degrees Celsius recommended by the datasheet, e.g. when it's
+    /// given in raw ticks or in Fahrenheit by mistake.
+    pub async fn set_temperature_offset(&mut self, offset: f32) -> Result<(), Error<E>> {
+        self.inner.set_temperature_offset(offset).await
+    }
+
+    /// Configure the temperature offset in degrees Celsius without checking
+    /// the range recommended by the datasheet, e.g. for enclosures with an
+    /// unusually high self-heating. Returns `Error::InvalidInput` only for
+    /// negative or non-finite offsets.
+    pub async fn set_temperature_offset_unchecked(&mut self, offset: f32) -> Result<(), Error<E>> {
+        self.inner.set_temperature_offset_unchecked(offset).await
+    }
+
+    /// Retrieve the configured temperature offset
+    pub async fn get_temperature_offset(&mut self) -> Result<f32, Error<E>> {
+        self.inner.get_temperature_offset().await
+    }
+
+    /// Configure the temperature offset in raw ticks of 175 / (2^16 - 1)
+    /// degrees Celsius. The value is sent to the sensor as is, so unlike
+    /// [Self::set_temperature_offset] it can be round-tripped losslessly
+    /// with [Self::get_temperature_offset_ticks].
+    pub async fn set_temperature_offset_ticks(&mut self, ticks: u16) -> Result<(), Error<E>> {
+        self.inner.set_temperature_offset_ticks(ticks).await
+    }
+
+    /// Retrieve the configured temperature offset in raw ticks of
+    /// 175 / (2^16 - 1) degrees Celsius
+    pub async fn get_temperature_offset_ticks(&mut self) -> Result<u16, Error<E>> {
+        self.inner.get_temperature_offset_ticks().await
+    }
+
+    /// Adjust the temperature offset by `delta` degrees Celsius, e.g. while
+    /// tuning it iteratively in the field. The current offset is read from
+    /// the sensor and the new one is clamped to non-negative values. Must be
+    /// called while the sensor is in idle mode.
+    ///
+    /// Returns the new offset in degrees Celsius, or `Error::InvalidInput`
+    /// if `delta` is not finite or the new offset exceeds the range
+    /// recommended by the datasheet.
+    pub async fn adjust_temperature_offset(&mut self, delta: f32) -> Result<f32, Error<E>> {
+        self.inner.adjust_temperature_offset(delta).await
+    }
+
+    /// Reading and writing the sensor altitude must be done while the SCD4x
+    /// is in idle mode. Typically, the sensor altitude is set once after
     /// device installation. To save the setting to the EEPROM, the
     /// `persist_settings()` (see Section 3.9.1) command must be issued.
     ///
@@ -320,6 +1301,21 @@ where
         self.inner.get_sensor_altitude().await
     }
 
+    /// Read the altitude from the given source and write it to the sensor.
+    /// When `persist` is set, the altitude is also stored in the EEPROM.
+    /// Must be called while the sensor is in idle mode.
+    ///
+    /// Returns the applied altitude or `None` if the source has no altitude
+    /// available. Returns `Error::InvalidInput` if the altitude is outside
+    /// of the valid range of 0 – 3’000 m.
+    pub async fn apply_altitude_from<S: AltitudeSource>(
+        &mut self,
+        source: &mut S,
+        persist: bool,
+    ) -> Result<Option<u16>, Error<E>> {
+        self.inner.apply_altitude_from(source, persist).await
+    }
+
     /// The `set_ambient_pressure()` command can be sent during periodic
     /// measurements to enable continuous pressure compensation. Note that
     /// setting an ambient pressure overrides any pressure compensation
@@ -331,6 +1327,15 @@ where
         self.inner.set_ambient_pressure(pressure).await
     }
 
+    /// Set the ambient pressure from a value in hPa, e.g. as reported by a
+    /// barometer. The pressure is rounded to the nearest hPa, which is the
+    /// resolution of the sensor. Returns `Error::InvalidInput` if the
+    /// pressure is not finite or is outside of the valid range of
+    /// 700-1200 HPa after the rounding.
+    pub async fn set_ambient_pressure_f32(&mut self, pressure: f32) -> Result<(), Error<E>> {
+        self.inner.set_ambient_pressure_f32(pressure).await
+    }
+
     /// The `get_ambient_pressure` command can be sent during periodic
     /// measurements to read out the previously  saved ambient pressure value
     /// set by the `set_ambient_pressure` command.
@@ -384,6 +1389,9 @@ where
     ///
     /// An `Ok(Some(_))` value indicates that the FRC was applied. It contains
     /// the magnitude of the correction
+    ///
+    /// Returns `Error::InsufficientRuntime` if the periodic measurement has
+    /// not been running for 3 minutes (see [Self::set_frc_runtime_check]).
     pub async fn perform_forced_recalibration(
         &mut self,
         ppm_co2: u16,
@@ -391,6 +1399,126 @@ where
         self.inner.perform_forced_recalibration(ppm_co2).await
     }
 
+    /// Run the forced recalibration (FRC) procedure described in the
+    /// datasheet: operate the sensor in periodic mode for 3 minutes, stop
+    /// the periodic measurement, perform the FRC with the given reference
+    /// and take a measurement after the recalibration. The sensor must be
+    /// exposed to a homogenous and constant CO2 concentration of
+    /// `reference_ppm` for the whole duration of the procedure.
+    ///
+    /// The periodic measurement is started if needed and is left in the
+    /// same state as before the call, including the low power periodic mode,
+    /// also when the procedure fails. Returns `Error::CalibrationFailed` if
+    /// the sensor rejected the recalibration.
+    pub async fn run_frc_procedure(&mut self, reference_ppm: u16) -> Result<FrcReport, Error<E>> {
+        self.inner.run_frc_procedure(reference_ppm).await
+    }
+
+    /// Determine and apply the temperature offset compensating the
+    /// self-heating of the sensor in its final enclosure (Section 3.7.1):
+    ///
+    /// `offset = T_scd4x - T_reference + offset_previous`
+    ///
+    /// The sensor and the reference are sampled `samples` times in periodic
+    /// mode and the means are used for the calculation. The sensor should
+    /// be in thermal equilibrium before the tuning. When `persist` is set,
+    /// the new offset is stored in the EEPROM.
+    ///
+    /// The periodic measurement is left in the same state as before the
+    /// call, including the low power periodic mode, also when the tuning
+    /// fails. Returns `Error::InvalidInput` if `samples` is zero and
+    /// `Error::CalibrationFailed` if the calculated offset is outside of the
+    /// range of 0 - 20 degrees Celsius recommended by the datasheet.
+    pub async fn tune_temperature_offset<R: ReferenceTemperature>(
+        &mut self,
+        reference: &mut R,
+        samples: u16,
+        persist: bool,
+    ) -> Result<TemperatureOffsetReport, Error<E>> {
+        self.inner
+            .tune_temperature_offset(reference, samples, persist)
+            .await
+    }
+
+    /// Perform a forced recalibration, handling the automatic self-calibration
+    /// according to the given [CalibrationPolicy]. Returns
+    /// `Error::NotAllowed` if the ASC is currently enabled and the policy does
+    /// not allow disabling it. An ASC correction that already happened is not
+    /// detected. The rest is the same as [Self::perform_forced_recalibration].
+    pub async fn perform_forced_recalibration_with_policy(
+        &mut self,
+        ppm_co2: u16,
+        policy: CalibrationPolicy,
+    ) -> Result<Option<i16>, Error<E>> {
+        self.inner
+            .perform_forced_recalibration_with_policy(ppm_co2, policy)
+            .await
+    }
+
+    /// Create a [CalibrationRecord] for the forced recalibration described
+    /// by `report`, e.g. as returned by [Self::run_frc_procedure]. The serial
+    /// number and the ASC state are read from the sensor, so the periodic
+    /// measurement must be stopped. The `timestamp` is stored as is.
+    pub async fn calibration_record(
+        &mut self,
+        report: &FrcReport,
+        timestamp: u64,
+    ) -> Result<CalibrationRecord, Error<E>> {
+        self.inner.calibration_record(report, timestamp).await
+    }
+
+    /// Perform a field calibration in an environment with a known and
+    /// stable CO2 concentration. Collects `config.samples` measurements in
+    /// periodic mode and performs a forced recalibration only if their
+    /// standard deviation does not exceed `config.max_stddev_ppm`. Otherwise
+    /// the run is rejected and the report contains no correction.
+    ///
+    /// The periodic measurement is left in the same state as before the call,
+    /// including the low power periodic mode, also when the run fails.
+    /// Returns `Error::InvalidInput` if the samples do not cover the 3 minutes
+    /// of operation required before a forced recalibration and
+    /// `Error::CalibrationFailed` if the sensor rejects the recalibration.
+    pub async fn run_field_calibration(
+        &mut self,
+        reference_ppm: u16,
+        config: FieldCalibrationConfig,
+    ) -> Result<FieldCalibrationReport, Error<E>> {
+        self.inner
+            .run_field_calibration(reference_ppm, config)
+            .await
+    }
+
+    /// Enable or disable the automatic self-calibration according to the
+    /// given [AscScheduler] and the current time. The sensor is reconfigured
+    /// only if the state differs from the one last applied by the scheduler,
+    /// in which case a running periodic measurement is stopped and restarted.
+    /// Returns whether the ASC is enabled.
+    ///
+    /// Should be called periodically, e.g. after each measurement.
+    pub async fn update_asc_schedule<C: TimeSource>(
+        &mut self,
+        scheduler: &mut AscScheduler<'_>,
+        clock: &mut C,
+    ) -> Result<bool, Error<E>> {
+        self.inner.update_asc_schedule(scheduler, clock).await
+    }
+
+    /// Apply the given settings back-to-back as one batch. A running periodic
+    /// measurement is stopped once before the batch and restarted after it.
+    /// When `persist` is set, the settings are stored in the EEPROM at the
+    /// end of the batch.
+    ///
+    /// All settings are validated before any of them is sent to the sensor.
+    /// Returns `Error::InvalidInput` if any of them is invalid. If a command
+    /// fails, the remaining settings are not applied.
+    pub async fn apply_settings(
+        &mut self,
+        settings: &[Scd4xSetting],
+        persist: bool,
+    ) -> Result<(), Error<E>> {
+        self.inner.apply_settings(settings, persist).await
+    }
+
     /// Configuration settings such as the temperature offset, sensor altitude
     /// and the ASC enabled/disabled parameters are by default stored in the
     /// volatile memory (RAM) only and will be lost after a power-cycle.
@@ -403,18 +1531,92 @@ where
         self.inner.persists_settings().await
     }
 
+    /// Read the configuration of the sensor and check it for inconsistent
+    /// or suspicious values, e.g. a zero temperature offset of a sensor
+    /// mounted in an enclosure, or an ASC target far from the 400 PPM of the
+    /// fresh outdoor air. `enclosed` tells if the sensor is mounted in an
+    /// enclosure. Can be sent only when the sensor is in idle mode.
+    ///
+    /// The ambient pressure is taken from the value set through the driver,
+    /// because the sensor reports the default pressure when none was set.
+    pub async fn validate_configuration(
+        &mut self,
+        enclosed: bool,
+    ) -> Result<ConfigurationReport, Error<E>> {
+        self.inner.validate_configuration(enclosed).await
+    }
+
     /// Reading out the serial number can be used to identify the chip
     /// and to verify the presence of the sensor.
     pub async fn serial_number(&mut self) -> Result<u64, Error<E>> {
         self.inner.serial_number().await
     }
 
+    /// Read the sensor variant. This can be used to verify that the device
+    /// connected to the bus is the expected SCD4x sensor. Returns
+    /// `Error::WrongDevice` if the response does not match a known variant.
+    pub async fn get_sensor_variant(&mut self) -> Result<SensorVariant, Error<E>> {
+        self.inner.get_sensor_variant().await
+    }
+
+    /// Read the raw sensor variant word, which also carries the firmware
+    /// revision of the sensor, e.g. to enable workarounds for specific
+    /// firmware versions.
+    pub async fn get_sensor_variant_info(&mut self) -> Result<SensorVariantInfo, Error<E>> {
+        self.inner.get_sensor_variant_info().await
+    }
+
+    /// Collect the details of the sensor and the counters of the driver into
+    /// one [Diagnostics] dump, e.g. to attach it to a support ticket. The
+    /// periodic measurement is not interrupted, so the details that can be
+    /// read only in idle mode are missing while it's running.
+    pub async fn diagnostics(&mut self) -> Diagnostics {
+        self.inner.diagnostics().await
+    }
+
+    /// Execute a [SensorCommand], e.g. one added by a newer firmware of the
+    /// sensor, and decode its response. The command is sent like the
+    /// built-in ones, so it's rejected with `Error::NotAllowed` during the
+    /// periodic measurement unless it allows it, and the CRC of the response
+    /// is verified. Returns `Error::InvalidInput` if the command expects more
+    /// than [MAX_RESPONSE_WORDS] response words.
+    pub async fn execute<C: SensorCommand + ?Sized>(
+        &mut self,
+        command: &C,
+    ) -> Result<C::Response, Error<E>> {
+        self.inner.execute(command).await
+    }
+
     /// The `perform_self_test()` command can be used as an end-of-line
     /// test to check the sensor functionality.
     pub async fn perform_self_test(&mut self) -> Result<bool, Error<E>> {
         self.inner.perform_self_test().await
     }
 
+    /// Same as `perform_self_test()`, but a detected malfunction is reported
+    /// as `Error::SelfTestFailed` carrying the raw status word returned by
+    /// the sensor.
+    pub async fn perform_self_test_strict(&mut self) -> Result<(), Error<E>> {
+        self.inner.perform_self_test_strict().await
+    }
+
+    /// Run the self-test if the application declares the sensor `idle`, i.e.
+    /// no measurements are needed for the next 10 seconds, and the given
+    /// [SelfTestScheduler] considers it due at `now_ms`. A running periodic
+    /// measurement is stopped for the self-test and restarted after it.
+    ///
+    /// Returns the record of the self-test, or `None` if it was not run.
+    pub async fn run_scheduled_self_test(
+        &mut self,
+        scheduler: &mut SelfTestScheduler,
+        now_ms: u64,
+        idle: bool,
+    ) -> Result<Option<SelfTestRecord>, Error<E>> {
+        self.inner
+            .run_scheduled_self_test(scheduler, now_ms, idle)
+            .await
+    }
+
     /// The perform_factory_reset command resets all configuration
     /// settings stored in the EEPROM and erases the FRC and ASC
     /// algorithm history.
@@ -422,6 +1624,43 @@ where
         self.inner.perform_factory_reset().await
     }
 
+    /// Same as `perform_self_test()`, but the given callback receives the
+    /// progress of the 10 seconds self-test every
+    /// [PROGRESS_INTERVAL_MILLIS] and may abandon it with `Error::Aborted`
+    pub async fn perform_self_test_with_progress<P: ProgressCallback>(
+        &mut self,
+        mut progress: P,
+    ) -> Result<bool, Error<E>> {
+        self.inner
+            .perform_self_test_with_progress(&mut progress)
+            .await
+    }
+
+    /// Same as `perform_factory_reset()`, but the given callback receives
+    /// the progress of the reset every [PROGRESS_INTERVAL_MILLIS] and may
+    /// abandon it with `Error::Aborted`
+    pub async fn perform_factory_reset_with_progress<P: ProgressCallback>(
+        &mut self,
+        mut progress: P,
+    ) -> Result<(), Error<E>> {
+        self.inner
+            .perform_factory_reset_with_progress(&mut progress)
+            .await
+    }
+
+    /// Same as `perform_forced_recalibration()`, but the given callback
+    /// receives the progress of the recalibration every
+    /// [PROGRESS_INTERVAL_MILLIS] and may abandon it with `Error::Aborted`
+    pub async fn perform_forced_recalibration_with_progress<P: ProgressCallback>(
+        &mut self,
+        ppm_co2: u16,
+        mut progress: P,
+    ) -> Result<Option<i16>, Error<E>> {
+        self.inner
+            .perform_forced_recalibration_with_progress(ppm_co2, &mut progress)
+            .await
+    }
+
     /// The reinit command reinitializes the sensor by reloading user
     /// settings from EEPROM. Before sending the reinit command, the
     /// `stop_periodic_measurement()` command must be issued.
@@ -439,6 +1678,19 @@ where
         self.inner.measure_single_shot().await
     }
 
+    /// Same as `measure_single_shot()`, but the given callback receives the
+    /// progress of the 5 seconds measurement every
+    /// [PROGRESS_INTERVAL_MILLIS] and may abandon the wait with
+    /// `Error::Aborted`
+    pub async fn measure_single_shot_with_progress<P: ProgressCallback>(
+        &mut self,
+        mut progress: P,
+    ) -> Result<(), Error<E>> {
+        self.inner
+            .measure_single_shot_with_progress(&mut progress)
+            .await
+    }
+
     /// On-demand measurement of relative humidity and temperature only.
     /// The sensor output is read out by using the `read_measurement()`
     /// command (Section 3.5.2). CO2 output is returned as 0 ppm.
@@ -494,27 +1746,187 @@ where
     }
 }
 
-struct Scd4x<I2C, D> {
-    i2c: I2C,
-    delay: D,
+#[cfg(feature = "scd41")]
+impl<T, E> Co2Sensor for Scd41<T>
+where
+    T: Transport<Error = E>,
+{
+    type Error = E;
+
+    async fn start_measurement(&mut self) -> Result<(), Error<E>> {
+        self.start_periodic_measurement().await
+    }
+
+    async fn stop_measurement(&mut self) -> Result<(), Error<E>> {
+        self.stop_periodic_measurement().await
+    }
+
+    async fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        self.inner.data_ready().await
+    }
+
+    async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.inner.read_measurement().await
+    }
+}
+
+/// Identifies the sensor by its serial number. Only the settings kept by the
+/// volatile settings cache are restored, see
+/// [Scd41::set_volatile_settings_cache]
+#[cfg(feature = "scd41")]
+impl<T, E> Reattach for Scd41<T>
+where
+    T: Transport<Error = E>,
+{
+    async fn reattach(&mut self) -> Result<(), Error<E>> {
+        self.inner.reattach().await
+    }
+}
+
+// Unlike the blocking driver, this is instantiated for each transport type:
+// async trait methods cannot be called through a `dyn Transport`.
+struct Scd4x<T> {
+    transport: T,
     measurement_started: bool,
+    low_power: bool,
+    single_shot_us: Option<u32>,
+    warm_up_samples: u8,
+    runtime_micros: u32,
+    measurements: u32,
+    operating_ms: u64,
+    operating_tick: Option<u64>,
+    operating_hours: u64,
+    frc_runtime_check: bool,
+    verify_crc: bool,
+    crc_failures: u8,
+    crc_recovery_threshold: u8,
+    counters: DiagnosticCounters,
+    last_error: Option<ErrorKind>,
+    volatile: Option<VolatileSettings>,
+    pending: VolatileSettings,
+    reset_suspected: bool,
+    altitude: Option<u16>,
+    ambient_pressure: Option<u16>,
+    compensation_check: bool,
+    correction: Option<MeasurementCorrection>,
+    busy_us: u32,
 }
 
-impl<I2C, D, E> Scd4x<I2C, D>
+impl<T, E> Scd4x<T>
 where
-    I2C: I2c<Error = E>,
-    D: DelayNs,
+    T: Transport<Error = E>,
 {
-    fn new(i2c: I2C, delay: D) -> Self {
+    fn new(transport: T) -> Self {
         Self {
-            i2c,
-            delay,
+            transport,
             measurement_started: false,
+            low_power: false,
+            single_shot_us: None,
+            warm_up_samples: 0,
+            runtime_micros: 0,
+            measurements: 0,
+            operating_ms: 0,
+            operating_tick: None,
+            operating_hours: 0,
+            frc_runtime_check: true,
+            verify_crc: true,
+            crc_failures: 0,
+            crc_recovery_threshold: 0,
+            counters: DiagnosticCounters::default(),
+            last_error: None,
+            volatile: None,
+            pending: VolatileSettings::default(),
+            reset_suspected: false,
+            altitude: None,
+            ambient_pressure: None,
+            compensation_check: false,
+            correction: None,
+            busy_us: 0,
+        }
+    }
+
+    fn update_operating_time(&mut self, now_ms: u64) -> bool {
+        if let Some(tick) = self.operating_tick {
+            if self.measurement_started {
+                let elapsed = now_ms.saturating_sub(tick);
+                self.operating_ms = self.operating_ms.saturating_add(elapsed);
+            }
+        }
+        self.operating_tick = self.measurement_started.then_some(now_ms);
+
+        let hours = self.operating_ms / OPERATING_HOUR_MILLIS;
+        let completed = hours != self.operating_hours;
+        self.operating_hours = hours;
+        completed
+    }
+
+    fn set_operating_time(&mut self, operating_time: Duration) {
+        self.operating_ms = u64::try_from(operating_time.as_millis()).unwrap_or(u64::MAX);
+        self.operating_hours = self.operating_ms / OPERATING_HOUR_MILLIS;
+    }
+
+    fn state(&self, address: u8) -> DriverState {
+        DriverState {
+            address,
+            measurement_started: self.measurement_started,
+            low_power: self.low_power,
+            single_shot_us: self.single_shot_us,
+            warm_up_samples: self.warm_up_samples,
+            runtime_micros: self.runtime_micros,
+            measurements: self.measurements,
+            operating_ms: self.operating_ms,
+            operating_tick: self.operating_tick,
+            frc_runtime_check: self.frc_runtime_check,
+            verify_crc: self.verify_crc,
+            crc_recovery_threshold: self.crc_recovery_threshold,
+            counters: self.counters,
+            last_error: self.last_error,
+            volatile: self.volatile,
+            pending: self.pending,
+            altitude: self.altitude,
+            ambient_pressure: self.ambient_pressure,
+            compensation_check: self.compensation_check,
+            correction: self.correction,
         }
     }
 
-    fn release(self) -> I2C {
-        self.i2c
+    fn restore_state(&mut self, state: &DriverState) {
+        self.measurement_started = state.measurement_started;
+        self.low_power = state.low_power;
+        self.single_shot_us = state.single_shot_us;
+        self.warm_up_samples = state.warm_up_samples;
+        self.runtime_micros = state.runtime_micros;
+        self.measurements = state.measurements;
+        self.operating_ms = state.operating_ms;
+        self.operating_tick = state.operating_tick;
+        self.operating_hours = state.operating_ms / OPERATING_HOUR_MILLIS;
+        self.frc_runtime_check = state.frc_runtime_check;
+        self.verify_crc = state.verify_crc;
+        self.crc_recovery_threshold = state.crc_recovery_threshold;
+        self.counters = state.counters;
+        self.last_error = state.last_error;
+        self.volatile = state.volatile;
+        self.pending = state.pending;
+        self.altitude = state.altitude;
+        self.ambient_pressure = state.ambient_pressure;
+        self.compensation_check = state.compensation_check;
+        self.correction = state.correction;
+    }
+
+    fn measurement_interval_ms(&self) -> Option<u32> {
+        if self.measurement_started {
+            Some(self.periodic_interval_ms())
+        } else {
+            self.single_shot_us.map(|us| us / 1000)
+        }
+    }
+
+    fn periodic_interval_ms(&self) -> u32 {
+        if self.low_power {
+            LOW_POWER_PERIODIC_MEASUREMENT_INTERVAL_MILLIS
+        } else {
+            PERIODIC_MEASUREMENT_INTERVAL_MILLIS
+        }
     }
 
     fn check_is_command_allowed(&self, cmd: Command) -> Result<(), Error<E>> {
@@ -525,43 +1937,173 @@ where
         Ok(())
     }
 
+    fn set_crc_recovery_threshold(&mut self, threshold: u8) {
+        self.crc_recovery_threshold = threshold;
+        self.crc_failures = 0;
+    }
+
     async fn read_response(&mut self, read_buf: &mut [u8]) -> Result<(), Error<E>> {
-        i2c_read(&mut self.i2c, I2C_ADDRESS, read_buf).await
+        let result = read_frame(&mut self.transport, read_buf, self.verify_crc).await;
+        if let Err(e) = &result {
+            self.record_error(e);
+        }
+
+        match &result {
+            Ok(()) => self.crc_failures = 0,
+            Err(Error::CRC(_)) => self.on_crc_failure().await?,
+            Err(Error::I2C(_)) => self.reset_suspected = true,
+            Err(_) => {}
+        }
+
+        result
     }
 
-    async fn write_command(&mut self, cmd: Command) -> Result<(), Error<E>> {
-        self.check_is_command_allowed(cmd)?;
+    async fn on_crc_failure(&mut self) -> Result<(), Error<E>> {
+        self.crc_failures = self.crc_failures.saturating_add(1);
+        if self.crc_recovery_threshold == 0 || self.crc_failures < self.crc_recovery_threshold {
+            return Ok(());
+        }
 
-        let buf = cmd.prepare();
-        i2c_write(&mut self.i2c, I2C_ADDRESS, &buf).await?;
-        self.delay.delay_ms(cmd.exec_time as u32).await;
+        warn!(
+            "{} consecutive CRC failures, recovering the sensor",
+            self.crc_failures
+        );
+        self.crc_failures = 0;
+        self.counters.recoveries = self.counters.recoveries.saturating_add(1);
+        self.recover().await?;
+        Err(Error::Recovered)
+    }
 
-        Ok(())
+    fn record_error(&mut self, error: &Error<E>) {
+        self.last_error = Some(self.counters.record(error));
     }
 
-    async fn write_command_with_data(&mut self, cmd: Command, data: u16) -> Result<(), Error<E>> {
-        self.check_is_command_allowed(cmd)?;
+    async fn recover(&mut self) -> Result<(), Error<E>> {
+        let was_running = self.measurement_started;
 
-        let buf = cmd.prepare_with_data(data);
-        i2c_write(&mut self.i2c, I2C_ADDRESS, &buf).await?;
-        self.delay.delay_ms(cmd.exec_time as u32).await;
+        self.stop_periodic_measurement().await?;
+        self.reinit().await?;
+        self.restore_volatile_settings().await?;
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
 
         Ok(())
     }
 
-    async fn command_with_response(
-        &mut self,
-        cmd: Command,
-        buf: &mut [u8],
-    ) -> Result<(), Error<E>> {
-        self.write_command(cmd).await?;
-        self.read_response(buf).await
+    async fn delay_ms(&mut self, ms: u32) {
+        self.transport.delay_ms(ms).await;
+        self.track_runtime(ms.saturating_mul(1000));
     }
 
-    async fn command_with_data_and_response(
-        &mut self,
-        cmd: Command,
-        data: u16,
+    async fn delay_us(&mut self, us: u32) {
+        self.transport.delay_us(us).await;
+        self.track_runtime(us);
+    }
+
+    fn track_runtime(&mut self, us: u32) {
+        if self.measurement_started {
+            self.runtime_micros = self.runtime_micros.saturating_add(us);
+        }
+    }
+
+    // The time the periodic measurement has been running, as observed by the
+    // driver: the delays it performed and the measurements it read
+    fn runtime_millis(&self) -> u32 {
+        let measured = self
+            .measurements
+            .saturating_mul(PERIODIC_MEASUREMENT_INTERVAL_MILLIS);
+        (self.runtime_micros / 1000).max(measured)
+    }
+
+    async fn wait_while_busy(&mut self) {
+        // A command abandoned by its progress callback is still executed by
+        // the sensor, which does not accept other commands until it is done
+        if self.busy_us > 0 {
+            let us = core::mem::take(&mut self.busy_us);
+            self.delay_us(us).await;
+        }
+    }
+
+    async fn send(&mut self, frame: &[u8]) -> Result<(), Error<E>> {
+        self.wait_while_busy().await;
+
+        let result = write_frame(&mut self.transport, frame).await;
+        if let Err(e) = &result {
+            self.reset_suspected = true;
+            self.record_error(e);
+        }
+
+        result
+    }
+
+    async fn write_command(&mut self, cmd: Command) -> Result<(), Error<E>> {
+        self.check_is_command_allowed(cmd)?;
+
+        let buf = cmd.prepare();
+        self.send(&buf).await?;
+        self.delay_us(cmd.exec_time_us).await;
+
+        Ok(())
+    }
+
+    async fn write_command_with_data(&mut self, cmd: Command, data: u16) -> Result<(), Error<E>> {
+        self.check_is_command_allowed(cmd)?;
+
+        let buf = cmd.prepare_with_data(data);
+        self.send(&buf).await?;
+        self.delay_us(cmd.exec_time_us).await;
+
+        Ok(())
+    }
+
+    async fn write_command_with_progress<P: ProgressCallback>(
+        &mut self,
+        cmd: Command,
+        data: Option<u16>,
+        progress: &mut P,
+    ) -> Result<(), Error<E>> {
+        self.check_is_command_allowed(cmd)?;
+
+        match data {
+            Some(data) => self.send(&cmd.prepare_with_data(data)).await?,
+            None => self.send(&cmd.prepare()).await?,
+        }
+
+        let total_ms = cmd.exec_time_ms();
+        let mut elapsed_ms = 0;
+        while elapsed_ms < total_ms {
+            let step = (total_ms - elapsed_ms).min(PROGRESS_INTERVAL_MILLIS);
+            self.delay_us(step * 1_000).await;
+            elapsed_ms += step;
+
+            let flow = progress.on_progress(Progress {
+                elapsed_ms,
+                total_ms,
+            });
+            if flow.is_break() && elapsed_ms < total_ms {
+                self.busy_us = (total_ms - elapsed_ms) * 1_000;
+                return Err(Error::Aborted);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn command_with_response(
+        &mut self,
+        cmd: Command,
+        buf: &mut [u8],
+    ) -> Result<(), Error<E>> {
+        self.write_command(cmd).await?;
+        self.read_response(buf).await
+    }
+
+    async fn command_with_data_and_response(
+        &mut self,
+        cmd: Command,
+        data: u16,
         read_buf: &mut [u8],
     ) -> Result<(), Error<E>> {
         self.write_command_with_data(cmd, data).await?;
@@ -571,21 +2113,299 @@ where
     async fn start_periodic_measurement(&mut self) -> Result<(), Error<E>> {
         self.write_command(START_PERIODIC_MEASUREMENT).await?;
         self.measurement_started = true;
+        self.low_power = false;
+        self.single_shot_us = None;
+        self.runtime_micros = 0;
+        self.measurements = 0;
+        self.operating_tick = None;
         Ok(())
     }
 
+    // Restart the measurement in the mode it was last started in
+    async fn resume_measurement(&mut self) -> Result<(), Error<E>> {
+        if self.low_power {
+            self.start_low_power_periodic_measurement().await
+        } else {
+            self.start_periodic_measurement().await
+        }
+    }
+
     async fn stop_periodic_measurement(&mut self) -> Result<(), Error<E>> {
         self.write_command(STOP_PERIODIC_MEASUREMENT).await?;
         self.measurement_started = false;
+
+        // The settings that fail stay queued for the next opportunity
+        if self.write_pending_settings().await.is_err() {
+            warn!("failed to write the pending settings");
+        }
+
+        Ok(())
+    }
+
+    async fn force_stop(&mut self) {
+        let buf = STOP_PERIODIC_MEASUREMENT.prepare();
+        // Ignore the result: the sensor may not acknowledge the command,
+        // e.g. when it's still busy with the previous one
+        let _ = write_frame(&mut self.transport, &buf).await;
+        self.delay_us(STOP_PERIODIC_MEASUREMENT.exec_time_us).await;
+        self.measurement_started = false;
+    }
+
+    async fn reset_to_known_state(&mut self) -> Result<(), Error<E>> {
+        self.force_stop().await;
+        self.reinit().await?;
+
+        // The sensor runs with its EEPROM settings now
+        if let Some(volatile) = &mut self.volatile {
+            *volatile = VolatileSettings::default();
+        }
+        self.reset_suspected = false;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "scd41")]
+    async fn wake_up_unacknowledged(&mut self) {
+        let buf = WAKE_UP.prepare();
+        // Ignore the result: the sensor does not acknowledge the command
+        let _ = write_frame(&mut self.transport, &buf).await;
+        self.delay_us(WAKE_UP.exec_time_us).await;
+    }
+
+    async fn ensure_idle(&mut self) -> Result<bool, Error<E>> {
+        let was_running = self.measurement_started;
+        if was_running {
+            self.stop_periodic_measurement().await?;
+        }
+
+        Ok(was_running)
+    }
+
+    fn set_volatile_settings_cache(&mut self, enabled: bool) {
+        self.volatile = enabled.then(VolatileSettings::default);
+        self.reset_suspected = false;
+    }
+
+    fn remember(&mut self, setting: Scd4xSetting) {
+        if let Some(volatile) = &mut self.volatile {
+            volatile.record(setting);
+        }
+    }
+
+    async fn reapply_volatile_settings(&mut self) -> Result<(), Error<E>> {
+        if self.volatile.is_none_or(|volatile| volatile.is_empty()) {
+            return Ok(());
+        }
+
+        let was_running = self.ensure_idle().await?;
+        let result = self.restore_volatile_settings().await;
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
+
+        result
+    }
+
+    // The sensor must be idle
+    async fn restore_volatile_settings(&mut self) -> Result<(), Error<E>> {
+        let Some(volatile) = self.volatile else {
+            return Ok(());
+        };
+
+        // The settings were accepted when they were first written
+        let check = core::mem::replace(&mut self.compensation_check, false);
+
+        let mut result = Ok(());
+        for setting in volatile.settings() {
+            result = self.apply_setting(setting).await;
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.compensation_check = check;
+        result
+    }
+
+    async fn defer_setting(&mut self, setting: Scd4xSetting) -> Result<bool, Error<E>> {
+        if !setting.is_valid() {
+            return Err(Error::InvalidInput);
+        }
+
+        // The ambient pressure is the only setting accepted during the
+        // periodic measurement
+        let allowed =
+            !self.measurement_started || matches!(setting, Scd4xSetting::AmbientPressure(_));
+        if !allowed {
+            self.pending.record(setting);
+            return Ok(false);
+        }
+
+        self.apply_setting(setting).await?;
+        Ok(true)
+    }
+
+    async fn apply_pending_settings(&mut self) -> Result<(), Error<E>> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let was_running = self.ensure_idle().await?;
+        let result = self.write_pending_settings().await;
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
+
+        result
+    }
+
+    // The sensor must be idle
+    async fn write_pending_settings(&mut self) -> Result<(), Error<E>> {
+        let pending = core::mem::take(&mut self.pending);
+
+        let mut settings = pending.settings();
+        while let Some(setting) = settings.next() {
+            if let Err(e) = self.apply_setting(setting).await {
+                self.pending.record(setting);
+                settings.for_each(|setting| self.pending.record(setting));
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn apply_setting(&mut self, setting: Scd4xSetting) -> Result<(), Error<E>> {
+        match setting {
+            // The setting is either validated or restored from the cache, which
+            // may hold an offset written without the range check
+            Scd4xSetting::TemperatureOffset(offset) => {
+                self.set_temperature_offset_unchecked(offset).await
+            }
+            Scd4xSetting::SensorAltitude(altitude) => self.set_sensor_altitude(altitude).await,
+            Scd4xSetting::AmbientPressure(pressure) => self.set_ambient_pressure(pressure).await,
+            Scd4xSetting::AutomaticSelfCalibration(enabled) => {
+                self.enable_automatic_self_calibration(enabled).await
+            }
+            Scd4xSetting::AutomaticSelfCalibrationTarget(ppm_co2) => {
+                self.set_automatic_self_calibration_target(ppm_co2).await
+            }
+        }
+    }
+
+    async fn check_sensor_reset(&mut self) -> Result<bool, Error<E>> {
+        self.reset_suspected = false;
+        if !self.sensor_was_reset().await? {
+            return Ok(false);
+        }
+
+        warn!("the sensor has restarted, restoring its volatile settings");
+        // The sensor restarts in idle mode
+        let was_running = self.measurement_started;
+        self.measurement_started = false;
+        self.restore_volatile_settings().await?;
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
+
+        Ok(true)
+    }
+
+    async fn reattach(&mut self) -> Result<(), Error<E>> {
+        // The sensor is idle after it was powered up
+        let was_running = self.measurement_started;
+        self.measurement_started = false;
+
+        let result = self.restore_after_power_up(was_running).await;
+        if result.is_err() {
+            self.measurement_started = was_running;
+        }
+
+        result
+    }
+
+    async fn restore_after_power_up(&mut self, was_running: bool) -> Result<(), Error<E>> {
+        // Identify the sensor before writing to it
+        self.serial_number().await?;
+        self.crc_failures = 0;
+        self.reset_suspected = false;
+        self.restore_volatile_settings().await?;
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
+
         Ok(())
     }
 
+    async fn sensor_was_reset(&mut self) -> Result<bool, Error<E>> {
+        if self.measurement_started {
+            // The sensor does not accept this command during the periodic
+            // measurement, so it has restarted if it does
+            let buf = GET_SERIAL_NUMBER.prepare();
+            if write_frame(&mut self.transport, &buf).await.is_err() {
+                return Ok(false);
+            }
+
+            self.delay_us(GET_SERIAL_NUMBER.exec_time_us).await;
+            self.read_response(&mut [0; 9]).await?;
+            return Ok(true);
+        }
+
+        // In idle mode the reset can only be detected by the settings that
+        // have been lost. The temperature offset is not compared, because
+        // it does not survive the round trip exactly
+        let Some(volatile) = self.volatile else {
+            return Ok(false);
+        };
+
+        if let Some(pressure) = volatile.ambient_pressure {
+            if self.get_ambient_pressure().await? != pressure {
+                return Ok(true);
+            }
+        }
+
+        if let Some(altitude) = volatile.sensor_altitude {
+            if self.get_sensor_altitude().await? != altitude {
+                return Ok(true);
+            }
+        }
+
+        if let Some(enabled) = volatile.automatic_self_calibration {
+            if self.get_automatic_self_calibration().await? != enabled {
+                return Ok(true);
+            }
+        }
+
+        if let Some(target) = volatile.automatic_self_calibration_target {
+            if self.get_automatic_self_calibration_target().await? != target {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
     async fn start_low_power_periodic_measurement(&mut self) -> Result<(), Error<E>> {
         self.write_command(START_LOW_POWER_PERIODIC_MEASUREMENT)
-            .await
+            .await?;
+        self.measurement_started = true;
+        self.low_power = true;
+        self.single_shot_us = None;
+        self.runtime_micros = 0;
+        self.measurements = 0;
+        self.operating_tick = None;
+        Ok(())
     }
 
     async fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        if self.reset_suspected && self.volatile.is_some() {
+            self.check_sensor_reset().await?;
+        }
+
         let mut buf = [0; 3];
         self.command_with_response(GET_DATA_READY_STATUS, &mut buf)
             .await?;
@@ -595,16 +2415,45 @@ where
     }
 
     async fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
-        let mut buf = [0; 9];
-        self.command_with_response(READ_MEASUREMENT, &mut buf)
-            .await?;
-        Ok(decode_measurement(buf))
+        self.read_measurement_with_buffer(&mut [0; 9]).await
+    }
+
+    async fn read_measurement_with_buffer(
+        &mut self,
+        buf: &mut [u8; 9],
+    ) -> Result<Measurement, Error<E>> {
+        self.command_with_response(READ_MEASUREMENT, buf).await?;
+        if is_all_zero(buf) {
+            debug!("the sensor responded with a measurement frame of zeros");
+            return Err(Error::NoData);
+        }
+
+        self.counters.measurements = self.counters.measurements.saturating_add(1);
+        if self.measurement_started {
+            self.measurements = self.measurements.saturating_add(1);
+        }
+
+        let measurement = decode_measurement(*buf);
+        Ok(match &self.correction {
+            Some(correction) => correction.apply(measurement),
+            None => measurement,
+        })
     }
 
     async fn set_temperature_offset(&mut self, offset: f32) -> Result<(), Error<E>> {
+        if !Scd4xSetting::TemperatureOffset(offset).is_valid() {
+            return Err(Error::InvalidInput);
+        }
+
+        self.set_temperature_offset_unchecked(offset).await
+    }
+
+    async fn set_temperature_offset_unchecked(&mut self, offset: f32) -> Result<(), Error<E>> {
         let value = encode_temperature_offset(offset)?;
         self.write_command_with_data(SET_TEMPERATURE_OFFSET, value)
-            .await
+            .await?;
+        self.remember(Scd4xSetting::TemperatureOffset(offset));
+        Ok(())
     }
 
     async fn get_temperature_offset(&mut self) -> Result<f32, Error<E>> {
@@ -614,13 +2463,66 @@ where
         Ok(decode_temperature_offset(buf))
     }
 
+    async fn set_temperature_offset_ticks(&mut self, ticks: u16) -> Result<(), Error<E>> {
+        self.write_command_with_data(SET_TEMPERATURE_OFFSET, ticks)
+            .await?;
+        self.remember(Scd4xSetting::TemperatureOffset(
+            temperature_offset_from_ticks(ticks),
+        ));
+        Ok(())
+    }
+
+    async fn get_temperature_offset_ticks(&mut self) -> Result<u16, Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(GET_TEMPERATURE_OFFSET, &mut buf)
+            .await?;
+
+        Ok(u16::from_be_bytes([buf[0], buf[1]]))
+    }
+
+    async fn adjust_temperature_offset(&mut self, delta: f32) -> Result<f32, Error<E>> {
+        if !delta.is_finite() {
+            return Err(Error::InvalidInput);
+        }
+
+        let offset = (self.get_temperature_offset().await? + delta).max(0.0);
+        self.set_temperature_offset(offset).await?;
+
+        Ok(offset)
+    }
+
+    fn active_compensation(&self) -> Compensation {
+        match (self.ambient_pressure, self.altitude) {
+            (Some(pressure), _) => Compensation::AmbientPressure(pressure),
+            (None, Some(altitude)) => Compensation::Altitude(altitude),
+            (None, None) => Compensation::None,
+        }
+    }
+
+    // The sensor ignores the altitude once an ambient pressure is given, so
+    // setting both is most likely a configuration mistake
+    fn check_compensation_conflict(&self, other: Option<u16>) -> Result<(), Error<E>> {
+        if other.is_some() {
+            warn!("both the altitude and the ambient pressure compensation are set");
+            if self.compensation_check {
+                return Err(Error::CompensationConflict);
+            }
+        }
+
+        Ok(())
+    }
+
     async fn set_sensor_altitude(&mut self, altitude: u16) -> Result<(), Error<E>> {
         if altitude > MAX_ALTITUDE {
             return Err(Error::InvalidInput);
         }
 
+        self.check_compensation_conflict(self.ambient_pressure)?;
         self.write_command_with_data(SET_SENSOR_ALTITUDE, altitude)
-            .await
+            .await?;
+        self.remember(Scd4xSetting::SensorAltitude(altitude));
+        self.altitude = Some(altitude);
+        Ok(())
     }
 
     async fn get_sensor_altitude(&mut self) -> Result<u16, Error<E>> {
@@ -631,13 +2533,41 @@ where
         Ok(u16::from_be_bytes([buf[0], buf[1]]))
     }
 
+    async fn apply_altitude_from<S: AltitudeSource>(
+        &mut self,
+        source: &mut S,
+        persist: bool,
+    ) -> Result<Option<u16>, Error<E>> {
+        let Some(altitude) = source.altitude() else {
+            return Ok(None);
+        };
+
+        let altitude = u16::try_from(altitude).map_err(|_| Error::InvalidInput)?;
+        self.set_sensor_altitude(altitude).await?;
+
+        if persist {
+            self.persists_settings().await?;
+        }
+
+        Ok(Some(altitude))
+    }
+
     async fn set_ambient_pressure(&mut self, pressure: u16) -> Result<(), Error<E>> {
         if !AMBIENT_PRESSURE_RANGE_HPA.contains(&pressure) {
             return Err(Error::InvalidInput);
         }
 
+        self.check_compensation_conflict(self.altitude)?;
         self.write_command_with_data(SET_AMBIENT_PRESSURE, pressure)
-            .await
+            .await?;
+        self.remember(Scd4xSetting::AmbientPressure(pressure));
+        self.ambient_pressure = Some(pressure);
+        Ok(())
+    }
+
+    async fn set_ambient_pressure_f32(&mut self, pressure: f32) -> Result<(), Error<E>> {
+        let pressure = encode_ambient_pressure(pressure)?;
+        self.set_ambient_pressure(pressure).await
     }
 
     async fn get_ambient_pressure(&mut self) -> Result<u16, Error<E>> {
@@ -650,7 +2580,9 @@ where
 
     async fn enable_automatic_self_calibration(&mut self, enabled: bool) -> Result<(), Error<E>> {
         self.write_command_with_data(SET_AUTOMATIC_SELF_CALIBRATION_ENABLED, enabled as u16)
-            .await
+            .await?;
+        self.remember(Scd4xSetting::AutomaticSelfCalibration(enabled));
+        Ok(())
     }
 
     async fn get_automatic_self_calibration(&mut self) -> Result<bool, Error<E>> {
@@ -667,7 +2599,9 @@ where
         ppm_co2: u16,
     ) -> Result<(), Error<E>> {
         self.write_command_with_data(SET_AUTOMATIC_SELF_CALIBRATION_TARGET, ppm_co2)
-            .await
+            .await?;
+        self.remember(Scd4xSetting::AutomaticSelfCalibrationTarget(ppm_co2));
+        Ok(())
     }
 
     async fn get_automatic_self_calibration_target(&mut self) -> Result<u16, Error<E>> {
@@ -682,14 +2616,345 @@ where
         &mut self,
         ppm_co2: u16,
     ) -> Result<Option<i16>, Error<E>> {
+        if self.frc_runtime_check && self.runtime_millis() < FRC_MIN_OPERATION_MILLIS {
+            return Err(Error::InsufficientRuntime);
+        }
+
         let mut buf = [0; 3];
         self.command_with_data_and_response(PERFORM_FORCED_RECALIBRATION, ppm_co2, &mut buf)
             .await?;
         Ok(decode_frc_status(buf))
     }
 
+    async fn perform_forced_recalibration_with_policy(
+        &mut self,
+        ppm_co2: u16,
+        policy: CalibrationPolicy,
+    ) -> Result<Option<i16>, Error<E>> {
+        let asc_enabled = self.get_automatic_self_calibration().await?;
+        if asc_enabled {
+            if !policy.disable_asc {
+                return Err(Error::NotAllowed);
+            }
+
+            self.enable_automatic_self_calibration(false).await?;
+        }
+
+        let result = self.perform_forced_recalibration(ppm_co2).await;
+
+        if asc_enabled && policy.restore_asc {
+            self.enable_automatic_self_calibration(true).await?;
+        }
+
+        if asc_enabled && !policy.restore_asc && policy.persist {
+            self.persists_settings().await?;
+        }
+
+        result
+    }
+
+    async fn calibration_record(
+        &mut self,
+        report: &FrcReport,
+        timestamp: u64,
+    ) -> Result<CalibrationRecord, Error<E>> {
+        Ok(CalibrationRecord {
+            reference_ppm: report.reference_ppm,
+            correction: report.correction,
+            asc_enabled: self.get_automatic_self_calibration().await?,
+            timestamp,
+            serial_number: self.serial_number().await?,
+        })
+    }
+    async fn run_frc_procedure(&mut self, reference_ppm: u16) -> Result<FrcReport, Error<E>> {
+        let was_running = self.measurement_started;
+        let low_power = self.low_power;
+
+        let result = self.frc_procedure(reference_ppm).await;
+        let restored = self.restore_measurement(was_running, low_power).await;
+
+        // The error of the procedure takes precedence over the one of the
+        // restore
+        let report = result?;
+        restored?;
+        Ok(report)
+    }
+
+    async fn frc_procedure(&mut self, reference_ppm: u16) -> Result<FrcReport, Error<E>> {
+        if !self.measurement_started {
+            self.start_periodic_measurement().await?;
+        }
+
+        let mut co2_before = 0;
+        for _ in 0..FRC_MIN_OPERATION_MILLIS / PERIODIC_MEASUREMENT_INTERVAL_MILLIS {
+            co2_before = self.wait_for_measurement().await?.co2;
+        }
+
+        self.stop_periodic_measurement().await?;
+        let correction = self.perform_forced_recalibration(reference_ppm).await?;
+
+        self.resume_measurement().await?;
+        let correction = correction.ok_or(Error::CalibrationFailed)?;
+
+        Ok(FrcReport {
+            reference_ppm,
+            correction,
+            co2_before,
+            co2_after: self.wait_for_measurement().await?.co2,
+        })
+    }
+
+    // Bring the periodic measurement back to the mode it was in before a
+    // procedure that started or stopped it, whether the procedure succeeded
+    // or not
+    async fn restore_measurement(
+        &mut self,
+        was_running: bool,
+        low_power: bool,
+    ) -> Result<(), Error<E>> {
+        if self.measurement_started && (!was_running || self.low_power != low_power) {
+            self.stop_periodic_measurement().await?;
+        }
+
+        self.low_power = low_power;
+        if was_running && !self.measurement_started {
+            self.resume_measurement().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run_field_calibration(
+        &mut self,
+        reference_ppm: u16,
+        config: FieldCalibrationConfig,
+    ) -> Result<FieldCalibrationReport, Error<E>> {
+        if (config.samples as u32) < FRC_MIN_OPERATION_MILLIS / PERIODIC_MEASUREMENT_INTERVAL_MILLIS
+        {
+            return Err(Error::InvalidInput);
+        }
+
+        let was_running = self.measurement_started;
+        let low_power = self.low_power;
+
+        let result = self
+            .field_calibration_procedure(reference_ppm, config)
+            .await;
+        let restored = self.restore_measurement(was_running, low_power).await;
+
+        // The error of the procedure takes precedence over the one of the
+        // restore
+        let report = result?;
+        restored?;
+        Ok(report)
+    }
+
+    async fn field_calibration_procedure(
+        &mut self,
+        reference_ppm: u16,
+        config: FieldCalibrationConfig,
+    ) -> Result<FieldCalibrationReport, Error<E>> {
+        if !self.measurement_started {
+            self.start_periodic_measurement().await?;
+        }
+
+        let mut stats = Statistics::default();
+        for _ in 0..config.samples {
+            stats.push(self.wait_for_measurement().await?.co2 as f32);
+        }
+
+        self.stop_periodic_measurement().await?;
+
+        let mut correction = None;
+        if stats.stddev() <= config.max_stddev_ppm {
+            let frc = self.perform_forced_recalibration(reference_ppm).await?;
+            correction = Some(frc.ok_or(Error::CalibrationFailed)?);
+        }
+
+        Ok(FieldCalibrationReport {
+            reference_ppm,
+            samples: config.samples,
+            mean_ppm: stats.mean(),
+            stddev_ppm: stats.stddev(),
+            correction,
+        })
+    }
+
+    async fn update_asc_schedule<C: TimeSource>(
+        &mut self,
+        scheduler: &mut AscScheduler<'_>,
+        clock: &mut C,
+    ) -> Result<bool, Error<E>> {
+        let enabled = scheduler.is_active(clock.time_of_week());
+        if scheduler.applied() == Some(enabled) {
+            return Ok(enabled);
+        }
+
+        let was_running = self.ensure_idle().await?;
+
+        self.enable_automatic_self_calibration(enabled).await?;
+        scheduler.set_applied(enabled);
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
+
+        Ok(enabled)
+    }
+
+    async fn apply_settings(
+        &mut self,
+        settings: &[Scd4xSetting],
+        persist: bool,
+    ) -> Result<(), Error<E>> {
+        if !settings.iter().all(Scd4xSetting::is_valid) {
+            return Err(Error::InvalidInput);
+        }
+
+        let was_running = self.ensure_idle().await?;
+
+        let mut result = Ok(());
+        for setting in settings {
+            result = self.apply_setting(*setting).await;
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if result.is_ok() && persist {
+            result = self.persists_settings().await;
+        }
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
+
+        result
+    }
+
+    async fn tune_temperature_offset<R: ReferenceTemperature>(
+        &mut self,
+        reference: &mut R,
+        samples: u16,
+        persist: bool,
+    ) -> Result<TemperatureOffsetReport, Error<E>> {
+        if samples == 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let was_running = self.measurement_started;
+        let low_power = self.low_power;
+
+        let result = self
+            .temperature_offset_procedure(reference, samples, persist)
+            .await;
+        let restored = self.restore_measurement(was_running, low_power).await;
+
+        // The error of the procedure takes precedence over the one of the
+        // restore
+        let report = result?;
+        restored?;
+        Ok(report)
+    }
+
+    async fn temperature_offset_procedure<R: ReferenceTemperature>(
+        &mut self,
+        reference: &mut R,
+        samples: u16,
+        persist: bool,
+    ) -> Result<TemperatureOffsetReport, Error<E>> {
+        self.ensure_idle().await?;
+
+        let previous_offset = self.get_temperature_offset().await?;
+
+        self.start_periodic_measurement().await?;
+        let mut sensor_sum = 0.0;
+        let mut reference_sum = 0.0;
+        for _ in 0..samples {
+            sensor_sum += self.wait_for_measurement().await?.temperature;
+            reference_sum += reference.temperature();
+        }
+        self.stop_periodic_measurement().await?;
+
+        let sensor_temperature = sensor_sum / samples as f32;
+        let reference_temperature = reference_sum / samples as f32;
+        let new_offset = sensor_temperature - reference_temperature + previous_offset;
+
+        if !Scd4xSetting::TemperatureOffset(new_offset).is_valid() {
+            return Err(Error::CalibrationFailed);
+        }
+
+        self.set_temperature_offset(new_offset).await?;
+        if persist {
+            self.persists_settings().await?;
+        }
+
+        Ok(TemperatureOffsetReport {
+            previous_offset,
+            new_offset,
+            sensor_temperature,
+            reference_temperature,
+        })
+    }
+
+    async fn wait_for_first_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        if !self.measurement_started {
+            return Err(Error::NotAllowed);
+        }
+
+        self.delay_ms(self.periodic_interval_ms()).await;
+
+        for _ in 0..self.warm_up_samples {
+            self.wait_for_measurement().await?;
+        }
+
+        self.wait_for_measurement().await
+    }
+
+    async fn wait_for_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        loop {
+            while !self.data_ready().await? {
+                self.delay_ms(DATA_READY_POLL_MILLIS).await;
+            }
+
+            // The sensor may report a frame of zeros right after the start
+            match self.read_measurement().await {
+                Err(Error::NoData) => self.delay_ms(DATA_READY_POLL_MILLIS).await,
+                result => return result,
+            }
+        }
+    }
+
+    async fn validate_configuration(
+        &mut self,
+        enclosed: bool,
+    ) -> Result<ConfigurationReport, Error<E>> {
+        let snapshot = self.configuration_snapshot().await?;
+
+        Ok(snapshot.validate(enclosed))
+    }
+
+    async fn configuration_snapshot(&mut self) -> Result<ConfigurationSnapshot, Error<E>> {
+        Ok(ConfigurationSnapshot {
+            temperature_offset: self.get_temperature_offset().await?,
+            altitude: self.get_sensor_altitude().await?,
+            ambient_pressure: self.ambient_pressure,
+            automatic_self_calibration: self.get_automatic_self_calibration().await?,
+            automatic_self_calibration_target: Some(
+                self.get_automatic_self_calibration_target().await?,
+            ),
+            measurement_interval: None,
+        })
+    }
+
     async fn persists_settings(&mut self) -> Result<(), Error<E>> {
-        self.write_command(PERSIST_SETTINGS).await
+        self.write_command(PERSIST_SETTINGS).await?;
+        if let Some(volatile) = &mut self.volatile {
+            volatile.persisted();
+        }
+
+        Ok(())
     }
 
     async fn serial_number(&mut self) -> Result<u64, Error<E>> {
@@ -700,41 +2965,234 @@ where
         Ok(decode_serial_number(buf))
     }
 
+    async fn get_sensor_variant(&mut self) -> Result<SensorVariant, Error<E>> {
+        let info = self.get_sensor_variant_info().await?;
+        info.variant().ok_or(Error::WrongDevice)
+    }
+
+    async fn get_sensor_variant_info(&mut self) -> Result<SensorVariantInfo, Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(GET_SENSOR_VARIANT, &mut buf)
+            .await?;
+
+        Ok(decode_sensor_variant_info(buf))
+    }
+
+    async fn diagnostics(&mut self) -> Diagnostics {
+        let mode = match (self.measurement_started, self.low_power) {
+            (true, false) => MeasurementMode::Periodic,
+            (true, true) => MeasurementMode::LowPowerPeriodic,
+            (false, _) if self.single_shot_us.is_some() => MeasurementMode::SingleShot,
+            (false, _) => MeasurementMode::Idle,
+        };
+
+        let mut diagnostics = Diagnostics::new(mode, self.counters, self.last_error);
+        diagnostics.operating_ms = Some(self.operating_ms);
+
+        // The sensor accepts the queries only while it's idle
+        if !self.measurement_started {
+            diagnostics.serial_number = self.serial_number().await.ok();
+            if let Ok(info) = self.get_sensor_variant_info().await {
+                diagnostics.variant = info.variant();
+                diagnostics.firmware_revision = Some(info.firmware_revision());
+            }
+            diagnostics.settings = self.configuration_snapshot().await.ok();
+        }
+
+        diagnostics
+    }
+
+    async fn execute<C: SensorCommand + ?Sized>(
+        &mut self,
+        command: &C,
+    ) -> Result<C::Response, Error<E>> {
+        let len = command.response_words();
+        if len > MAX_RESPONSE_WORDS {
+            return Err(Error::InvalidInput);
+        }
+
+        let cmd = Command {
+            op_code: command.op_code(),
+            exec_time_us: command.exec_time_us(),
+            allowed_while_running: command.allowed_while_running(),
+        };
+        match command.argument() {
+            Some(data) => self.write_command_with_data(cmd, data).await?,
+            None => self.write_command(cmd).await?,
+        }
+
+        let mut words = [0; MAX_RESPONSE_WORDS];
+        if len > 0 {
+            let mut buf = [0; MAX_RESPONSE_WORDS * 3];
+            self.read_response(&mut buf[..len * 3]).await?;
+            words_from_chunks(&buf, &mut words[..len]);
+        }
+
+        Ok(command.decode(&words[..len]))
+    }
+
+    async fn expect_variant(&mut self, expected: SensorVariant) -> Result<(), Error<E>> {
+        if self.get_sensor_variant().await? != expected {
+            return Err(Error::WrongDevice);
+        }
+
+        Ok(())
+    }
+
     async fn perform_self_test(&mut self) -> Result<bool, Error<E>> {
+        let status = self.self_test_status().await?;
+        Ok(status == 0)
+    }
+
+    async fn perform_self_test_strict(&mut self) -> Result<(), Error<E>> {
+        match self.self_test_status().await? {
+            0 => Ok(()),
+            status => Err(Error::SelfTestFailed(status)),
+        }
+    }
+
+    async fn run_scheduled_self_test(
+        &mut self,
+        scheduler: &mut SelfTestScheduler,
+        now_ms: u64,
+        idle: bool,
+    ) -> Result<Option<SelfTestRecord>, Error<E>> {
+        if !idle || !scheduler.is_due(now_ms) {
+            return Ok(None);
+        }
+
+        let was_running = self.ensure_idle().await?;
+        let status = self.self_test_status().await;
+
+        if was_running {
+            self.resume_measurement().await?;
+        }
+
+        Ok(Some(scheduler.record(now_ms, status?)))
+    }
+
+    async fn self_test_status(&mut self) -> Result<u16, Error<E>> {
         let mut buf = [0; 3];
         self.command_with_response(PERFORM_SELF_TEST, &mut buf)
             .await?;
 
-        let status = u16::from_be_bytes([buf[0], buf[1]]);
-        Ok(status == 0)
+        Ok(u16::from_be_bytes([buf[0], buf[1]]))
     }
 
     async fn perform_factory_reset(&mut self) -> Result<(), Error<E>> {
-        self.write_command(PERFORM_FACTORY_RESET).await
+        self.write_command(PERFORM_FACTORY_RESET).await?;
+        self.altitude = None;
+        self.ambient_pressure = None;
+        if let Some(volatile) = &mut self.volatile {
+            *volatile = VolatileSettings::default();
+        }
+
+        Ok(())
+    }
+
+    async fn perform_self_test_with_progress<P: ProgressCallback>(
+        &mut self,
+        progress: &mut P,
+    ) -> Result<bool, Error<E>> {
+        self.write_command_with_progress(PERFORM_SELF_TEST, None, progress)
+            .await?;
+
+        let mut buf = [0; 3];
+        self.read_response(&mut buf).await?;
+        Ok(u16::from_be_bytes([buf[0], buf[1]]) == 0)
+    }
+
+    async fn perform_factory_reset_with_progress<P: ProgressCallback>(
+        &mut self,
+        progress: &mut P,
+    ) -> Result<(), Error<E>> {
+        self.write_command_with_progress(PERFORM_FACTORY_RESET, None, progress)
+            .await?;
+        self.altitude = None;
+        self.ambient_pressure = None;
+        if let Some(volatile) = &mut self.volatile {
+            *volatile = VolatileSettings::default();
+        }
+
+        Ok(())
+    }
+
+    async fn perform_forced_recalibration_with_progress<P: ProgressCallback>(
+        &mut self,
+        ppm_co2: u16,
+        progress: &mut P,
+    ) -> Result<Option<i16>, Error<E>> {
+        if self.frc_runtime_check && self.runtime_millis() < FRC_MIN_OPERATION_MILLIS {
+            return Err(Error::InsufficientRuntime);
+        }
+
+        self.write_command_with_progress(PERFORM_FORCED_RECALIBRATION, Some(ppm_co2), progress)
+            .await?;
+
+        let mut buf = [0; 3];
+        self.read_response(&mut buf).await?;
+        Ok(decode_frc_status(buf))
+    }
+
+    #[cfg(feature = "scd41")]
+    async fn measure_single_shot_with_progress<P: ProgressCallback>(
+        &mut self,
+        progress: &mut P,
+    ) -> Result<(), Error<E>> {
+        self.write_command_with_progress(MEASURE_SINGLE_SHOT, None, progress)
+            .await?;
+        self.single_shot_us = Some(MEASURE_SINGLE_SHOT.exec_time_us);
+        self.operating_ms = self
+            .operating_ms
+            .saturating_add(MEASURE_SINGLE_SHOT.exec_time_ms().into());
+        Ok(())
     }
 
     async fn reinit(&mut self) -> Result<(), Error<E>> {
-        self.write_command(REINIT).await
+        self.write_command(REINIT).await?;
+        self.ambient_pressure = None;
+        Ok(())
     }
 
     #[cfg(feature = "scd41")]
     async fn measure_single_shot(&mut self) -> Result<(), Error<E>> {
-        self.write_command(MEASURE_SINGLE_SHOT).await
+        self.write_command(MEASURE_SINGLE_SHOT).await?;
+        self.single_shot_us = Some(MEASURE_SINGLE_SHOT.exec_time_us);
+        self.operating_ms = self
+            .operating_ms
+            .saturating_add(MEASURE_SINGLE_SHOT.exec_time_ms().into());
+        Ok(())
     }
 
     #[cfg(feature = "scd41")]
     async fn measure_single_shot_rht_only(&mut self) -> Result<(), Error<E>> {
-        self.write_command(MEASURE_SINGLE_SHOT_RHT_ONLY).await
+        self.write_command(MEASURE_SINGLE_SHOT_RHT_ONLY).await?;
+        self.single_shot_us = Some(MEASURE_SINGLE_SHOT_RHT_ONLY.exec_time_us);
+        self.operating_ms = self
+            .operating_ms
+            .saturating_add(MEASURE_SINGLE_SHOT_RHT_ONLY.exec_time_ms().into());
+        Ok(())
     }
 
     #[cfg(feature = "scd41")]
     async fn power_down(&mut self) -> Result<(), Error<E>> {
-        self.write_command(POWER_DOWN).await
+        self.write_command(POWER_DOWN).await?;
+        self.ambient_pressure = None;
+        Ok(())
     }
 
     #[cfg(feature = "scd41")]
     async fn wake_up(&mut self) -> Result<(), Error<E>> {
-        self.write_command(WAKE_UP).await
+        self.check_is_command_allowed(WAKE_UP)?;
+        self.wait_while_busy().await;
+
+        // The NACK is expected, so it is neither counted as a bus error nor
+        // recorded as the last error
+        let buf = WAKE_UP.prepare();
+        write_frame(&mut self.transport, &buf).await?;
+        self.delay_us(WAKE_UP.exec_time_us).await;
+
+        Ok(())
     }
 
     #[cfg(feature = "scd41")]
@@ -0,0 +1,66 @@
+use crate::error::Error;
+use crate::measurement::Measurement;
+
+/// The operations shared by all supported CO2 sensors. Application code,
+/// such as displays or loggers, can be written once against this trait and
+/// work with whichever sensor is populated.
+#[allow(async_fn_in_trait)]
+pub trait Co2Sensor {
+    /// The error reported by the transport of the sensor
+    type Error;
+
+    /// Start the periodic measurement using the default settings of the
+    /// sensor
+    async fn start_measurement(&mut self) -> Result<(), Error<Self::Error>>;
+
+    /// Stop the periodic measurement
+    async fn stop_measurement(&mut self) -> Result<(), Error<Self::Error>>;
+
+    /// Check whether a new measurement can be read
+    async fn data_ready(&mut self) -> Result<bool, Error<Self::Error>>;
+
+    /// Read the latest measurement. Returns `Error::NoData` if the sensor
+    /// responded with a frame of zeros instead of a measurement
+    async fn read_measurement(&mut self) -> Result<Measurement, Error<Self::Error>>;
+}
+
+impl<S: Co2Sensor> Co2Sensor for &mut S {
+    type Error = S::Error;
+
+    async fn start_measurement(&mut self) -> Result<(), Error<Self::Error>> {
+        (**self).start_measurement().await
+    }
+
+    async fn stop_measurement(&mut self) -> Result<(), Error<Self::Error>> {
+        (**self).stop_measurement().await
+    }
+
+    async fn data_ready(&mut self) -> Result<bool, Error<Self::Error>> {
+        (**self).data_ready().await
+    }
+
+    async fn read_measurement(&mut self) -> Result<Measurement, Error<Self::Error>> {
+        (**self).read_measurement().await
+    }
+}
+
+/// The re-initialization of a sensor that was detached from the bus and
+/// attached again, e.g. a field-swappable sensor module. Implemented by all
+/// drivers and used by [HotPlugSensor](super::hotplug::HotPlugSensor)
+#[allow(async_fn_in_trait)]
+pub trait Reattach: Co2Sensor {
+    /// Identify the sensor that responds again and restore the
+    /// configuration written through the driver, then resume the
+    /// measurement if it was running when the sensor was detached. The
+    /// sensor is assumed to have been powered up in the meantime.
+    ///
+    /// The state of the driver is kept when the call fails, so that it can
+    /// be repeated, e.g. while the sensor is still booting
+    async fn reattach(&mut self) -> Result<(), Error<Self::Error>>;
+}
+
+impl<S: Reattach> Reattach for &mut S {
+    async fn reattach(&mut self) -> Result<(), Error<Self::Error>> {
+        (**self).reattach().await
+    }
+}
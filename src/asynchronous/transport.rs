@@ -0,0 +1,266 @@
+use crate::error::Error;
+use crate::wire::common::{check_chunked_with_len3, crc8_verify_chunked_3};
+use crate::wire::hex::HexDump;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::{Error as I2cError, ErrorKind, I2c};
+
+/// The transport used by the drivers to exchange frames with the sensor.
+///
+/// The drivers build the command frames, wait for the command execution
+/// and verify the CRC of the responses, so an implementation only needs to
+/// move the bytes. Implement this trait in order to inject instrumented or
+/// fake transports, or to reach the sensor over something other than I2C.
+#[allow(async_fn_in_trait)]
+pub trait Transport {
+    /// The error reported by the underlying bus
+    type Error;
+
+    /// Send a command frame to the sensor
+    async fn write_frame(&mut self, frame: &[u8]) -> Result<(), Self::Error>;
+
+    /// Read a response frame from the sensor. The length of `buf` is the
+    /// expected length of the response
+    async fn read_frame(&mut self, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Wait for the sensor to process a command
+    async fn delay_ms(&mut self, ms: u32);
+
+    /// Wait for the sensor to process a command that takes less than a few
+    /// milliseconds. The default implementation rounds up to milliseconds
+    async fn delay_us(&mut self, us: u32) {
+        self.delay_ms(us.div_ceil(1000)).await;
+    }
+}
+
+/// A procedure restoring the I2C bus after a failed transfer, e.g. toggling
+/// SCL to release a stuck device or power-cycling a bus switch. It is
+/// invoked by [I2cTransport] for all errors except a missing acknowledgement,
+/// which usually means that the sensor is still busy.
+pub trait BusRecovery {
+    /// Try to recover the bus after an error of the given kind. Returns
+    /// whether the bus was recovered, in which case the failed transfer is
+    /// retried once
+    fn recover(&mut self, error: ErrorKind) -> bool;
+}
+
+impl<F> BusRecovery for F
+where
+    F: FnMut(ErrorKind) -> bool,
+{
+    fn recover(&mut self, error: ErrorKind) -> bool {
+        self(error)
+    }
+}
+
+/// The default [BusRecovery], which does not attempt to recover the bus
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NoBusRecovery;
+
+impl BusRecovery for NoBusRecovery {
+    fn recover(&mut self, _error: ErrorKind) -> bool {
+        false
+    }
+}
+
+/// The default [Transport], which talks to the sensor over an I2C bus
+pub struct I2cTransport<I2C, D, R = NoBusRecovery> {
+    i2c: I2C,
+    delay: D,
+    address: u8,
+    recovery: R,
+}
+
+impl<I2C, D> I2cTransport<I2C, D>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Create a new transport for the sensor at the given I2C address
+    pub fn new(i2c: I2C, delay: D, address: u8) -> Self {
+        Self {
+            i2c,
+            delay,
+            address,
+            recovery: NoBusRecovery,
+        }
+    }
+
+    /// Invoke the given procedure after bus errors, before retrying the
+    /// failed transfer
+    pub fn with_bus_recovery<R: BusRecovery>(self, recovery: R) -> I2cTransport<I2C, D, R> {
+        I2cTransport {
+            i2c: self.i2c,
+            delay: self.delay,
+            address: self.address,
+            recovery,
+        }
+    }
+}
+
+impl<I2C, D, R> I2cTransport<I2C, D, R>
+where
+    I2C: I2c,
+    D: DelayNs,
+    R: BusRecovery,
+{
+    /// Release the I2C bus and the delay implementation
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+
+    /// The I2C address of the sensor
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    fn recover(&mut self, error: &I2C::Error) -> bool {
+        match error.kind() {
+            ErrorKind::NoAcknowledge(_) => false,
+            kind => {
+                debug!("recovering the I2C bus after {:?}", kind);
+                self.recovery.recover(kind)
+            }
+        }
+    }
+}
+
+impl<I2C, D, R> Transport for I2cTransport<I2C, D, R>
+where
+    I2C: I2c,
+    D: DelayNs,
+    R: BusRecovery,
+{
+    type Error = I2C::Error;
+
+    async fn write_frame(&mut self, frame: &[u8]) -> Result<(), Self::Error> {
+        match self.i2c.write(self.address, frame).await {
+            Err(e) if self.recover(&e) => self.i2c.write(self.address, frame).await,
+            result => result,
+        }
+    }
+
+    async fn read_frame(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        match self.i2c.read(self.address, buf).await {
+            Err(e) if self.recover(&e) => self.i2c.read(self.address, buf).await,
+            result => result,
+        }
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms).await;
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us).await;
+    }
+}
+
+/// A [DelayNs] splitting long delays into chunks of at most `chunk_ms`
+/// milliseconds and invoking a hook after each chunk, e.g. to feed a
+/// watchdog while the sensor executes a long command such as the self-test
+/// (10 s) or the factory reset (1.2 s). Wrap the delay passed to the
+/// driver, e.g. `ChunkedDelay::new(delay, 500, || watchdog.feed())`
+pub struct ChunkedDelay<D, F> {
+    delay: D,
+    chunk_ms: u32,
+    hook: F,
+}
+
+impl<D, F> ChunkedDelay<D, F>
+where
+    D: DelayNs,
+    F: FnMut(),
+{
+    /// Wrap the given delay. A `chunk_ms` of zero is treated as one
+    /// millisecond
+    pub fn new(delay: D, chunk_ms: u32, hook: F) -> Self {
+        Self {
+            delay,
+            chunk_ms: chunk_ms.max(1),
+            hook,
+        }
+    }
+
+    /// Release the wrapped delay and the hook
+    pub fn release(self) -> (D, F) {
+        (self.delay, self.hook)
+    }
+}
+
+impl<D, F> DelayNs for ChunkedDelay<D, F>
+where
+    D: DelayNs,
+    F: FnMut(),
+{
+    async fn delay_ns(&mut self, mut ns: u32) {
+        let chunk = self.chunk_ms.saturating_mul(1_000_000);
+        while ns > chunk {
+            self.delay.delay_ns(chunk).await;
+            (self.hook)();
+            ns -= chunk;
+        }
+
+        self.delay.delay_ns(ns).await;
+        (self.hook)();
+    }
+
+    async fn delay_us(&mut self, mut us: u32) {
+        let chunk = self.chunk_ms.saturating_mul(1_000);
+        while us > chunk {
+            self.delay.delay_us(chunk).await;
+            (self.hook)();
+            us -= chunk;
+        }
+
+        self.delay.delay_us(us).await;
+        (self.hook)();
+    }
+
+    async fn delay_ms(&mut self, mut ms: u32) {
+        while ms > self.chunk_ms {
+            self.delay.delay_ms(self.chunk_ms).await;
+            (self.hook)();
+            ms -= self.chunk_ms;
+        }
+
+        self.delay.delay_ms(ms).await;
+        (self.hook)();
+    }
+}
+
+pub(crate) async fn read_frame<E, T: Transport<Error = E>>(
+    transport: &mut T,
+    read_buf: &mut [u8],
+    verify_crc: bool,
+) -> Result<(), Error<E>> {
+    check_chunked_with_len3(read_buf)?;
+
+    transport
+        .read_frame(read_buf)
+        .await
+        .map_err(|e| Error::I2C(e))?;
+
+    trace!("read {}", HexDump(read_buf));
+    if verify_crc {
+        crc8_verify_chunked_3(read_buf).map_err(|e| {
+            debug!("CRC mismatch: {:?} in {}", e, HexDump(read_buf));
+            Error::CRC(e)
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn write_frame<E, T: Transport<Error = E>>(
+    transport: &mut T,
+    payload: &[u8],
+) -> Result<(), Error<E>> {
+    trace!("write {}", HexDump(payload));
+    transport
+        .write_frame(payload)
+        .await
+        .map_err(|e| Error::I2C(e))?;
+
+    Ok(())
+}
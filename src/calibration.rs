@@ -0,0 +1,776 @@
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+use crate::math::sqrt;
+use crate::measurement::Measurement;
+
+/// The outcome of a guided forced recalibration (FRC) procedure
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrcReport {
+    /// The reference CO2 concentration in PPM the sensor was calibrated to
+    pub reference_ppm: u16,
+
+    /// The correction in PPM applied by the sensor
+    pub correction: i16,
+
+    /// The last CO2 concentration in PPM measured before the recalibration
+    pub co2_before: u16,
+
+    /// The first CO2 concentration in PPM measured after the recalibration
+    pub co2_after: u16,
+}
+
+/// A source of the reference ambient temperature used when tuning the
+/// temperature offset of a sensor. A plain `f32` can be used as a
+/// constant reference temperature provided by the user
+pub trait ReferenceTemperature {
+    /// The current ambient temperature in Celsius
+    fn temperature(&mut self) -> f32;
+}
+
+impl ReferenceTemperature for f32 {
+    fn temperature(&mut self) -> f32 {
+        *self
+    }
+}
+
+/// A reference sensor measuring the ambient temperature and humidity next
+/// to the CO2 sensor, used by the [CrossValidator]. A plain `(f32, f32)`
+/// can be used as a constant reference provided by the user
+pub trait ReferenceClimate {
+    /// The current ambient temperature in Celsius and relative humidity
+    /// (RH%), or `None` if the reference is not available at the moment
+    fn climate(&mut self) -> Option<(f32, f32)>;
+}
+
+impl ReferenceClimate for (f32, f32) {
+    fn climate(&mut self) -> Option<(f32, f32)> {
+        Some(*self)
+    }
+}
+
+/// The maximal differences between the sensor and the reference readings
+/// tolerated by the [CrossValidator]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CrossValidationLimits {
+    /// The maximal absolute temperature difference in Celsius
+    pub temperature: f32,
+
+    /// The maximal absolute relative humidity difference in RH%
+    pub humidity: f32,
+}
+
+impl Default for CrossValidationLimits {
+    /// The combined typical accuracy of an SCD4x and a reference sensor of a
+    /// similar class
+    fn default() -> Self {
+        Self {
+            temperature: 1.5,
+            humidity: 9.0,
+        }
+    }
+}
+
+/// The outcome of a comparison of a measurement against the reference
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CrossValidation {
+    /// The sensor temperature minus the reference temperature in Celsius
+    pub temperature_delta: f32,
+
+    /// The sensor humidity minus the reference humidity in RH%
+    pub humidity_delta: f32,
+
+    /// Whether the temperature difference exceeds the limit
+    pub temperature_diverged: bool,
+
+    /// Whether the humidity difference exceeds the limit
+    pub humidity_diverged: bool,
+}
+
+impl CrossValidation {
+    /// Check if any of the differences exceeds its limit
+    pub fn is_diverged(&self) -> bool {
+        self.temperature_diverged || self.humidity_diverged
+    }
+}
+
+/// Compares the temperature and humidity reported by the sensor against a
+/// [ReferenceClimate] in the field. A persistent divergence points to
+/// problems with the airflow in the enclosure, e.g. heat from nearby
+/// components, or to a drifting unit.
+///
+/// The validator keeps the mean differences of all compared measurements
+/// in order to suggest an updated temperature offset.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct CrossValidator {
+    limits: CrossValidationLimits,
+    count: u32,
+    temperature_sum: f32,
+    humidity_sum: f32,
+}
+
+impl CrossValidator {
+    /// Create a new validator with the given limits
+    pub fn new(limits: CrossValidationLimits) -> Self {
+        Self {
+            limits,
+            count: 0,
+            temperature_sum: 0.0,
+            humidity_sum: 0.0,
+        }
+    }
+
+    /// Compare the measurement against the current reading of the reference.
+    /// `None` if the reference is not available
+    pub fn check<R: ReferenceClimate>(
+        &mut self,
+        measurement: &Measurement,
+        reference: &mut R,
+    ) -> Option<CrossValidation> {
+        let (temperature, humidity) = reference.climate()?;
+
+        let temperature_delta = measurement.temperature - temperature;
+        let humidity_delta = measurement.humidity - humidity;
+
+        self.count += 1;
+        self.temperature_sum += temperature_delta;
+        self.humidity_sum += humidity_delta;
+
+        Some(CrossValidation {
+            temperature_delta,
+            humidity_delta,
+            temperature_diverged: temperature_delta.abs() > self.limits.temperature,
+            humidity_diverged: humidity_delta.abs() > self.limits.humidity,
+        })
+    }
+
+    /// The number of measurements compared since the last reset
+    pub fn samples(&self) -> u32 {
+        self.count
+    }
+
+    /// The mean of the compared measurements, as in [CrossValidator::check].
+    /// `None` if no measurements were compared
+    pub fn mean(&self) -> Option<CrossValidation> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let temperature_delta = self.temperature_sum / self.count as f32;
+        let humidity_delta = self.humidity_sum / self.count as f32;
+
+        Some(CrossValidation {
+            temperature_delta,
+            humidity_delta,
+            temperature_diverged: temperature_delta.abs() > self.limits.temperature,
+            humidity_diverged: humidity_delta.abs() > self.limits.humidity,
+        })
+    }
+
+    /// The temperature offset in Celsius that compensates the mean
+    /// temperature difference, given the `current_offset` configured on the
+    /// sensor. `None` if no measurements were compared.
+    ///
+    /// The offset is not clamped to the range supported by the sensor
+    pub fn suggested_temperature_offset(&self, current_offset: f32) -> Option<f32> {
+        Some(current_offset + self.mean()?.temperature_delta)
+    }
+
+    /// Forget the compared measurements, e.g. after the temperature offset
+    /// was updated
+    pub fn reset(&mut self) {
+        self.count = 0;
+        self.temperature_sum = 0.0;
+        self.humidity_sum = 0.0;
+    }
+}
+
+/// A source of the altitude of the sensor, e.g. a GPS receiver or a stored
+/// site profile. A plain `u16` can be used as a constant altitude in meters
+pub trait AltitudeSource {
+    /// The altitude in meters above sea level or `None` if it is not
+    /// available at the moment, e.g. there is no GPS fix
+    fn altitude(&mut self) -> Option<i32>;
+}
+
+impl AltitudeSource for u16 {
+    fn altitude(&mut self) -> Option<i32> {
+        Some(*self as i32)
+    }
+}
+
+/// The outcome of a temperature offset tuning
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureOffsetReport {
+    /// The temperature offset in Celsius before the tuning
+    pub previous_offset: f32,
+
+    /// The temperature offset in Celsius written to the sensor
+    pub new_offset: f32,
+
+    /// The mean temperature in Celsius measured by the sensor
+    pub sensor_temperature: f32,
+
+    /// The mean reference temperature in Celsius
+    pub reference_temperature: f32,
+}
+
+/// Controls how the automatic self-calibration (ASC) is handled around a
+/// forced recalibration (FRC). The ASC and the FRC overwrite each other's
+/// corrections, so performing the steps in the wrong order silently changes
+/// the behavior of the sensor.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CalibrationPolicy {
+    /// Disable the ASC before the FRC if it is enabled. When not set, the
+    /// FRC is refused while the ASC is enabled, because the ASC would
+    /// overwrite the FRC correction. Only the ASC state reported by the
+    /// sensor is checked: the driver does not track when the ASC last ran,
+    /// so an FRC right after an ASC correction is not refused
+    pub disable_asc: bool,
+
+    /// Re-enable the ASC after the FRC if it was enabled before it
+    pub restore_asc: bool,
+
+    /// Persist the settings if the ASC state was changed by the FRC, so
+    /// the change survives a power-cycle. The EEPROM is not written when
+    /// the ASC state is unchanged
+    pub persist: bool,
+}
+
+impl Default for CalibrationPolicy {
+    fn default() -> Self {
+        Self {
+            disable_asc: true,
+            restore_asc: true,
+            persist: false,
+        }
+    }
+}
+
+/// An auditable record of a forced recalibration, e.g. to be reported to a
+/// backend as part of the calibration history of a device. Can be
+/// serialized when the `serde` feature is enabled
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CalibrationRecord {
+    /// The reference CO2 concentration in PPM the sensor was calibrated to
+    pub reference_ppm: u16,
+
+    /// The correction in PPM applied by the sensor
+    pub correction: i16,
+
+    /// Whether the automatic self-calibration was enabled after the
+    /// recalibration
+    pub asc_enabled: bool,
+
+    /// The time of the recalibration as provided by the application. The
+    /// unit and the epoch are not interpreted by the driver
+    pub timestamp: u64,
+
+    /// The serial number of the calibrated sensor
+    pub serial_number: u64,
+}
+
+/// A polynomial of up to the third degree correcting a single field of the
+/// measurements, e.g. derived by characterizing a unit against a reference
+/// rig. The coefficients are in ascending order, i.e. the corrected value is
+/// `c0 + c1 * x + c2 * x² + c3 * x³`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polynomial {
+    /// The coefficients in ascending order of the powers
+    pub coefficients: [f32; 4],
+}
+
+impl Polynomial {
+    /// The polynomial that leaves the values unchanged
+    pub const IDENTITY: Self = Self::linear(0.0, 1.0);
+
+    /// Create a polynomial from its coefficients in ascending order
+    pub const fn new(coefficients: [f32; 4]) -> Self {
+        Self { coefficients }
+    }
+
+    /// A linear correction `offset + gain * x`
+    pub const fn linear(offset: f32, gain: f32) -> Self {
+        Self::new([offset, gain, 0.0, 0.0])
+    }
+
+    /// Evaluate the polynomial at `x`
+    pub fn apply(&self, x: f32) -> f32 {
+        // Horner's method
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &coefficient| acc * x + coefficient)
+    }
+}
+
+impl Default for Polynomial {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// A correction applied by the drivers to each measurement before it is
+/// returned, in addition to the single-point forced recalibration of the
+/// sensor. Can be serialized when the `serde` feature is enabled
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeasurementCorrection {
+    /// The correction of the CO2 concentration in PPM
+    pub co2: Polynomial,
+
+    /// The correction of the temperature in Celsius
+    pub temperature: Polynomial,
+
+    /// The correction of the relative humidity in RH%
+    pub humidity: Polynomial,
+}
+
+impl MeasurementCorrection {
+    /// Correct the measurement. The CO2 concentration is rounded and limited
+    /// to the range of `u16`, and the humidity to 0 - 100 %. A CO2
+    /// concentration of 0, e.g. of a temperature and humidity only
+    /// measurement, is left unchanged
+    pub fn apply(&self, measurement: Measurement) -> Measurement {
+        let co2 = match measurement.co2 {
+            0 => 0,
+            co2 => {
+                let corrected = self.co2.apply(co2 as f32) + 0.5;
+                corrected.clamp(0.0, u16::MAX as f32) as u16
+            }
+        };
+
+        Measurement {
+            temperature: self.temperature.apply(measurement.temperature),
+            humidity: self.humidity.apply(measurement.humidity).clamp(0.0, 100.0),
+            co2,
+        }
+    }
+}
+
+/// Configuration of a field calibration run
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldCalibrationConfig {
+    /// The number of measurements collected before the recalibration. The
+    /// sensor must be operated for at least 3 minutes before a forced
+    /// recalibration, so this must cover at least that period
+    pub samples: u16,
+
+    /// The maximal standard deviation in PPM of the collected measurements.
+    /// Runs with a higher deviation are rejected, because the environment
+    /// is not stable enough for a recalibration
+    pub max_stddev_ppm: f32,
+}
+
+impl Default for FieldCalibrationConfig {
+    fn default() -> Self {
+        Self {
+            samples: 60,
+            max_stddev_ppm: 20.0,
+        }
+    }
+}
+
+/// The outcome of a field calibration run
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldCalibrationReport {
+    /// The reference CO2 concentration in PPM
+    pub reference_ppm: u16,
+
+    /// The number of collected measurements
+    pub samples: u16,
+
+    /// The mean CO2 concentration in PPM of the collected measurements
+    pub mean_ppm: f32,
+
+    /// The standard deviation in PPM of the collected measurements
+    pub stddev_ppm: f32,
+
+    /// The correction in PPM applied by the sensor. `None` if the run was
+    /// rejected because the environment was not stable
+    pub correction: Option<i16>,
+}
+
+/// A point in time within a week, as used by the [AscScheduler]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimeOfWeek {
+    /// Day of the week in the range `0..=6`, where 0 is Monday
+    pub weekday: u8,
+
+    /// Minutes since midnight in the range `0..1440`
+    pub minutes: u16,
+}
+
+/// A source of the local time used to drive the [AscScheduler]
+pub trait TimeSource {
+    /// The current local time
+    fn time_of_week(&mut self) -> TimeOfWeek;
+}
+
+/// A daily time window during which the automatic self-calibration is
+/// enabled. A window whose end is before its start spans midnight and ends
+/// on the following day
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AscWindow {
+    /// Bit mask of the days on which the window starts, where bit 0 is
+    /// Monday. See [AscWindow::WEEKDAYS], [AscWindow::WEEKEND] and
+    /// [AscWindow::EVERY_DAY]
+    pub days: u8,
+
+    /// Start of the window in minutes since midnight (inclusive)
+    pub start_minutes: u16,
+
+    /// End of the window in minutes since midnight (exclusive)
+    pub end_minutes: u16,
+}
+
+impl AscWindow {
+    /// Monday to Friday
+    pub const WEEKDAYS: u8 = 0b0001_1111;
+
+    /// Saturday and Sunday
+    pub const WEEKEND: u8 = 0b0110_0000;
+
+    /// All days of the week
+    pub const EVERY_DAY: u8 = 0b0111_1111;
+
+    /// Check if the window contains the given time
+    pub fn contains(&self, time: TimeOfWeek) -> bool {
+        let starts_on = |weekday: u8| self.days & (1 << (weekday % 7)) != 0;
+
+        if self.start_minutes <= self.end_minutes {
+            return starts_on(time.weekday)
+                && (self.start_minutes..self.end_minutes).contains(&time.minutes);
+        }
+
+        (starts_on(time.weekday) && time.minutes >= self.start_minutes)
+            || (starts_on(time.weekday + 6) && time.minutes < self.end_minutes)
+    }
+}
+
+/// Enables the automatic self-calibration (ASC) only during the configured
+/// time windows, e.g. at night or over the weekend, when the building is
+/// empty and the sensor sees background CO2 levels. This improves the ASC in
+/// spaces that are occupied most of the time.
+///
+/// The scheduler remembers the ASC state it last applied, so the sensor is
+/// reconfigured only when a window starts or ends. The ASC state is not
+/// persisted, so after a power-cycle the sensor falls back to its stored
+/// setting until the next update.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct AscScheduler<'a> {
+    windows: &'a [AscWindow],
+    applied: Option<bool>,
+}
+
+impl<'a> AscScheduler<'a> {
+    /// Create a new scheduler enabling the ASC during the given windows
+    pub fn new(windows: &'a [AscWindow]) -> Self {
+        Self {
+            windows,
+            applied: None,
+        }
+    }
+
+    /// Check if the ASC should be enabled at the given time
+    pub fn is_active(&self, time: TimeOfWeek) -> bool {
+        self.windows.iter().any(|w| w.contains(time))
+    }
+
+    /// The ASC state last applied to the sensor, if any
+    pub fn applied(&self) -> Option<bool> {
+        self.applied
+    }
+
+    #[cfg(any(feature = "scd40", feature = "scd41"))]
+    pub(crate) fn set_applied(&mut self, enabled: bool) {
+        self.applied = Some(enabled);
+    }
+}
+
+/// The outcome of a self-test run by the [SelfTestScheduler]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfTestRecord {
+    /// The time of the self-test in milliseconds, as provided by the
+    /// application
+    pub timestamp_ms: u64,
+
+    /// The raw status word returned by the sensor, where `0` means that no
+    /// malfunction was detected
+    pub status: u16,
+}
+
+impl SelfTestRecord {
+    /// Check if no malfunction was detected
+    pub fn passed(&self) -> bool {
+        self.status == 0
+    }
+}
+
+/// Runs the self-test of the sensor opportunistically during the idle
+/// windows declared by the application, e.g. at night when no measurements
+/// are needed, but at most once per the configured interval. The self-test
+/// takes 10 seconds, during which the sensor does not measure.
+///
+/// The last record can be persisted by the application and restored after a
+/// restart via [SelfTestScheduler::restore], so that the rate limit survives
+/// it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct SelfTestScheduler {
+    min_interval_ms: u64,
+    last: Option<SelfTestRecord>,
+    runs: u32,
+    failures: u32,
+}
+
+impl SelfTestScheduler {
+    /// One week in milliseconds
+    pub const WEEKLY: u64 = 7 * 24 * 60 * 60 * 1_000;
+
+    /// Create a new scheduler running the self-test at most once per
+    /// `min_interval_ms` milliseconds
+    pub fn new(min_interval_ms: u64) -> Self {
+        Self {
+            min_interval_ms,
+            last: None,
+            runs: 0,
+            failures: 0,
+        }
+    }
+
+    /// Restore the record of the last self-test, e.g. after a restart
+    pub fn restore(&mut self, last: SelfTestRecord) {
+        self.last = Some(last);
+    }
+
+    /// Check if the self-test should run at the given time
+    pub fn is_due(&self, now_ms: u64) -> bool {
+        self.last
+            .is_none_or(|last| now_ms.saturating_sub(last.timestamp_ms) >= self.min_interval_ms)
+    }
+
+    /// The record of the last self-test, if any
+    pub fn last(&self) -> Option<SelfTestRecord> {
+        self.last
+    }
+
+    /// The number of self-tests run by this scheduler
+    pub fn runs(&self) -> u32 {
+        self.runs
+    }
+
+    /// The number of self-tests run by this scheduler that detected a
+    /// malfunction
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    #[cfg(any(feature = "scd40", feature = "scd41"))]
+    pub(crate) fn record(&mut self, timestamp_ms: u64, status: u16) -> SelfTestRecord {
+        let record = SelfTestRecord {
+            timestamp_ms,
+            status,
+        };
+
+        self.last = Some(record);
+        self.runs = self.runs.saturating_add(1);
+        if !record.passed() {
+            self.failures = self.failures.saturating_add(1);
+        }
+
+        record
+    }
+}
+
+/// Running mean and standard deviation (Welford's algorithm)
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct Statistics {
+    count: u32,
+    mean: f32,
+    m2: f32,
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+impl Statistics {
+    pub(crate) fn push(&mut self, value: f32) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f32;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub(crate) fn mean(&self) -> f32 {
+        self.mean
+    }
+
+    /// The population standard deviation
+    pub(crate) fn stddev(&self) -> f32 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        sqrt(self.m2 / self.count as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measurement_correction() {
+        let correction = MeasurementCorrection {
+            co2: Polynomial::new([10.0, 1.1, 0.0001, 0.0]),
+            temperature: Polynomial::linear(-0.5, 1.0),
+            humidity: Polynomial::linear(5.0, 1.0),
+        };
+
+        let corrected = correction.apply(Measurement {
+            temperature: 25.0,
+            humidity: 98.0,
+            co2: 1_000,
+        });
+        assert_eq!(1_210, corrected.co2);
+        assert!((24.5 - corrected.temperature).abs() < 0.001);
+        assert_eq!(100.0, corrected.humidity);
+
+        let rht_only = correction.apply(Measurement {
+            temperature: 25.0,
+            humidity: 40.0,
+            co2: 0,
+        });
+        assert_eq!(0, rht_only.co2);
+
+        let clamped = MeasurementCorrection {
+            co2: Polynomial::linear(-2_000.0, 1.0),
+            ..MeasurementCorrection::default()
+        };
+        assert_eq!(0, clamped.apply(corrected).co2);
+    }
+
+    #[test]
+    fn test_self_test_scheduler() {
+        let mut scheduler = SelfTestScheduler::new(1_000);
+        assert!(scheduler.is_due(0));
+
+        scheduler.restore(SelfTestRecord {
+            timestamp_ms: 5_000,
+            status: 0,
+        });
+        assert!(!scheduler.is_due(5_999));
+        assert!(scheduler.is_due(6_000));
+        assert_eq!(0, scheduler.runs());
+        assert!(scheduler.last().unwrap().passed());
+    }
+
+    #[test]
+    fn test_cross_validation() {
+        let measurement = |temperature, humidity| Measurement {
+            temperature,
+            humidity,
+            co2: 600,
+        };
+
+        let mut validator = CrossValidator::new(CrossValidationLimits::default());
+        assert_eq!(None, validator.mean());
+        assert_eq!(None, validator.suggested_temperature_offset(4.0));
+
+        let mut reference = (21.0, 45.0);
+        let check = validator
+            .check(&measurement(22.0, 42.0), &mut reference)
+            .unwrap();
+        assert!(!check.is_diverged());
+
+        let check = validator
+            .check(&measurement(24.0, 33.0), &mut reference)
+            .unwrap();
+        assert!(check.temperature_diverged);
+        assert!(check.humidity_diverged);
+        assert_eq!(3.0, check.temperature_delta);
+        assert_eq!(-12.0, check.humidity_delta);
+
+        let mean = validator.mean().unwrap();
+        assert_eq!(2, validator.samples());
+        assert_eq!(2.0, mean.temperature_delta);
+        assert_eq!(-7.5, mean.humidity_delta);
+        assert!(mean.temperature_diverged);
+        assert!(!mean.humidity_diverged);
+        assert_eq!(Some(6.0), validator.suggested_temperature_offset(4.0));
+
+        struct Unavailable;
+        impl ReferenceClimate for Unavailable {
+            fn climate(&mut self) -> Option<(f32, f32)> {
+                None
+            }
+        }
+
+        assert_eq!(
+            None,
+            validator.check(&measurement(0.0, 0.0), &mut Unavailable)
+        );
+        validator.reset();
+        assert_eq!(0, validator.samples());
+    }
+
+    #[cfg(any(feature = "scd40", feature = "scd41"))]
+    #[test]
+    fn test_statistics() {
+        let mut stats = Statistics::default();
+        assert_eq!(0.0, stats.stddev());
+
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.push(value);
+        }
+
+        assert!((5.0 - stats.mean()).abs() < 1e-5);
+        assert!((2.0 - stats.stddev()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_asc_window() {
+        let at = |weekday, hours: u16| TimeOfWeek {
+            weekday,
+            minutes: hours * 60,
+        };
+
+        let day = AscWindow {
+            days: AscWindow::WEEKEND,
+            start_minutes: 8 * 60,
+            end_minutes: 20 * 60,
+        };
+        assert!(day.contains(at(5, 8)));
+        assert!(day.contains(at(6, 19)));
+        assert!(!day.contains(at(6, 20)));
+        assert!(!day.contains(at(4, 12)));
+
+        // Friday to Saturday night
+        let night = AscWindow {
+            days: 1 << 4,
+            start_minutes: 22 * 60,
+            end_minutes: 6 * 60,
+        };
+        assert!(night.contains(at(4, 23)));
+        assert!(night.contains(at(5, 2)));
+        assert!(!night.contains(at(5, 22)));
+        assert!(!night.contains(at(4, 2)));
+        assert!(!night.contains(at(4, 12)));
+    }
+}
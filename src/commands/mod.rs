@@ -0,0 +1,111 @@
+/// The commands of the SCD30 sensor and its timings. The sensor needs a
+/// delay of 5 ms after each command, before the response can be read or
+/// another command can be sent
+pub mod scd30;
+
+/// The commands of the SCD40 and SCD41 sensors, with their execution times
+/// and whether they are accepted during the periodic measurement, and the
+/// measurement intervals of the sensors
+pub mod scd4x;
+
+/// The largest number of data words in the response of a [SensorCommand]
+pub const MAX_RESPONSE_WORDS: usize = 9;
+
+/// A command executed through the `execute()` method of the drivers, which
+/// sends it over the same transport as the built-in commands: the frame is
+/// built with the CRC of the argument, the execution time is waited for,
+/// the CRC of the response is verified and the SCD4x drivers reject the
+/// commands not accepted during the periodic measurement.
+///
+/// The trait is sealed unless the `custom-commands` feature is enabled, in
+/// which case it can be implemented outside of this crate to define new
+/// commands, e.g. ones added by a newer firmware of the sensor. Without the
+/// feature, it's implemented only by the built-in commands, which are sent
+/// without an argument and whose response is not read.
+///
+/// The driver does not know what a custom command does, so it does not
+/// update its state, e.g. whether the sensor is measuring.
+pub trait SensorCommand: sealed::Sealed {
+    /// The value decoded from the response of the sensor
+    type Response;
+
+    /// The opcode sent on the wire
+    fn op_code(&self) -> u16;
+
+    /// The time in microseconds the sensor needs to execute the command,
+    /// before the response can be read or another command can be sent
+    fn exec_time_us(&self) -> u32;
+
+    /// Whether the SCD4x sensors accept the command during the periodic
+    /// measurement. Ignored by the SCD30, which accepts every command at any
+    /// time
+    fn allowed_while_running(&self) -> bool {
+        false
+    }
+
+    /// The data word sent after the opcode, if any
+    fn argument(&self) -> Option<u16> {
+        None
+    }
+
+    /// The number of data words in the response, up to
+    /// [MAX_RESPONSE_WORDS]. `0` if the command has no response
+    fn response_words(&self) -> usize {
+        0
+    }
+
+    /// Decode the response from its data words, whose CRC was already
+    /// verified
+    fn decode(&self, words: &[u16]) -> Self::Response;
+}
+
+impl SensorCommand for scd4x::Command {
+    type Response = ();
+
+    fn op_code(&self) -> u16 {
+        self.op_code
+    }
+
+    fn exec_time_us(&self) -> u32 {
+        self.exec_time_us
+    }
+
+    fn allowed_while_running(&self) -> bool {
+        self.allowed_while_running
+    }
+
+    fn decode(&self, _words: &[u16]) -> Self::Response {}
+}
+
+impl SensorCommand for scd30::Command {
+    type Response = ();
+
+    fn op_code(&self) -> u16 {
+        scd30::Command::op_code(*self)
+    }
+
+    fn exec_time_us(&self) -> u32 {
+        self.exec_time().as_micros() as u32
+    }
+
+    fn allowed_while_running(&self) -> bool {
+        true
+    }
+
+    fn decode(&self, _words: &[u16]) -> Self::Response {}
+}
+
+#[cfg(not(feature = "custom-commands"))]
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::scd4x::Command {}
+    impl Sealed for super::scd30::Command {}
+}
+
+#[cfg(feature = "custom-commands")]
+mod sealed {
+    pub trait Sealed {}
+
+    impl<T: ?Sized> Sealed for T {}
+}
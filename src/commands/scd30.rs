@@ -0,0 +1,131 @@
+use crate::wire::common::opcode_with_data_into_payload;
+use core::time::Duration;
+
+/// The delay in milliseconds after each write command (Section 1.1.2).
+///
+/// The datasheet is ambiguous whether the driver should wait after each write
+/// command. For some commands (1.4.4-GetDataReady, 1.4.5-DataMeasurement)
+/// it's explicitly specified that the implementations must wait at least 3ms
+/// before reading the response. For other commands, such as 1.4.6-FRC/ASC, it
+/// is not explicitly specified, but then it would contradict the diagram
+/// at 1.1.2. So take the safer route and always perform a delay after a write
+/// command
+pub const WRITE_DELAY_MILLIS: u32 = 5;
+
+/// The boot delay of the sensor is at most 2s (Section 1.1)
+pub const BOOT_DELAY_MILLIS: u32 = 2_000;
+
+/// The delay after each command, before the response can be read or another
+/// command can be sent. Same as [WRITE_DELAY_MILLIS]
+pub const WRITE_DELAY: Duration = Duration::from_millis(WRITE_DELAY_MILLIS as u64);
+
+/// The time the sensor needs to boot after a power-on or a [SOFT_RESET].
+/// Same as [BOOT_DELAY_MILLIS]
+pub const BOOT_DELAY: Duration = Duration::from_millis(BOOT_DELAY_MILLIS as u64);
+
+/// Start the continuous measurement, optionally with pressure compensation
+pub const START_CONTINUOUS_MEASUREMENT: Command = Command(0x0010);
+/// Stop the continuous measurement
+pub const STOP_CONTINUOUS_MEASUREMENT: Command = Command(0x0104);
+/// Get or set the measurement interval in seconds
+pub const GET_SET_MEASUREMENT_INTERVAL: Command = Command(0x4600);
+/// Check whether a measurement can be read
+pub const GET_DATA_READY_STATUS: Command = Command(0x0202);
+/// Read the latest measurement
+pub const READ_MEASUREMENT: Command = Command(0x0300);
+/// Get or set whether the automatic self-calibration is enabled
+pub const MANAGE_AUTOMATIC_SELF_CALIBRATION: Command = Command(0x5306);
+/// Get or set the forced recalibration reference value in PPM
+pub const SET_FORCED_RECALIBRATION_VALUE: Command = Command(0x5204);
+/// Get or set the temperature offset in 0.01 degrees Celsius
+pub const GET_SET_TEMPERATURE_OFFSET: Command = Command(0x5403);
+/// Get or set the altitude above sea level in meters
+pub const GET_SET_ALTITUDE_COMPENSATION: Command = Command(0x5102);
+/// Read the firmware version as (major, minor) bytes
+pub const READ_FIRMWARE_VERSION: Command = Command(0xD100);
+/// Restart the sensor
+pub const SOFT_RESET: Command = Command(0xD304);
+
+/// A command of the SCD30 sensor. Commands which set a value and commands
+/// which read it back share the same opcode and differ only by the
+/// presence of the data word
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Command(u16);
+
+impl Command {
+    /// The opcode of the command
+    pub const fn op_code(self) -> u16 {
+        self.0
+    }
+
+    /// The time the sensor needs to execute the command, before the response
+    /// can be read or another command can be sent. The same for all commands,
+    /// except for [SOFT_RESET], after which the sensor needs [BOOT_DELAY]
+    pub const fn exec_time(self) -> Duration {
+        if self.0 == SOFT_RESET.0 {
+            return BOOT_DELAY;
+        }
+
+        WRITE_DELAY
+    }
+
+    /// Build the frame of a command without arguments
+    pub const fn prepare(self) -> [u8; 2] {
+        self.0.to_be_bytes()
+    }
+
+    /// Build the frame of a command with an argument
+    pub const fn prepare_with_data(self, data: u16) -> [u8; 5] {
+        opcode_with_data_into_payload(self.0, data)
+    }
+}
+
+/// All commands of the SCD30 sensor with their names, e.g. for decoding
+/// captured bus traffic
+pub const ALL: [(&str, Command); 11] = [
+    ("START_CONTINUOUS_MEASUREMENT", START_CONTINUOUS_MEASUREMENT),
+    ("STOP_CONTINUOUS_MEASUREMENT", STOP_CONTINUOUS_MEASUREMENT),
+    ("GET_SET_MEASUREMENT_INTERVAL", GET_SET_MEASUREMENT_INTERVAL),
+    ("GET_DATA_READY_STATUS", GET_DATA_READY_STATUS),
+    ("READ_MEASUREMENT", READ_MEASUREMENT),
+    (
+        "MANAGE_AUTOMATIC_SELF_CALIBRATION",
+        MANAGE_AUTOMATIC_SELF_CALIBRATION,
+    ),
+    (
+        "SET_FORCED_RECALIBRATION_VALUE",
+        SET_FORCED_RECALIBRATION_VALUE,
+    ),
+    ("GET_SET_TEMPERATURE_OFFSET", GET_SET_TEMPERATURE_OFFSET),
+    (
+        "GET_SET_ALTITUDE_COMPENSATION",
+        GET_SET_ALTITUDE_COMPENSATION,
+    ),
+    ("READ_FIRMWARE_VERSION", READ_FIRMWARE_VERSION),
+    ("SOFT_RESET", SOFT_RESET),
+];
+
+/// Find the command with the given opcode
+pub fn by_op_code(op_code: u16) -> Option<(&'static str, Command)> {
+    ALL.iter()
+        .copied()
+        .find(|(_, cmd)| cmd.op_code() == op_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_op_code() {
+        assert_eq!(Some(("SOFT_RESET", SOFT_RESET)), by_op_code(0xD304));
+        assert_eq!(None, by_op_code(0x0000));
+    }
+
+    #[test]
+    fn test_exec_time() {
+        assert_eq!(Duration::from_millis(5), READ_MEASUREMENT.exec_time());
+        assert_eq!(Duration::from_secs(2), SOFT_RESET.exec_time());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::wire::common::opcode_with_data_into_payload;
+use core::time::Duration;
+
+/// The interval between two measurements in periodic mode in milliseconds
+pub const PERIODIC_MEASUREMENT_INTERVAL_MILLIS: u32 = 5_000;
+
+/// The interval between two measurements in low power periodic mode in
+/// milliseconds (Section 3.6.1)
+pub const LOW_POWER_PERIODIC_MEASUREMENT_INTERVAL_MILLIS: u32 = 30_000;
+
+/// The minimum operation time in periodic mode before a forced
+/// recalibration in milliseconds (Section 3.8.1)
+pub const FRC_MIN_OPERATION_MILLIS: u32 = 3 * 60 * 1_000;
+
+/// The interval between two measurements in periodic mode. Same as
+/// [PERIODIC_MEASUREMENT_INTERVAL_MILLIS]
+pub const PERIODIC_MEASUREMENT_INTERVAL: Duration =
+    Duration::from_millis(PERIODIC_MEASUREMENT_INTERVAL_MILLIS as u64);
+
+/// The interval between two measurements in low power periodic mode. Same
+/// as [LOW_POWER_PERIODIC_MEASUREMENT_INTERVAL_MILLIS]
+pub const LOW_POWER_PERIODIC_MEASUREMENT_INTERVAL: Duration =
+    Duration::from_millis(LOW_POWER_PERIODIC_MEASUREMENT_INTERVAL_MILLIS as u64);
+
+/// The minimum operation time in periodic mode before a forced
+/// recalibration. Same as [FRC_MIN_OPERATION_MILLIS]
+pub const FRC_MIN_OPERATION: Duration = Duration::from_millis(FRC_MIN_OPERATION_MILLIS as u64);
+
+/// Start the periodic measurement with a 5s interval
+pub const START_PERIODIC_MEASUREMENT: Command = Command::new(0x21b1, 0, false);
+/// Start the periodic measurement with a 30s interval
+pub const START_LOW_POWER_PERIODIC_MEASUREMENT: Command = Command::new(0x21ac, 0, false);
+/// Stop the periodic measurement
+pub const STOP_PERIODIC_MEASUREMENT: Command = Command::new(0x3f86, 500_000, true);
+
+/// Check whether a measurement can be read
+pub const GET_DATA_READY_STATUS: Command = Command::new(0xe4b8, 1_000, true);
+/// Read the latest measurement
+pub const READ_MEASUREMENT: Command = Command::new(0xec05, 1_000, true);
+
+/// Set the temperature offset
+pub const SET_TEMPERATURE_OFFSET: Command = Command::new(0x241d, 1_000, false);
+/// Get the temperature offset
+pub const GET_TEMPERATURE_OFFSET: Command = Command::new(0x2318, 1_000, false);
+
+/// Set the altitude above sea level in meters
+pub const SET_SENSOR_ALTITUDE: Command = Command::new(0x2427, 1_000, false);
+/// Get the altitude above sea level in meters
+pub const GET_SENSOR_ALTITUDE: Command = Command::new(0x2322, 1_000, false);
+
+/// Set the ambient pressure in hPa
+pub const SET_AMBIENT_PRESSURE: Command = Command::new(0xe000, 1_000, true);
+/// Get the ambient pressure in hPa
+pub const GET_AMBIENT_PRESSURE: Command = Command::new(0xe000, 1_000, true);
+
+/// Enable or disable the automatic self-calibration
+pub const SET_AUTOMATIC_SELF_CALIBRATION_ENABLED: Command = Command::new(0x2416, 1_000, false);
+/// Check whether the automatic self-calibration is enabled
+pub const GET_AUTOMATIC_SELF_CALIBRATION_ENABLED: Command = Command::new(0x2313, 1_000, false);
+
+/// Set the automatic self-calibration target in PPM
+pub const SET_AUTOMATIC_SELF_CALIBRATION_TARGET: Command = Command::new(0x243a, 1_000, false);
+/// Get the automatic self-calibration target in PPM
+pub const GET_AUTOMATIC_SELF_CALIBRATION_TARGET: Command = Command::new(0x233f, 1_000, false);
+/// Perform a forced recalibration against a reference value in PPM
+pub const PERFORM_FORCED_RECALIBRATION: Command = Command::new(0x362f, 400_000, false);
+
+/// Store the configuration in the EEPROM
+pub const PERSIST_SETTINGS: Command = Command::new(0x3615, 800_000, false);
+/// Read the serial number
+pub const GET_SERIAL_NUMBER: Command = Command::new(0x3682, 1_000, false);
+/// Read the sensor variant
+pub const GET_SENSOR_VARIANT: Command = Command::new(0x202f, 1_000, false);
+
+/// Perform the built-in self test
+pub const PERFORM_SELF_TEST: Command = Command::new(0x3639, 10_000_000, false);
+/// Reset the configuration to the factory defaults
+pub const PERFORM_FACTORY_RESET: Command = Command::new(0x3632, 1_200_000, false);
+/// Reload the configuration from the EEPROM
+pub const REINIT: Command = Command::new(0x3646, 30_000, false);
+
+/// Perform a single-shot measurement (SCD41 only)
+pub const MEASURE_SINGLE_SHOT: Command = Command::new(0x219d, 5_000_000, false);
+
+/// Perform a single-shot temperature and humidity measurement (SCD41 only)
+pub const MEASURE_SINGLE_SHOT_RHT_ONLY: Command = Command::new(0x2196, 50_000, false);
+
+/// Put the sensor into sleep mode (SCD41 only)
+pub const POWER_DOWN: Command = Command::new(0x36e0, 1_000, false);
+
+/// Wake up the sensor from sleep mode (SCD41 only)
+pub const WAKE_UP: Command = Command::new(0x36f6, 30_000, false);
+
+/// Set the initial automatic self-calibration period in hours (SCD41 only)
+pub const SET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD: Command =
+    Command::new(0x2445, 1_000, false);
+
+/// Get the initial automatic self-calibration period in hours (SCD41 only)
+pub const GET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD: Command =
+    Command::new(0x2340, 1_000, false);
+
+/// Set the standard automatic self-calibration period in hours (SCD41 only)
+pub const SET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD: Command =
+    Command::new(0x244e, 1_000, false);
+
+/// Get the standard automatic self-calibration period in hours (SCD41 only)
+pub const GET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD: Command =
+    Command::new(0x234b, 1_000, false);
+
+/// A command of the SCD4x sensors
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Command {
+    /// The opcode sent on the wire
+    pub op_code: u16,
+
+    /// The time in microseconds the sensor needs to execute the command,
+    /// before the response can be read or another command can be sent
+    pub exec_time_us: u32,
+
+    /// Whether the sensor accepts the command during periodic measurement
+    pub allowed_while_running: bool,
+}
+
+impl Command {
+    const fn new(op_code: u16, exec_time_us: u32, allowed_while_running: bool) -> Self {
+        Self {
+            op_code,
+            exec_time_us,
+            allowed_while_running,
+        }
+    }
+
+    /// The time the sensor needs to execute the command, same as
+    /// [Command::exec_time_us]
+    pub const fn exec_time(self) -> Duration {
+        Duration::from_micros(self.exec_time_us as u64)
+    }
+
+    /// The time the sensor needs to execute the command in milliseconds,
+    /// rounded up
+    pub const fn exec_time_ms(self) -> u32 {
+        self.exec_time_us.div_ceil(1_000)
+    }
+
+    /// Build the frame of a command without arguments
+    pub const fn prepare(self) -> [u8; 2] {
+        self.op_code.to_be_bytes()
+    }
+
+    /// Build the frame of a command with an argument
+    pub const fn prepare_with_data(self, data: u16) -> [u8; 5] {
+        opcode_with_data_into_payload(self.op_code, data)
+    }
+}
+
+/// All commands of the SCD4x sensors with their names, e.g. for decoding
+/// captured bus traffic. [SET_AMBIENT_PRESSURE] and [GET_AMBIENT_PRESSURE]
+/// share the same opcode and differ only by the presence of the data word
+pub const ALL: [(&str, Command); 30] = [
+    ("START_PERIODIC_MEASUREMENT", START_PERIODIC_MEASUREMENT),
+    (
+        "START_LOW_POWER_PERIODIC_MEASUREMENT",
+        START_LOW_POWER_PERIODIC_MEASUREMENT,
+    ),
+    ("STOP_PERIODIC_MEASUREMENT", STOP_PERIODIC_MEASUREMENT),
+    ("GET_DATA_READY_STATUS", GET_DATA_READY_STATUS),
+    ("READ_MEASUREMENT", READ_MEASUREMENT),
+    ("SET_TEMPERATURE_OFFSET", SET_TEMPERATURE_OFFSET),
+    ("GET_TEMPERATURE_OFFSET", GET_TEMPERATURE_OFFSET),
+    ("SET_SENSOR_ALTITUDE", SET_SENSOR_ALTITUDE),
+    ("GET_SENSOR_ALTITUDE", GET_SENSOR_ALTITUDE),
+    ("SET_AMBIENT_PRESSURE", SET_AMBIENT_PRESSURE),
+    ("GET_AMBIENT_PRESSURE", GET_AMBIENT_PRESSURE),
+    (
+        "SET_AUTOMATIC_SELF_CALIBRATION_ENABLED",
+        SET_AUTOMATIC_SELF_CALIBRATION_ENABLED,
+    ),
+    (
+        "GET_AUTOMATIC_SELF_CALIBRATION_ENABLED",
+        GET_AUTOMATIC_SELF_CALIBRATION_ENABLED,
+    ),
+    (
+        "SET_AUTOMATIC_SELF_CALIBRATION_TARGET",
+        SET_AUTOMATIC_SELF_CALIBRATION_TARGET,
+    ),
+    (
+        "GET_AUTOMATIC_SELF_CALIBRATION_TARGET",
+        GET_AUTOMATIC_SELF_CALIBRATION_TARGET,
+    ),
+    ("PERFORM_FORCED_RECALIBRATION", PERFORM_FORCED_RECALIBRATION),
+    ("PERSIST_SETTINGS", PERSIST_SETTINGS),
+    ("GET_SERIAL_NUMBER", GET_SERIAL_NUMBER),
+    ("GET_SENSOR_VARIANT", GET_SENSOR_VARIANT),
+    ("PERFORM_SELF_TEST", PERFORM_SELF_TEST),
+    ("PERFORM_FACTORY_RESET", PERFORM_FACTORY_RESET),
+    ("REINIT", REINIT),
+    ("MEASURE_SINGLE_SHOT", MEASURE_SINGLE_SHOT),
+    ("MEASURE_SINGLE_SHOT_RHT_ONLY", MEASURE_SINGLE_SHOT_RHT_ONLY),
+    ("POWER_DOWN", POWER_DOWN),
+    ("WAKE_UP", WAKE_UP),
+    (
+        "SET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD",
+        SET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD,
+    ),
+    (
+        "GET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD",
+        GET_AUTOMATIC_SELF_CALIBRATION_INITIAL_PERIOD,
+    ),
+    (
+        "SET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD",
+        SET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD,
+    ),
+    (
+        "GET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD",
+        GET_AUTOMATIC_SELF_CALIBRATION_STANDARD_PERIOD,
+    ),
+];
+
+/// Find the command with the given opcode
+pub fn by_op_code(op_code: u16) -> Option<(&'static str, Command)> {
+    ALL.iter().copied().find(|(_, cmd)| cmd.op_code == op_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_op_code() {
+        assert_eq!(
+            Some(("STOP_PERIODIC_MEASUREMENT", STOP_PERIODIC_MEASUREMENT)),
+            by_op_code(0x3f86)
+        );
+        assert_eq!(None, by_op_code(0x0000));
+    }
+
+    #[test]
+    fn test_exec_time() {
+        assert_eq!(
+            Duration::from_millis(500),
+            STOP_PERIODIC_MEASUREMENT.exec_time()
+        );
+        assert_eq!(Duration::from_secs(10), PERFORM_SELF_TEST.exec_time());
+        assert_eq!(0, START_PERIODIC_MEASUREMENT.exec_time_ms());
+        assert_eq!(1, GET_DATA_READY_STATUS.exec_time_ms());
+        assert_eq!(30, WAKE_UP.exec_time_ms());
+    }
+}
@@ -0,0 +1,411 @@
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+use crate::calibration::MeasurementCorrection;
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+use crate::diagnostics::DiagnosticCounters;
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+use crate::error::ErrorKind;
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+use crate::wire::scd4x::{AMBIENT_PRESSURE_RANGE_HPA, MAX_ALTITUDE, MAX_TEMPERATURE_OFFSET};
+
+/// The source of the pressure compensation of the CO2 measurement, as
+/// configured through the driver. Both sensors ignore the altitude once an
+/// ambient pressure is given, so only one of them is active at a time
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Compensation {
+    /// Neither the altitude nor the ambient pressure was set by the driver
+    #[default]
+    None,
+
+    /// The altitude above sea level in meters
+    Altitude(u16),
+
+    /// The ambient pressure in hPa
+    AmbientPressure(u16),
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+/// A configuration setting of the SCD4x sensors, used to apply several
+/// settings as one batch
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Scd4xSetting {
+    /// The temperature offset in Celsius
+    TemperatureOffset(f32),
+
+    /// The sensor altitude in meters above sea level
+    SensorAltitude(u16),
+
+    /// The ambient pressure in hPa
+    AmbientPressure(u16),
+
+    /// Whether the automatic self-calibration is enabled
+    AutomaticSelfCalibration(bool),
+
+    /// The ASC baseline target in PPM
+    AutomaticSelfCalibrationTarget(u16),
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+impl Scd4xSetting {
+    /// Check if the value of the setting is accepted by the sensor. The
+    /// temperature offset must be within the range of 0 - 20 degrees Celsius
+    /// recommended by the datasheet
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Scd4xSetting::TemperatureOffset(offset) => {
+                offset.is_finite() && !offset.is_sign_negative() && offset <= MAX_TEMPERATURE_OFFSET
+            }
+            Scd4xSetting::SensorAltitude(altitude) => altitude <= MAX_ALTITUDE,
+            Scd4xSetting::AmbientPressure(pressure) => {
+                AMBIENT_PRESSURE_RANGE_HPA.contains(&pressure)
+            }
+            Scd4xSetting::AutomaticSelfCalibration(_) => true,
+            Scd4xSetting::AutomaticSelfCalibrationTarget(_) => true,
+        }
+    }
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+/// The settings written to an SCD4x sensor that have not been persisted to
+/// its EEPROM, and thus are lost when the sensor restarts, e.g. after a
+/// brown-out of its supply rail
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VolatileSettings {
+    /// The temperature offset in Celsius
+    pub temperature_offset: Option<f32>,
+
+    /// The sensor altitude in meters above sea level
+    pub sensor_altitude: Option<u16>,
+
+    /// The ambient pressure in hPa. The sensor never persists it
+    pub ambient_pressure: Option<u16>,
+
+    /// Whether the automatic self-calibration is enabled
+    pub automatic_self_calibration: Option<bool>,
+
+    /// The ASC baseline target in PPM
+    pub automatic_self_calibration_target: Option<u16>,
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+impl VolatileSettings {
+    /// Check if there are no settings to be restored
+    pub fn is_empty(&self) -> bool {
+        self.settings().next().is_none()
+    }
+
+    /// The settings to be restored, in the order they would be applied
+    pub fn settings(&self) -> impl Iterator<Item = Scd4xSetting> {
+        [
+            self.temperature_offset.map(Scd4xSetting::TemperatureOffset),
+            self.sensor_altitude.map(Scd4xSetting::SensorAltitude),
+            self.ambient_pressure.map(Scd4xSetting::AmbientPressure),
+            self.automatic_self_calibration
+                .map(Scd4xSetting::AutomaticSelfCalibration),
+            self.automatic_self_calibration_target
+                .map(Scd4xSetting::AutomaticSelfCalibrationTarget),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub(crate) fn record(&mut self, setting: Scd4xSetting) {
+        match setting {
+            Scd4xSetting::TemperatureOffset(offset) => self.temperature_offset = Some(offset),
+            Scd4xSetting::SensorAltitude(altitude) => self.sensor_altitude = Some(altitude),
+            Scd4xSetting::AmbientPressure(pressure) => self.ambient_pressure = Some(pressure),
+            Scd4xSetting::AutomaticSelfCalibration(enabled) => {
+                self.automatic_self_calibration = Some(enabled)
+            }
+            Scd4xSetting::AutomaticSelfCalibrationTarget(ppm) => {
+                self.automatic_self_calibration_target = Some(ppm)
+            }
+        }
+    }
+
+    // Everything except the ambient pressure is stored in the EEPROM by the
+    // persist settings command
+    pub(crate) fn persisted(&mut self) {
+        *self = Self {
+            ambient_pressure: self.ambient_pressure,
+            ..Self::default()
+        };
+    }
+}
+
+/// A snapshot of the state tracked by an SCD4x driver, e.g. the measurement
+/// mode and the cached volatile settings. It can be kept in a memory that
+/// is retained while the MCU is in deep sleep, such as the RTC RAM, in order
+/// to recreate the driver after the wake-up without stopping or
+/// re-initializing a sensor that kept measuring. Can be serialized when the
+/// `serde` feature is enabled.
+///
+/// The time spent in deep sleep is not known to the driver, so it is not
+/// counted towards the runtime required before a forced recalibration.
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriverState {
+    pub(crate) address: u8,
+    pub(crate) measurement_started: bool,
+    pub(crate) low_power: bool,
+    pub(crate) single_shot_us: Option<u32>,
+    pub(crate) warm_up_samples: u8,
+    pub(crate) runtime_micros: u32,
+    pub(crate) measurements: u32,
+    pub(crate) operating_ms: u64,
+    pub(crate) operating_tick: Option<u64>,
+    pub(crate) frc_runtime_check: bool,
+    pub(crate) verify_crc: bool,
+    pub(crate) crc_recovery_threshold: u8,
+    pub(crate) counters: DiagnosticCounters,
+    pub(crate) last_error: Option<ErrorKind>,
+    pub(crate) volatile: Option<VolatileSettings>,
+    pub(crate) pending: VolatileSettings,
+    pub(crate) altitude: Option<u16>,
+    pub(crate) ambient_pressure: Option<u16>,
+    pub(crate) compensation_check: bool,
+    pub(crate) correction: Option<MeasurementCorrection>,
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+impl DriverState {
+    /// The I2C address of the sensor
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Check if the sensor was measuring periodically when the state was
+    /// captured
+    pub fn is_measuring(&self) -> bool {
+        self.measurement_started
+    }
+
+    /// The counters of the driver when the state was captured
+    pub fn counters(&self) -> DiagnosticCounters {
+        self.counters
+    }
+}
+
+/// The settings written to an SCD30 sensor by the driver. The sensor stores
+/// them in its non-volatile memory, but the driver keeps a copy to verify
+/// and restore them after a reset. `None` if the setting was not written
+/// by the driver
+#[cfg(feature = "scd30")]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Scd30Settings {
+    /// The measurement interval in seconds
+    pub measurement_interval: Option<u16>,
+
+    /// The temperature offset in ticks of 0.01 degrees Celsius
+    pub temperature_offset: Option<u16>,
+
+    /// The altitude above sea level in meters
+    pub altitude: Option<u16>,
+
+    /// Whether the automatic self-calibration is enabled
+    pub automatic_self_calibration: Option<bool>,
+
+    /// The ambient pressure in hPa the continuous measurement was last
+    /// started with, where `0` disables the pressure compensation
+    pub ambient_pressure: Option<u16>,
+}
+
+#[cfg(feature = "scd30")]
+impl Scd30Settings {
+    /// The source of the pressure compensation according to the settings.
+    /// A non-zero ambient pressure takes precedence over the altitude
+    pub fn compensation(&self) -> Compensation {
+        match (self.ambient_pressure, self.altitude) {
+            (Some(pressure), _) if pressure != 0 => Compensation::AmbientPressure(pressure),
+            (_, Some(altitude)) => Compensation::Altitude(altitude),
+            _ => Compensation::None,
+        }
+    }
+
+    pub(crate) fn has_ambient_pressure(&self) -> bool {
+        self.ambient_pressure.is_some_and(|pressure| pressure != 0)
+    }
+}
+
+/// The default ASC baseline target in PPM, i.e. the CO2 concentration of
+/// the fresh outdoor air
+const ASC_BASELINE_PPM: u16 = 400;
+
+/// The largest deviation of the ASC target from the baseline, which is not
+/// reported as suspicious
+const ASC_TARGET_TOLERANCE_PPM: u16 = 50;
+
+/// The measurement interval in seconds the ASC of the SCD30 is designed for
+const SCD30_ASC_INTERVAL_SECONDS: u16 = 2;
+
+/// The configuration read back from a sensor, used to validate it before
+/// the sensor is deployed
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigurationSnapshot {
+    /// The temperature offset in Celsius
+    pub temperature_offset: f32,
+
+    /// The altitude above sea level in meters
+    pub altitude: u16,
+
+    /// The ambient pressure in hPa set through the driver. Neither sensor
+    /// reports whether the pressure compensation is in use, so `None` if it
+    /// was not set by the driver
+    pub ambient_pressure: Option<u16>,
+
+    /// Whether the automatic self-calibration is enabled
+    pub automatic_self_calibration: bool,
+
+    /// The ASC baseline target in PPM. `None` for the SCD30, which does not
+    /// support it
+    pub automatic_self_calibration_target: Option<u16>,
+
+    /// The measurement interval in seconds. `None` for the SCD4x, where it
+    /// is defined by the measurement mode
+    pub measurement_interval: Option<u16>,
+}
+
+impl ConfigurationSnapshot {
+    /// Check the configuration for inconsistent or suspicious values.
+    /// `enclosed` tells if the sensor is mounted in an enclosure, where a
+    /// temperature offset is needed to compensate its self-heating
+    pub fn validate(&self, enclosed: bool) -> ConfigurationReport {
+        let asc = self.automatic_self_calibration;
+
+        ConfigurationReport {
+            snapshot: *self,
+            missing_temperature_offset: enclosed && self.temperature_offset == 0.0,
+            asc_target_off_baseline: asc
+                && self
+                    .automatic_self_calibration_target
+                    .is_some_and(|ppm| ppm.abs_diff(ASC_BASELINE_PPM) > ASC_TARGET_TOLERANCE_PPM),
+            compensation_conflict: self.altitude != 0 && self.ambient_pressure.is_some(),
+            asc_interval_mismatch: asc
+                && self
+                    .measurement_interval
+                    .is_some_and(|seconds| seconds != SCD30_ASC_INTERVAL_SECONDS),
+        }
+    }
+}
+
+/// The outcome of the validation of the configuration of a sensor, meant
+/// for provisioning tools. None of the findings prevents the sensor from
+/// measuring, but each of them usually degrades the accuracy
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigurationReport {
+    /// The validated configuration
+    pub snapshot: ConfigurationSnapshot,
+
+    /// The temperature offset is zero, although the sensor is mounted in
+    /// an enclosure
+    pub missing_temperature_offset: bool,
+
+    /// The ASC is enabled, but its target is far from the 400 PPM of the
+    /// fresh outdoor air
+    pub asc_target_off_baseline: bool,
+
+    /// Both the altitude and the ambient pressure compensation are set,
+    /// thus the altitude is ignored
+    pub compensation_conflict: bool,
+
+    /// The ASC is enabled, but the measurement interval differs from the
+    /// 2 seconds the ASC of the SCD30 is designed for
+    pub asc_interval_mismatch: bool,
+}
+
+impl ConfigurationReport {
+    /// Check if the validation did not find any issues
+    pub fn is_consistent(&self) -> bool {
+        !(self.missing_temperature_offset
+            || self.asc_target_off_baseline
+            || self.compensation_conflict
+            || self.asc_interval_mismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> ConfigurationSnapshot {
+        ConfigurationSnapshot {
+            temperature_offset: 4.0,
+            altitude: 0,
+            ambient_pressure: None,
+            automatic_self_calibration: true,
+            automatic_self_calibration_target: Some(400),
+            measurement_interval: None,
+        }
+    }
+
+    #[test]
+    fn consistent_configuration() {
+        assert!(snapshot().validate(true).is_consistent());
+    }
+
+    #[test]
+    fn missing_temperature_offset() {
+        let snapshot = ConfigurationSnapshot {
+            temperature_offset: 0.0,
+            ..snapshot()
+        };
+
+        assert!(snapshot.validate(true).missing_temperature_offset);
+        assert!(snapshot.validate(false).is_consistent());
+    }
+
+    #[test]
+    fn asc_target_off_baseline() {
+        let snapshot = ConfigurationSnapshot {
+            automatic_self_calibration_target: Some(500),
+            ..snapshot()
+        };
+        assert!(snapshot.validate(true).asc_target_off_baseline);
+
+        let snapshot = ConfigurationSnapshot {
+            automatic_self_calibration: false,
+            ..snapshot
+        };
+        assert!(snapshot.validate(true).is_consistent());
+    }
+
+    #[test]
+    fn compensation_conflict() {
+        let snapshot = ConfigurationSnapshot {
+            altitude: 600,
+            ambient_pressure: Some(950),
+            ..snapshot()
+        };
+
+        let report = snapshot.validate(true);
+        assert!(report.compensation_conflict);
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn asc_interval_mismatch() {
+        let snapshot = ConfigurationSnapshot {
+            automatic_self_calibration_target: None,
+            measurement_interval: Some(30),
+            ..snapshot()
+        };
+        assert!(snapshot.validate(true).asc_interval_mismatch);
+
+        let snapshot = ConfigurationSnapshot {
+            measurement_interval: Some(2),
+            ..snapshot
+        };
+        assert!(snapshot.validate(true).is_consistent());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::config::ConfigurationSnapshot;
+use crate::error::{Error, ErrorKind};
+use crate::measurement::Fixed;
+use crate::variant::{FirmwareVersion, SensorVariant};
+use core::fmt::{Display, Formatter};
+
+/// The measurement mode of the sensor as tracked by the driver
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MeasurementMode {
+    /// No measurement was started through the driver
+    #[default]
+    Idle,
+
+    /// The periodic measurement of the SCD4x, or the continuous measurement
+    /// of the SCD30
+    Periodic,
+
+    /// The low power periodic measurement of the SCD4x
+    LowPowerPeriodic,
+
+    /// The single shot measurements of the SCD41 and the SCD43
+    SingleShot,
+}
+
+/// The counters of the events seen by a driver since it was created
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiagnosticCounters {
+    /// The number of measurements read from the sensor
+    pub measurements: u32,
+
+    /// The number of failed transfers on the bus
+    pub bus_errors: u32,
+
+    /// The number of responses that failed the CRC validation
+    pub crc_errors: u32,
+
+    /// The number of times the sensor was re-initialized after repeated
+    /// CRC failures
+    pub recoveries: u32,
+}
+
+impl DiagnosticCounters {
+    pub(crate) fn record<E>(&mut self, error: &Error<E>) -> ErrorKind {
+        match error {
+            Error::I2C(_) => self.bus_errors = self.bus_errors.saturating_add(1),
+            Error::CRC(_) => self.crc_errors = self.crc_errors.saturating_add(1),
+            _ => {}
+        }
+
+        error.kind()
+    }
+}
+
+/// A dump of everything the driver knows about its sensor, meant to be
+/// attached to support tickets and crash reports. It implements `Display`,
+/// and can be serialized when the `serde` feature is enabled.
+///
+/// The sensor is queried without interrupting the measurement, so the
+/// SCD4x details that can only be read while the sensor is idle are `None`
+/// during the periodic measurement. A query that fails is also reported as
+/// `None`. The counters and the last error are captured before the queries
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics {
+    /// The sensor model
+    pub variant: Option<SensorVariant>,
+
+    /// The serial number of the SCD4x sensors
+    pub serial_number: Option<u64>,
+
+    /// The firmware version of the SCD30
+    pub firmware_version: Option<FirmwareVersion>,
+
+    /// The firmware revision reported by the SCD4x sensors
+    pub firmware_revision: Option<u16>,
+
+    /// The configuration read back from the sensor
+    pub settings: Option<ConfigurationSnapshot>,
+
+    /// The measurement mode tracked by the driver
+    pub mode: MeasurementMode,
+
+    /// The time in milliseconds the sensor spent in the periodic
+    /// measurement, as tracked by the SCD4x driver
+    pub operating_ms: Option<u64>,
+
+    /// The counters of the driver
+    pub counters: DiagnosticCounters,
+
+    /// The category of the last error of the communication with the sensor,
+    /// if any
+    pub last_error: Option<ErrorKind>,
+}
+
+impl Diagnostics {
+    pub(crate) fn new(
+        mode: MeasurementMode,
+        counters: DiagnosticCounters,
+        last_error: Option<ErrorKind>,
+    ) -> Self {
+        Self {
+            mode,
+            counters,
+            last_error,
+            ..Self::default()
+        }
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.variant {
+            Some(variant) => writeln!(f, "variant: {}", variant)?,
+            None => writeln!(f, "variant: -")?,
+        }
+        match self.serial_number {
+            Some(serial) => writeln!(f, "serial number: {:#014x}", serial)?,
+            None => writeln!(f, "serial number: -")?,
+        }
+        if let Some(version) = self.firmware_version {
+            writeln!(f, "firmware: {}", version)?;
+        }
+        if let Some(revision) = self.firmware_revision {
+            writeln!(f, "firmware revision: {:#05x}", revision)?;
+        }
+
+        match &self.settings {
+            Some(settings) => {
+                let offset = Fixed::new(settings.temperature_offset, 2);
+                writeln!(f, "temperature offset: {} C", offset)?;
+                writeln!(f, "altitude: {} m", settings.altitude)?;
+                match settings.ambient_pressure {
+                    Some(pressure) => writeln!(f, "ambient pressure: {} hPa", pressure)?,
+                    None => writeln!(f, "ambient pressure: -")?,
+                }
+                writeln!(f, "ASC: {}", settings.automatic_self_calibration)?;
+                if let Some(target) = settings.automatic_self_calibration_target {
+                    writeln!(f, "ASC target: {} ppm", target)?;
+                }
+                if let Some(interval) = settings.measurement_interval {
+                    writeln!(f, "measurement interval: {} s", interval)?;
+                }
+            }
+            None => writeln!(f, "settings: -")?,
+        }
+
+        writeln!(f, "mode: {:?}", self.mode)?;
+        if let Some(operating_ms) = self.operating_ms {
+            writeln!(f, "operating time: {} ms", operating_ms)?;
+        }
+
+        let counters = &self.counters;
+        writeln!(f, "measurements: {}", counters.measurements)?;
+        writeln!(f, "bus errors: {}", counters.bus_errors)?;
+        writeln!(f, "CRC errors: {}", counters.crc_errors)?;
+        writeln!(f, "recoveries: {}", counters.recoveries)?;
+        match self.last_error {
+            Some(kind) => write!(f, "last error: {:?}", kind),
+            None => write!(f, "last error: -"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_record_errors() {
+        let mut counters = DiagnosticCounters::default();
+        assert_eq!(ErrorKind::Bus, counters.record(&Error::I2C(())));
+        assert_eq!(ErrorKind::State, counters.record(&Error::<()>::NoData));
+
+        assert_eq!(1, counters.bus_errors);
+        assert_eq!(0, counters.crc_errors);
+        assert_eq!(0, counters.measurements);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::math::round;
+use crate::measurement::Measurement;
+
+/// The 16-bit UUID of the BTHome service data
+pub const BTHOME_UUID: u16 = 0xFCD2;
+
+/// The maximal length of the service data written by [write_bthome]
+pub const BTHOME_MAX_LEN: usize = 12;
+
+// BTHome v2, unencrypted, sent at regular intervals
+const DEVICE_INFO: u8 = 0x40;
+
+const PACKET_ID: u8 = 0x00;
+const TEMPERATURE: u8 = 0x02;
+const HUMIDITY: u8 = 0x03;
+const CO2: u8 = 0x12;
+
+/// Write a measurement as BTHome v2 service data, to be advertised with the
+/// [BTHOME_UUID], e.g. for Home Assistant. Returns the number of bytes
+/// written, or `None` if the buffer is too small; [BTHOME_MAX_LEN] bytes are
+/// always enough.
+///
+/// The `packet_id` should change with each new measurement, so that the
+/// receivers can discard the duplicates of the same advertisement. The
+/// temperature and the humidity are rounded to 0.01 and are omitted if they
+/// are not finite. The data is not encrypted.
+pub fn write_bthome(m: &Measurement, packet_id: u8, buf: &mut [u8]) -> Option<usize> {
+    let mut out = Cursor { buf, len: 0 };
+    out.put(&[DEVICE_INFO, PACKET_ID, packet_id])?;
+
+    // The objects must be sorted by their IDs
+    if m.temperature.is_finite() {
+        let value = round(m.temperature * 100.0).clamp(i16::MIN as f32, i16::MAX as f32);
+        let [lo, hi] = (value as i16).to_le_bytes();
+        out.put(&[TEMPERATURE, lo, hi])?;
+    }
+
+    if m.humidity.is_finite() {
+        let value = round(m.humidity * 100.0).clamp(0.0, u16::MAX as f32);
+        let [lo, hi] = (value as u16).to_le_bytes();
+        out.put(&[HUMIDITY, lo, hi])?;
+    }
+
+    let [lo, hi] = m.co2.to_le_bytes();
+    out.put(&[CO2, lo, hi])?;
+
+    Some(out.len)
+}
+
+struct Cursor<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Cursor<'_> {
+    fn put(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.len + bytes.len();
+        self.buf.get_mut(self.len..end)?.copy_from_slice(bytes);
+        self.len = end;
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_bthome() {
+        let m = Measurement {
+            temperature: 22.456,
+            humidity: 41.2,
+            co2: 1_250,
+        };
+
+        let mut buf = [0; BTHOME_MAX_LEN];
+        assert_eq!(Some(12), write_bthome(&m, 7, &mut buf));
+        assert_eq!(
+            [0x40, 0x00, 0x07, 0x02, 0xC6, 0x08, 0x03, 0x18, 0x10, 0x12, 0xE2, 0x04],
+            buf
+        );
+
+        // Too small buffer
+        assert_eq!(None, write_bthome(&m, 7, &mut buf[..11]));
+    }
+
+    #[test]
+    fn test_write_bthome_negative_and_invalid() {
+        let m = Measurement {
+            temperature: -5.5,
+            humidity: f32::NAN,
+            co2: 400,
+        };
+
+        let mut buf = [0; BTHOME_MAX_LEN];
+        assert_eq!(Some(9), write_bthome(&m, 0, &mut buf));
+        assert_eq!(
+            [0x40, 0x00, 0x00, 0x02, 0xDA, 0xFD, 0x12, 0x90, 0x01],
+            buf[..9]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::measurement::TimestampedMeasurement;
+use core::fmt::{Result, Write};
+
+/// The header of the CSV measurement logs, without the line terminator
+pub const CSV_HEADER: &str = "timestamp,co2,temperature,humidity";
+
+/// Write the header line of a CSV measurement log, terminated with `\r\n`
+/// as specified by RFC 4180
+pub fn write_csv_header(out: &mut impl Write) -> Result {
+    out.write_str(CSV_HEADER)?;
+    out.write_str("\r\n")
+}
+
+/// Write a measurement as a CSV line, terminated with `\r\n`. The
+/// temperature and the humidity are written with two decimal places, which
+/// exceeds the resolution of the sensors
+pub fn write_csv_row(row: &TimestampedMeasurement, out: &mut impl Write) -> Result {
+    let m = &row.measurement;
+    write!(
+        out,
+        "{},{},{:.2},{:.2}\r\n",
+        row.timestamp, m.co2, m.temperature, m.humidity
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::measurement::Measurement;
+    use std::string::String;
+
+    #[test]
+    fn test_write_csv() {
+        let row = TimestampedMeasurement {
+            timestamp: 1_700_000_000,
+            measurement: Measurement {
+                temperature: 22.456,
+                humidity: -0.001,
+                co2: 800,
+            },
+        };
+
+        let mut out = String::new();
+        write_csv_header(&mut out).unwrap();
+        write_csv_row(&row, &mut out).unwrap();
+        assert_eq!(
+            "timestamp,co2,temperature,humidity\r\n1700000000,800,22.46,-0.00\r\n",
+            out
+        );
+    }
+}
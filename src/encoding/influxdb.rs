@@ -0,0 +1,88 @@
+use crate::measurement::TimestampedMeasurement;
+use core::fmt::{Result, Write};
+
+/// Write a measurement as a record of the InfluxDB line protocol, terminated
+/// with `\n`, e.g.
+///
+/// `co2,room=kitchen co2=800i,temperature=22.46,humidity=41.20 1700000000`
+///
+/// The timestamp is written as is, so the precision of the receiver, e.g.
+/// the `precision` setting of Telegraf, must match its units. The
+/// temperature and the humidity are written with two decimal places and are
+/// omitted if they are not finite. Tags with empty keys or values are
+/// omitted, as they are rejected by InfluxDB. The commas, spaces and equal
+/// signs in the names are escaped.
+pub fn write_line_protocol(
+    out: &mut impl Write,
+    measurement: &str,
+    tags: &[(&str, &str)],
+    row: &TimestampedMeasurement,
+) -> Result {
+    escape(out, measurement, &[',', ' '])?;
+
+    for (key, value) in tags.iter().filter(|(k, v)| !k.is_empty() && !v.is_empty()) {
+        out.write_char(',')?;
+        escape(out, key, &[',', '=', ' '])?;
+        out.write_char('=')?;
+        escape(out, value, &[',', '=', ' '])?;
+    }
+
+    let m = &row.measurement;
+    write!(out, " co2={}i", m.co2)?;
+    for (key, value) in [("temperature", m.temperature), ("humidity", m.humidity)] {
+        if value.is_finite() {
+            write!(out, ",{key}={value:.2}")?;
+        }
+    }
+
+    writeln!(out, " {}", row.timestamp)
+}
+
+fn escape(out: &mut impl Write, value: &str, special: &[char]) -> Result {
+    for c in value.chars() {
+        if c == '\\' || special.contains(&c) {
+            out.write_char('\\')?;
+        }
+        out.write_char(c)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::measurement::Measurement;
+    use std::string::String;
+
+    #[test]
+    fn test_write_line_protocol() {
+        let mut row = TimestampedMeasurement {
+            timestamp: 1_700_000_000,
+            measurement: Measurement {
+                temperature: 22.456,
+                humidity: 41.2,
+                co2: 800,
+            },
+        };
+
+        let mut out = String::new();
+        let tags = [
+            ("serial", "0123456789ab"),
+            ("room", "living room"),
+            ("floor", ""),
+        ];
+        write_line_protocol(&mut out, "co2", &tags, &row).unwrap();
+        assert_eq!(
+            "co2,serial=0123456789ab,room=living\\ room co2=800i,temperature=22.46,humidity=41.20 1700000000\n",
+            out
+        );
+
+        row.measurement.humidity = f32::NAN;
+        let mut out = String::new();
+        write_line_protocol(&mut out, "air quality", &[], &row).unwrap();
+        assert_eq!("air\\ quality co2=800i,temperature=22.46 1700000000\n", out);
+    }
+}
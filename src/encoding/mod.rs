@@ -0,0 +1,18 @@
+//! Encoders producing the common data formats of the measurement logs and
+//! telemetry, without allocations
+
+/// BTHome v2 service data for BLE advertisements, e.g. for Home Assistant
+pub mod bthome;
+
+/// Comma-separated values for measurement logs, e.g. on an SD card
+pub mod csv;
+
+/// InfluxDB line protocol records, e.g. for Telegraf. This module needs to
+/// be enabled via the `influxdb` feature flag
+#[cfg(feature = "influxdb")]
+pub mod influxdb;
+
+/// Sensor measurement lists (SenML, RFC 8428) in the JSON format, e.g. for
+/// LwM2M. This module needs to be enabled via the `senml` feature flag
+#[cfg(feature = "senml")]
+pub mod senml;
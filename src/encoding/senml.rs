@@ -0,0 +1,104 @@
+use crate::measurement::TimestampedMeasurement;
+use core::fmt::{Result, Write};
+
+/// Write a measurement as a SenML pack in the JSON format (RFC 8428), e.g.
+///
+/// `[{"bn":"urn:dev:mac:0024befffe804ff1:","bt":1700000000,"n":"co2","u":"ppm","v":800},`
+/// `{"n":"temperature","u":"Cel","v":22.46},{"n":"humidity","u":"%RH","v":41.20}]`
+///
+/// The base name is prepended by the receivers to the names of the records,
+/// so it usually ends with a separator such as `:` or `/`, and it is omitted
+/// if it's empty. The timestamp is written as the base time, thus it must be
+/// in seconds since the UNIX epoch, or in seconds relative to the current
+/// time if it's lower than 2^28. The temperature and the humidity are
+/// written with two decimal places and are omitted if they are not finite.
+pub fn write_senml_json(
+    out: &mut impl Write,
+    base_name: &str,
+    row: &TimestampedMeasurement,
+) -> Result {
+    out.write_char('[')?;
+
+    out.write_char('{')?;
+    if !base_name.is_empty() {
+        out.write_str("\"bn\":\"")?;
+        escape(out, base_name)?;
+        out.write_str("\",")?;
+    }
+    write!(
+        out,
+        "\"bt\":{},\"n\":\"co2\",\"u\":\"ppm\",\"v\":{}}}",
+        row.timestamp, row.measurement.co2
+    )?;
+
+    let m = &row.measurement;
+    for (name, unit, value) in [
+        ("temperature", "Cel", m.temperature),
+        ("humidity", "%RH", m.humidity),
+    ] {
+        if value.is_finite() {
+            write!(
+                out,
+                ",{{\"n\":\"{name}\",\"u\":\"{unit}\",\"v\":{value:.2}}}"
+            )?;
+        }
+    }
+
+    out.write_char(']')
+}
+
+fn escape(out: &mut impl Write, value: &str) -> Result {
+    for c in value.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::measurement::Measurement;
+    use std::string::String;
+
+    #[test]
+    fn test_write_senml_json() {
+        let mut row = TimestampedMeasurement {
+            timestamp: 1_700_000_000,
+            measurement: Measurement {
+                temperature: 22.456,
+                humidity: 41.2,
+                co2: 800,
+            },
+        };
+
+        let mut out = String::new();
+        write_senml_json(&mut out, "urn:dev:ow:10e2073a01080063:", &row).unwrap();
+        assert_eq!(
+            concat!(
+                r#"[{"bn":"urn:dev:ow:10e2073a01080063:","bt":1700000000,"n":"co2","u":"ppm","v":800},"#,
+                r#"{"n":"temperature","u":"Cel","v":22.46},{"n":"humidity","u":"%RH","v":41.20}]"#
+            ),
+            out
+        );
+
+        row.measurement.temperature = f32::NAN;
+        let mut out = String::new();
+        write_senml_json(&mut out, "", &row).unwrap();
+        assert_eq!(
+            r#"[{"bt":1700000000,"n":"co2","u":"ppm","v":800},{"n":"humidity","u":"%RH","v":41.20}]"#,
+            out
+        );
+
+        let mut out = String::new();
+        escape(&mut out, "a\"b\\c\n").unwrap();
+        assert_eq!(r#"a\"b\\c\u000a"#, out);
+    }
+}
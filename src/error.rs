@@ -1,15 +1,191 @@
 #[derive(Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+#[non_exhaustive]
 pub enum Error<E> {
     /// I2C bus error
     I2C(E),
 
-    /// CRC validation failed
-    CRC,
+    /// CRC validation failed. Contains the details about the first
+    /// word of the response that failed the validation
+    CRC(CrcError),
 
     /// The operation cannot be executed with the current state of the sensor
     NotAllowed,
 
     /// An invalid input was passed as a parameter
     InvalidInput,
+
+    /// The device at the sensor's address is not the expected sensor
+    WrongDevice,
+
+    /// The sensor self test detected a malfunction. Contains the raw
+    /// status word returned by the sensor
+    SelfTestFailed(u16),
+
+    /// The sensor was re-initialized after repeated CRC failures. The
+    /// operation that triggered the recovery was not completed and
+    /// can be retried
+    Recovered,
+
+    /// The sensor rejected the calibration, e.g. because the forced
+    /// recalibration was requested without prior operation of the sensor
+    CalibrationFailed,
+
+    /// The forced recalibration was requested before the sensor was
+    /// operated in periodic measurement mode for the required time
+    InsufficientRuntime,
+
+    /// The length of the buffer does not match the response of the sensor,
+    /// e.g. it's not a multiple of the 3-byte words of the response
+    InvalidBufferLength,
+
+    /// The operation was abandoned by its progress callback. The sensor
+    /// still executes the command, so the driver waits for the remaining
+    /// execution time before sending the next command
+    Aborted,
+
+    /// Both the altitude and the ambient pressure compensation were set,
+    /// while only the ambient pressure takes effect. Only reported when the
+    /// conflict check of the driver is enabled
+    CompensationConflict,
+
+    /// The sensor responded with a measurement frame of zeros, which it
+    /// sends in some setups right after a change of its operating mode.
+    /// No measurement is available, so it should be read again later
+    NoData,
+}
+impl<E> Error<E> {
+    /// Get the category of this error. Useful when the application needs to
+    /// handle whole groups of errors the same way, without matching on every
+    /// variant
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::I2C(_) => ErrorKind::Bus,
+            Error::CRC(_) => ErrorKind::Integrity,
+            Error::NotAllowed => ErrorKind::State,
+            Error::InvalidInput => ErrorKind::Input,
+            Error::WrongDevice => ErrorKind::Device,
+            Error::SelfTestFailed(_) => ErrorKind::Malfunction,
+            Error::Recovered => ErrorKind::Integrity,
+            Error::CalibrationFailed => ErrorKind::State,
+            Error::InsufficientRuntime => ErrorKind::State,
+            Error::InvalidBufferLength => ErrorKind::Input,
+            Error::Aborted => ErrorKind::State,
+            Error::CompensationConflict => ErrorKind::Input,
+            Error::NoData => ErrorKind::State,
+        }
+    }
+}
+
+#[cfg(feature = "embedded-sensors")]
+impl<E: core::fmt::Debug> embedded_sensors_hal::sensor::Error for Error<E> {
+    fn kind(&self) -> embedded_sensors_hal::sensor::ErrorKind {
+        use embedded_sensors_hal::sensor::ErrorKind as SensorErrorKind;
+
+        match self.kind() {
+            ErrorKind::Bus | ErrorKind::Integrity => SensorErrorKind::Peripheral,
+            ErrorKind::Input => SensorErrorKind::InvalidInput,
+            _ => SensorErrorKind::Other,
+        }
+    }
+}
+
+/// An error of one of several sensors operated together, e.g. by a
+/// `SensorGroup`
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct GroupError<E> {
+    /// The index of the sensor that failed
+    pub index: usize,
+
+    /// The error reported by the driver of the sensor
+    pub error: Error<E>,
+}
+
+/// Details about a response word that failed the CRC validation
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct CrcError {
+    /// Index of the 16-bit word (i.e. the 3-byte chunk) within the response
+    pub word: usize,
+
+    /// The CRC byte received from the sensor
+    pub received: u8,
+
+    /// The CRC calculated over the received data bytes
+    pub expected: u8,
+}
+
+/// Categories of errors returned by the drivers
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The communication with the sensor over the bus failed
+    Bus,
+
+    /// The data received from the sensor is corrupted
+    Integrity,
+
+    /// The sensor is not in a state that allows the requested operation
+    State,
+
+    /// The parameters passed to the driver are not valid
+    Input,
+
+    /// The connected device is not the one the driver expects
+    Device,
+
+    /// The sensor reported an internal malfunction
+    Malfunction,
+}
+
+/// Error type of the flash-backed measurement log
+#[cfg(feature = "embedded-storage")]
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum LogError<E> {
+    /// The flash operation failed
+    Flash(E),
+
+    /// The log region is not aligned to the sectors of the flash, or the
+    /// flash does not support the record size
+    InvalidRegion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind() {
+        let crc = CrcError {
+            word: 0,
+            received: 0,
+            expected: 1,
+        };
+
+        assert_eq!(ErrorKind::Bus, Error::I2C(()).kind());
+        assert_eq!(ErrorKind::Integrity, Error::<()>::CRC(crc).kind());
+        assert_eq!(ErrorKind::Integrity, Error::<()>::Recovered.kind());
+        assert_eq!(ErrorKind::State, Error::<()>::NotAllowed.kind());
+        assert_eq!(ErrorKind::State, Error::<()>::CalibrationFailed.kind());
+        assert_eq!(ErrorKind::State, Error::<()>::InsufficientRuntime.kind());
+        assert_eq!(ErrorKind::State, Error::<()>::Aborted.kind());
+        assert_eq!(ErrorKind::State, Error::<()>::NoData.kind());
+        assert_eq!(ErrorKind::Input, Error::<()>::InvalidInput.kind());
+        assert_eq!(ErrorKind::Input, Error::<()>::InvalidBufferLength.kind());
+        assert_eq!(ErrorKind::Input, Error::<()>::CompensationConflict.kind());
+        assert_eq!(ErrorKind::Device, Error::<()>::WrongDevice.kind());
+        assert_eq!(
+            ErrorKind::Malfunction,
+            Error::<()>::SelfTestFailed(1).kind()
+        );
+    }
 }
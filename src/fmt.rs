@@ -0,0 +1,35 @@
+// Logging macros forwarding to the `log` facade when the `log` feature is
+// enabled. Without it the arguments are only borrowed, so that values used
+// solely for logging don't trigger unused warnings.
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        log_impl!(trace, $($arg)*)
+    };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        log_impl!(debug, $($arg)*)
+    };
+}
+
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        log_impl!(warn, $($arg)*)
+    };
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_impl {
+    ($level:ident, $($arg:tt)*) => {
+        ::log::$level!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_impl {
+    ($level:ident, $fmt:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+}
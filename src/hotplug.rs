@@ -0,0 +1,108 @@
+use crate::error::Error;
+
+/// The default number of consecutive transfers that were not acknowledged
+/// by the sensor, after which it is considered detached from the bus
+pub const DETACH_THRESHOLD: u8 = 3;
+
+/// A change of the presence of a sensor on the bus, reported by the
+/// `HotPlugSensor` wrappers of the drivers
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum HotPlugEvent {
+    /// The sensor stopped acknowledging its address
+    Detached,
+
+    /// The sensor responds again and was re-initialized
+    Attached,
+}
+
+// The presence of the sensor as deduced from the outcome of its transfers.
+// Shared by the blocking and the async wrappers, which classify the errors
+#[derive(Debug)]
+pub(crate) struct Presence {
+    threshold: u8,
+    nacks: u8,
+    attached: bool,
+    event: Option<HotPlugEvent>,
+}
+
+impl Presence {
+    pub(crate) fn new() -> Self {
+        Self {
+            threshold: DETACH_THRESHOLD,
+            nacks: 0,
+            attached: true,
+            event: None,
+        }
+    }
+
+    pub(crate) fn set_threshold(&mut self, threshold: u8) {
+        self.threshold = threshold.max(1);
+    }
+
+    pub(crate) fn is_attached(&self) -> bool {
+        self.attached
+    }
+
+    pub(crate) fn check<E>(&self) -> Result<(), Error<E>> {
+        match self.attached {
+            true => Ok(()),
+            false => Err(Error::NotAllowed),
+        }
+    }
+
+    // Any outcome other than a missing acknowledgement of the address means
+    // that the sensor is still on the bus
+    pub(crate) fn record(&mut self, absent: bool) {
+        if !absent {
+            self.nacks = 0;
+            return;
+        }
+
+        self.nacks = self.nacks.saturating_add(1);
+        if self.attached && self.nacks >= self.threshold {
+            warn!("the sensor did not respond {} times, detached", self.nacks);
+            self.attached = false;
+            self.event = Some(HotPlugEvent::Detached);
+        }
+    }
+
+    pub(crate) fn attach(&mut self) {
+        debug!("the sensor was attached and re-initialized");
+        self.nacks = 0;
+        self.attached = true;
+        self.event = Some(HotPlugEvent::Attached);
+    }
+
+    pub(crate) fn take_event(&mut self) -> Option<HotPlugEvent> {
+        self.event.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detached_after_consecutive_nacks() {
+        let mut presence = Presence::new();
+        presence.record(true);
+        presence.record(true);
+        presence.record(false);
+        presence.record(true);
+        presence.record(true);
+        assert!(presence.is_attached());
+        assert_eq!(None, presence.take_event());
+
+        presence.record(true);
+        assert!(!presence.is_attached());
+        assert_eq!(Err(Error::<()>::NotAllowed), presence.check());
+        assert_eq!(Some(HotPlugEvent::Detached), presence.take_event());
+        assert_eq!(None, presence.take_event());
+
+        presence.attach();
+        assert!(presence.is_attached());
+        assert_eq!(Some(HotPlugEvent::Attached), presence.take_event());
+    }
+}
@@ -11,12 +11,51 @@
 //!
 //! ## Feature Flags
 //!
-//! - `defmt`: Derive `defmt::Format` for the error type
+//! - `defmt`: Derive `defmt::Format` for the public data, configuration,
+//!   command and error types, as well as for the analysis helpers
+//! - `log`: Emit trace and debug messages, e.g. the raw frames exchanged with
+//!   the sensor and the recovery attempts, through the `log` facade. All
+//!   public types implement `Debug` regardless of this feature
+//! - `serde`: Derive `Serialize` and `Deserialize` for the calibration records
+//! - `json`: Serialize the measurements as JSON via `serde-json-core`
 //! - `sync`: Enable the blocking driver implementation for the selected sensors
 //! - `async`: Enable the async driver implementation for the selected sensors
 //! - `scd30`: Enable the driver for the SCD30 sensor
 //! - `scd40`: Enable the driver for the SCD40 sensor
 //! - `scd41`: Enable the driver for the SCD41 sensor
+//! - `simulator`: Enable the host-side sensor simulator (requires `std`)
+//! - `danger-skip-crc`: Allow disabling the CRC verification of the responses
+//! - `embedded-sensors`: Implement the temperature and humidity traits of
+//!   `embedded-sensors-hal` and `embedded-sensors-hal-async` for the drivers
+//! - `embedded-storage`: Enable the measurement log stored in a NOR flash
+//! - `influxdb`: Encode the measurements as InfluxDB line protocol records
+//! - `senml`: Encode the measurements as SenML packs in the JSON format
+//! - `embassy`: Type aliases of the drivers using `embassy_time::Delay`, e.g.
+//!   `EmbassyScd41<I2C>`, and constructors taking only the I2C bus
+//! - `ufmt`: Implement `ufmt::uDebug` for the public data and error types,
+//!   and `ufmt::uDisplay` for the measurements and the sensor variants
+//! - `custom-commands`: Allow implementing `commands::SensorCommand` outside
+//!   of the crate, in order to execute user-defined commands through the
+//!   drivers
+//!
+//! ## Send and Sync
+//!
+//! The drivers own their transport and keep only plain data besides it. A
+//! driver, as well as the `I2cTransport` it uses, is `Send` (`Sync`)
+//! whenever the I2C bus, the delay and the bus recovery are, so it can be
+//! moved into an Embassy task or shared as an RTIC resource. The futures
+//! returned by the async drivers are `Send` as long as those of the bus and
+//! the delay are. These guarantees are asserted at compile time.
+
+#[cfg(feature = "simulator")]
+extern crate std;
+
+// Must be declared first, so that the macros are visible in the other modules
+#[macro_use]
+mod fmt;
+
+// Float functions that are not available in `core`
+mod math;
 
 /// Error type used by the library
 pub mod error;
@@ -24,6 +63,44 @@ pub mod error;
 /// Shared measurement type used by the various sensors
 pub mod measurement;
 
+/// Reports produced by the calibration helpers of the drivers
+pub mod calibration;
+
+/// Analysis of series of measurements
+pub mod analysis;
+
+/// Scheduling of the recalibration and the self-test of the sensors
+pub mod maintenance;
+
+/// Composable post-processing of the measurements, e.g. filters and corrections
+pub mod pipeline;
+
+/// Encoders of the measurements into common data formats
+pub mod encoding;
+
+/// Measurement log stored in a NOR flash via `embedded-storage`. This
+/// module needs to be enabled via the `embedded-storage` feature flag
+#[cfg(feature = "embedded-storage")]
+pub mod logger;
+
+/// Identification of the sensor models supported by the library
+pub mod variant;
+
+/// Estimation of the current draw of the sensors per operating mode
+pub mod power;
+
+/// Sensor settings that can be applied as a batch or restored after a reset
+pub mod config;
+
+/// Progress reporting of the long running commands, e.g. the self-test
+pub mod progress;
+
+/// Detection of the sensors being detached from the bus and attached again
+pub mod hotplug;
+
+/// Diagnostic dumps of the sensors and the counters of the drivers
+pub mod diagnostics;
+
 /// Synchronous (blocking) driver implementations using embedded-hal. This
 /// module needs to be enabled via the `sync` feature flag
 #[cfg(feature = "sync")]
@@ -34,9 +111,22 @@ pub mod synchronous;
 #[cfg(feature = "async")]
 pub mod asynchronous;
 
-/// Shared code across the sync/async implementations
-#[doc(hidden)]
-pub(crate) mod internal;
+/// Host-side simulators of the supported sensors, implementing both the
+/// blocking and the async I2C traits. Intended for testing applications
+/// without real hardware. This module needs to be enabled via the
+/// `simulator` feature flag and requires `std`
+#[cfg(feature = "simulator")]
+pub mod simulator;
+
+/// Opcodes and properties of the commands of all supported sensors, for
+/// tooling such as bus sniffers and protocol analyzers. This module is
+/// available regardless of the enabled sensors
+pub mod commands;
+
+/// Wire format of the supported sensors: command opcodes, payload builders,
+/// response decoders and CRC. Shared across the sync/async implementations
+/// and usable on its own when the frames are transported by other means
+pub mod wire;
 
 #[cfg(not(all(
     any(feature = "sync", feature = "async"),
@@ -0,0 +1,371 @@
+//! A measurement log in a region of a NOR flash, e.g. for offline data
+//! loggers. The records are appended in a ring: when the region is full,
+//! the oldest sector is erased to make room for the new records, so all
+//! sectors are erased equally often.
+//!
+//! One sector is always kept erased in order to find the end of the log
+//! after a restart, thus the region must span at least two sectors.
+
+use crate::error::LogError;
+use crate::measurement::{Measurement, TimestampedMeasurement};
+use crate::wire::crc::crc8;
+use embedded_storage::nor_flash::NorFlash;
+
+/// The size of a record in bytes
+pub const RECORD_SIZE: usize = 16;
+
+// Distinguishes the written records from the erased flash
+const RECORD_MARKER: u8 = 0xA5;
+const ERASED: u8 = 0xFF;
+
+/// Measurement log stored in the flash region `offset..offset + len`.
+///
+/// The offset and the length must be multiples of the erase size of the
+/// flash, and the record size must be a multiple of its read and write
+/// sizes. This module needs to be enabled via the `embedded-storage`
+/// feature flag
+pub struct FlashLogger<F> {
+    flash: F,
+    offset: u32,
+    slots: u32,
+    head: u32,
+}
+
+impl<F: NorFlash> FlashLogger<F> {
+    /// Open the log stored in the given region of the flash and find its
+    /// end. If the end cannot be found, e.g. because the region was never
+    /// used as a log or because the power was lost while a sector was being
+    /// erased, the first sector is erased and the log continues from there.
+    pub fn new(flash: F, offset: u32, len: u32) -> Result<Self, LogError<F::Error>> {
+        let sector = F::ERASE_SIZE as u32;
+        let valid = RECORD_SIZE.is_multiple_of(F::READ_SIZE)
+            && RECORD_SIZE.is_multiple_of(F::WRITE_SIZE)
+            && F::ERASE_SIZE.is_multiple_of(RECORD_SIZE)
+            && offset.is_multiple_of(sector)
+            && len.is_multiple_of(sector)
+            && len / sector >= 2
+            && offset as usize + len as usize <= flash.capacity();
+        if !valid {
+            return Err(LogError::InvalidRegion);
+        }
+
+        let mut logger = Self {
+            flash,
+            offset,
+            slots: len / RECORD_SIZE as u32,
+            head: 0,
+        };
+        logger.head = logger.find_head()?;
+        Ok(logger)
+    }
+
+    /// Release the flash held by this logger
+    pub fn release(self) -> F {
+        self.flash
+    }
+
+    /// The number of records the log is guaranteed to keep. Up to a sector
+    /// of older records may be available too, until the next sector erase
+    pub fn capacity(&self) -> u32 {
+        self.slots - self.slots_per_sector()
+    }
+
+    /// Append a measurement to the log, erasing the oldest records if the
+    /// log is full
+    pub fn append(&mut self, record: &TimestampedMeasurement) -> Result<(), LogError<F::Error>> {
+        let buf = encode(record);
+        let address = self.address(self.head);
+        self.flash.write(address, &buf).map_err(LogError::Flash)?;
+
+        self.head = (self.head + 1) % self.slots;
+        if self.head.is_multiple_of(self.slots_per_sector()) {
+            self.erase_sector(self.head)?;
+        }
+
+        Ok(())
+    }
+
+    /// Erase all records
+    pub fn clear(&mut self) -> Result<(), LogError<F::Error>> {
+        let to = self.address(self.slots);
+        self.flash.erase(self.offset, to).map_err(LogError::Flash)?;
+        self.head = 0;
+        Ok(())
+    }
+
+    /// Iterate over the records from the oldest to the newest. Corrupted
+    /// records, e.g. partially written ones due to a power loss, are skipped
+    pub fn records(&mut self) -> Records<'_, F> {
+        Records {
+            next: self.head,
+            remaining: self.slots,
+            logger: self,
+        }
+    }
+
+    fn slots_per_sector(&self) -> u32 {
+        (F::ERASE_SIZE / RECORD_SIZE) as u32
+    }
+
+    fn address(&self, slot: u32) -> u32 {
+        self.offset + slot * RECORD_SIZE as u32
+    }
+
+    fn read_slot(&mut self, slot: u32) -> Result<[u8; RECORD_SIZE], LogError<F::Error>> {
+        let mut buf = [0; RECORD_SIZE];
+        let address = self.address(slot);
+        self.flash
+            .read(address, &mut buf)
+            .map_err(LogError::Flash)?;
+        Ok(buf)
+    }
+
+    fn is_empty(&mut self, slot: u32) -> Result<bool, LogError<F::Error>> {
+        Ok(self.read_slot(slot)?.iter().all(|&b| b == ERASED))
+    }
+
+    fn erase_sector(&mut self, slot: u32) -> Result<(), LogError<F::Error>> {
+        let first = slot - slot % self.slots_per_sector();
+        let from = self.address(first);
+        let to = from + F::ERASE_SIZE as u32;
+        self.flash.erase(from, to).map_err(LogError::Flash)
+    }
+
+    // The end of the log is the only empty slot following a written one
+    fn find_head(&mut self) -> Result<u32, LogError<F::Error>> {
+        let mut previous_empty = self.is_empty(self.slots - 1)?;
+        let mut all_empty = previous_empty;
+
+        for slot in 0..self.slots {
+            let empty = self.is_empty(slot)?;
+            if empty && !previous_empty {
+                return Ok(slot);
+            }
+
+            all_empty &= empty;
+            previous_empty = empty;
+        }
+
+        if !all_empty {
+            self.erase_sector(0)?;
+        }
+
+        Ok(0)
+    }
+}
+
+/// Iterator over the records of a [FlashLogger], from the oldest to the
+/// newest
+pub struct Records<'a, F> {
+    logger: &'a mut FlashLogger<F>,
+    next: u32,
+    remaining: u32,
+}
+
+impl<F: NorFlash> Iterator for Records<'_, F> {
+    type Item = Result<TimestampedMeasurement, LogError<F::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            let slot = self.next;
+            self.next = (self.next + 1) % self.logger.slots;
+            self.remaining -= 1;
+
+            match self.logger.read_slot(slot) {
+                Ok(buf) => {
+                    if let Some(record) = decode(&buf) {
+                        return Some(Ok(record));
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        None
+    }
+}
+
+// The temperature and the humidity are stored in hundredths
+fn encode(record: &TimestampedMeasurement) -> [u8; RECORD_SIZE] {
+    let m = &record.measurement;
+    let temperature = hundredths(m.temperature) as i16;
+    let humidity = hundredths(m.humidity) as u16;
+
+    let mut buf = [0; RECORD_SIZE];
+    buf[0] = RECORD_MARKER;
+    buf[1..9].copy_from_slice(&record.timestamp.to_le_bytes());
+    buf[9..11].copy_from_slice(&m.co2.to_le_bytes());
+    buf[11..13].copy_from_slice(&temperature.to_le_bytes());
+    buf[13..15].copy_from_slice(&humidity.to_le_bytes());
+    buf[15] = crc8(&buf[..RECORD_SIZE - 1]);
+    buf
+}
+
+fn decode(buf: &[u8; RECORD_SIZE]) -> Option<TimestampedMeasurement> {
+    if buf[0] != RECORD_MARKER || buf[15] != crc8(&buf[..RECORD_SIZE - 1]) {
+        return None;
+    }
+
+    let mut timestamp = [0; 8];
+    timestamp.copy_from_slice(&buf[1..9]);
+    let temperature = i16::from_le_bytes([buf[11], buf[12]]);
+    let humidity = u16::from_le_bytes([buf[13], buf[14]]);
+
+    Some(TimestampedMeasurement {
+        timestamp: u64::from_le_bytes(timestamp),
+        measurement: Measurement {
+            temperature: temperature as f32 / 100.0,
+            humidity: humidity as f32 / 100.0,
+            co2: u16::from_le_bytes([buf[9], buf[10]]),
+        },
+    })
+}
+
+// Rounded to the nearest integer. The casts by the callers saturate
+fn hundredths(value: f32) -> f32 {
+    let value = value * 100.0;
+    if value < 0.0 {
+        value - 0.5
+    } else {
+        value + 0.5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_storage::nor_flash::{ErrorType, NorFlashErrorKind, ReadNorFlash};
+
+    const SECTOR: usize = 64;
+    const SECTORS: usize = 4;
+
+    // Emulates a NOR flash, where the writes can only clear bits
+    struct RamFlash([u8; SECTOR * SECTORS]);
+
+    impl ErrorType for RamFlash {
+        type Error = NorFlashErrorKind;
+    }
+
+    impl ReadNorFlash for RamFlash {
+        const READ_SIZE: usize = 1;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+            bytes.copy_from_slice(&self.0[offset..offset + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    impl NorFlash for RamFlash {
+        const WRITE_SIZE: usize = 4;
+        const ERASE_SIZE: usize = SECTOR;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            self.0[from as usize..to as usize].fill(ERASED);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            let offset = offset as usize;
+            for (dst, src) in self.0[offset..offset + bytes.len()].iter_mut().zip(bytes) {
+                *dst &= src;
+            }
+            Ok(())
+        }
+    }
+
+    fn record(timestamp: u64) -> TimestampedMeasurement {
+        TimestampedMeasurement {
+            timestamp,
+            measurement: Measurement {
+                temperature: -12.34,
+                humidity: 56.78,
+                co2: 400 + timestamp as u16,
+            },
+        }
+    }
+
+    fn timestamps(logger: &mut FlashLogger<RamFlash>) -> [u64; 16] {
+        let mut out = [u64::MAX; 16];
+        for (idx, record) in logger.records().enumerate() {
+            out[idx] = record.unwrap().timestamp;
+        }
+        out
+    }
+
+    #[test]
+    fn test_append_and_reopen() {
+        let mut logger = FlashLogger::new(RamFlash([ERASED; SECTOR * SECTORS]), 0, 256).unwrap();
+        assert_eq!(12, logger.capacity());
+        assert_eq!(None, logger.records().next().map(|r| r.unwrap().timestamp));
+
+        for timestamp in 0..6 {
+            logger.append(&record(timestamp)).unwrap();
+        }
+
+        let mut logger = FlashLogger::new(logger.release(), 0, 256).unwrap();
+        logger.append(&record(6)).unwrap();
+
+        let first = logger.records().next().unwrap().unwrap();
+        assert_eq!(0, first.timestamp);
+        assert_eq!(400, first.measurement.co2);
+        assert!((-12.34 - first.measurement.temperature).abs() < 0.001);
+        assert!((56.78 - first.measurement.humidity).abs() < 0.001);
+        assert_eq!(7, logger.records().count());
+    }
+
+    #[test]
+    fn test_wraps_around() {
+        let mut logger = FlashLogger::new(RamFlash([ERASED; SECTOR * SECTORS]), 0, 256).unwrap();
+        for timestamp in 0..21 {
+            logger.append(&record(timestamp)).unwrap();
+        }
+
+        // The sector after the newest record is kept erased
+        let mut expected = [u64::MAX; 16];
+        for (idx, timestamp) in (8..21).enumerate() {
+            expected[idx] = timestamp;
+        }
+        assert_eq!(expected, timestamps(&mut logger));
+
+        let mut logger = FlashLogger::new(logger.release(), 0, 256).unwrap();
+        assert_eq!(expected, timestamps(&mut logger));
+    }
+
+    #[test]
+    fn test_skips_corrupted_records() {
+        let mut logger = FlashLogger::new(RamFlash([ERASED; SECTOR * SECTORS]), 0, 256).unwrap();
+        for timestamp in 0..3 {
+            logger.append(&record(timestamp)).unwrap();
+        }
+
+        let mut flash = logger.release();
+        flash.0[RECORD_SIZE + 3] ^= 0x01;
+
+        let mut logger = FlashLogger::new(flash, 0, 256).unwrap();
+        let mut expected = [u64::MAX; 16];
+        expected[..2].copy_from_slice(&[0, 2]);
+        assert_eq!(expected, timestamps(&mut logger));
+    }
+
+    #[test]
+    fn test_invalid_region() {
+        let flash = || RamFlash([ERASED; SECTOR * SECTORS]);
+        assert!(matches!(
+            FlashLogger::new(flash(), 0, 64),
+            Err(LogError::InvalidRegion)
+        ));
+        assert!(matches!(
+            FlashLogger::new(flash(), 32, 128),
+            Err(LogError::InvalidRegion)
+        ));
+        assert!(matches!(
+            FlashLogger::new(flash(), 128, 192),
+            Err(LogError::InvalidRegion)
+        ));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::analysis::drift::DriftReport;
+use crate::calibration::{CalibrationRecord, SelfTestRecord};
+
+/// Configuration of a [MaintenanceScheduler]. Each limit can be disabled by
+/// setting it to `None`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MaintenancePolicy {
+    /// The maximal operating time of the sensor in milliseconds between two
+    /// recalibrations
+    pub recalibration_runtime_ms: Option<u64>,
+
+    /// The maximal time in milliseconds between two recalibrations
+    pub recalibration_interval_ms: Option<u64>,
+
+    /// The maximal time in milliseconds between two self-tests
+    pub self_test_interval_ms: Option<u64>,
+
+    /// Whether a drift reported by the [DriftMonitor](crate::analysis::drift::DriftMonitor)
+    /// makes a recalibration due
+    pub recalibrate_on_drift: bool,
+
+    /// The minimal time in milliseconds between two reminders of the same
+    /// kind, so that a due task does not flood the application with events
+    pub reminder_interval_ms: u64,
+}
+
+impl Default for MaintenancePolicy {
+    /// A yearly recalibration or one after 8 000 operating hours, whichever
+    /// comes first, a monthly self-test and daily reminders
+    fn default() -> Self {
+        const HOUR_MS: u64 = 60 * 60 * 1_000;
+        const DAY_MS: u64 = 24 * HOUR_MS;
+
+        Self {
+            recalibration_runtime_ms: Some(8_000 * HOUR_MS),
+            recalibration_interval_ms: Some(365 * DAY_MS),
+            self_test_interval_ms: Some(30 * DAY_MS),
+            recalibrate_on_drift: true,
+            reminder_interval_ms: DAY_MS,
+        }
+    }
+}
+
+/// The reason a recalibration of the sensor is due
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecalibrationReason {
+    /// The drift monitor recommends a recalibration
+    Drift,
+
+    /// The sensor has been operated for too long since the last
+    /// recalibration
+    OperatingTime,
+
+    /// Too much time has passed since the last recalibration
+    Age,
+}
+
+/// A maintenance task that is due, emitted by [MaintenanceScheduler::poll]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MaintenanceReminder {
+    /// A forced recalibration of the sensor is due
+    Recalibration(RecalibrationReason),
+
+    /// A self-test of the sensor is due
+    SelfTest,
+}
+
+/// Tracks when the sensor was last recalibrated and self-tested, its
+/// operating time and the reports of a drift monitor, in order to tell if a
+/// recalibration or a self-test is due.
+///
+/// The timestamps are in milliseconds and must come from the same clock.
+/// This includes the timestamps of the calibration records, which are
+/// otherwise not interpreted by the driver. The operating time is the one
+/// accounted by the driver, e.g. `Scd41::operating_time()`. Without any
+/// records, the sensor is considered calibrated at zero operating time and
+/// at the first poll, and the self-test is due.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct MaintenanceScheduler {
+    policy: MaintenancePolicy,
+    calibrated_at_ms: Option<u64>,
+    calibrated_operating_ms: u64,
+    self_tested_at_ms: Option<u64>,
+    drifted: bool,
+    recalibration_reminded_ms: Option<u64>,
+    self_test_reminded_ms: Option<u64>,
+}
+
+impl MaintenanceScheduler {
+    /// Create a new scheduler with the given policy
+    pub fn new(policy: MaintenancePolicy) -> Self {
+        Self {
+            policy,
+            calibrated_at_ms: None,
+            calibrated_operating_ms: 0,
+            self_tested_at_ms: None,
+            drifted: false,
+            recalibration_reminded_ms: None,
+            self_test_reminded_ms: None,
+        }
+    }
+
+    /// Record a recalibration of the sensor, which was operated for
+    /// `operating_ms` milliseconds at the time. It can also be used to
+    /// restore the last record after a restart
+    pub fn record_calibration(&mut self, record: &CalibrationRecord, operating_ms: u64) {
+        self.calibrated_at_ms = Some(record.timestamp);
+        self.calibrated_operating_ms = operating_ms;
+        self.drifted = false;
+        self.recalibration_reminded_ms = None;
+    }
+
+    /// Record a self-test of the sensor, e.g. one run by the
+    /// [SelfTestScheduler](crate::calibration::SelfTestScheduler)
+    pub fn record_self_test(&mut self, record: &SelfTestRecord) {
+        self.self_tested_at_ms = Some(record.timestamp_ms);
+        self.self_test_reminded_ms = None;
+    }
+
+    /// Record the report of a completed window of a drift monitor
+    pub fn record_drift(&mut self, report: &DriftReport) {
+        self.drifted = report.calibration_recommended;
+    }
+
+    /// Check if a recalibration is due at `now_ms`, given the current
+    /// operating time of the sensor. A drift takes precedence over the
+    /// operating time, which takes precedence over the age
+    pub fn recalibration_due(&self, now_ms: u64, operating_ms: u64) -> Option<RecalibrationReason> {
+        let calibrated_at_ms = self.calibrated_at_ms.unwrap_or(now_ms);
+        let runtime_ms = operating_ms.saturating_sub(self.calibrated_operating_ms);
+        let age_ms = now_ms.saturating_sub(calibrated_at_ms);
+
+        if self.policy.recalibrate_on_drift && self.drifted {
+            Some(RecalibrationReason::Drift)
+        } else if exceeds(runtime_ms, self.policy.recalibration_runtime_ms) {
+            Some(RecalibrationReason::OperatingTime)
+        } else if exceeds(age_ms, self.policy.recalibration_interval_ms) {
+            Some(RecalibrationReason::Age)
+        } else {
+            None
+        }
+    }
+
+    /// Check if a self-test is due at `now_ms`
+    pub fn self_test_due(&self, now_ms: u64) -> bool {
+        match (self.policy.self_test_interval_ms, self.self_tested_at_ms) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(interval), Some(last)) => now_ms.saturating_sub(last) >= interval,
+        }
+    }
+
+    /// Check the schedule and return a reminder for a task that is due,
+    /// unless a reminder of the same kind was returned less than the
+    /// reminder interval ago. Should be called repeatedly until it returns
+    /// `None`, because only one reminder is returned per call
+    pub fn poll(&mut self, now_ms: u64, operating_ms: u64) -> Option<MaintenanceReminder> {
+        self.calibrated_at_ms.get_or_insert(now_ms);
+
+        if let Some(reason) = self.recalibration_due(now_ms, operating_ms) {
+            if self.remind(now_ms, self.recalibration_reminded_ms) {
+                self.recalibration_reminded_ms = Some(now_ms);
+                return Some(MaintenanceReminder::Recalibration(reason));
+            }
+        }
+
+        if self.self_test_due(now_ms) && self.remind(now_ms, self.self_test_reminded_ms) {
+            self.self_test_reminded_ms = Some(now_ms);
+            return Some(MaintenanceReminder::SelfTest);
+        }
+
+        None
+    }
+
+    fn remind(&self, now_ms: u64, reminded_ms: Option<u64>) -> bool {
+        reminded_ms
+            .is_none_or(|last| now_ms.saturating_sub(last) >= self.policy.reminder_interval_ms)
+    }
+}
+
+fn exceeds(value: u64, limit: Option<u64>) -> bool {
+    limit.is_some_and(|limit| value >= limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: MaintenancePolicy = MaintenancePolicy {
+        recalibration_runtime_ms: Some(1_000),
+        recalibration_interval_ms: Some(10_000),
+        self_test_interval_ms: Some(5_000),
+        recalibrate_on_drift: true,
+        reminder_interval_ms: 100,
+    };
+
+    fn calibration(timestamp: u64) -> CalibrationRecord {
+        CalibrationRecord {
+            reference_ppm: 400,
+            correction: 0,
+            asc_enabled: true,
+            timestamp,
+            serial_number: 0,
+        }
+    }
+
+    #[test]
+    fn recalibration_due_by_operating_time_and_age() {
+        let mut scheduler = MaintenanceScheduler::new(POLICY);
+        scheduler.record_calibration(&calibration(1_000), 500);
+
+        assert_eq!(None, scheduler.recalibration_due(2_000, 1_499));
+        assert_eq!(
+            Some(RecalibrationReason::OperatingTime),
+            scheduler.recalibration_due(2_000, 1_500)
+        );
+        assert_eq!(
+            Some(RecalibrationReason::Age),
+            scheduler.recalibration_due(11_000, 500)
+        );
+    }
+
+    #[test]
+    fn recalibration_due_by_drift() {
+        let mut scheduler = MaintenanceScheduler::new(POLICY);
+        scheduler.record_calibration(&calibration(0), 0);
+        scheduler.record_drift(&DriftReport {
+            baseline_ppm: 480,
+            deviation_ppm: 80,
+            drifted_windows: 2,
+            calibration_recommended: true,
+        });
+        assert_eq!(
+            Some(RecalibrationReason::Drift),
+            scheduler.recalibration_due(0, 0)
+        );
+
+        scheduler.record_calibration(&calibration(10), 0);
+        assert_eq!(None, scheduler.recalibration_due(10, 0));
+    }
+
+    #[test]
+    fn poll_rate_limits_the_reminders() {
+        let mut scheduler = MaintenanceScheduler::new(POLICY);
+
+        // The self-test was never run, the sensor is considered calibrated
+        assert_eq!(Some(MaintenanceReminder::SelfTest), scheduler.poll(0, 0));
+        assert_eq!(None, scheduler.poll(99, 0));
+        assert_eq!(Some(MaintenanceReminder::SelfTest), scheduler.poll(100, 0));
+
+        scheduler.record_self_test(&SelfTestRecord {
+            timestamp_ms: 100,
+            status: 0,
+        });
+        assert_eq!(None, scheduler.poll(200, 999));
+        assert_eq!(
+            Some(MaintenanceReminder::Recalibration(
+                RecalibrationReason::OperatingTime
+            )),
+            scheduler.poll(300, 1_000)
+        );
+        assert_eq!(None, scheduler.poll(300, 1_000));
+
+        // Both tasks are due, one reminder per call
+        assert_eq!(
+            Some(MaintenanceReminder::Recalibration(
+                RecalibrationReason::OperatingTime
+            )),
+            scheduler.poll(5_100, 1_000)
+        );
+        assert_eq!(
+            Some(MaintenanceReminder::SelfTest),
+            scheduler.poll(5_100, 1_000)
+        );
+        assert_eq!(None, scheduler.poll(5_100, 1_000));
+    }
+
+    #[test]
+    fn disabled_limits() {
+        let mut scheduler = MaintenanceScheduler::new(MaintenancePolicy {
+            recalibration_runtime_ms: None,
+            recalibration_interval_ms: None,
+            self_test_interval_ms: None,
+            ..POLICY
+        });
+
+        assert_eq!(None, scheduler.poll(u64::MAX, u64::MAX));
+    }
+}
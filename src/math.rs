@@ -0,0 +1,147 @@
+// e^x, with a relative error below 1e-6
+pub(crate) fn exp(x: f32) -> f32 {
+    if x > 88.0 {
+        return f32::INFINITY;
+    }
+    if x < -87.0 {
+        return 0.0;
+    }
+
+    // e^x = 2^k * e^r, where |r| <= ln(2) / 2
+    let k = (x * core::f32::consts::LOG2_E + if x < 0.0 { -0.5 } else { 0.5 }) as i32;
+    let r = x - k as f32 * core::f32::consts::LN_2;
+
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    for n in 1..10 {
+        term *= r / n as f32;
+        sum += term;
+    }
+
+    sum * f32::from_bits(((k + 127) as u32) << 23)
+}
+
+// Natural logarithm. `None` for non-positive and non-finite values
+pub(crate) fn ln(value: f32) -> Option<f32> {
+    if !value.is_finite() || value <= 0.0 {
+        return None;
+    }
+
+    // value = mantissa * 2^exponent, with the mantissa in [1, 2)
+    let bits = value.to_bits();
+    let mut exponent = ((bits >> 23) & 0xFF) as i32 - 127;
+    let mut mantissa = f32::from_bits((bits & 0x007F_FFFF) | 0x3F80_0000);
+    if exponent == -127 {
+        // Subnormal value
+        let normalized = value * (1u32 << 23) as f32;
+        return ln(normalized).map(|l| l - 23.0 * core::f32::consts::LN_2);
+    }
+
+    if mantissa > core::f32::consts::SQRT_2 {
+        mantissa /= 2.0;
+        exponent += 1;
+    }
+
+    // ln(m) = 2 * atanh((m - 1) / (m + 1)), with |z| < 0.172
+    let z = (mantissa - 1.0) / (mantissa + 1.0);
+    let z2 = z * z;
+    let mut term = z;
+    let mut sum = 0.0;
+    for k in 0..6 {
+        sum += term / (2 * k + 1) as f32;
+        term *= z2;
+    }
+
+    Some(2.0 * sum + exponent as f32 * core::f32::consts::LN_2)
+}
+
+// Square root by Newton's method. `0` for non-positive values
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+pub(crate) fn sqrt(value: f32) -> f32 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+
+    let mut x = if value > 1.0 { value } else { 1.0 };
+    for _ in 0..32 {
+        let next = 0.5 * (x + value / x);
+        if next >= x {
+            break;
+        }
+        x = next;
+    }
+
+    x
+}
+
+// Round half away from zero
+pub(crate) fn round(value: f32) -> f32 {
+    if value.is_sign_negative() {
+        (value - 0.5) as i32 as f32
+    } else {
+        (value + 0.5) as i32 as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exp() {
+        for (x, expected) in [
+            (0.0_f32, 1.0_f32),
+            (1.0, core::f32::consts::E),
+            (-1.0, 0.367_879_44),
+            (10.0, 22_026.465),
+            (-20.0, 2.061_153_6e-9),
+        ] {
+            let actual = exp(x);
+            assert!(
+                (expected - actual).abs() <= expected * 1e-6,
+                "exp({x}) = {actual}"
+            );
+        }
+        assert_eq!(f32::INFINITY, exp(100.0));
+        assert_eq!(0.0, exp(-100.0));
+    }
+
+    #[test]
+    fn test_ln() {
+        assert_eq!(None, ln(0.0));
+        assert_eq!(None, ln(-1.0));
+        assert_eq!(None, ln(f32::NAN));
+
+        for (value, expected) in [
+            (1.0_f32, 0.0_f32),
+            (core::f32::consts::E, 1.0),
+            (0.5, -core::f32::consts::LN_2),
+            (1_000.0, 6.907_755_4),
+            (1e-40, -92.103_4),
+        ] {
+            let actual = ln(value).unwrap();
+            let tolerance = 1e-5 * expected.abs().max(1.0);
+            assert!(
+                (expected - actual).abs() < tolerance,
+                "ln({value}) = {actual}"
+            );
+        }
+    }
+
+    #[cfg(any(feature = "scd40", feature = "scd41"))]
+    #[test]
+    fn test_sqrt() {
+        for value in [0.0_f32, 0.25, 1.0, 2.0, 100.0, 12_345.0] {
+            let root = sqrt(value);
+            assert!((root * root - value).abs() <= value * 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(2.0, round(1.5));
+        assert_eq!(1.0, round(1.49));
+        assert_eq!(-2.0, round(-1.5));
+        assert_eq!(0.0, round(0.0));
+    }
+}
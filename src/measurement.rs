@@ -1,3 +1,7 @@
+use crate::math::exp;
+use crate::variant::SensorVariant;
+use core::fmt::{Display, Formatter, Write};
+
 /// Structure containing the measurements from a CO2 sensor
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -11,3 +15,590 @@ pub struct Measurement {
     /// Measured CO2 concentration in PPM
     pub co2: u16,
 }
+
+/// A measurement together with the time it was taken at
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimestampedMeasurement {
+    /// The time of the measurement in application-defined units, e.g. the
+    /// seconds since the Unix epoch or the milliseconds since boot
+    pub timestamp: u64,
+
+    /// The measurement
+    pub measurement: Measurement,
+}
+
+/// One measurement of each sensor of a group, taken within the same
+/// measurement interval, e.g. by a `SensorGroup`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MeasurementBatch<const N: usize> {
+    /// The measurements in the order of the sensors. `None` if the sensor
+    /// did not provide a new measurement before the timeout
+    pub measurements: [Option<Measurement>; N],
+
+    /// The time in milliseconds between reading the first and the last
+    /// measurement of the batch, at the resolution of the polling interval
+    pub spread_ms: u32,
+}
+
+impl<const N: usize> MeasurementBatch<N> {
+    /// Check if every sensor provided a measurement
+    pub fn is_complete(&self) -> bool {
+        self.measurements.iter().all(Option::is_some)
+    }
+
+    /// The measurements that were provided, in the order of the sensors
+    pub fn iter(&self) -> impl Iterator<Item = &Measurement> {
+        self.measurements.iter().flatten()
+    }
+}
+
+impl Measurement {
+    /// Serialize the measurement as JSON into `buf` and return the number of
+    /// bytes written. The schema is `{"co2":<ppm>,"t":<celsius>,"rh":<rh%>}`.
+    ///
+    /// This method needs to be enabled via the `json` feature flag
+    #[cfg(feature = "json")]
+    pub fn to_json(&self, buf: &mut [u8]) -> Result<usize, serde_json_core::ser::Error> {
+        #[derive(serde::Serialize)]
+        struct Json {
+            co2: u16,
+            t: f32,
+            rh: f32,
+        }
+
+        let json = Json {
+            co2: self.co2,
+            t: self.temperature,
+            rh: self.humidity,
+        };
+        serde_json_core::to_slice(&json, buf)
+    }
+
+    /// The uncertainty of the CO2 concentration in PPM, i.e. the reading is
+    /// within `co2 ± co2_uncertainty` according to the datasheet accuracy
+    /// of the given sensor model
+    pub fn co2_uncertainty(&self, variant: SensorVariant) -> u16 {
+        variant.accuracy().co2_uncertainty(self.co2)
+    }
+
+    /// The absolute humidity in g/m³, i.e. the mass of the water vapour in a
+    /// cubic meter of air, calculated from the measured temperature and
+    /// relative humidity
+    pub fn absolute_humidity(&self) -> f32 {
+        let vapour_pressure = self.humidity / 100.0 * saturation_vapour_pressure(self.temperature);
+        ABSOLUTE_HUMIDITY_K * vapour_pressure / (self.temperature + ZERO_CELSIUS)
+    }
+
+    /// The relative humidity (RH%) of the measured air at `temperature`
+    /// Celsius, assuming its absolute humidity does not change. E.g. the
+    /// humidity of the room, when the sensor is warmer than the room because
+    /// of its enclosure, or the humidity at a reference temperature.
+    ///
+    /// The result is limited to 100 %, where the water vapour condenses
+    pub fn humidity_at(&self, temperature: f32) -> f32 {
+        let vapour_pressure =
+            self.absolute_humidity() * (temperature + ZERO_CELSIUS) / ABSOLUTE_HUMIDITY_K;
+        let humidity = 100.0 * vapour_pressure / saturation_vapour_pressure(temperature);
+        humidity.clamp(0.0, 100.0)
+    }
+
+    /// The temperature with a fixed number of decimal places, rendered
+    /// without the float formatting of `core`
+    pub fn temperature_fixed(&self, decimals: u8) -> Fixed {
+        Fixed::new(self.temperature, decimals)
+    }
+
+    /// The relative humidity with a fixed number of decimal places,
+    /// rendered without the float formatting of `core`
+    pub fn humidity_fixed(&self, decimals: u8) -> Fixed {
+        Fixed::new(self.humidity, decimals)
+    }
+
+    /// Render the measurement as `<co2> ppm, <temperature> C, <humidity> %RH`
+    /// into `buf`, with the temperature and the humidity rounded to
+    /// `decimals` decimal places. Returns the number of bytes written, or
+    /// `None` if `buf` is too short. Unlike `{:.2}`, this does not link the
+    /// float formatting of `core`, which costs several kB of flash
+    pub fn write_fixed(&self, decimals: u8, buf: &mut [u8]) -> Option<usize> {
+        let mut out = SliceWriter { buf, len: 0 };
+        write!(
+            out,
+            "{} ppm, {} C, {} %RH",
+            self.co2,
+            self.temperature_fixed(decimals),
+            self.humidity_fixed(decimals)
+        )
+        .ok()?;
+
+        Some(out.len)
+    }
+}
+
+/// The largest number of decimal places rendered by [Fixed]
+pub const MAX_DECIMALS: u8 = 6;
+
+// A sign, the 20 digits of `u64::MAX`, the decimal point and the decimals
+const MAX_FIXED_LEN: usize = 22 + MAX_DECIMALS as usize;
+
+/// A value rendered with a fixed number of decimal places using integer
+/// arithmetic only, e.g. `21.35`. It implements `Display`, so it can be
+/// written into any `core::fmt::Write`, such as a `heapless::String`,
+/// without linking the float formatting of `core`.
+///
+/// The value is rounded half away from zero. Values beyond the range of
+/// `u64` saturate, while NaN and the infinities are rendered as `NaN`,
+/// `inf` and `-inf`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fixed {
+    value: f32,
+    decimals: u8,
+}
+
+impl Fixed {
+    /// Render `value` with `decimals` decimal places, which are limited to
+    /// [MAX_DECIMALS]
+    pub fn new(value: f32, decimals: u8) -> Self {
+        Self {
+            value,
+            decimals: decimals.min(MAX_DECIMALS),
+        }
+    }
+
+    /// Render the value into `buf` and return the number of bytes written,
+    /// or `None` if `buf` is too short
+    pub fn write_to(&self, buf: &mut [u8]) -> Option<usize> {
+        let mut digits = [0; MAX_FIXED_LEN];
+        let text = self.render(&mut digits);
+        buf.get_mut(..text.len())?.copy_from_slice(text);
+        Some(text.len())
+    }
+
+    // Render the value right-aligned into `buf` and return the used part
+    fn render<'a>(&self, buf: &'a mut [u8; MAX_FIXED_LEN]) -> &'a [u8] {
+        let value = self.value;
+        if value.is_nan() {
+            return b"NaN";
+        }
+
+        let negative = value.is_sign_negative();
+        let magnitude = if negative { -value } else { value };
+        if magnitude.is_infinite() {
+            return if negative { b"-inf" } else { b"inf" };
+        }
+
+        // The conversion saturates for values beyond the range of `u64`
+        let scale = 10u64.pow(u32::from(self.decimals));
+        let mut scaled = (magnitude * scale as f32 + 0.5) as u64;
+
+        let mut pos = buf.len();
+        let mut push = |buf: &mut [u8; MAX_FIXED_LEN], byte| {
+            pos -= 1;
+            buf[pos] = byte;
+            pos
+        };
+
+        for _ in 0..self.decimals {
+            push(buf, b'0' + (scaled % 10) as u8);
+            scaled /= 10;
+        }
+        if self.decimals > 0 {
+            push(buf, b'.');
+        }
+
+        let mut start = push(buf, b'0' + (scaled % 10) as u8);
+        scaled /= 10;
+        while scaled > 0 {
+            start = push(buf, b'0' + (scaled % 10) as u8);
+            scaled /= 10;
+        }
+
+        // Don't render a negative zero, e.g. of -0.001 with 2 decimals
+        let zero = buf[start..]
+            .iter()
+            .all(|&byte| byte == b'0' || byte == b'.');
+        if negative && !zero {
+            start = push(buf, b'-');
+        }
+
+        &buf[start..]
+    }
+}
+
+impl Display for Fixed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut digits = [0; MAX_FIXED_LEN];
+        let text = self.render(&mut digits);
+
+        // The rendered value consists of ASCII characters only
+        f.write_str(core::str::from_utf8(text).map_err(|_| core::fmt::Error)?)
+    }
+}
+
+// Writes formatted text into a byte slice, failing when it is full
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        let out = self.buf.get_mut(self.len..end).ok_or(core::fmt::Error)?;
+        out.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDebug for Measurement {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        f.debug_struct("Measurement")?
+            .field("temperature", &Hundredths(self.temperature))?
+            .field("humidity", &Hundredths(self.humidity))?
+            .field("co2", &self.co2)?
+            .finish()
+    }
+}
+
+/// Formats the measurement as `<co2> ppm, <temperature> C, <humidity> %RH`.
+/// The temperature and the humidity are written with two decimal places,
+/// as `ufmt` does not support floating point numbers
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Measurement {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        ufmt::uwrite!(
+            f,
+            "{} ppm, {} C, {} %RH",
+            self.co2,
+            Hundredths(self.temperature),
+            Hundredths(self.humidity)
+        )
+    }
+}
+
+// Formats a value with two decimal places for `ufmt`, which does not support
+// floating point numbers. Rendered by [Fixed], so that the output matches the
+// one of `core::fmt`
+#[cfg(feature = "ufmt")]
+struct Hundredths(f32);
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Hundredths {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        let mut digits = [0; MAX_FIXED_LEN];
+        let text = Fixed::new(self.0, 2).render(&mut digits);
+
+        // The rendered value consists of ASCII characters only
+        for &byte in text {
+            f.write_char(byte as char)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDebug for Hundredths {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        ufmt::uDisplay::fmt(self, f)
+    }
+}
+
+/// The ambient pressure in hPa assumed by the sensors when the pressure
+/// compensation is disabled
+pub const STANDARD_PRESSURE_HPA: f32 = 1013.25;
+
+/// Correct the CO2 concentration in PPM for the difference between the
+/// actual ambient pressure and the pressure the sensor compensates for, e.g.
+/// when the pressure configured on an SCD30 can't be refreshed without
+/// restarting the continuous measurement.
+///
+/// The NDIR principle measures the number of CO2 molecules in the optical
+/// cell, which is proportional to the pressure of the gas, so the reading is
+/// scaled by `configured_hpa / actual_hpa`. A `configured_hpa` of `0` means
+/// that the pressure compensation is disabled and the sensor assumes the
+/// [STANDARD_PRESSURE_HPA]. Returns the reading unchanged if `actual_hpa` is
+/// not a positive pressure.
+pub fn co2_corrected_for_pressure(
+    measurement: &Measurement,
+    actual_hpa: f32,
+    configured_hpa: u16,
+) -> u16 {
+    if actual_hpa.is_nan() || actual_hpa <= 0.0 {
+        return measurement.co2;
+    }
+
+    let configured_hpa = match configured_hpa {
+        0 => STANDARD_PRESSURE_HPA,
+        hpa => hpa as f32,
+    };
+
+    let corrected = measurement.co2 as f32 * configured_hpa / actual_hpa + 0.5;
+    if corrected >= u16::MAX as f32 {
+        return u16::MAX;
+    }
+
+    corrected as u16
+}
+
+// The temperature in Kelvin at 0 degrees Celsius
+const ZERO_CELSIUS: f32 = 273.15;
+
+// The mass in grams of water vapour per cubic meter at 1 hPa and 1 Kelvin:
+// `100 * M_water / R = 100 * 18.015 / 8.314`
+const ABSOLUTE_HUMIDITY_K: f32 = 216.7;
+
+// The saturation vapour pressure of water in hPa at `temperature` Celsius,
+// by the Magnus formula with the constants recommended by Sensirion for the
+// range of -45 to 60 degrees Celsius
+fn saturation_vapour_pressure(temperature: f32) -> f32 {
+    6.112 * exp(17.62 * temperature / (243.12 + temperature))
+}
+
+/// The typical accuracy of the measurements as specified by the datasheets.
+/// The CO2 accuracy is given as a fixed part plus a part relative to the
+/// reading, i.e. `±(co2_ppm + co2_percent % of the reading)`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Accuracy {
+    /// The fixed part of the CO2 accuracy in PPM
+    pub co2_ppm: u16,
+
+    /// The part of the CO2 accuracy relative to the reading in percent
+    pub co2_percent: u8,
+
+    /// The temperature accuracy in Celsius
+    pub temperature: f32,
+
+    /// The relative humidity accuracy in RH%
+    pub humidity: f32,
+}
+
+impl Accuracy {
+    /// Sensirion SCD30: 400 - 10'000 PPM
+    pub const SCD30: Accuracy = Accuracy {
+        co2_ppm: 30,
+        co2_percent: 3,
+        temperature: 0.4,
+        humidity: 3.0,
+    };
+
+    /// Sensirion SCD40: 400 - 2'000 PPM
+    pub const SCD40: Accuracy = Accuracy {
+        co2_ppm: 50,
+        co2_percent: 5,
+        temperature: 0.8,
+        humidity: 6.0,
+    };
+
+    /// Sensirion SCD41: 400 - 5'000 PPM
+    pub const SCD41: Accuracy = Accuracy {
+        co2_ppm: 40,
+        co2_percent: 5,
+        temperature: 0.8,
+        humidity: 6.0,
+    };
+
+    /// Sensirion SCD43: 400 - 5'000 PPM
+    pub const SCD43: Accuracy = Accuracy {
+        co2_ppm: 30,
+        co2_percent: 3,
+        temperature: 0.8,
+        humidity: 6.0,
+    };
+
+    /// The CO2 uncertainty of a reading in PPM, rounded up
+    pub fn co2_uncertainty(&self, co2: u16) -> u16 {
+        let relative = (u32::from(co2) * u32::from(self.co2_percent)).div_ceil(100);
+        let total = u32::from(self.co2_ppm) + relative;
+        total.min(u32::from(u16::MAX)) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(value: f32, decimals: u8) -> ([u8; 32], usize) {
+        let mut buf = [0; 32];
+        let len = Fixed::new(value, decimals).write_to(&mut buf).unwrap();
+        (buf, len)
+    }
+
+    #[test]
+    fn test_fixed() {
+        let cases: [(f32, u8, &str); 10] = [
+            (21.346, 2, "21.35"),
+            (21.344, 1, "21.3"),
+            (0.05, 1, "0.1"),
+            (-7.25, 1, "-7.3"),
+            (-0.001, 2, "0.00"),
+            (99.6, 0, "100"),
+            (3.0, 3, "3.000"),
+            (f32::NAN, 2, "NaN"),
+            (f32::NEG_INFINITY, 2, "-inf"),
+            (1e30, 0, "18446744073709551615"),
+        ];
+
+        for (value, decimals, expected) in cases {
+            let (buf, len) = fixed(value, decimals);
+            assert_eq!(expected.as_bytes(), &buf[..len], "{}", expected);
+        }
+
+        assert_eq!(None, Fixed::new(21.35, 2).write_to(&mut [0; 4]));
+    }
+
+    #[test]
+    fn test_write_fixed() {
+        let m = Measurement {
+            temperature: 21.456,
+            humidity: 45.0,
+            co2: 812,
+        };
+
+        let mut buf = [0; 32];
+        let len = m.write_fixed(1, &mut buf).unwrap();
+        assert_eq!(b"812 ppm, 21.5 C, 45.0 %RH", &buf[..len]);
+        assert_eq!(None, m.write_fixed(1, &mut [0; 16]));
+    }
+
+    #[test]
+    fn test_co2_uncertainty() {
+        let m = Measurement {
+            temperature: 25.0,
+            humidity: 50.0,
+            co2: 1_000,
+        };
+
+        assert_eq!(60, m.co2_uncertainty(SensorVariant::Scd30));
+        assert_eq!(100, m.co2_uncertainty(SensorVariant::Scd40));
+        assert_eq!(90, m.co2_uncertainty(SensorVariant::Scd41));
+        assert_eq!(60, m.co2_uncertainty(SensorVariant::Scd43));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json() {
+        let m = Measurement {
+            temperature: 22.5,
+            humidity: 40.25,
+            co2: 800,
+        };
+
+        let mut buf = [0; 64];
+        let len = m.to_json(&mut buf).unwrap();
+        assert_eq!(br#"{"co2":800,"t":22.5,"rh":40.25}"#, &buf[..len]);
+
+        let mut small = [0; 8];
+        assert_eq!(
+            Err(serde_json_core::ser::Error::BufferFull),
+            m.to_json(&mut small)
+        );
+    }
+
+    #[test]
+    fn test_humidity() {
+        let m = Measurement {
+            temperature: 20.0,
+            humidity: 50.0,
+            co2: 600,
+        };
+        assert!((8.62 - m.absolute_humidity()).abs() < 0.01);
+        assert!((50.0 - m.humidity_at(20.0)).abs() < 0.01);
+
+        // The sensor is warmer than the room
+        let m = Measurement {
+            temperature: 25.0,
+            humidity: 40.0,
+            co2: 600,
+        };
+        let humidity = m.humidity_at(20.0);
+        assert!((53.3 - humidity).abs() < 0.1, "{humidity}");
+        assert_eq!(100.0, m.humidity_at(0.0));
+    }
+
+    #[test]
+    fn test_co2_corrected_for_pressure() {
+        let m = Measurement {
+            temperature: 20.0,
+            humidity: 50.0,
+            co2: 1_000,
+        };
+
+        assert_eq!(1_000, co2_corrected_for_pressure(&m, 1_000.0, 1_000));
+        assert_eq!(1_020, co2_corrected_for_pressure(&m, 980.0, 1_000));
+        assert_eq!(981, co2_corrected_for_pressure(&m, 1_033.0, 1_013));
+        assert_eq!(1_067, co2_corrected_for_pressure(&m, 950.0, 0));
+        assert_eq!(1_000, co2_corrected_for_pressure(&m, f32::NAN, 1_000));
+        assert_eq!(1_000, co2_corrected_for_pressure(&m, 0.0, 1_000));
+        assert_eq!(u16::MAX, co2_corrected_for_pressure(&m, 0.001, 1_000));
+    }
+
+    #[test]
+    fn test_co2_uncertainty_rounds_up() {
+        assert_eq!(51, Accuracy::SCD40.co2_uncertainty(1));
+        assert_eq!(3_327, Accuracy::SCD40.co2_uncertainty(u16::MAX));
+    }
+
+    #[cfg(feature = "ufmt")]
+    #[test]
+    fn test_ufmt() {
+        extern crate std;
+        use std::string::{String, ToString};
+
+        struct Buf(String);
+
+        impl ufmt::uWrite for Buf {
+            type Error = core::convert::Infallible;
+
+            fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+
+        let m = Measurement {
+            temperature: 22.456,
+            humidity: 5.0,
+            co2: 800,
+        };
+
+        let mut buf = Buf(String::new());
+        ufmt::uwrite!(buf, "{}", m).unwrap();
+        assert_eq!("800 ppm, 22.46 C, 5.00 %RH", buf.0);
+
+        let mut buf = Buf(String::new());
+        ufmt::uwrite!(buf, "{:?}", m).unwrap();
+        assert_eq!(
+            "Measurement { temperature: 22.46, humidity: 5.00, co2: 800 }",
+            buf.0
+        );
+
+        for (value, expected) in [
+            (-0.001, "0.00"),
+            (-12.345, "-12.35"),
+            (f32::NAN, "NaN"),
+            (f32::NEG_INFINITY, "-inf"),
+        ] {
+            let mut buf = Buf(String::new());
+            ufmt::uwrite!(buf, "{}", Hundredths(value)).unwrap();
+            assert_eq!(expected, buf.0);
+            assert_eq!(expected, Fixed::new(value, 2).to_string());
+        }
+    }
+}
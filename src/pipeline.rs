@@ -0,0 +1,402 @@
+//! Composable post-processing of the measurements. The processors implement
+//! [MeasurementProcessor](crate::pipeline::MeasurementProcessor) and are
+//! chained with [then](crate::pipeline::MeasurementProcessor::then),
+//! e.g. warm-up discard → median → EMA → correction → validation:
+//!
+//! ```
+//! use libscd::calibration::{MeasurementCorrection, Polynomial};
+//! use libscd::pipeline::{
+//!     ExponentialSmoothing, MeasurementProcessor, MedianFilter, PlausibilityCheck, WarmUpDiscard,
+//! };
+//!
+//! let pipeline = WarmUpDiscard::new(3)
+//!     .then(MedianFilter::<5>::new())
+//!     .then(ExponentialSmoothing::new(0.2))
+//!     .then(MeasurementCorrection {
+//!         co2: Polynomial::linear(-12.0, 1.02),
+//!         ..MeasurementCorrection::default()
+//!     })
+//!     .then(PlausibilityCheck::default());
+//! ```
+//!
+//! A pipeline can process a stream of measurements via
+//! [process_iter](crate::pipeline::MeasurementProcessor::process_iter), or
+//! wrap a driver, e.g. `libscd::synchronous::pipeline::ProcessedSensor`.
+//! The processors keep their state in fixed-size buffers and don't allocate
+
+use crate::analysis::median;
+use crate::calibration::MeasurementCorrection;
+use crate::measurement::Measurement;
+
+/// A step of the post-processing of the measurements, such as a filter or
+/// a correction
+pub trait MeasurementProcessor {
+    /// Process a measurement. Returns `None` if the measurement is
+    /// discarded, e.g. during the warm-up of the sensor or because it is
+    /// not plausible
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement>;
+
+    /// Forget the measurements seen so far, e.g. after the sensor was
+    /// restarted
+    fn reset(&mut self) {}
+
+    /// Chain another processor, which receives the measurements that were
+    /// not discarded by this one
+    fn then<P: MeasurementProcessor>(self, next: P) -> Chain<Self, P>
+    where
+        Self: Sized,
+    {
+        Chain { first: self, next }
+    }
+
+    /// Process a stream of measurements, skipping the discarded ones
+    fn process_iter<I>(&mut self, measurements: I) -> impl Iterator<Item = Measurement>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = Measurement>,
+    {
+        measurements
+            .into_iter()
+            .filter_map(move |measurement| self.process(measurement))
+    }
+}
+
+impl<F> MeasurementProcessor for F
+where
+    F: FnMut(Measurement) -> Option<Measurement>,
+{
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement> {
+        self(measurement)
+    }
+}
+
+impl MeasurementProcessor for MeasurementCorrection {
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement> {
+        Some(self.apply(measurement))
+    }
+}
+
+/// Two processors applied one after another, created by
+/// [MeasurementProcessor::then]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct Chain<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<A, B> Chain<A, B> {
+    /// The processors of the chain, e.g. to inspect their state
+    pub fn parts(&self) -> (&A, &B) {
+        (&self.first, &self.next)
+    }
+
+    /// The processors of the chain, e.g. to reconfigure them
+    pub fn parts_mut(&mut self) -> (&mut A, &mut B) {
+        (&mut self.first, &mut self.next)
+    }
+}
+
+impl<A: MeasurementProcessor, B: MeasurementProcessor> MeasurementProcessor for Chain<A, B> {
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement> {
+        self.first
+            .process(measurement)
+            .and_then(|measurement| self.next.process(measurement))
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.next.reset();
+    }
+}
+
+/// Discards the first measurements after the start or a reset, while the
+/// sensor is still warming up
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct WarmUpDiscard {
+    samples: u8,
+    remaining: u8,
+}
+
+impl WarmUpDiscard {
+    /// Create a new processor discarding the first `samples` measurements
+    pub fn new(samples: u8) -> Self {
+        Self {
+            samples,
+            remaining: samples,
+        }
+    }
+
+    /// Check if the warm-up is over
+    pub fn is_warm(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl MeasurementProcessor for WarmUpDiscard {
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement> {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            return None;
+        }
+
+        Some(measurement)
+    }
+
+    fn reset(&mut self) {
+        self.remaining = self.samples;
+    }
+}
+
+/// Replaces each field of the measurement with its median over the last
+/// `N` measurements, which removes isolated spikes. Until the window is
+/// full, the median of the measurements seen so far is used
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct MedianFilter<const N: usize> {
+    co2: [f32; N],
+    temperature: [f32; N],
+    humidity: [f32; N],
+    len: usize,
+    next: usize,
+}
+
+impl<const N: usize> MedianFilter<N> {
+    /// Create a new filter with a window of `N` measurements
+    pub fn new() -> Self {
+        Self {
+            co2: [0.0; N],
+            temperature: [0.0; N],
+            humidity: [0.0; N],
+            len: 0,
+            next: 0,
+        }
+    }
+}
+
+impl<const N: usize> Default for MedianFilter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> MeasurementProcessor for MedianFilter<N> {
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement> {
+        if N == 0 {
+            return Some(measurement);
+        }
+
+        self.co2[self.next] = measurement.co2 as f32;
+        self.temperature[self.next] = measurement.temperature;
+        self.humidity[self.next] = measurement.humidity;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+
+        let median_of = |values: &[f32; N]| {
+            let mut window = *values;
+            median(&mut window[..self.len]).unwrap_or_default()
+        };
+
+        Some(Measurement {
+            temperature: median_of(&self.temperature),
+            humidity: median_of(&self.humidity),
+            co2: (median_of(&self.co2) + 0.5) as u16,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+}
+
+/// Smooths the measurements with an exponential moving average (EMA)
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone)]
+pub struct ExponentialSmoothing {
+    alpha: f32,
+    state: Option<(f32, f32, f32)>,
+}
+
+impl ExponentialSmoothing {
+    /// Create a new filter with the smoothing factor `alpha`, which is
+    /// limited to the range of `(0, 1]`. The lower it is, the smoother and
+    /// the more delayed the output is, while `1` disables the smoothing
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha: alpha.clamp(f32::EPSILON, 1.0),
+            state: None,
+        }
+    }
+}
+
+impl MeasurementProcessor for ExponentialSmoothing {
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement> {
+        let sample = (
+            measurement.co2 as f32,
+            measurement.temperature,
+            measurement.humidity,
+        );
+
+        let (co2, temperature, humidity) = match self.state {
+            None => sample,
+            Some((co2, temperature, humidity)) => (
+                co2 + self.alpha * (sample.0 - co2),
+                temperature + self.alpha * (sample.1 - temperature),
+                humidity + self.alpha * (sample.2 - humidity),
+            ),
+        };
+        self.state = Some((co2, temperature, humidity));
+
+        Some(Measurement {
+            temperature,
+            humidity,
+            co2: (co2 + 0.5) as u16,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Discards the measurements outside of the given limits, e.g. after a
+/// glitch of the sensor. The relative humidity must also be within 0 - 100 %
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PlausibilityCheck {
+    /// The lowest plausible CO2 concentration in PPM
+    pub min_co2: u16,
+
+    /// The highest plausible CO2 concentration in PPM
+    pub max_co2: u16,
+
+    /// The lowest plausible temperature in Celsius
+    pub min_temperature: f32,
+
+    /// The highest plausible temperature in Celsius
+    pub max_temperature: f32,
+}
+
+impl Default for PlausibilityCheck {
+    /// The measurement and the operating ranges of the SCD4x and SCD30
+    /// sensors: up to 40 000 PPM and -10 to 60 degrees Celsius. A CO2
+    /// concentration of 0, e.g. of a temperature and humidity only
+    /// measurement, is accepted
+    fn default() -> Self {
+        Self {
+            min_co2: 0,
+            max_co2: 40_000,
+            min_temperature: -10.0,
+            max_temperature: 60.0,
+        }
+    }
+}
+
+impl PlausibilityCheck {
+    /// Check if the measurement is within the limits
+    pub fn is_plausible(&self, measurement: &Measurement) -> bool {
+        (self.min_co2..=self.max_co2).contains(&measurement.co2)
+            && (self.min_temperature..=self.max_temperature).contains(&measurement.temperature)
+            && (0.0..=100.0).contains(&measurement.humidity)
+    }
+}
+
+impl MeasurementProcessor for PlausibilityCheck {
+    fn process(&mut self, measurement: Measurement) -> Option<Measurement> {
+        self.is_plausible(&measurement).then_some(measurement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn m(co2: u16, temperature: f32, humidity: f32) -> Measurement {
+        Measurement {
+            temperature,
+            humidity,
+            co2,
+        }
+    }
+
+    #[test]
+    fn test_warm_up_discard() {
+        let mut discard = WarmUpDiscard::new(2);
+        assert!(discard.process(m(400, 20.0, 40.0)).is_none());
+        assert!(discard.process(m(400, 20.0, 40.0)).is_none());
+        assert!(discard.is_warm());
+        assert!(discard.process(m(400, 20.0, 40.0)).is_some());
+
+        discard.reset();
+        assert!(discard.process(m(400, 20.0, 40.0)).is_none());
+    }
+
+    #[test]
+    fn test_median_filter_removes_spikes() {
+        let mut filter = MedianFilter::<3>::new();
+        let co2: [u16; 5] = core::array::from_fn(|i| {
+            let input = [400, 410, 5_000, 420, 430][i];
+            filter.process(m(input, 20.0, 40.0)).unwrap().co2
+        });
+
+        assert_eq!([400, 405, 410, 420, 430], co2);
+    }
+
+    #[test]
+    fn test_exponential_smoothing() {
+        let mut ema = ExponentialSmoothing::new(0.5);
+        assert_eq!(400, ema.process(m(400, 20.0, 40.0)).unwrap().co2);
+
+        let smoothed = ema.process(m(600, 22.0, 50.0)).unwrap();
+        assert_eq!(500, smoothed.co2);
+        assert_eq!(21.0, smoothed.temperature);
+        assert_eq!(45.0, smoothed.humidity);
+
+        ema.reset();
+        assert_eq!(600, ema.process(m(600, 22.0, 50.0)).unwrap().co2);
+    }
+
+    #[test]
+    fn test_plausibility_check() {
+        let mut check = PlausibilityCheck::default();
+        assert!(check.process(m(800, 21.0, 45.0)).is_some());
+        assert!(check.process(m(45_000, 21.0, 45.0)).is_none());
+        assert!(check.process(m(800, f32::NAN, 45.0)).is_none());
+        assert!(check.process(m(800, 21.0, 101.0)).is_none());
+    }
+
+    #[test]
+    fn test_chain() {
+        let mut pipeline = WarmUpDiscard::new(1)
+            .then(MedianFilter::<3>::new())
+            .then(|mut measurement: Measurement| {
+                measurement.co2 += 1;
+                Some(measurement)
+            })
+            .then(PlausibilityCheck {
+                max_co2: 1_500,
+                ..PlausibilityCheck::default()
+            });
+
+        let input = [
+            m(5_000, 20.0, 40.0),
+            m(400, 20.0, 40.0),
+            m(2_000, 20.0, 40.0),
+            m(2_000, 20.0, 40.0),
+        ];
+        let mut output = [0; 4];
+        let mut len = 0;
+        for measurement in pipeline.process_iter(input) {
+            output[len] = measurement.co2;
+            len += 1;
+        }
+        assert_eq!([401, 1_201], output[..len]);
+
+        pipeline.reset();
+        let (warm_up, _) = pipeline.parts().0.parts().0.parts();
+        assert!(!warm_up.is_warm());
+        assert!(pipeline.process(m(400, 20.0, 40.0)).is_none());
+    }
+}
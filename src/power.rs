@@ -0,0 +1,113 @@
+//! Estimation of the average supply current of the sensors, based on the
+//! typical figures at 3.3 V from the datasheets. The estimates are meant for
+//! battery-life budgeting and do not account for the bus traffic or for the
+//! variance between individual sensors.
+
+/// Average current of the SCD4x in periodic measurement mode
+pub const SCD4X_PERIODIC_MICROAMPS: u32 = 15_000;
+
+/// Average current of the SCD4x in low power periodic measurement mode
+pub const SCD4X_LOW_POWER_PERIODIC_MICROAMPS: u32 = 3_200;
+
+/// Current of the SCD4x in idle mode
+pub const SCD4X_IDLE_MICROAMPS: u32 = 200;
+
+/// Charge consumed by a single SCD4x measurement in microcoulombs. Derived
+/// from the periodic mode, which performs one measurement every 5 seconds.
+/// Consistent with the 0.45 mA average of the SCD41 single shot
+/// measurements every 5 minutes given by the datasheet
+pub const SCD4X_MEASUREMENT_MICROCOULOMBS: u32 = 75_000;
+
+/// Average current of the SCD30 in continuous measurement mode with the
+/// default 2 seconds measurement interval
+pub const SCD30_DEFAULT_INTERVAL_MICROAMPS: u32 = 19_000;
+
+/// The part of the SCD30 current that does not depend on the measurement
+/// interval. The datasheet gives only the figure at 2 seconds, so longer
+/// intervals are extrapolated from it using this base current
+pub const SCD30_BASE_MICROAMPS: u32 = 5_000;
+
+/// The operating mode of a sensor
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OperatingMode {
+    /// SCD4x periodic measurement mode (5 seconds interval)
+    Scd4xPeriodic,
+
+    /// SCD4x low power periodic measurement mode (30 seconds interval)
+    Scd4xLowPowerPeriodic,
+
+    /// SCD41 single shot measurements every `interval_secs` seconds, with
+    /// the sensor idle between the measurements
+    Scd4xSingleShot {
+        /// The interval between two measurements in seconds
+        interval_secs: u32,
+    },
+
+    /// SCD30 continuous measurement with the given interval in seconds
+    Scd30Continuous {
+        /// The measurement interval in seconds
+        interval_secs: u16,
+    },
+}
+
+/// Estimate the average supply current in microamps of a sensor operated
+/// in the given mode
+pub fn average_current_microamps(mode: OperatingMode) -> u32 {
+    match mode {
+        OperatingMode::Scd4xPeriodic => SCD4X_PERIODIC_MICROAMPS,
+        OperatingMode::Scd4xLowPowerPeriodic => SCD4X_LOW_POWER_PERIODIC_MICROAMPS,
+        OperatingMode::Scd4xSingleShot { interval_secs } => {
+            // A single shot measurement takes 5 seconds, so the sensor
+            // cannot measure more often than in periodic mode
+            let interval_secs = interval_secs.max(5);
+            let measurement = SCD4X_MEASUREMENT_MICROCOULOMBS / interval_secs;
+            SCD4X_IDLE_MICROAMPS + measurement
+        }
+        OperatingMode::Scd30Continuous { interval_secs } => {
+            let interval_secs = interval_secs.max(2) as u32;
+            let measurement = (SCD30_DEFAULT_INTERVAL_MICROAMPS - SCD30_BASE_MICROAMPS) * 2;
+            SCD30_BASE_MICROAMPS + measurement / interval_secs
+        }
+    }
+}
+
+/// Estimate the battery life in hours of a sensor operated in the given mode
+/// and powered from a battery with the given capacity in mAh
+pub fn battery_life_hours(mode: OperatingMode, capacity_mah: u32) -> u32 {
+    let microamps = average_current_microamps(mode) as u64;
+    (capacity_mah as u64 * 1000 / microamps) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scd4x_single_shot() {
+        // Datasheet: 0.45 mA with one measurement every 5 minutes
+        let mode = OperatingMode::Scd4xSingleShot { interval_secs: 300 };
+        assert_eq!(450, average_current_microamps(mode));
+
+        // Measuring every 5 seconds is equivalent to the periodic mode
+        let mode = OperatingMode::Scd4xSingleShot { interval_secs: 1 };
+        assert_eq!(
+            SCD4X_IDLE_MICROAMPS + SCD4X_PERIODIC_MICROAMPS,
+            average_current_microamps(mode)
+        );
+    }
+
+    #[test]
+    fn test_scd30_continuous() {
+        let mode = OperatingMode::Scd30Continuous { interval_secs: 2 };
+        assert_eq!(19_000, average_current_microamps(mode));
+
+        let mode = OperatingMode::Scd30Continuous { interval_secs: 28 };
+        assert_eq!(6_000, average_current_microamps(mode));
+    }
+
+    #[test]
+    fn test_battery_life() {
+        assert_eq!(200, battery_life_hours(OperatingMode::Scd4xPeriodic, 3_000));
+    }
+}
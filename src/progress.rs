@@ -0,0 +1,78 @@
+use core::ops::ControlFlow;
+
+/// The interval at which the `*_with_progress` operations of the drivers
+/// report the progress of a long running command
+pub const PROGRESS_INTERVAL_MILLIS: u32 = 100;
+
+/// The progress of a long running command, such as the self-test or the
+/// factory reset, as reported to the callback of the `*_with_progress`
+/// operations
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Progress {
+    /// The time waited for the command so far in milliseconds
+    pub elapsed_ms: u32,
+
+    /// The execution time of the command in milliseconds
+    pub total_ms: u32,
+}
+
+impl Progress {
+    /// The time until the command completes in milliseconds
+    pub const fn remaining_ms(&self) -> u32 {
+        self.total_ms.saturating_sub(self.elapsed_ms)
+    }
+
+    /// The completed part of the command in percent
+    pub const fn percent(&self) -> u8 {
+        if self.total_ms == 0 || self.elapsed_ms >= self.total_ms {
+            return 100;
+        }
+
+        (self.elapsed_ms as u64 * 100 / self.total_ms as u64) as u8
+    }
+}
+
+/// A callback receiving the progress of a long running command. Returning
+/// `ControlFlow::Break` abandons the operation with `Error::Aborted`
+pub trait ProgressCallback {
+    /// Report the progress of the command
+    fn on_progress(&mut self, progress: Progress) -> ControlFlow<()>;
+}
+
+impl<F> ProgressCallback for F
+where
+    F: FnMut(Progress) -> ControlFlow<()>,
+{
+    fn on_progress(&mut self, progress: Progress) -> ControlFlow<()> {
+        self(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let progress = Progress {
+            elapsed_ms: 2_500,
+            total_ms: 10_000,
+        };
+        assert_eq!(7_500, progress.remaining_ms());
+        assert_eq!(25, progress.percent());
+
+        let done = Progress {
+            elapsed_ms: 10_000,
+            total_ms: 10_000,
+        };
+        assert_eq!(0, done.remaining_ms());
+        assert_eq!(100, done.percent());
+
+        let instant = Progress {
+            elapsed_ms: 0,
+            total_ms: 0,
+        };
+        assert_eq!(100, instant.percent());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::wire::crc::crc8;
+use core::time::Duration;
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::vec::Vec;
+
+/// Simulated SCD30 sensor
+pub mod scd30;
+
+/// Simulated SCD40, SCD41 and SCD43 sensors
+pub mod scd4x;
+
+/// The environment "measured" by a simulated sensor
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Environment {
+    /// CO2 concentration in PPM
+    pub co2: u16,
+
+    /// Temperature in Celsius
+    pub temperature: f32,
+
+    /// Relative humidity in %
+    pub humidity: f32,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            co2: 420,
+            temperature: 25.0,
+            humidity: 50.0,
+        }
+    }
+}
+
+/// Virtual time shared between a simulated sensor and its `SimulatedDelay`.
+/// The time advances only when a delay is performed or when it is advanced
+/// explicitly, which makes the simulation deterministic.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl SimulatedClock {
+    /// Create a new clock starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The time elapsed since the clock was created
+    pub fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        self.nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    /// Create a delay implementation that advances this clock
+    pub fn delay(&self) -> SimulatedDelay {
+        SimulatedDelay {
+            clock: self.clone(),
+        }
+    }
+}
+
+/// Delay implementation that does not block, but instead advances the
+/// virtual time of the simulated sensors
+#[derive(Debug, Clone)]
+pub struct SimulatedDelay {
+    clock: SimulatedClock,
+}
+
+impl embedded_hal::delay::DelayNs for SimulatedDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance(Duration::from_nanos(ns as u64));
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for SimulatedDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance(Duration::from_nanos(ns as u64));
+    }
+}
+
+const NACK_ADDRESS: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+const NACK_DATA: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+
+/// A command frame as written by the driver
+struct Frame {
+    op_code: u16,
+    data: Option<u16>,
+}
+
+fn parse_frame(bytes: &[u8]) -> Result<Frame, ErrorKind> {
+    match bytes {
+        [op_hi, op_lo] => Ok(Frame {
+            op_code: u16::from_be_bytes([*op_hi, *op_lo]),
+            data: None,
+        }),
+        [op_hi, op_lo, d_hi, d_lo, crc] if crc8(&[*d_hi, *d_lo]) == *crc => Ok(Frame {
+            op_code: u16::from_be_bytes([*op_hi, *op_lo]),
+            data: Some(u16::from_be_bytes([*d_hi, *d_lo])),
+        }),
+        _ => Err(NACK_DATA),
+    }
+}
+
+fn encode_words(words: &[u16]) -> Vec<u8> {
+    words
+        .iter()
+        .flat_map(|w| {
+            let [hi, lo] = w.to_be_bytes();
+            [hi, lo, crc8(&[hi, lo])]
+        })
+        .collect()
+}
+
+fn copy_response(response: &mut Option<Vec<u8>>, buf: &mut [u8]) -> Result<(), ErrorKind> {
+    let bytes = response.take().ok_or(NACK_ADDRESS)?;
+    if buf.len() > bytes.len() {
+        return Err(ErrorKind::Other);
+    }
+
+    buf.copy_from_slice(&bytes[..buf.len()]);
+    Ok(())
+}
@@ -0,0 +1,330 @@
+use crate::simulator::{
+    copy_response, encode_words, parse_frame, Environment, Frame, SimulatedClock, SimulatedDelay,
+    NACK_ADDRESS, NACK_DATA,
+};
+use core::time::Duration;
+use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::vec::Vec;
+
+/// The I2C address of the simulated sensor
+pub const I2C_ADDRESS: u8 = 0x61;
+
+/// The firmware version (major, minor) reported by the simulated sensor
+pub const FIRMWARE_VERSION: (u8, u8) = (3, 66);
+
+const SOFT_RESET_DURATION: Duration = Duration::from_millis(2_000);
+const DEFAULT_MEASUREMENT_INTERVAL: u16 = 2;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Settings {
+    measurement_interval: u16,
+    asc_enabled: u16,
+    frc_reference: u16,
+    temperature_offset: u16,
+    altitude: u16,
+    continuous: bool,
+    ambient_pressure: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            measurement_interval: DEFAULT_MEASUREMENT_INTERVAL,
+            asc_enabled: 0,
+            frc_reference: 400,
+            temperature_offset: 0,
+            altitude: 0,
+            continuous: false,
+            ambient_pressure: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    environment: Environment,
+    settings: Settings,
+    co2_correction: i32,
+    busy_until: Duration,
+    next_sample_at: Duration,
+    sample: Option<[f32; 3]>,
+    response: Option<Vec<u8>>,
+    attached: bool,
+}
+
+/// A simulated SCD30 sensor implementing the `embedded-hal` and
+/// `embedded-hal-async` I2C traits.
+///
+/// All settings are treated as non-volatile, i.e. they survive a soft
+/// reset or a power cycle, as described in the interface description.
+/// Like [FakeScd4x](crate::simulator::scd4x::FakeScd4x), the type is cheap
+/// to clone and all clones share the same state.
+#[derive(Debug, Clone)]
+pub struct FakeScd30 {
+    state: Arc<Mutex<State>>,
+    clock: SimulatedClock,
+}
+
+impl Default for FakeScd30 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeScd30 {
+    /// Create a new simulated sensor
+    pub fn new() -> Self {
+        let state = State {
+            environment: Environment::default(),
+            settings: Settings::default(),
+            co2_correction: 0,
+            busy_until: Duration::ZERO,
+            next_sample_at: Duration::ZERO,
+            sample: None,
+            response: None,
+            attached: true,
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            clock: SimulatedClock::new(),
+        }
+    }
+
+    /// The virtual clock driving the simulation
+    pub fn clock(&self) -> &SimulatedClock {
+        &self.clock
+    }
+
+    /// Create a delay implementation that advances the virtual time of
+    /// this sensor
+    pub fn delay(&self) -> SimulatedDelay {
+        self.clock.delay()
+    }
+
+    /// Set the environment that the sensor will measure
+    pub fn set_environment(&self, environment: Environment) {
+        self.state().environment = environment;
+    }
+
+    /// Whether the continuous measurement is running
+    pub fn is_measuring(&self) -> bool {
+        self.state().settings.continuous
+    }
+
+    /// The ambient pressure passed to the last start of the continuous
+    /// measurement, in hPa. Zero means that the compensation is disabled
+    pub fn ambient_pressure(&self) -> u16 {
+        self.state().settings.ambient_pressure
+    }
+
+    /// Simulate a power cycle of the sensor
+    pub fn power_cycle(&self) {
+        let now = self.clock.now();
+        self.state().restart(now);
+    }
+
+    /// Simulate unplugging the sensor from the bus. It does not acknowledge
+    /// its address until it's attached again
+    pub fn detach(&self) {
+        self.state().attached = false;
+    }
+
+    /// Simulate plugging the sensor back into the bus, which powers it up
+    pub fn attach(&self) {
+        self.power_cycle();
+        self.state().attached = true;
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self, bytes: &[u8]) -> Result<(), ErrorKind> {
+        let now = self.clock.now();
+        let mut state = self.state();
+        state.update(now);
+
+        if now < state.busy_until {
+            return Err(NACK_ADDRESS);
+        }
+
+        let frame = parse_frame(bytes)?;
+        state.response = None;
+        state.execute(frame, now)
+    }
+
+    fn read(&self, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        let now = self.clock.now();
+        let mut state = self.state();
+        state.update(now);
+
+        if now < state.busy_until {
+            return Err(NACK_ADDRESS);
+        }
+
+        copy_response(&mut state.response, buf)
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        if address != I2C_ADDRESS || !self.state().attached {
+            return Err(NACK_ADDRESS);
+        }
+
+        for op in operations {
+            match op {
+                Operation::Write(bytes) => self.write(bytes)?,
+                Operation::Read(buf) => self.read(buf)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl State {
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.settings.measurement_interval as u64)
+    }
+
+    fn update(&mut self, now: Duration) {
+        if !self.settings.continuous {
+            return;
+        }
+
+        if now >= self.next_sample_at {
+            self.sample = Some(self.measure());
+            while self.next_sample_at <= now {
+                self.next_sample_at += self.interval();
+            }
+        }
+    }
+
+    fn measure(&self) -> [f32; 3] {
+        let offset = self.settings.temperature_offset as f32 / 100.0;
+        let co2 = (self.environment.co2 as i32 + self.co2_correction).clamp(0, 40_000);
+
+        [
+            co2 as f32,
+            self.environment.temperature - offset,
+            self.environment.humidity,
+        ]
+    }
+
+    fn restart(&mut self, now: Duration) {
+        // The continuous measurement is resumed automatically after a reset
+        self.sample = None;
+        self.response = None;
+        self.busy_until = now + SOFT_RESET_DURATION;
+        self.next_sample_at = self.busy_until + self.interval();
+    }
+
+    fn respond(&mut self, words: &[u16]) {
+        self.response = Some(encode_words(words));
+    }
+
+    fn execute(&mut self, frame: Frame, now: Duration) -> Result<(), ErrorKind> {
+        match (frame.op_code, frame.data) {
+            (START_CONTINUOUS_MEASUREMENT, Some(pressure)) => {
+                if pressure != 0 && !(700..=1400).contains(&pressure) {
+                    return Err(NACK_DATA);
+                }
+
+                self.settings.ambient_pressure = pressure;
+                if !self.settings.continuous {
+                    self.settings.continuous = true;
+                    self.sample = None;
+                    self.next_sample_at = now + self.interval();
+                }
+            }
+            (STOP_CONTINUOUS_MEASUREMENT, None) => {
+                self.settings.continuous = false;
+                self.sample = None;
+            }
+            (MEASUREMENT_INTERVAL, Some(interval)) => {
+                if !(2..=1800).contains(&interval) {
+                    return Err(NACK_DATA);
+                }
+                self.settings.measurement_interval = interval;
+            }
+            (MEASUREMENT_INTERVAL, None) => self.respond(&[self.settings.measurement_interval]),
+            (GET_DATA_READY_STATUS, None) => {
+                self.respond(&[self.sample.is_some() as u16]);
+            }
+            (READ_MEASUREMENT, None) => {
+                let sample = self.sample.take().ok_or(NACK_DATA)?;
+                let mut words = [0u16; 6];
+                for (idx, value) in sample.iter().enumerate() {
+                    let bits = value.to_bits();
+                    words[idx * 2] = (bits >> 16) as u16;
+                    words[idx * 2 + 1] = bits as u16;
+                }
+                self.respond(&words);
+            }
+            (AUTOMATIC_SELF_CALIBRATION, Some(enabled)) => self.settings.asc_enabled = enabled,
+            (AUTOMATIC_SELF_CALIBRATION, None) => self.respond(&[self.settings.asc_enabled]),
+            (FORCED_RECALIBRATION_VALUE, Some(reference)) => {
+                if !(400..=2000).contains(&reference) {
+                    return Err(NACK_DATA);
+                }
+
+                self.settings.frc_reference = reference;
+                self.co2_correction = reference as i32 - self.environment.co2 as i32;
+            }
+            (FORCED_RECALIBRATION_VALUE, None) => self.respond(&[self.settings.frc_reference]),
+            (TEMPERATURE_OFFSET, Some(offset)) => self.settings.temperature_offset = offset,
+            (TEMPERATURE_OFFSET, None) => self.respond(&[self.settings.temperature_offset]),
+            (ALTITUDE_COMPENSATION, Some(altitude)) => self.settings.altitude = altitude,
+            (ALTITUDE_COMPENSATION, None) => self.respond(&[self.settings.altitude]),
+            (READ_FIRMWARE_VERSION, None) => {
+                let (major, minor) = FIRMWARE_VERSION;
+                self.respond(&[u16::from_be_bytes([major, minor])]);
+            }
+            (SOFT_RESET, None) => self.restart(now),
+            _ => return Err(NACK_DATA),
+        }
+
+        Ok(())
+    }
+}
+
+impl ErrorType for FakeScd30 {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal::i2c::I2c for FakeScd30 {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        FakeScd30::transaction(self, address, operations)
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for FakeScd30 {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        FakeScd30::transaction(self, address, operations)
+    }
+}
+
+const START_CONTINUOUS_MEASUREMENT: u16 = 0x0010;
+const STOP_CONTINUOUS_MEASUREMENT: u16 = 0x0104;
+const MEASUREMENT_INTERVAL: u16 = 0x4600;
+const GET_DATA_READY_STATUS: u16 = 0x0202;
+const READ_MEASUREMENT: u16 = 0x0300;
+const AUTOMATIC_SELF_CALIBRATION: u16 = 0x5306;
+const FORCED_RECALIBRATION_VALUE: u16 = 0x5204;
+const TEMPERATURE_OFFSET: u16 = 0x5403;
+const ALTITUDE_COMPENSATION: u16 = 0x5102;
+const READ_FIRMWARE_VERSION: u16 = 0xD100;
+const SOFT_RESET: u16 = 0xD304;
@@ -0,0 +1,473 @@
+use crate::simulator::{
+    copy_response, encode_words, parse_frame, Environment, Frame, SimulatedClock, SimulatedDelay,
+    NACK_ADDRESS, NACK_DATA,
+};
+use crate::variant::SensorVariant;
+use core::time::Duration;
+use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::vec::Vec;
+
+/// The I2C address of the simulated sensor
+pub const I2C_ADDRESS: u8 = 0x62;
+
+const PERIODIC_INTERVAL: Duration = Duration::from_secs(5);
+const LOW_POWER_PERIODIC_INTERVAL: Duration = Duration::from_secs(30);
+
+// Default values from the datasheet
+const DEFAULT_TEMPERATURE_OFFSET: u16 = 0x05B6; // ~4 C
+const DEFAULT_AMBIENT_PRESSURE: u16 = 1013;
+const DEFAULT_ASC_TARGET: u16 = 400;
+const DEFAULT_ASC_INITIAL_PERIOD: u16 = 44;
+const DEFAULT_ASC_STANDARD_PERIOD: u16 = 156;
+
+/// The operating mode of the simulated sensor
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    Idle,
+    Periodic,
+    LowPowerPeriodic,
+    Sleep,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Settings {
+    temperature_offset: u16,
+    altitude: u16,
+    asc_enabled: u16,
+    asc_target: u16,
+    asc_initial_period: u16,
+    asc_standard_period: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            temperature_offset: DEFAULT_TEMPERATURE_OFFSET,
+            altitude: 0,
+            asc_enabled: 1,
+            asc_target: DEFAULT_ASC_TARGET,
+            asc_initial_period: DEFAULT_ASC_INITIAL_PERIOD,
+            asc_standard_period: DEFAULT_ASC_STANDARD_PERIOD,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    variant: SensorVariant,
+    serial_number: u64,
+    environment: Environment,
+    self_test_status: u16,
+    mode: Mode,
+    settings: Settings,
+    eeprom: Settings,
+    ambient_pressure: u16,
+    co2_correction: i32,
+    operated: bool,
+    busy_until: Duration,
+    next_sample_at: Duration,
+    sample: Option<[u16; 3]>,
+    response: Option<Vec<u8>>,
+    attached: bool,
+}
+
+/// A simulated SCD4x sensor implementing the `embedded-hal` and
+/// `embedded-hal-async` I2C traits.
+///
+/// The simulation models the operating modes of the sensor, the command
+/// execution times (the sensor does not acknowledge commands while busy),
+/// the measurement timing, the configuration stored in RAM and EEPROM and
+/// the CRC of the exchanged data words.
+///
+/// The type is cheap to clone and all clones share the same state, so a
+/// clone can be kept by the test in order to control the environment
+/// while the driver owns another one.
+#[derive(Debug, Clone)]
+pub struct FakeScd4x {
+    state: Arc<Mutex<State>>,
+    clock: SimulatedClock,
+}
+
+impl FakeScd4x {
+    /// Create a new simulated sensor of the given variant. Returns
+    /// `None` if the variant does not belong to the SCD4x family.
+    pub fn new(variant: SensorVariant) -> Option<Self> {
+        if variant == SensorVariant::Scd30 {
+            return None;
+        }
+
+        let state = State {
+            variant,
+            serial_number: 0x0000_BEEF_CAFE,
+            environment: Environment::default(),
+            self_test_status: 0,
+            mode: Mode::Idle,
+            settings: Settings::default(),
+            eeprom: Settings::default(),
+            ambient_pressure: DEFAULT_AMBIENT_PRESSURE,
+            co2_correction: 0,
+            operated: false,
+            busy_until: Duration::ZERO,
+            next_sample_at: Duration::ZERO,
+            sample: None,
+            response: None,
+            attached: true,
+        };
+
+        Some(Self {
+            state: Arc::new(Mutex::new(state)),
+            clock: SimulatedClock::new(),
+        })
+    }
+
+    /// The virtual clock driving the simulation
+    pub fn clock(&self) -> &SimulatedClock {
+        &self.clock
+    }
+
+    /// Create a delay implementation that advances the virtual time of
+    /// this sensor
+    pub fn delay(&self) -> SimulatedDelay {
+        self.clock.delay()
+    }
+
+    /// Set the environment that the sensor will measure
+    pub fn set_environment(&self, environment: Environment) {
+        self.state().environment = environment;
+    }
+
+    /// Set the serial number reported by the sensor
+    pub fn set_serial_number(&self, serial_number: u64) {
+        self.state().serial_number = serial_number & 0xFFFF_FFFF_FFFF;
+    }
+
+    /// Set the status word returned by the self test. Zero means that
+    /// no malfunction was detected
+    pub fn set_self_test_status(&self, status: u16) {
+        self.state().self_test_status = status;
+    }
+
+    /// The current operating mode of the sensor
+    pub fn mode(&self) -> Mode {
+        self.state().mode
+    }
+
+    /// The ambient pressure configured by the driver, in hPa
+    pub fn ambient_pressure(&self) -> u16 {
+        self.state().ambient_pressure
+    }
+
+    /// Simulate a power cycle of the sensor. Any configuration that
+    /// was not persisted is lost
+    pub fn power_cycle(&self) {
+        let mut state = self.state();
+        state.mode = Mode::Idle;
+        state.settings = state.eeprom;
+        state.ambient_pressure = DEFAULT_AMBIENT_PRESSURE;
+        state.operated = false;
+        state.sample = None;
+        state.response = None;
+    }
+
+    /// Simulate unplugging the sensor from the bus. It does not acknowledge
+    /// its address until it's attached again
+    pub fn detach(&self) {
+        self.state().attached = false;
+    }
+
+    /// Simulate plugging the sensor back into the bus, which powers it up
+    pub fn attach(&self) {
+        self.power_cycle();
+        self.state().attached = true;
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self, bytes: &[u8]) -> Result<(), ErrorKind> {
+        let now = self.clock.now();
+        let mut state = self.state();
+        state.update(now);
+
+        if now < state.busy_until {
+            return Err(NACK_ADDRESS);
+        }
+
+        let frame = parse_frame(bytes)?;
+        state.response = None;
+        state.execute(frame, now)
+    }
+
+    fn read(&self, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        let now = self.clock.now();
+        let mut state = self.state();
+        state.update(now);
+
+        if now < state.busy_until {
+            return Err(NACK_ADDRESS);
+        }
+
+        copy_response(&mut state.response, buf)
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        if address != I2C_ADDRESS || !self.state().attached {
+            return Err(NACK_ADDRESS);
+        }
+
+        for op in operations {
+            match op {
+                Operation::Write(bytes) => self.write(bytes)?,
+                Operation::Read(buf) => self.read(buf)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl State {
+    fn update(&mut self, now: Duration) {
+        let interval = match self.mode {
+            Mode::Periodic => PERIODIC_INTERVAL,
+            Mode::LowPowerPeriodic => LOW_POWER_PERIODIC_INTERVAL,
+            Mode::Idle | Mode::Sleep => return,
+        };
+
+        if now >= self.next_sample_at {
+            self.sample = Some(self.measure(true));
+            while self.next_sample_at <= now {
+                self.next_sample_at += interval;
+            }
+        }
+    }
+
+    fn measure(&self, with_co2: bool) -> [u16; 3] {
+        // The self-heating of the sensor matches the default temperature
+        // offset, so the default configuration reports the true temperature
+        let offset = self.settings.temperature_offset as i32 - DEFAULT_TEMPERATURE_OFFSET as i32;
+        let temperature = self.environment.temperature - offset as f32 * 175.0 / 65535.0;
+        let raw_temperature = ((temperature + 45.0) * 65535.0 / 175.0).clamp(0.0, 65535.0);
+        let raw_humidity = (self.environment.humidity * 65535.0 / 100.0).clamp(0.0, 65535.0);
+
+        let co2 = match with_co2 {
+            true => (self.environment.co2 as i32 + self.co2_correction).clamp(0, 40_000) as u16,
+            false => 0,
+        };
+
+        [co2, raw_temperature as u16, raw_humidity as u16]
+    }
+
+    fn supports_scd41_commands(&self) -> bool {
+        self.variant != SensorVariant::Scd40
+    }
+
+    fn respond(&mut self, words: &[u16]) {
+        self.response = Some(encode_words(words));
+    }
+
+    fn execute(&mut self, frame: Frame, now: Duration) -> Result<(), ErrorKind> {
+        let (exec_millis, allowed_while_running) = command_properties(frame.op_code)
+            .filter(|_| {
+                frame.op_code < 0x2190 || frame.op_code > 0x21a0 || self.supports_scd41_commands()
+            })
+            .ok_or(NACK_DATA)?;
+
+        match self.mode {
+            Mode::Sleep if frame.op_code != WAKE_UP => return Err(NACK_ADDRESS),
+            Mode::Periodic | Mode::LowPowerPeriodic if !allowed_while_running => {
+                return Err(NACK_DATA);
+            }
+            _ => {}
+        }
+
+        self.busy_until = now + Duration::from_millis(exec_millis);
+
+        match (frame.op_code, frame.data) {
+            (START_PERIODIC_MEASUREMENT, None) => self.start(Mode::Periodic, now),
+            (START_LOW_POWER_PERIODIC_MEASUREMENT, None) => self.start(Mode::LowPowerPeriodic, now),
+            (STOP_PERIODIC_MEASUREMENT, None) => self.mode = Mode::Idle,
+            (READ_MEASUREMENT, None) => {
+                let sample = self.sample.take().ok_or(NACK_DATA)?;
+                self.respond(&sample);
+            }
+            (GET_DATA_READY_STATUS, None) => {
+                let status = match self.sample.is_some() {
+                    true => 0x8006,
+                    false => 0x8000,
+                };
+                self.respond(&[status]);
+            }
+            (SET_TEMPERATURE_OFFSET, Some(value)) => self.settings.temperature_offset = value,
+            (GET_TEMPERATURE_OFFSET, None) => self.respond(&[self.settings.temperature_offset]),
+            (SET_SENSOR_ALTITUDE, Some(value)) => self.settings.altitude = value,
+            (GET_SENSOR_ALTITUDE, None) => self.respond(&[self.settings.altitude]),
+            (AMBIENT_PRESSURE, Some(value)) => self.ambient_pressure = value,
+            (AMBIENT_PRESSURE, None) => self.respond(&[self.ambient_pressure]),
+            (SET_ASC_ENABLED, Some(value)) => self.settings.asc_enabled = value,
+            (GET_ASC_ENABLED, None) => self.respond(&[self.settings.asc_enabled]),
+            (SET_ASC_TARGET, Some(value)) => self.settings.asc_target = value,
+            (GET_ASC_TARGET, None) => self.respond(&[self.settings.asc_target]),
+            (PERFORM_FORCED_RECALIBRATION, Some(reference)) => {
+                let status = self.forced_recalibration(reference);
+                self.respond(&[status]);
+            }
+            (PERSIST_SETTINGS, None) => self.eeprom = self.settings,
+            (GET_SERIAL_NUMBER, None) => {
+                let sn = self.serial_number;
+                self.respond(&[(sn >> 32) as u16, (sn >> 16) as u16, sn as u16]);
+            }
+            (GET_SENSOR_VARIANT, None) => {
+                let variant = match self.variant {
+                    SensorVariant::Scd41 => 0x1000,
+                    SensorVariant::Scd43 => 0x5000,
+                    _ => 0x0000,
+                };
+                self.respond(&[variant]);
+            }
+            (PERFORM_SELF_TEST, None) => self.respond(&[self.self_test_status]),
+            (PERFORM_FACTORY_RESET, None) => {
+                self.settings = Settings::default();
+                self.eeprom = Settings::default();
+                self.co2_correction = 0;
+            }
+            (REINIT, None) => self.settings = self.eeprom,
+            (MEASURE_SINGLE_SHOT, None) => {
+                self.operated = true;
+                self.sample = Some(self.measure(true));
+            }
+            (MEASURE_SINGLE_SHOT_RHT_ONLY, None) => self.sample = Some(self.measure(false)),
+            (POWER_DOWN, None) => self.mode = Mode::Sleep,
+            (WAKE_UP, None) => {
+                // The sensor does not acknowledge the wake-up command
+                self.mode = Mode::Idle;
+                return Err(NACK_ADDRESS);
+            }
+            (SET_ASC_INITIAL_PERIOD, Some(value)) => self.settings.asc_initial_period = value,
+            (GET_ASC_INITIAL_PERIOD, None) => self.respond(&[self.settings.asc_initial_period]),
+            (SET_ASC_STANDARD_PERIOD, Some(value)) => self.settings.asc_standard_period = value,
+            (GET_ASC_STANDARD_PERIOD, None) => self.respond(&[self.settings.asc_standard_period]),
+            _ => return Err(NACK_DATA),
+        }
+
+        Ok(())
+    }
+
+    fn start(&mut self, mode: Mode, now: Duration) {
+        let interval = match mode {
+            Mode::LowPowerPeriodic => LOW_POWER_PERIODIC_INTERVAL,
+            _ => PERIODIC_INTERVAL,
+        };
+
+        self.mode = mode;
+        self.operated = true;
+        self.sample = None;
+        self.next_sample_at = now + interval;
+    }
+
+    fn forced_recalibration(&mut self, reference: u16) -> u16 {
+        // Section 3.8.1 of the datasheet: 0xFFFF indicates that the FRC
+        // failed because the sensor was not operated before the command
+        if !self.operated {
+            return 0xFFFF;
+        }
+
+        let measured = self.environment.co2 as i32 + self.co2_correction;
+        let correction = reference as i32 - measured;
+        self.co2_correction += correction;
+
+        (correction + 0x8000).clamp(0, 0xFFFE) as u16
+    }
+}
+
+impl ErrorType for FakeScd4x {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal::i2c::I2c for FakeScd4x {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        FakeScd4x::transaction(self, address, operations)
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for FakeScd4x {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        FakeScd4x::transaction(self, address, operations)
+    }
+}
+
+const START_PERIODIC_MEASUREMENT: u16 = 0x21b1;
+const START_LOW_POWER_PERIODIC_MEASUREMENT: u16 = 0x21ac;
+const STOP_PERIODIC_MEASUREMENT: u16 = 0x3f86;
+const READ_MEASUREMENT: u16 = 0xec05;
+const GET_DATA_READY_STATUS: u16 = 0xe4b8;
+const SET_TEMPERATURE_OFFSET: u16 = 0x241d;
+const GET_TEMPERATURE_OFFSET: u16 = 0x2318;
+const SET_SENSOR_ALTITUDE: u16 = 0x2427;
+const GET_SENSOR_ALTITUDE: u16 = 0x2322;
+const AMBIENT_PRESSURE: u16 = 0xe000;
+const SET_ASC_ENABLED: u16 = 0x2416;
+const GET_ASC_ENABLED: u16 = 0x2313;
+const SET_ASC_TARGET: u16 = 0x243a;
+const GET_ASC_TARGET: u16 = 0x233f;
+const PERFORM_FORCED_RECALIBRATION: u16 = 0x362f;
+const PERSIST_SETTINGS: u16 = 0x3615;
+const GET_SERIAL_NUMBER: u16 = 0x3682;
+const GET_SENSOR_VARIANT: u16 = 0x202f;
+const PERFORM_SELF_TEST: u16 = 0x3639;
+const PERFORM_FACTORY_RESET: u16 = 0x3632;
+const REINIT: u16 = 0x3646;
+const MEASURE_SINGLE_SHOT: u16 = 0x219d;
+const MEASURE_SINGLE_SHOT_RHT_ONLY: u16 = 0x2196;
+const POWER_DOWN: u16 = 0x36e0;
+const WAKE_UP: u16 = 0x36f6;
+const SET_ASC_INITIAL_PERIOD: u16 = 0x2445;
+const GET_ASC_INITIAL_PERIOD: u16 = 0x2340;
+const SET_ASC_STANDARD_PERIOD: u16 = 0x244e;
+const GET_ASC_STANDARD_PERIOD: u16 = 0x234b;
+
+/// The execution time in milliseconds and whether the command is
+/// accepted during periodic measurement (Section 3.5 of the datasheet)
+fn command_properties(op_code: u16) -> Option<(u64, bool)> {
+    let properties = match op_code {
+        START_PERIODIC_MEASUREMENT => (0, false),
+        START_LOW_POWER_PERIODIC_MEASUREMENT => (0, false),
+        STOP_PERIODIC_MEASUREMENT => (500, true),
+        READ_MEASUREMENT => (1, true),
+        GET_DATA_READY_STATUS => (1, true),
+        AMBIENT_PRESSURE => (1, true),
+        SET_TEMPERATURE_OFFSET | GET_TEMPERATURE_OFFSET => (1, false),
+        SET_SENSOR_ALTITUDE | GET_SENSOR_ALTITUDE => (1, false),
+        SET_ASC_ENABLED | GET_ASC_ENABLED => (1, false),
+        SET_ASC_TARGET | GET_ASC_TARGET => (1, false),
+        PERFORM_FORCED_RECALIBRATION => (400, false),
+        PERSIST_SETTINGS => (800, false),
+        GET_SERIAL_NUMBER | GET_SENSOR_VARIANT => (1, false),
+        PERFORM_SELF_TEST => (10_000, false),
+        PERFORM_FACTORY_RESET => (1_200, false),
+        REINIT => (30, false),
+        MEASURE_SINGLE_SHOT => (5_000, false),
+        MEASURE_SINGLE_SHOT_RHT_ONLY => (50, false),
+        POWER_DOWN => (1, false),
+        WAKE_UP => (30, false),
+        SET_ASC_INITIAL_PERIOD | GET_ASC_INITIAL_PERIOD => (1, false),
+        SET_ASC_STANDARD_PERIOD | GET_ASC_STANDARD_PERIOD => (1, false),
+        _ => return None,
+    };
+
+    Some(properties)
+}
@@ -0,0 +1,166 @@
+use crate::error::Error;
+use crate::measurement::Measurement;
+use crate::synchronous::sensor::{Co2Sensor, Reattach};
+use crate::synchronous::transport::{I2cTransport, Transport};
+use crate::variant::{ProbeReport, SensorVariant};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+#[cfg(feature = "scd30")]
+use crate::synchronous::scd30::Scd30;
+#[cfg(feature = "scd40")]
+use crate::synchronous::scd4x::Scd40;
+#[cfg(feature = "scd41")]
+use crate::synchronous::scd4x::Scd41;
+
+/// A driver for any of the supported sensors, selected at runtime. Useful
+/// when the devices are populated with different sensors and the one that
+/// is actually present is detected with [probe_bus](super::probe::probe_bus).
+///
+/// The operations shared by all sensors are available via [Co2Sensor],
+/// while the sensor specific ones can be reached by matching on the variant.
+pub enum AnySensor<T> {
+    /// An SCD30 sensor
+    #[cfg(feature = "scd30")]
+    Scd30(Scd30<T>),
+
+    /// An SCD40 sensor
+    #[cfg(feature = "scd40")]
+    Scd40(Scd40<T>),
+
+    /// An SCD41 sensor
+    #[cfg(feature = "scd41")]
+    Scd41(Scd41<T>),
+}
+
+impl<I2C, D> AnySensor<I2cTransport<I2C, D>>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    /// Create the driver for the sensor found by the bus probe. Returns
+    /// `None` if the probe did not find any of the enabled sensors.
+    ///
+    /// An SCD30 takes precedence if both sensor types are present. When the
+    /// variant of the SCD4x is not known, e.g. because it was measuring
+    /// during the probe, the SCD40 driver is used if it's enabled, as it
+    /// works with both SCD40 and SCD41 sensors.
+    pub fn from_probe(report: &ProbeReport, i2c: I2C, delay: D) -> Option<Self> {
+        #[cfg(feature = "scd30")]
+        if report.scd30 {
+            return Some(Self::Scd30(Scd30::new(i2c, delay)));
+        }
+
+        if !report.scd4x {
+            return None;
+        }
+
+        #[cfg(feature = "scd41")]
+        if matches!(
+            report.scd4x_variant,
+            Some(SensorVariant::Scd41 | SensorVariant::Scd43)
+        ) || cfg!(not(feature = "scd40"))
+        {
+            return Some(Self::Scd41(Scd41::new(i2c, delay)));
+        }
+
+        #[cfg(feature = "scd40")]
+        return Some(Self::Scd40(Scd40::new(i2c, delay)));
+
+        #[allow(unreachable_code)]
+        None
+    }
+
+    /// Release the I2C bus held by this sensor
+    pub fn release(self) -> I2C {
+        match self {
+            #[cfg(feature = "scd30")]
+            Self::Scd30(sensor) => sensor.release(),
+            #[cfg(feature = "scd40")]
+            Self::Scd40(sensor) => sensor.release(),
+            #[cfg(feature = "scd41")]
+            Self::Scd41(sensor) => sensor.release(),
+        }
+    }
+}
+
+impl<T> AnySensor<T> {
+    /// The model of the sensor handled by this driver
+    pub fn variant(&self) -> SensorVariant {
+        match self {
+            #[cfg(feature = "scd30")]
+            Self::Scd30(_) => SensorVariant::Scd30,
+            #[cfg(feature = "scd40")]
+            Self::Scd40(_) => SensorVariant::Scd40,
+            #[cfg(feature = "scd41")]
+            Self::Scd41(_) => SensorVariant::Scd41,
+        }
+    }
+}
+
+impl<T, E> Co2Sensor for AnySensor<T>
+where
+    T: Transport<Error = E>,
+{
+    type Error = E;
+
+    fn start_measurement(&mut self) -> Result<(), Error<E>> {
+        match self {
+            #[cfg(feature = "scd30")]
+            Self::Scd30(sensor) => sensor.start_measurement(),
+            #[cfg(feature = "scd40")]
+            Self::Scd40(sensor) => sensor.start_measurement(),
+            #[cfg(feature = "scd41")]
+            Self::Scd41(sensor) => sensor.start_measurement(),
+        }
+    }
+
+    fn stop_measurement(&mut self) -> Result<(), Error<E>> {
+        match self {
+            #[cfg(feature = "scd30")]
+            Self::Scd30(sensor) => sensor.stop_measurement(),
+            #[cfg(feature = "scd40")]
+            Self::Scd40(sensor) => sensor.stop_measurement(),
+            #[cfg(feature = "scd41")]
+            Self::Scd41(sensor) => sensor.stop_measurement(),
+        }
+    }
+
+    fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        match self {
+            #[cfg(feature = "scd30")]
+            Self::Scd30(sensor) => Co2Sensor::data_ready(sensor),
+            #[cfg(feature = "scd40")]
+            Self::Scd40(sensor) => Co2Sensor::data_ready(sensor),
+            #[cfg(feature = "scd41")]
+            Self::Scd41(sensor) => Co2Sensor::data_ready(sensor),
+        }
+    }
+
+    fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        match self {
+            #[cfg(feature = "scd30")]
+            Self::Scd30(sensor) => Co2Sensor::read_measurement(sensor),
+            #[cfg(feature = "scd40")]
+            Self::Scd40(sensor) => Co2Sensor::read_measurement(sensor),
+            #[cfg(feature = "scd41")]
+            Self::Scd41(sensor) => Co2Sensor::read_measurement(sensor),
+        }
+    }
+}
+
+impl<T, E> Reattach for AnySensor<T>
+where
+    T: Transport<Error = E>,
+{
+    fn reattach(&mut self) -> Result<(), Error<E>> {
+        match self {
+            #[cfg(feature = "scd30")]
+            Self::Scd30(sensor) => sensor.reattach(),
+            #[cfg(feature = "scd40")]
+            Self::Scd40(sensor) => sensor.reattach(),
+            #[cfg(feature = "scd41")]
+            Self::Scd41(sensor) => sensor.reattach(),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::error::Error;
+use crate::synchronous::sensor::Co2Sensor;
+use crate::synchronous::transport::Transport;
+use core::fmt::Debug;
+use embedded_sensors_hal::humidity::{Percentage, RelativeHumiditySensor};
+use embedded_sensors_hal::sensor::ErrorType;
+use embedded_sensors_hal::temperature::{DegreesCelsius, TemperatureSensor};
+
+#[cfg(feature = "scd30")]
+use crate::synchronous::scd30::Scd30;
+#[cfg(feature = "scd40")]
+use crate::synchronous::scd4x::Scd40;
+#[cfg(feature = "scd41")]
+use crate::synchronous::scd4x::Scd41;
+
+// The traits are foreign, so they cannot be implemented for all `Co2Sensor`
+// types at once. Each call reads (and thus consumes) a whole measurement,
+// so the sensor must be measuring and have data ready.
+macro_rules! impl_sensor_traits {
+    ($driver:ident) => {
+        impl<T, E> ErrorType for $driver<T>
+        where
+            T: Transport<Error = E>,
+            E: Debug,
+        {
+            type Error = Error<E>;
+        }
+
+        impl<T, E> TemperatureSensor for $driver<T>
+        where
+            T: Transport<Error = E>,
+            E: Debug,
+        {
+            fn temperature(&mut self) -> Result<DegreesCelsius, Self::Error> {
+                Ok(Co2Sensor::read_measurement(self)?.temperature)
+            }
+        }
+
+        impl<T, E> RelativeHumiditySensor for $driver<T>
+        where
+            T: Transport<Error = E>,
+            E: Debug,
+        {
+            fn relative_humidity(&mut self) -> Result<Percentage, Self::Error> {
+                Ok(Co2Sensor::read_measurement(self)?.humidity)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "scd30")]
+impl_sensor_traits!(Scd30);
+#[cfg(feature = "scd40")]
+impl_sensor_traits!(Scd40);
+#[cfg(feature = "scd41")]
+impl_sensor_traits!(Scd41);
@@ -0,0 +1,127 @@
+use crate::error::{Error, GroupError};
+use crate::measurement::{Measurement, MeasurementBatch};
+use crate::synchronous::sensor::Co2Sensor;
+use embedded_hal::delay::DelayNs;
+
+/// The default interval at which a [SensorGroup] polls its sensors for new
+/// measurements
+pub const GROUP_POLL_INTERVAL_MILLIS: u32 = 100;
+
+/// Operates several sensors, possibly on different buses, so that their
+/// measurement windows are aligned, and gathers one measurement of each
+/// within the same measurement interval, e.g. for studies of the spatial
+/// CO2 gradient.
+///
+/// The sensors are started back-to-back, so their windows are offset only by
+/// the duration of the start commands. The internal clocks of the sensors
+/// drift apart over long periods, which can be corrected by stopping and
+/// starting the group again.
+///
+/// The sensors must be of the same type. Different sensors can be grouped
+/// via [AnySensor](super::any::AnySensor), or as `&mut dyn Co2Sensor` when
+/// they are on buses of different types.
+pub struct SensorGroup<S, const N: usize> {
+    sensors: [S; N],
+    poll_interval_ms: u32,
+}
+
+impl<S, E, const N: usize> SensorGroup<S, N>
+where
+    S: Co2Sensor<Error = E>,
+{
+    /// Create a new group of the given sensors
+    pub fn new(sensors: [S; N]) -> Self {
+        Self {
+            sensors,
+            poll_interval_ms: GROUP_POLL_INTERVAL_MILLIS,
+        }
+    }
+
+    /// Set the interval at which the sensors are polled for new
+    /// measurements. It's also the resolution of the spread of a batch
+    pub fn set_poll_interval(&mut self, interval_ms: u32) {
+        self.poll_interval_ms = interval_ms.max(1);
+    }
+
+    /// The sensors of the group
+    pub fn sensors(&mut self) -> &mut [S; N] {
+        &mut self.sensors
+    }
+
+    /// Release the sensors of the group
+    pub fn release(self) -> [S; N] {
+        self.sensors
+    }
+
+    /// Start the periodic measurement of all sensors back-to-back. Stops at
+    /// the first sensor that fails, leaving the previous ones measuring
+    pub fn start(&mut self) -> Result<(), GroupError<E>> {
+        self.for_each(|sensor| sensor.start_measurement())
+    }
+
+    /// Stop the periodic measurement of all sensors. Stops at the first
+    /// sensor that fails
+    pub fn stop(&mut self) -> Result<(), GroupError<E>> {
+        self.for_each(|sensor| sensor.stop_measurement())
+    }
+
+    /// Poll the sensors until each of them provided a new measurement or
+    /// `timeout_ms` elapsed, and return the measurements as a batch. The
+    /// timeout should cover one measurement interval of the sensors. Stops
+    /// at the first sensor that fails
+    pub fn sample<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        timeout_ms: u32,
+    ) -> Result<MeasurementBatch<N>, GroupError<E>> {
+        let mut measurements: [Option<Measurement>; N] = core::array::from_fn(|_| None);
+        let mut first_ms = None;
+        let mut last_ms = 0;
+        let mut elapsed_ms = 0;
+
+        loop {
+            for (index, sensor) in self.sensors.iter_mut().enumerate() {
+                if measurements[index].is_some() {
+                    continue;
+                }
+
+                let wrap = |error| GroupError { index, error };
+                if !sensor.data_ready().map_err(wrap)? {
+                    continue;
+                }
+
+                // A frame of zeros is not a measurement, so keep polling
+                match sensor.read_measurement() {
+                    Err(Error::NoData) => continue,
+                    result => measurements[index] = Some(result.map_err(wrap)?),
+                }
+
+                first_ms.get_or_insert(elapsed_ms);
+                last_ms = elapsed_ms;
+            }
+
+            if measurements.iter().all(Option::is_some) || elapsed_ms >= timeout_ms {
+                break;
+            }
+
+            delay.delay_ms(self.poll_interval_ms);
+            elapsed_ms = elapsed_ms.saturating_add(self.poll_interval_ms);
+        }
+
+        Ok(MeasurementBatch {
+            measurements,
+            spread_ms: last_ms - first_ms.unwrap_or(last_ms),
+        })
+    }
+
+    fn for_each<F>(&mut self, mut f: F) -> Result<(), GroupError<E>>
+    where
+        F: FnMut(&mut S) -> Result<(), Error<E>>,
+    {
+        for (index, sensor) in self.sensors.iter_mut().enumerate() {
+            f(sensor).map_err(|error| GroupError { index, error })?;
+        }
+
+        Ok(())
+    }
+}
@@ -6,5 +6,59 @@ pub mod scd30;
 #[cfg(any(feature = "scd40", feature = "scd41"))]
 pub mod scd4x;
 
-/// Common utilities for I2C communication as described by the SCD datasheets
-mod i2c;
+/// A driver for any of the supported sensors, selected at runtime
+pub mod any;
+
+/// A sensor-agnostic interface implemented by all drivers
+pub mod sensor;
+
+/// Time-aligned sampling of several sensors
+pub mod group;
+
+/// Post-processing of the measurements of a driver
+pub mod pipeline;
+
+/// Detection of a sensor being detached from the bus and attached again
+pub mod hotplug;
+
+// Implementations of the `embedded-sensors-hal` traits for the drivers
+#[cfg(feature = "embedded-sensors")]
+mod embedded_sensors;
+
+/// Detection of the sensors present on the I2C bus
+pub mod probe;
+
+/// The transport used by the drivers to communicate with the sensors
+pub mod transport;
+
+// The drivers own their transport and keep only plain data besides it, so
+// they are `Send` and `Sync` whenever the bus, the delay and the bus recovery
+// are. Checked here, so that a regression fails the build of the library
+// instead of the build of an application moving a driver into a task.
+#[allow(dead_code)]
+const _: () = {
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    fn send<I2C: Send, D: Send, R: Send>() {
+        assert_send::<transport::I2cTransport<I2C, D, R>>();
+        assert_send::<any::AnySensor<transport::I2cTransport<I2C, D, R>>>();
+        #[cfg(feature = "scd30")]
+        assert_send::<scd30::Scd30<transport::I2cTransport<I2C, D, R>>>();
+        #[cfg(feature = "scd40")]
+        assert_send::<scd4x::Scd40<transport::I2cTransport<I2C, D, R>>>();
+        #[cfg(feature = "scd41")]
+        assert_send::<scd4x::Scd41<transport::I2cTransport<I2C, D, R>>>();
+    }
+
+    fn sync<I2C: Sync, D: Sync, R: Sync>() {
+        assert_sync::<transport::I2cTransport<I2C, D, R>>();
+        assert_sync::<any::AnySensor<transport::I2cTransport<I2C, D, R>>>();
+        #[cfg(feature = "scd30")]
+        assert_sync::<scd30::Scd30<transport::I2cTransport<I2C, D, R>>>();
+        #[cfg(feature = "scd40")]
+        assert_sync::<scd4x::Scd40<transport::I2cTransport<I2C, D, R>>>();
+        #[cfg(feature = "scd41")]
+        assert_sync::<scd4x::Scd41<transport::I2cTransport<I2C, D, R>>>();
+    }
+};
@@ -0,0 +1,68 @@
+use crate::error::Error;
+use crate::measurement::Measurement;
+use crate::pipeline::MeasurementProcessor;
+use crate::synchronous::sensor::Co2Sensor;
+
+/// A driver whose measurements pass through a [MeasurementProcessor], e.g.
+/// a chain of filters and corrections built with
+/// [MeasurementProcessor::then]
+#[derive(Debug)]
+pub struct ProcessedSensor<S, P> {
+    sensor: S,
+    processor: P,
+}
+
+impl<S, P> ProcessedSensor<S, P>
+where
+    S: Co2Sensor,
+    P: MeasurementProcessor,
+{
+    /// Wrap the driver of a sensor
+    pub fn new(sensor: S, processor: P) -> Self {
+        Self { sensor, processor }
+    }
+
+    /// The wrapped driver, e.g. to send commands not covered by [Co2Sensor]
+    pub fn sensor(&mut self) -> &mut S {
+        &mut self.sensor
+    }
+
+    /// The processor of the measurements
+    pub fn processor(&mut self) -> &mut P {
+        &mut self.processor
+    }
+
+    /// Release the driver and the processor
+    pub fn release(self) -> (S, P) {
+        (self.sensor, self.processor)
+    }
+
+    /// Start the periodic measurement and reset the processor, so that e.g.
+    /// the warm-up starts over
+    pub fn start_measurement(&mut self) -> Result<(), Error<S::Error>> {
+        self.sensor.start_measurement()?;
+        self.processor.reset();
+        Ok(())
+    }
+
+    /// Stop the periodic measurement
+    pub fn stop_measurement(&mut self) -> Result<(), Error<S::Error>> {
+        self.sensor.stop_measurement()
+    }
+
+    /// Check whether a new measurement can be read
+    pub fn data_ready(&mut self) -> Result<bool, Error<S::Error>> {
+        self.sensor.data_ready()
+    }
+
+    /// Read the latest measurement and pass it through the processor.
+    /// Returns `Ok(None)` if the processor discarded it, or if the sensor
+    /// responded with a frame of zeros instead of a measurement
+    pub fn read_measurement(&mut self) -> Result<Option<Measurement>, Error<S::Error>> {
+        let measurement = match self.sensor.read_measurement() {
+            Err(Error::NoData) => return Ok(None),
+            result => result?,
+        };
+        Ok(self.processor.process(measurement))
+    }
+}
@@ -0,0 +1,132 @@
+use crate::error::Error;
+use crate::variant::ProbeReport;
+use crate::wire::common::crc8_verify_chunked_3;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{Error as I2cError, ErrorKind, I2c};
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+use crate::variant::SensorVariant;
+
+/// Detect which of the sensors enabled via the feature flags are present
+/// on the I2C bus.
+///
+/// Each address is probed with a read command that is accepted in every
+/// operating mode of the sensor and has no side effects. A missing
+/// acknowledgement or a response with an invalid CRC is reported as an
+/// absent sensor, while any other bus error is returned to the caller.
+/// An SCD41 that has been powered down does not respond until woken up.
+pub fn probe_bus<I2C, D>(i2c: &mut I2C, delay: &mut D) -> Result<ProbeReport, Error<I2C::Error>>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    #[allow(unused_mut)]
+    let mut report = ProbeReport::default();
+
+    #[cfg(feature = "scd30")]
+    {
+        report.scd30 = probe_scd30(i2c, delay)?;
+    }
+
+    #[cfg(any(feature = "scd40", feature = "scd41"))]
+    if let Some(variant) = probe_scd4x(i2c, delay)? {
+        report.scd4x = true;
+        report.scd4x_variant = variant;
+    }
+
+    Ok(report)
+}
+
+#[cfg(feature = "scd30")]
+fn probe_scd30<I2C, D>(i2c: &mut I2C, delay: &mut D) -> Result<bool, Error<I2C::Error>>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    use crate::wire::scd30::{I2C_ADDRESS, READ_FIRMWARE_VERSION, WRITE_DELAY_MILLIS};
+
+    let mut buf = [0; 3];
+    identify(
+        i2c,
+        delay,
+        I2C_ADDRESS,
+        &READ_FIRMWARE_VERSION.prepare(),
+        WRITE_DELAY_MILLIS * 1000,
+        &mut buf,
+    )
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+fn probe_scd4x<I2C, D>(
+    i2c: &mut I2C,
+    delay: &mut D,
+) -> Result<Option<Option<SensorVariant>>, Error<I2C::Error>>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    use crate::wire::scd4x::{
+        decode_sensor_variant, GET_DATA_READY_STATUS, GET_SENSOR_VARIANT, I2C_ADDRESS,
+    };
+
+    let mut buf = [0; 3];
+    let cmd = GET_DATA_READY_STATUS;
+    if !identify(
+        i2c,
+        delay,
+        I2C_ADDRESS,
+        &cmd.prepare(),
+        cmd.exec_time_us,
+        &mut buf,
+    )? {
+        return Ok(None);
+    }
+
+    let cmd = GET_SENSOR_VARIANT;
+    if !identify(
+        i2c,
+        delay,
+        I2C_ADDRESS,
+        &cmd.prepare(),
+        cmd.exec_time_us,
+        &mut buf,
+    )? {
+        return Ok(Some(None));
+    }
+
+    Ok(Some(decode_sensor_variant::<()>(buf).ok()))
+}
+
+/// Send a command and read its response, returning whether a valid
+/// response was received
+fn identify<I2C, D>(
+    i2c: &mut I2C,
+    delay: &mut D,
+    address: u8,
+    command: &[u8],
+    exec_time_us: u32,
+    buf: &mut [u8],
+) -> Result<bool, Error<I2C::Error>>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    if let Err(e) = i2c.write(address, command) {
+        return not_acknowledged(e);
+    }
+
+    delay.delay_us(exec_time_us);
+
+    if let Err(e) = i2c.read(address, buf) {
+        return not_acknowledged(e);
+    }
+
+    Ok(crc8_verify_chunked_3(buf).is_ok())
+}
+
+fn not_acknowledged<E: I2cError>(e: E) -> Result<bool, Error<E>> {
+    match e.kind() {
+        ErrorKind::NoAcknowledge(_) => Ok(false),
+        _ => Err(Error::I2C(e)),
+    }
+}
@@ -1,63 +1,188 @@
-pub use crate::internal::scd30::I2C_ADDRESS;
+pub use crate::wire::scd30::I2C_ADDRESS;
 
-use crate::error::Error;
+use crate::calibration::{MeasurementCorrection, ReferenceTemperature, TemperatureOffsetReport};
+use crate::commands::{SensorCommand, MAX_RESPONSE_WORDS};
+use crate::config::{Compensation, ConfigurationReport, ConfigurationSnapshot, Scd30Settings};
+use crate::diagnostics::{DiagnosticCounters, Diagnostics, MeasurementMode};
+use crate::error::{Error, ErrorKind};
 use crate::measurement::Measurement;
-use crate::synchronous::i2c::{i2c_read, i2c_write};
+use crate::synchronous::sensor::{Co2Sensor, Reattach};
+use crate::synchronous::transport::{read_frame, write_frame, I2cTransport, Transport};
+use crate::variant::{FirmwareVersion, SensorVariant};
+use core::num::NonZeroU16;
+use core::time::Duration;
 use embedded_hal::delay::DelayNs;
 use embedded_hal::i2c::I2c;
 
-use crate::internal::scd30::{
-    decode_measurement_data, Command, AMBIENT_PRESSURE_DISABLE_COMPENSATION,
-    AMBIENT_PRESSURE_RANGE_HPA, BOOT_DELAY_MILLIS, FRC_PPM_RANGE, GET_DATA_READY_STATUS,
-    GET_SET_ALTITUDE_COMPENSATION, GET_SET_MEASUREMENT_INTERVAL, GET_SET_TEMPERATURE_OFFSET,
-    MANAGE_AUTOMATIC_SELF_CALIBRATION, MEASUREMENT_INTERVAL_RANGE, READ_FIRMWARE_VERSION,
-    READ_MEASUREMENT, SET_FORCED_RECALIBRATION_VALUE, SOFT_RESET, START_CONTINUOUS_MEASUREMENT,
-    STOP_CONTINUOUS_MEASUREMENT, WRITE_DELAY_MILLIS,
+use crate::wire::common::{is_all_zero, opcode_with_data_into_payload, words_from_chunks};
+use crate::wire::scd30::{
+    decode_measurement_data, encode_measurement_interval, Command,
+    AMBIENT_PRESSURE_DISABLE_COMPENSATION, AMBIENT_PRESSURE_RANGE_HPA, BOOT_DELAY_MILLIS,
+    FRC_PPM_RANGE, GET_DATA_READY_STATUS, GET_SET_ALTITUDE_COMPENSATION,
+    GET_SET_MEASUREMENT_INTERVAL, GET_SET_TEMPERATURE_OFFSET, MANAGE_AUTOMATIC_SELF_CALIBRATION,
+    MEASUREMENT_INTERVAL_RANGE, POWER_ON_ATTEMPTS, POWER_ON_RETRY_DELAY_MILLIS,
+    READ_FIRMWARE_VERSION, READ_MEASUREMENT, SET_FORCED_RECALIBRATION_VALUE, SOFT_RESET,
+    START_CONTINUOUS_MEASUREMENT, STOP_CONTINUOUS_MEASUREMENT, WRITE_DELAY_MILLIS,
 };
 
+const DATA_READY_POLL_MILLIS: u32 = 250;
+
 /// Driver implementation for the SCD30 CO2 sensor.
 ///
 /// This sensor needs to be enabled via the `scd30` feature flag
-pub struct Scd30<I2C, D> {
-    i2c: I2C,
-    delay: D,
+pub struct Scd30<T> {
+    inner: Inner<T>,
 }
 
-impl<I2C, D, E> Scd30<I2C, D>
+/// An [Scd30] on an I2C bus, waiting with `embassy_time::Delay`, so that
+/// applications embedding the driver only need to name the bus type
+///
+/// This alias needs to be enabled via the `embassy` feature flag
+#[cfg(feature = "embassy")]
+pub type EmbassyScd30<I2C> = Scd30<I2cTransport<I2C, embassy_time::Delay>>;
+
+#[cfg(feature = "embassy")]
+impl<I2C> EmbassyScd30<I2C>
 where
-    I2C: I2c<Error = E>,
+    I2C: I2c,
+{
+    /// Create a new sensor using the provided I2C bus and the Embassy delay
+    pub fn new_embassy(i2c: I2C) -> Self {
+        Self::new(i2c, embassy_time::Delay)
+    }
+}
+
+impl<I2C, D> Scd30<I2cTransport<I2C, D>>
+where
+    I2C: I2c,
     D: DelayNs,
 {
     /// Create a new SCD30 sensor using the provided I2C and delay implementations
     pub fn new(i2c: I2C, delay: D) -> Self {
-        Self { i2c, delay }
+        Self::with_transport(I2cTransport::new(i2c, delay, I2C_ADDRESS))
+    }
+
+    /// Create a new SCD30 sensor right after the sensor has been powered on.
+    ///
+    /// Waits for the sensor to boot and then reads its firmware version to
+    /// verify the communication, retrying while the sensor is not ready to
+    /// respond yet. The last error is returned if the sensor does not
+    /// respond after [POWER_ON_ATTEMPTS] attempts.
+    pub fn new_after_power_on(i2c: I2C, delay: D) -> Result<Self, Error<I2C::Error>> {
+        let mut sensor = Self::new(i2c, delay);
+        sensor.inner().wait_until_booted()?;
+        Ok(sensor)
     }
 
     /// Release the I2C bus held by this sensor
     pub fn release(self) -> I2C {
-        self.i2c
+        self.inner.transport.release().0
     }
+}
 
-    fn read_response(&mut self, read_buf: &mut [u8]) -> Result<(), Error<E>> {
-        i2c_read(&mut self.i2c, I2C_ADDRESS, read_buf)
+impl<T, E> Scd30<T>
+where
+    T: Transport<Error = E>,
+{
+    /// Create a new SCD30 sensor communicating over a custom transport
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            inner: Inner {
+                measurement_started: false,
+                state_check: true,
+                compensation_check: false,
+                correction: None,
+                settings: Scd30Settings::default(),
+                verify_crc: true,
+                crc_failures: 0,
+                crc_recovery_threshold: 0,
+                counters: DiagnosticCounters::default(),
+                last_error: None,
+                transport,
+            },
+        }
     }
 
-    fn write_command(&mut self, cmd: Command) -> Result<(), Error<E>> {
-        i2c_write(&mut self.i2c, I2C_ADDRESS, &cmd.prepare())?;
-        self.delay.delay_ms(WRITE_DELAY_MILLIS);
-        Ok(())
+    /// Release the transport held by this sensor
+    pub fn release_transport(self) -> T {
+        self.inner.transport
     }
 
-    fn write_command_with_data(&mut self, cmd: Command, data: u16) -> Result<(), Error<E>> {
-        let buf = cmd.prepare_with_data(data);
-        i2c_write(&mut self.i2c, I2C_ADDRESS, &buf)?;
-        self.delay.delay_ms(WRITE_DELAY_MILLIS);
-        Ok(())
+    /// Enable the automatic recovery of the sensor after `threshold`
+    /// consecutive CRC failures. The recovery performs a soft reset, writes
+    /// the settings written by this driver again, and resumes the continuous
+    /// measurement with the last ambient pressure if it was active. As the
+    /// responses can't be trusted at that point, the settings are written
+    /// without reading them back first. When the recovery
+    /// succeeds, the failed operation returns `Error::Recovered` and can
+    /// be retried.
+    ///
+    /// A threshold of `0` disables the automatic recovery (default).
+    pub fn set_crc_recovery_threshold(&mut self, threshold: u8) {
+        self.inner.crc_recovery_threshold = threshold;
+        self.inner.crc_failures = 0;
     }
 
-    fn command_with_response(&mut self, cmd: Command, read_buf: &mut [u8]) -> Result<(), Error<E>> {
-        self.write_command(cmd)?;
-        self.read_response(read_buf)
+    /// Disable the CRC verification of the responses received from the
+    /// sensor. The commands sent to the sensor still carry a CRC.
+    ///
+    /// **DANGER**: Corrupted responses are returned as valid data. Only use
+    /// this if the bus layer already guarantees the integrity of the data.
+    #[cfg(feature = "danger-skip-crc")]
+    pub fn dangerously_skip_crc_verification(&mut self, skip: bool) {
+        self.inner.verify_crc = !skip;
+    }
+
+    /// Enable or disable the validation of the commands against the state
+    /// of the continuous measurement tracked by the driver. The forced
+    /// recalibration and the temperature offset tuning require a running
+    /// measurement and return `Error::NotAllowed` otherwise.
+    ///
+    /// The SCD30 resumes the continuous measurement after a power-cycle, so
+    /// the driver also considers the measurement as running once the sensor
+    /// reports ready data. The check can be disabled if the measurement is
+    /// known to run, but the driver has not observed it yet.
+    ///
+    /// The check is enabled by default.
+    pub fn set_state_check(&mut self, enabled: bool) {
+        self.inner.state_check = enabled;
+    }
+
+    /// The source of the pressure compensation set through the driver. A
+    /// non-zero ambient pressure the continuous measurement was started
+    /// with takes precedence over the altitude, because the sensor ignores
+    /// the altitude then
+    pub fn active_compensation(&self) -> Compensation {
+        self.inner.settings.compensation()
+    }
+
+    /// Enable or disable returning `Error::CompensationConflict` when both
+    /// the altitude and a non-zero ambient pressure compensation are set
+    /// through the driver. A conflict is always logged as a warning, but is
+    /// otherwise accepted when the check is disabled.
+    ///
+    /// The check is disabled by default.
+    pub fn set_compensation_conflict_check(&mut self, enabled: bool) {
+        self.inner.compensation_check = enabled;
+    }
+
+    /// Set a correction applied to each measurement before it is returned,
+    /// e.g. one derived by characterizing the unit against a reference rig,
+    /// when the single-point forced recalibration is not sufficient. `None`
+    /// disables the correction, which is the default.
+    pub fn set_measurement_correction(&mut self, correction: Option<MeasurementCorrection>) {
+        self.inner.correction = correction;
+    }
+
+    /// The correction applied to the measurements, if any
+    pub fn measurement_correction(&self) -> Option<&MeasurementCorrection> {
+        self.inner.correction.as_ref()
+    }
+
+    /// Check if the continuous measurement is running according to the state
+    /// tracked by the driver
+    pub fn is_measuring(&self) -> bool {
+        self.inner.measurement_started
     }
 
     /// Starts continuous measurement of the SCD30 to measure CO2 concentration, humidity and temperature. Measurement data
@@ -77,18 +202,25 @@ where
         &mut self,
         ambient_pressure_hpa: u16,
     ) -> Result<(), Error<E>> {
-        if !AMBIENT_PRESSURE_RANGE_HPA.contains(&ambient_pressure_hpa)
-            && AMBIENT_PRESSURE_DISABLE_COMPENSATION != ambient_pressure_hpa
-        {
-            return Err(Error::InvalidInput);
-        }
+        self.inner()
+            .start_continuous_measurement(ambient_pressure_hpa)
+    }
 
-        self.write_command_with_data(START_CONTINUOUS_MEASUREMENT, ambient_pressure_hpa)
+    /// Same as [Self::start_continuous_measurement], but `None` disables the
+    /// pressure compensation instead of the magic `0` value. The valid range
+    /// for the ambient pressure is `700..=1400` HPa.
+    pub fn start_continuous_measurement_with_pressure(
+        &mut self,
+        ambient_pressure_hpa: Option<NonZeroU16>,
+    ) -> Result<(), Error<E>> {
+        self.inner().start_continuous_measurement(
+            ambient_pressure_hpa.map_or(AMBIENT_PRESSURE_DISABLE_COMPENSATION, NonZeroU16::get),
+        )
     }
 
     /// Stops the continuous measurement of the SCD30.
     pub fn stop_continuous_measurement(&mut self) -> Result<(), Error<E>> {
-        self.write_command(STOP_CONTINUOUS_MEASUREMENT)
+        self.inner().stop_continuous_measurement()
     }
 
     /// Sets the interval used by the SCD30 sensor to measure in continuous
@@ -99,19 +231,29 @@ where
     ///
     /// The valid range is `2..=1800` seconds
     pub fn set_measurement_interval(&mut self, interval_seconds: u16) -> Result<(), Error<E>> {
-        if !MEASUREMENT_INTERVAL_RANGE.contains(&interval_seconds) {
-            return Err(Error::InvalidInput);
-        }
-
-        self.write_command_with_data(GET_SET_MEASUREMENT_INTERVAL, interval_seconds)
+        self.inner().set_measurement_interval(interval_seconds)
     }
 
     /// Retrieve the configured measurement interval
     pub fn get_measurement_interval(&mut self) -> Result<u16, Error<E>> {
-        let mut buf = [0; 3];
-        self.command_with_response(GET_SET_MEASUREMENT_INTERVAL, &mut buf)?;
+        self.inner().get_measurement_interval()
+    }
 
-        Ok(u16::from_be_bytes([buf[0], buf[1]]))
+    /// Set the measurement interval like [Self::set_measurement_interval].
+    /// The interval must be a whole number of seconds in the range
+    /// `2..=1800` seconds.
+    pub fn set_measurement_interval_duration(
+        &mut self,
+        interval: Duration,
+    ) -> Result<(), Error<E>> {
+        let seconds = encode_measurement_interval(interval)?;
+        self.set_measurement_interval(seconds)
+    }
+
+    /// Retrieve the configured measurement interval as a [Duration]
+    pub fn get_measurement_interval_duration(&mut self) -> Result<Duration, Error<E>> {
+        let seconds = self.get_measurement_interval()?;
+        Ok(Duration::from_secs(seconds.into()))
     }
 
     /// Data ready command is used to determine if a measurement can be read
@@ -125,11 +267,7 @@ where
     /// It is recommended to use data ready status byte before
     /// readout of the measurement values.
     pub fn data_ready(&mut self) -> Result<bool, Error<E>> {
-        let mut buf = [0; 3];
-        self.command_with_response(GET_DATA_READY_STATUS, &mut buf)?;
-
-        let val = u16::from_be_bytes([buf[0], buf[1]]);
-        Ok(val == 1)
+        self.inner().data_ready()
     }
 
     /// When new measurement data is available it can be read out with the
@@ -137,10 +275,22 @@ where
     /// delay of > 3ms following the write sequence. Make sure that the
     /// measurement is completed by reading the data ready status bit
     /// before read out.
+    ///
+    /// Returns `Error::NoData` if the sensor responds with a frame of zeros,
+    /// e.g. right after a change of its operating mode.
     pub fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
-        let mut buf = [0; 18];
-        self.command_with_response(READ_MEASUREMENT, &mut buf)?;
-        Ok(decode_measurement_data(buf))
+        self.inner().read_measurement()
+    }
+
+    /// Same as [Self::read_measurement], but the response is received into
+    /// the caller-provided buffer instead of a buffer on the stack, e.g. for
+    /// HALs that require the DMA buffers to be placed in specific memory
+    /// regions.
+    pub fn read_measurement_with_buffer(
+        &mut self,
+        buf: &mut [u8; 18],
+    ) -> Result<Measurement, Error<E>> {
+        self.inner().read_measurement_with_buffer(buf)
     }
 
     /// Continuous automatic self-calibration can be (de-)activated with the
@@ -169,16 +319,12 @@ where
     /// down while ASC is activated SCD30 will continue with automatic
     /// self-calibration after repowering without sending the command.
     pub fn enable_automatic_self_calibration(&mut self, enable: bool) -> Result<(), Error<E>> {
-        self.write_command_with_data(MANAGE_AUTOMATIC_SELF_CALIBRATION, enable as u16)
+        self.inner().enable_automatic_self_calibration(enable)
     }
 
     /// Check if the automatic self calibration algorithm is enabled
     pub fn get_automatic_self_calibration(&mut self) -> Result<bool, Error<E>> {
-        let mut buf = [0; 3];
-        self.command_with_response(MANAGE_AUTOMATIC_SELF_CALIBRATION, &mut buf)?;
-
-        let raw_status = u16::from_be_bytes([buf[0], buf[1]]);
-        Ok(raw_status != 0)
+        self.inner().get_automatic_self_calibration()
     }
 
     /// Forced recalibration (FRC) is used to compensate for sensor drifts when
@@ -198,12 +344,11 @@ where
     ///
     ///  After repowering the sensor, the command will return the standard
     /// reference value of 400 ppm.
+    ///
+    /// Returns `Error::NotAllowed` if the continuous measurement is not
+    /// running.
     pub fn set_forced_recalibration_value(&mut self, ppm: u16) -> Result<(), Error<E>> {
-        if !FRC_PPM_RANGE.contains(&ppm) {
-            return Err(Error::InvalidInput);
-        }
-
-        self.write_command_with_data(SET_FORCED_RECALIBRATION_VALUE, ppm)
+        self.inner().set_forced_recalibration_value(ppm)
     }
 
     /// The on-board RH/T sensor is influenced by thermal self-heating of
@@ -219,15 +364,47 @@ where
     ///
     /// Unit: C * 100 => one tick corresponds to 0.01 degrees Celsius
     pub fn set_temperature_offset(&mut self, offset: u16) -> Result<(), Error<E>> {
-        self.write_command_with_data(GET_SET_TEMPERATURE_OFFSET, offset)
+        self.inner().set_temperature_offset(offset)
     }
 
     /// Retrieve the configured temperature offset
     pub fn get_temperature_offset(&mut self) -> Result<u16, Error<E>> {
-        let mut buf = [0; 3];
-        self.command_with_response(GET_SET_TEMPERATURE_OFFSET, &mut buf)?;
+        self.inner().get_temperature_offset()
+    }
 
-        Ok(u16::from_be_bytes([buf[0], buf[1]]))
+    /// Adjust the temperature offset by `delta` degrees Celsius, e.g. while
+    /// tuning it iteratively in the field. The current offset is read from
+    /// the sensor, and the new one is rounded to ticks of 0.01 degrees
+    /// Celsius and clamped to the range supported by the sensor.
+    ///
+    /// Returns the new offset in degrees Celsius, or `Error::InvalidInput`
+    /// if `delta` is not finite.
+    pub fn adjust_temperature_offset(&mut self, delta: f32) -> Result<f32, Error<E>> {
+        self.inner().adjust_temperature_offset(delta)
+    }
+
+    /// Determine and apply the temperature offset compensating the
+    /// self-heating of the sensor in its final enclosure:
+    ///
+    /// `offset = T_scd30 - T_reference + offset_previous`
+    ///
+    /// The reported temperature already accounts for the currently active
+    /// offset, which is read back from the sensor and added to the result.
+    /// The sensor and the reference are sampled `samples` times and the
+    /// means are used for the calculation. The new offset is rounded to
+    /// ticks of 0.01 degrees Celsius and is saved in non-volatile memory.
+    ///
+    /// The continuous measurement must be running and the sensor should be
+    /// in thermal equilibrium before the tuning. Returns `Error::NotAllowed`
+    /// if the measurement is not running, `Error::InvalidInput` if `samples`
+    /// is zero and `Error::CalibrationFailed` if the calculated offset is out
+    /// of the range supported by the sensor.
+    pub fn tune_temperature_offset<R: ReferenceTemperature>(
+        &mut self,
+        reference: &mut R,
+        samples: u16,
+    ) -> Result<TemperatureOffsetReport, Error<E>> {
+        self.inner().tune_temperature_offset(reference, samples)
     }
 
     /// Measurements of CO2 concentration based on the NDIR principle are
@@ -239,23 +416,59 @@ where
     /// Altitude value is saved in non-volatile memory. The last set value
     /// will be used for altitude compensation after repowering.
     pub fn set_altitude_compensation(&mut self, altitude: u16) -> Result<(), Error<E>> {
-        self.write_command_with_data(GET_SET_ALTITUDE_COMPENSATION, altitude)
+        self.inner().set_altitude_compensation(altitude)
     }
 
     // Read the configured altitude compensation value
     pub fn get_altitude_compensation(&mut self) -> Result<u16, Error<E>> {
-        let mut buf = [0; 3];
-        self.command_with_response(GET_SET_ALTITUDE_COMPENSATION, &mut buf)?;
-        Ok(u16::from_be_bytes([buf[0], buf[1]]))
+        self.inner().get_altitude_compensation()
+    }
+
+    /// Read the configuration of the sensor and check it for inconsistent
+    /// or suspicious values, e.g. a zero temperature offset of a sensor
+    /// mounted in an enclosure, or a measurement interval other than the
+    /// 2 seconds the ASC is designed for. `enclosed` tells if the sensor is
+    /// mounted in an enclosure.
+    ///
+    /// The ambient pressure is taken from the last start of the continuous
+    /// measurement through the driver, because the sensor does not report it.
+    pub fn validate_configuration(
+        &mut self,
+        enclosed: bool,
+    ) -> Result<ConfigurationReport, Error<E>> {
+        self.inner().validate_configuration(enclosed)
+    }
+
+    /// Read the firmware version of the SCD30 module like
+    /// [Self::read_firmware_version], but as a [FirmwareVersion], which
+    /// can be compared and displayed
+    pub fn get_firmware_version(&mut self) -> Result<FirmwareVersion, Error<E>> {
+        self.read_firmware_version().map(FirmwareVersion::from)
     }
 
     /// Following command can be used to read out the firmware version of
     /// SCD30 module. The returned value is in the format `(Major, Minor)`
     pub fn read_firmware_version(&mut self) -> Result<(u8, u8), Error<E>> {
-        let mut buf = [0; 3];
-        self.command_with_response(READ_FIRMWARE_VERSION, &mut buf)?;
+        self.inner().read_firmware_version()
+    }
 
-        Ok((buf[0], buf[1]))
+    /// Collect the details of the sensor and the counters of the driver into
+    /// one [Diagnostics] dump, e.g. to attach it to a support ticket. The
+    /// continuous measurement is not interrupted.
+    pub fn diagnostics(&mut self) -> Diagnostics {
+        self.inner().diagnostics()
+    }
+
+    /// Execute a [SensorCommand], e.g. one added by a newer firmware of the
+    /// sensor, and decode its response. The command is sent like the
+    /// built-in ones, and the CRC of the response is verified. Returns
+    /// `Error::InvalidInput` if the command expects more than
+    /// [MAX_RESPONSE_WORDS] response words.
+    pub fn execute<C: SensorCommand + ?Sized>(
+        &mut self,
+        command: &C,
+    ) -> Result<C::Response, Error<E>> {
+        self.inner().execute(command)
     }
 
     /// The SCD30 provides a soft reset mechanism that forces the sensor into
@@ -271,8 +484,546 @@ where
     /// The sensor is able to receive the command at any time, regardless of
     /// its internal state.
     pub fn soft_reset(&mut self) -> Result<(), Error<E>> {
+        self.inner().soft_reset()
+    }
+
+    /// Perform a soft reset and restore the configuration written by this
+    /// driver: the measurement interval, the temperature offset, the altitude
+    /// and the ASC state are read back after the boot delay and written again
+    /// if the sensor reports different values. The continuous measurement is
+    /// restarted with the last ambient pressure if it was running.
+    ///
+    /// The settings not written by this driver are left as stored by the
+    /// sensor. See [Self::settings].
+    pub fn soft_reset_and_restore(&mut self) -> Result<(), Error<E>> {
+        self.inner().soft_reset_and_restore()
+    }
+
+    /// The settings written to the sensor by this driver, which are
+    /// verified and restored by [Self::soft_reset_and_restore]
+    pub fn settings(&self) -> &Scd30Settings {
+        &self.inner.settings
+    }
+
+    fn inner(&mut self) -> &mut Inner<dyn Transport<Error = E> + '_> {
+        &mut self.inner
+    }
+}
+
+/// Starts the continuous measurement without ambient pressure compensation
+impl<T, E> Co2Sensor for Scd30<T>
+where
+    T: Transport<Error = E>,
+{
+    type Error = E;
+
+    fn start_measurement(&mut self) -> Result<(), Error<E>> {
+        self.start_continuous_measurement(AMBIENT_PRESSURE_DISABLE_COMPENSATION)
+    }
+
+    fn stop_measurement(&mut self) -> Result<(), Error<E>> {
+        self.stop_continuous_measurement()
+    }
+
+    fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        self.inner().data_ready()
+    }
+
+    fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.inner().read_measurement()
+    }
+}
+
+/// Identifies the sensor by its firmware version and restores the settings
+/// like [Scd30::soft_reset_and_restore]
+impl<T, E> Reattach for Scd30<T>
+where
+    T: Transport<Error = E>,
+{
+    fn reattach(&mut self) -> Result<(), Error<E>> {
+        self.inner().reattach()
+    }
+}
+
+// Same layout as `Scd4x` in the sibling module: the command logic is
+// implemented for `Inner<dyn Transport>` only, so the drivers for different
+// buses share a single copy of it.
+struct Inner<T: ?Sized> {
+    measurement_started: bool,
+    state_check: bool,
+    compensation_check: bool,
+    correction: Option<MeasurementCorrection>,
+    settings: Scd30Settings,
+    verify_crc: bool,
+    crc_failures: u8,
+    crc_recovery_threshold: u8,
+    counters: DiagnosticCounters,
+    last_error: Option<ErrorKind>,
+    transport: T,
+}
+
+impl<E> Inner<dyn Transport<Error = E> + '_> {
+    fn check_is_measuring(&self) -> Result<(), Error<E>> {
+        if self.state_check && !self.measurement_started {
+            return Err(Error::NotAllowed);
+        }
+
+        Ok(())
+    }
+
+    fn read_response(&mut self, read_buf: &mut [u8]) -> Result<(), Error<E>> {
+        let result = read_frame(&mut self.transport, read_buf, self.verify_crc);
+        if let Err(e) = &result {
+            self.record_error(e);
+        }
+
+        match &result {
+            Ok(()) => self.crc_failures = 0,
+            Err(Error::CRC(_)) => self.on_crc_failure()?,
+            Err(_) => {}
+        }
+
+        result
+    }
+
+    fn on_crc_failure(&mut self) -> Result<(), Error<E>> {
+        self.crc_failures = self.crc_failures.saturating_add(1);
+        if self.crc_recovery_threshold == 0 || self.crc_failures < self.crc_recovery_threshold {
+            return Ok(());
+        }
+
+        warn!(
+            "{} consecutive CRC failures, recovering the sensor",
+            self.crc_failures
+        );
+        self.crc_failures = 0;
+        self.counters.recoveries = self.counters.recoveries.saturating_add(1);
+
+        let was_running = self.measurement_started;
+        self.soft_reset()?;
+
+        let check = core::mem::replace(&mut self.compensation_check, false);
+        let result = self.rewrite_settings(was_running);
+        self.compensation_check = check;
+        result?;
+
+        Err(Error::Recovered)
+    }
+
+    // The responses of the sensor can't be trusted after repeated CRC
+    // failures, so unlike `restore_settings()` the settings are written
+    // without reading them back first
+    fn rewrite_settings(&mut self, was_running: bool) -> Result<(), Error<E>> {
+        let settings = self.settings;
+
+        if let Some(interval) = settings.measurement_interval {
+            self.set_measurement_interval(interval)?;
+        }
+        if let Some(offset) = settings.temperature_offset {
+            self.set_temperature_offset(offset)?;
+        }
+        if let Some(altitude) = settings.altitude {
+            self.set_altitude_compensation(altitude)?;
+        }
+        if let Some(enabled) = settings.automatic_self_calibration {
+            self.enable_automatic_self_calibration(enabled)?;
+        }
+
+        if was_running {
+            let pressure = settings
+                .ambient_pressure
+                .unwrap_or(AMBIENT_PRESSURE_DISABLE_COMPENSATION);
+            self.start_continuous_measurement(pressure)?;
+        }
+
+        Ok(())
+    }
+
+    fn record_error(&mut self, error: &Error<E>) {
+        self.last_error = Some(self.counters.record(error));
+    }
+
+    // Every command must be followed by a delay before the next transfer,
+    // otherwise back-to-back commands may not be acknowledged
+    fn send(&mut self, frame: &[u8]) -> Result<(), Error<E>> {
+        if let Err(e) = write_frame(&mut self.transport, frame) {
+            self.record_error(&e);
+            return Err(e);
+        }
+
+        self.transport.delay_ms(WRITE_DELAY_MILLIS);
+        Ok(())
+    }
+
+    fn write_command(&mut self, cmd: Command) -> Result<(), Error<E>> {
+        self.send(&cmd.prepare())
+    }
+
+    fn write_command_with_data(&mut self, cmd: Command, data: u16) -> Result<(), Error<E>> {
+        self.send(&cmd.prepare_with_data(data))
+    }
+
+    fn command_with_response(&mut self, cmd: Command, read_buf: &mut [u8]) -> Result<(), Error<E>> {
+        self.write_command(cmd)?;
+        self.read_response(read_buf)
+    }
+
+    fn start_continuous_measurement(&mut self, ambient_pressure_hpa: u16) -> Result<(), Error<E>> {
+        if !AMBIENT_PRESSURE_RANGE_HPA.contains(&ambient_pressure_hpa)
+            && AMBIENT_PRESSURE_DISABLE_COMPENSATION != ambient_pressure_hpa
+        {
+            return Err(Error::InvalidInput);
+        }
+
+        let conflict = ambient_pressure_hpa != AMBIENT_PRESSURE_DISABLE_COMPENSATION
+            && self.settings.altitude.is_some();
+        self.check_compensation_conflict(conflict)?;
+
+        self.write_command_with_data(START_CONTINUOUS_MEASUREMENT, ambient_pressure_hpa)?;
+        self.measurement_started = true;
+        self.settings.ambient_pressure = Some(ambient_pressure_hpa);
+        Ok(())
+    }
+
+    fn stop_continuous_measurement(&mut self) -> Result<(), Error<E>> {
+        self.write_command(STOP_CONTINUOUS_MEASUREMENT)?;
+        self.measurement_started = false;
+        Ok(())
+    }
+
+    fn set_measurement_interval(&mut self, interval_seconds: u16) -> Result<(), Error<E>> {
+        if !MEASUREMENT_INTERVAL_RANGE.contains(&interval_seconds) {
+            return Err(Error::InvalidInput);
+        }
+
+        self.write_command_with_data(GET_SET_MEASUREMENT_INTERVAL, interval_seconds)?;
+        self.settings.measurement_interval = Some(interval_seconds);
+        Ok(())
+    }
+
+    fn get_measurement_interval(&mut self) -> Result<u16, Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(GET_SET_MEASUREMENT_INTERVAL, &mut buf)?;
+
+        Ok(u16::from_be_bytes([buf[0], buf[1]]))
+    }
+
+    fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(GET_DATA_READY_STATUS, &mut buf)?;
+
+        let ready = u16::from_be_bytes([buf[0], buf[1]]) == 1;
+        self.measurement_started |= ready;
+        Ok(ready)
+    }
+
+    fn read_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        self.read_measurement_with_buffer(&mut [0; 18])
+    }
+
+    fn read_measurement_with_buffer(
+        &mut self,
+        buf: &mut [u8; 18],
+    ) -> Result<Measurement, Error<E>> {
+        self.command_with_response(READ_MEASUREMENT, buf)?;
+        if is_all_zero(buf) {
+            debug!("the sensor responded with a measurement frame of zeros");
+            return Err(Error::NoData);
+        }
+
+        self.counters.measurements = self.counters.measurements.saturating_add(1);
+
+        let measurement = decode_measurement_data(*buf);
+        Ok(match &self.correction {
+            Some(correction) => correction.apply(measurement),
+            None => measurement,
+        })
+    }
+
+    fn enable_automatic_self_calibration(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.write_command_with_data(MANAGE_AUTOMATIC_SELF_CALIBRATION, enable as u16)?;
+        self.settings.automatic_self_calibration = Some(enable);
+        Ok(())
+    }
+
+    fn get_automatic_self_calibration(&mut self) -> Result<bool, Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(MANAGE_AUTOMATIC_SELF_CALIBRATION, &mut buf)?;
+
+        let raw_status = u16::from_be_bytes([buf[0], buf[1]]);
+        Ok(raw_status != 0)
+    }
+
+    fn set_forced_recalibration_value(&mut self, ppm: u16) -> Result<(), Error<E>> {
+        self.check_is_measuring()?;
+        if !FRC_PPM_RANGE.contains(&ppm) {
+            return Err(Error::InvalidInput);
+        }
+
+        self.write_command_with_data(SET_FORCED_RECALIBRATION_VALUE, ppm)
+    }
+
+    fn set_temperature_offset(&mut self, offset: u16) -> Result<(), Error<E>> {
+        self.write_command_with_data(GET_SET_TEMPERATURE_OFFSET, offset)?;
+        self.settings.temperature_offset = Some(offset);
+        Ok(())
+    }
+
+    fn get_temperature_offset(&mut self) -> Result<u16, Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(GET_SET_TEMPERATURE_OFFSET, &mut buf)?;
+
+        Ok(u16::from_be_bytes([buf[0], buf[1]]))
+    }
+
+    fn adjust_temperature_offset(&mut self, delta: f32) -> Result<f32, Error<E>> {
+        if !delta.is_finite() {
+            return Err(Error::InvalidInput);
+        }
+
+        let ticks = self.get_temperature_offset()? as f32 + delta * 100.0;
+        let ticks = (ticks.clamp(0.0, u16::MAX as f32) + 0.5) as u16;
+        self.set_temperature_offset(ticks)?;
+
+        Ok(ticks as f32 / 100.0)
+    }
+
+    fn tune_temperature_offset<R: ReferenceTemperature>(
+        &mut self,
+        reference: &mut R,
+        samples: u16,
+    ) -> Result<TemperatureOffsetReport, Error<E>> {
+        self.check_is_measuring()?;
+        if samples == 0 {
+            return Err(Error::InvalidInput);
+        }
+
+        let previous_offset = self.get_temperature_offset()? as f32 / 100.0;
+
+        let mut sensor_sum = 0.0;
+        let mut reference_sum = 0.0;
+        for _ in 0..samples {
+            sensor_sum += self.wait_for_measurement()?.temperature;
+            reference_sum += reference.temperature();
+        }
+
+        let sensor_temperature = sensor_sum / samples as f32;
+        let reference_temperature = reference_sum / samples as f32;
+        let new_offset = sensor_temperature - reference_temperature + previous_offset;
+
+        let ticks = new_offset * 100.0;
+        if !(0.0..=u16::MAX as f32).contains(&ticks) {
+            return Err(Error::CalibrationFailed);
+        }
+
+        let ticks = (ticks + 0.5) as u16;
+        self.set_temperature_offset(ticks)?;
+
+        Ok(TemperatureOffsetReport {
+            previous_offset,
+            new_offset: ticks as f32 / 100.0,
+            sensor_temperature,
+            reference_temperature,
+        })
+    }
+
+    fn wait_for_measurement(&mut self) -> Result<Measurement, Error<E>> {
+        loop {
+            while !self.data_ready()? {
+                self.transport.delay_ms(DATA_READY_POLL_MILLIS);
+            }
+
+            // The sensor may report a frame of zeros right after the start
+            match self.read_measurement() {
+                Err(Error::NoData) => self.transport.delay_ms(DATA_READY_POLL_MILLIS),
+                result => return result,
+            }
+        }
+    }
+
+    fn set_altitude_compensation(&mut self, altitude: u16) -> Result<(), Error<E>> {
+        self.check_compensation_conflict(self.settings.has_ambient_pressure())?;
+        self.write_command_with_data(GET_SET_ALTITUDE_COMPENSATION, altitude)?;
+        self.settings.altitude = Some(altitude);
+        Ok(())
+    }
+
+    fn get_altitude_compensation(&mut self) -> Result<u16, Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(GET_SET_ALTITUDE_COMPENSATION, &mut buf)?;
+        Ok(u16::from_be_bytes([buf[0], buf[1]]))
+    }
+
+    fn validate_configuration(&mut self, enclosed: bool) -> Result<ConfigurationReport, Error<E>> {
+        let snapshot = self.configuration_snapshot()?;
+
+        Ok(snapshot.validate(enclosed))
+    }
+
+    fn configuration_snapshot(&mut self) -> Result<ConfigurationSnapshot, Error<E>> {
+        Ok(ConfigurationSnapshot {
+            temperature_offset: self.get_temperature_offset()? as f32 / 100.0,
+            altitude: self.get_altitude_compensation()?,
+            ambient_pressure: self
+                .settings
+                .ambient_pressure
+                .filter(|&pressure| pressure != AMBIENT_PRESSURE_DISABLE_COMPENSATION),
+            automatic_self_calibration: self.get_automatic_self_calibration()?,
+            automatic_self_calibration_target: None,
+            measurement_interval: Some(self.get_measurement_interval()?),
+        })
+    }
+
+    fn read_firmware_version(&mut self) -> Result<(u8, u8), Error<E>> {
+        let mut buf = [0; 3];
+        self.command_with_response(READ_FIRMWARE_VERSION, &mut buf)?;
+
+        Ok((buf[0], buf[1]))
+    }
+
+    fn diagnostics(&mut self) -> Diagnostics {
+        let mode = match self.measurement_started {
+            true => MeasurementMode::Periodic,
+            false => MeasurementMode::Idle,
+        };
+
+        let mut diagnostics = Diagnostics::new(mode, self.counters, self.last_error);
+        diagnostics.variant = Some(SensorVariant::Scd30);
+        diagnostics.firmware_version = self.read_firmware_version().map(FirmwareVersion::from).ok();
+        diagnostics.settings = self.configuration_snapshot().ok();
+        diagnostics
+    }
+
+    fn execute<C: SensorCommand + ?Sized>(&mut self, command: &C) -> Result<C::Response, Error<E>> {
+        let len = command.response_words();
+        if len > MAX_RESPONSE_WORDS {
+            return Err(Error::InvalidInput);
+        }
+
+        match command.argument() {
+            Some(data) => self.send(&opcode_with_data_into_payload(command.op_code(), data))?,
+            None => self.send(&command.op_code().to_be_bytes())?,
+        }
+
+        // The delay after the command was already waited for by `send()`
+        let remaining_us = command
+            .exec_time_us()
+            .saturating_sub(WRITE_DELAY_MILLIS * 1_000);
+        if remaining_us > 0 {
+            self.transport.delay_us(remaining_us);
+        }
+
+        let mut words = [0; MAX_RESPONSE_WORDS];
+        if len > 0 {
+            let mut buf = [0; MAX_RESPONSE_WORDS * 3];
+            self.read_response(&mut buf[..len * 3])?;
+            words_from_chunks(&buf, &mut words[..len]);
+        }
+
+        Ok(command.decode(&words[..len]))
+    }
+
+    fn soft_reset(&mut self) -> Result<(), Error<E>> {
         self.write_command(SOFT_RESET)?;
-        self.delay.delay_ms(BOOT_DELAY_MILLIS);
+        self.transport.delay_ms(BOOT_DELAY_MILLIS);
+        Ok(())
+    }
+
+    fn soft_reset_and_restore(&mut self) -> Result<(), Error<E>> {
+        let was_running = self.measurement_started;
+        self.soft_reset()?;
+        self.restore_accepted_settings(was_running)
+    }
+
+    fn reattach(&mut self) -> Result<(), Error<E>> {
+        // Identify the sensor before writing to it
+        self.read_firmware_version()?;
+        self.crc_failures = 0;
+
+        let was_running = self.measurement_started;
+        self.restore_accepted_settings(was_running)
+    }
+
+    // The settings were accepted when they were first written
+    fn restore_accepted_settings(&mut self, was_running: bool) -> Result<(), Error<E>> {
+        let check = core::mem::replace(&mut self.compensation_check, false);
+        let result = self.restore_and_resume(was_running);
+        self.compensation_check = check;
+        result
+    }
+
+    fn restore_and_resume(&mut self, was_running: bool) -> Result<(), Error<E>> {
+        self.restore_settings()?;
+
+        if was_running {
+            let pressure = self
+                .settings
+                .ambient_pressure
+                .unwrap_or(AMBIENT_PRESSURE_DISABLE_COMPENSATION);
+            self.start_continuous_measurement(pressure)?;
+        }
+
         Ok(())
     }
+
+    // The sensor ignores the altitude once an ambient pressure is given, so
+    // setting both is most likely a configuration mistake
+    fn check_compensation_conflict(&self, conflict: bool) -> Result<(), Error<E>> {
+        if conflict {
+            warn!("both the altitude and the ambient pressure compensation are set");
+            if self.compensation_check {
+                return Err(Error::CompensationConflict);
+            }
+        }
+
+        Ok(())
+    }
+
+    // The settings are stored in the non-volatile memory of the sensor, so
+    // they are written only when they differ in order to spare it
+    fn restore_settings(&mut self) -> Result<(), Error<E>> {
+        let settings = self.settings;
+
+        if let Some(interval) = settings.measurement_interval {
+            if self.get_measurement_interval()? != interval {
+                self.set_measurement_interval(interval)?;
+            }
+        }
+
+        if let Some(offset) = settings.temperature_offset {
+            if self.get_temperature_offset()? != offset {
+                self.set_temperature_offset(offset)?;
+            }
+        }
+
+        if let Some(altitude) = settings.altitude {
+            if self.get_altitude_compensation()? != altitude {
+                self.set_altitude_compensation(altitude)?;
+            }
+        }
+
+        if let Some(enabled) = settings.automatic_self_calibration {
+            if self.get_automatic_self_calibration()? != enabled {
+                self.enable_automatic_self_calibration(enabled)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn wait_until_booted(&mut self) -> Result<(), Error<E>> {
+        self.transport.delay_ms(BOOT_DELAY_MILLIS);
+
+        let mut attempt = 1;
+        loop {
+            match self.read_firmware_version() {
+                Ok(_) => return Ok(()),
+                Err(e) if attempt >= POWER_ON_ATTEMPTS => return Err(e),
+                Err(_) => {
+                    attempt += 1;
+                    self.transport.delay_ms(POWER_ON_RETRY_DELAY_MILLIS);
+                }
+            }
+        }
+    }
 }
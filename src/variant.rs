@@ -0,0 +1,159 @@
+use crate::measurement::Accuracy;
+
+/// The sensor models supported by the library
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SensorVariant {
+    /// Sensirion SCD30
+    Scd30,
+
+    /// Sensirion SCD40
+    Scd40,
+
+    /// Sensirion SCD41
+    Scd41,
+
+    /// Sensirion SCD43
+    Scd43,
+}
+
+/// The response of the SCD4x get sensor variant command. Besides the model
+/// of the sensor, the word carries a revision of the sensor firmware
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct SensorVariantInfo {
+    /// The raw response word
+    pub raw: u16,
+}
+
+impl SensorVariantInfo {
+    /// The sensor model encoded in bits 15:12. `None` if the model is
+    /// not a known SCD4x sensor
+    pub fn variant(&self) -> Option<SensorVariant> {
+        match self.raw >> 12 {
+            0b0000 => Some(SensorVariant::Scd40),
+            0b0001 => Some(SensorVariant::Scd41),
+            0b0101 => Some(SensorVariant::Scd43),
+            _ => None,
+        }
+    }
+
+    /// The firmware revision encoded in bits 11:0. The datasheet does not
+    /// document the format, but newer firmware reports higher values, so
+    /// they can be compared to gate workarounds on the sensor firmware
+    pub fn firmware_revision(&self) -> u16 {
+        self.raw & 0x0FFF
+    }
+}
+
+/// The firmware version of the SCD30 in the format `Major.Minor`, e.g. `3.66`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FirmwareVersion {
+    /// The major version
+    pub major: u8,
+
+    /// The minor version
+    pub minor: u8,
+}
+
+impl FirmwareVersion {
+    /// Create a new firmware version
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+
+    /// Whether this version is the same as or newer than `major.minor`.
+    /// Useful to enable the behavior that depends on the firmware, e.g.
+    /// `version.is_at_least(3, 66)`
+    pub fn is_at_least(&self, major: u8, minor: u8) -> bool {
+        *self >= Self::new(major, minor)
+    }
+}
+
+impl From<(u8, u8)> for FirmwareVersion {
+    fn from((major, minor): (u8, u8)) -> Self {
+        Self::new(major, minor)
+    }
+}
+
+impl core::fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for FirmwareVersion {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        ufmt::uwrite!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// The sensors found on the I2C bus by `probe_bus()`
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProbeReport {
+    /// An SCD30 responded at its address
+    pub scd30: bool,
+
+    /// An SCD4x responded at its address
+    pub scd4x: bool,
+
+    /// The model of the SCD4x. It is not available while the sensor is
+    /// measuring, because the sensor rejects the variant command then
+    pub scd4x_variant: Option<SensorVariant>,
+}
+
+impl ProbeReport {
+    /// Whether none of the sensors responded
+    pub fn is_empty(&self) -> bool {
+        !self.scd30 && !self.scd4x
+    }
+}
+
+impl SensorVariant {
+    /// The datasheet accuracy of the measurements of this sensor model
+    pub const fn accuracy(self) -> Accuracy {
+        match self {
+            SensorVariant::Scd30 => Accuracy::SCD30,
+            SensorVariant::Scd40 => Accuracy::SCD40,
+            SensorVariant::Scd41 => Accuracy::SCD41,
+            SensorVariant::Scd43 => Accuracy::SCD43,
+        }
+    }
+
+    /// The name of the sensor model, e.g. `SCD41`
+    pub const fn name(self) -> &'static str {
+        match self {
+            SensorVariant::Scd30 => "SCD30",
+            SensorVariant::Scd40 => "SCD40",
+            SensorVariant::Scd41 => "SCD41",
+            SensorVariant::Scd43 => "SCD43",
+        }
+    }
+}
+
+impl core::fmt::Display for SensorVariant {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for SensorVariant {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,180 @@
+use crate::error::{CrcError, Error};
+use crate::wire::crc::crc8;
+
+// The responses consist of 3-byte chunks, so the length of the read buffer
+// must be a multiple of 3
+pub(crate) fn check_chunked_with_len3<E>(buf: &[u8]) -> Result<(), Error<E>> {
+    if !buf.len().is_multiple_of(3) {
+        return Err(Error::InvalidBufferLength);
+    }
+
+    Ok(())
+}
+
+/// Verify the CRC of a response consisting of 3-byte chunks: two data
+/// bytes followed by their CRC. Returns the first mismatching word
+pub fn crc8_verify_chunked_3(data: &[u8]) -> Result<(), CrcError> {
+    for (word, chunk) in data.chunks_exact(3).enumerate() {
+        let expected = crc8(&chunk[..2]);
+        if expected != chunk[2] {
+            return Err(CrcError {
+                word,
+                received: chunk[2],
+                expected,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check whether all data words of a response consisting of 3-byte chunks
+/// are zero, e.g. a measurement frame sent before the first measurement
+/// was completed. The CRC bytes are not checked
+pub fn is_all_zero(data: &[u8]) -> bool {
+    data.chunks(3)
+        .all(|chunk| chunk.iter().take(2).all(|&byte| byte == 0))
+}
+
+// Copy the data words of a response consisting of 3-byte chunks into
+// `words`, skipping their CRC
+pub(crate) fn words_from_chunks(data: &[u8], words: &mut [u16]) {
+    for (word, chunk) in words.iter_mut().zip(data.chunks_exact(3)) {
+        *word = u16::from_be_bytes([chunk[0], chunk[1]]);
+    }
+}
+
+/// Build the 5-byte frame of a command with an argument: the opcode,
+/// followed by the data word and its CRC
+pub const fn opcode_with_data_into_payload(opcode: u16, data: u16) -> [u8; 5] {
+    let c = opcode.to_be_bytes();
+    let d = data.to_be_bytes();
+
+    let mut buf = [0; 5];
+
+    // Replace with `buf[0..2].copy_from_slice(&c);` when it becomes a `const` function
+    buf[0] = c[0];
+    buf[1] = c[1];
+
+    // Replace with `buf[2..4].copy_from_slice(&d);` when it becomes a `const` function
+    buf[2] = d[0];
+    buf[3] = d[1];
+
+    buf[4] = crc8(&d);
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_buf_len_is_multiple_of_three() {
+        assert_eq!(Ok(()), check_chunked_with_len3::<()>(&[0; 3]));
+        assert_eq!(Ok(()), check_chunked_with_len3::<()>(&[0; 6]));
+        assert_eq!(Ok(()), check_chunked_with_len3::<()>(&[0; 9]));
+    }
+
+    #[test]
+    fn test_is_all_zero() {
+        assert!(is_all_zero(&[0x00, 0x00, 0x81, 0x00, 0x00, 0x81]));
+        // The CRC bytes are ignored
+        assert!(is_all_zero(&[0x00, 0x00, 0xFF]));
+        assert!(!is_all_zero(&[0x00, 0x00, 0x81, 0x00, 0x01, 0xB0]));
+    }
+
+    #[test]
+    fn test_buf_len_is_not_multiple_of_three() {
+        assert_eq!(
+            Err(Error::InvalidBufferLength),
+            check_chunked_with_len3::<()>(&[0; 4])
+        );
+    }
+
+    #[test]
+    fn test_chunked_crc8() {
+        assert_eq!(Ok(()), crc8_verify_chunked_3(&[0xBE, 0xEF, 0x92]));
+        assert_eq!(
+            Ok(()),
+            crc8_verify_chunked_3(&[0xBE, 0xEF, 0x92, 0xBE, 0xEF, 0x92])
+        );
+    }
+
+    #[test]
+    fn test_chunked_crc8_with_wrong_checksum_1() {
+        let expected = CrcError {
+            word: 0,
+            received: 0x90,
+            expected: 0x92,
+        };
+        assert_eq!(Err(expected), crc8_verify_chunked_3(&[0xBE, 0xEF, 0x90]));
+    }
+
+    #[test]
+    fn test_chunked_crc8_with_wrong_checksum_2() {
+        let expected = CrcError {
+            word: 0,
+            received: 0x91,
+            expected: 0x92,
+        };
+        assert_eq!(
+            Err(expected),
+            crc8_verify_chunked_3(&[0xBE, 0xEF, 0x91, 0xBE, 0xEF, 0x92])
+        );
+    }
+
+    #[test]
+    fn test_chunked_crc8_with_wrong_checksum_3() {
+        let expected = CrcError {
+            word: 1,
+            received: 0x91,
+            expected: 0x92,
+        };
+        assert_eq!(
+            Err(expected),
+            crc8_verify_chunked_3(&[0xBE, 0xEF, 0x92, 0xBE, 0xEF, 0x91])
+        );
+    }
+
+    #[test]
+    fn test_opcode_with_data_into_payload() {
+        let result = opcode_with_data_into_payload(0x0102, 0x0304);
+        assert_eq!([0x01, 0x02, 0x03, 0x04, 0x68], result);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_payload_round_trip(opcode in any::<u16>(), data in any::<u16>()) {
+            let payload = opcode_with_data_into_payload(opcode, data);
+            prop_assert_eq!(opcode, u16::from_be_bytes([payload[0], payload[1]]));
+            prop_assert_eq!(data, u16::from_be_bytes([payload[2], payload[3]]));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&payload[2..]));
+        }
+
+        #[test]
+        fn prop_chunked_crc8_detects_corrupted_word(
+            words in proptest::collection::vec(any::<u16>(), 1..16),
+            corrupted in any::<proptest::sample::Index>(),
+            bit in 0..16u32,
+        ) {
+            let mut buf = [0; 48];
+            let buf = &mut buf[..words.len() * 3];
+            for (chunk, word) in buf.chunks_exact_mut(3).zip(&words) {
+                let [msb, lsb] = word.to_be_bytes();
+                chunk.copy_from_slice(&[msb, lsb, crc8(&[msb, lsb])]);
+            }
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(buf));
+
+            // The CRC detects any single bit error in the data word
+            let word = corrupted.index(words.len());
+            let flipped = (words[word] ^ (1 << bit)).to_be_bytes();
+            buf[word * 3..word * 3 + 2].copy_from_slice(&flipped);
+
+            let err = crc8_verify_chunked_3(buf).unwrap_err();
+            prop_assert_eq!(word, err.word);
+            prop_assert_eq!(crc8(&flipped), err.expected);
+        }
+    }
+}
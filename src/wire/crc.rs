@@ -2,6 +2,8 @@ const CRC8_POLY: u8 = 0x31;
 const CRC8_INITIAL: u8 = 0xFF;
 const BYTE_MSB: u8 = 1 << 7;
 
+/// Compute the Sensirion CRC-8 (polynomial 0x31, initial value 0xFF)
+/// of the given bytes
 pub const fn crc8(data: &[u8]) -> u8 {
     let mut crc = CRC8_INITIAL;
 
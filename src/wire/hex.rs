@@ -0,0 +1,80 @@
+use core::fmt::{Debug, Display, Formatter, Result};
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Renders a raw frame as lowercase hex bytes separated by spaces, e.g.
+/// `be ef 92`, without allocating. Useful for logging the exact bytes of a
+/// failing transaction
+#[derive(Copy, Clone)]
+pub struct HexDump<'a>(pub &'a [u8]);
+
+impl Display for HexDump<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for (idx, byte) in self.0.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Debug for HexDump<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// The number of characters needed to render a frame of `len` bytes with
+/// [write_hex]
+pub const fn hex_len(len: usize) -> usize {
+    (len * 3).saturating_sub(1)
+}
+
+/// Render a raw frame into `buf` in the format of [HexDump] and return the
+/// number of bytes written, or `None` if `buf` is shorter than [hex_len]
+pub fn write_hex(frame: &[u8], buf: &mut [u8]) -> Option<usize> {
+    let len = hex_len(frame.len());
+    let out = buf.get_mut(..len)?;
+
+    for (idx, byte) in frame.iter().enumerate() {
+        let pos = idx * 3;
+        if idx > 0 {
+            out[pos - 1] = b' ';
+        }
+        out[pos] = DIGITS[(byte >> 4) as usize];
+        out[pos + 1] = DIGITS[(byte & 0x0F) as usize];
+    }
+
+    Some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_display() {
+        assert_eq!("be ef 92", format!("{}", HexDump(&[0xBE, 0xEF, 0x92])));
+        assert_eq!("00", format!("{:?}", HexDump(&[0x00])));
+        assert_eq!("", format!("{}", HexDump(&[])));
+    }
+
+    #[test]
+    fn test_write_hex() {
+        let frame = [0x01, 0xF4, 0x33, 0xA0];
+        let mut buf = [0u8; 16];
+
+        let len = write_hex(&frame, &mut buf).unwrap();
+        assert_eq!(hex_len(frame.len()), len);
+        assert_eq!(b"01 f4 33 a0", &buf[..len]);
+
+        assert_eq!(Some(0), write_hex(&[], &mut []));
+        assert_eq!(None, write_hex(&frame, &mut buf[..10]));
+    }
+}
@@ -0,0 +1,20 @@
+/// Helpers for building and verifying frames, shared by all sensors. The
+/// framing (16-bit opcodes, 16-bit data words each followed by a CRC) is the
+/// one used by the other Sensirion I2C sensors as well, e.g. SHT4x and SGP4x
+pub mod common;
+
+/// The CRC-8 checksum protecting each data word on the bus. It is the same
+/// checksum as in the `sensirion-i2c` crate, so frames built by either crate
+/// are accepted by the other
+pub mod crc;
+
+/// Rendering of raw frames as hex, e.g. for logging failed transactions
+pub mod hex;
+
+/// Wire format of the SCD30 sensor
+#[cfg(feature = "scd30")]
+pub mod scd30;
+
+/// Wire format of the SCD40 and SCD41 sensors
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+pub mod scd4x;
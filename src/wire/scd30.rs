@@ -0,0 +1,198 @@
+use crate::error::Error;
+use crate::measurement::Measurement;
+use core::ops::Range;
+use core::time::Duration;
+
+// The command set is defined in the public `commands` module and re-exported
+// here, so the wire format of the sensor is available in one place
+pub use crate::commands::scd30::*;
+
+/// The I2C address of the sensor (Section 1.1.1)
+pub const I2C_ADDRESS: u8 = 0x61;
+
+/// How many times the firmware version is read after the boot delay before
+/// giving up on a freshly powered sensor
+pub const POWER_ON_ATTEMPTS: u8 = 5;
+
+/// The delay in milliseconds between the attempts to reach a freshly powered
+/// sensor
+pub const POWER_ON_RETRY_DELAY_MILLIS: u32 = 100;
+
+/// Passing this value as the ambient pressure disables the pressure
+/// compensation (Section 1.4.1)
+pub const AMBIENT_PRESSURE_DISABLE_COMPENSATION: u16 = 0;
+
+/// The accepted ambient pressure compensation values in hPa
+pub const AMBIENT_PRESSURE_RANGE_HPA: Range<u16> = 700..1401;
+
+/// The accepted measurement intervals in seconds (Section 1.4.3)
+pub const MEASUREMENT_INTERVAL_RANGE: Range<u16> = 2..1801;
+
+/// The accepted forced recalibration reference values in PPM
+/// (Section 1.4.6)
+pub const FRC_PPM_RANGE: Range<u16> = 400..2001;
+
+/// Decode the response of [READ_MEASUREMENT]: CO2, temperature and
+/// humidity as big-endian `f32` values, each split into two CRC-protected
+/// words (Section 1.5). The CRC is not verified
+pub fn decode_measurement_data(buf: [u8; 18]) -> Measurement {
+    let co2 = f32::from_be_bytes([buf[0], buf[1], buf[3], buf[4]]);
+    let tmp = f32::from_be_bytes([buf[6], buf[7], buf[9], buf[10]]);
+    let hum = f32::from_be_bytes([buf[12], buf[13], buf[15], buf[16]]);
+
+    Measurement {
+        temperature: tmp,
+        humidity: hum,
+        co2: co2 as u16,
+    }
+}
+
+/// Encode a measurement interval into the argument of
+/// [GET_SET_MEASUREMENT_INTERVAL]. Returns [Error::InvalidInput] if the
+/// interval is not a whole number of seconds or is outside of
+/// [MEASUREMENT_INTERVAL_RANGE]
+pub fn encode_measurement_interval<E>(interval: Duration) -> Result<u16, Error<E>> {
+    if interval.subsec_nanos() != 0 {
+        return Err(Error::InvalidInput);
+    }
+
+    u16::try_from(interval.as_secs())
+        .ok()
+        .filter(|seconds| MEASUREMENT_INTERVAL_RANGE.contains(seconds))
+        .ok_or(Error::InvalidInput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::common::crc8_verify_chunked_3;
+    use crate::wire::crc::crc8;
+    use proptest::prelude::*;
+
+    const F32_TOLERANCE: f32 = 0.05;
+
+    #[test]
+    fn test_encode_measurement_interval() {
+        let encode = encode_measurement_interval::<()>;
+        assert_eq!(Ok(2), encode(Duration::from_secs(2)));
+        assert_eq!(Ok(1800), encode(Duration::from_secs(1800)));
+        assert_eq!(Err(Error::InvalidInput), encode(Duration::from_secs(1)));
+        assert_eq!(Err(Error::InvalidInput), encode(Duration::from_secs(1801)));
+        assert_eq!(
+            Err(Error::InvalidInput),
+            encode(Duration::from_secs(1 << 20))
+        );
+        assert_eq!(
+            Err(Error::InvalidInput),
+            encode(Duration::from_millis(2_500))
+        );
+    }
+
+    #[test]
+    fn test_prepare_command() {
+        assert_eq!([0x00, 0x10], START_CONTINUOUS_MEASUREMENT.prepare());
+    }
+
+    #[test]
+    fn test_prepare_command_with_data() {
+        assert_eq!(
+            [0x54, 0x03, 0x01, 0xF4, 0x33],
+            GET_SET_TEMPERATURE_OFFSET.prepare_with_data(0x01F4)
+        );
+    }
+
+    #[test]
+    fn test_decode_measurement_data() {
+        const EXPECTED_HUMIDITY: f32 = 48.8;
+        const EXPECTED_TEMPERATURE: f32 = 27.2;
+
+        let buf = [
+            0x43, 0xDB, 0xCB, // CO2: MMSB, MLSB, CRC
+            0x8C, 0x2E, 0x8F, // CO2: LMSB, LLSB, CRC
+            0x41, 0xD9, 0x70, // TMP: MMSB, MLSB, CRC
+            0xE7, 0xFF, 0xF5, // TMP: LMSB, LLSB, CRC
+            0x42, 0x43, 0xBF, // RH%: MMSB, MLSB, CRC
+            0x3A, 0x1B, 0x74, // RH%: LMSB, LLSB, CRC
+        ];
+
+        let m = decode_measurement_data(buf);
+        assert_eq!(439, m.co2);
+        assert!(
+            (EXPECTED_HUMIDITY - m.humidity).abs() < F32_TOLERANCE,
+            "Expected: {}; Actual: {}",
+            EXPECTED_HUMIDITY,
+            m.humidity
+        );
+        assert!(
+            (EXPECTED_TEMPERATURE - m.temperature).abs() < F32_TOLERANCE,
+            "Expected: {}; Actual: {}",
+            EXPECTED_TEMPERATURE,
+            m.temperature
+        );
+    }
+
+    fn encode_measurement_data(values: [f32; 3]) -> [u8; 18] {
+        let mut buf = [0; 18];
+        let words = values.iter().flat_map(|v| {
+            let bits = v.to_bits();
+            [(bits >> 16) as u16, bits as u16]
+        });
+
+        for (chunk, word) in buf.chunks_exact_mut(3).zip(words) {
+            let [msb, lsb] = word.to_be_bytes();
+            chunk.copy_from_slice(&[msb, lsb, crc8(&[msb, lsb])]);
+        }
+
+        buf
+    }
+
+    /// Simulate the sensor echoing back a word written by the driver
+    fn echo(payload: [u8; 5]) -> [u8; 3] {
+        [payload[2], payload[3], payload[4]]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_decode_measurement_data(
+            co2 in 0.0f32..40_000.0,
+            temperature in -40.0f32..70.0,
+            humidity in 0.0f32..100.0,
+        ) {
+            let buf = encode_measurement_data([co2, temperature, humidity]);
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&buf));
+
+            let m = decode_measurement_data(buf);
+            prop_assert_eq!(co2 as u16, m.co2);
+            prop_assert_eq!(temperature.to_bits(), m.temperature.to_bits());
+            prop_assert_eq!(humidity.to_bits(), m.humidity.to_bits());
+        }
+
+        #[test]
+        fn prop_encode_decode_ambient_pressure(pressure in AMBIENT_PRESSURE_RANGE_HPA) {
+            let response = echo(START_CONTINUOUS_MEASUREMENT.prepare_with_data(pressure));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+            prop_assert_eq!(pressure, u16::from_be_bytes([response[0], response[1]]));
+        }
+
+        #[test]
+        fn prop_encode_decode_measurement_interval(interval in MEASUREMENT_INTERVAL_RANGE) {
+            let response = echo(GET_SET_MEASUREMENT_INTERVAL.prepare_with_data(interval));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+            prop_assert_eq!(interval, u16::from_be_bytes([response[0], response[1]]));
+        }
+
+        #[test]
+        fn prop_encode_decode_frc_value(ppm in FRC_PPM_RANGE) {
+            let response = echo(SET_FORCED_RECALIBRATION_VALUE.prepare_with_data(ppm));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+            prop_assert_eq!(ppm, u16::from_be_bytes([response[0], response[1]]));
+        }
+
+        #[test]
+        fn prop_encode_decode_altitude(altitude in any::<u16>()) {
+            let response = echo(GET_SET_ALTITUDE_COMPENSATION.prepare_with_data(altitude));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+            prop_assert_eq!(altitude, u16::from_be_bytes([response[0], response[1]]));
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use crate::error::Error;
+use crate::measurement::Measurement;
+use crate::variant::{SensorVariant, SensorVariantInfo};
+use core::ops::Range;
+
+// The command set is defined in the public `commands` module and re-exported
+// here, so the wire format of the sensor is available in one place
+pub use crate::commands::scd4x::*;
+
+/// The I2C address of the sensor
+pub const I2C_ADDRESS: u8 = 0x62;
+
+/// The maximum sensor altitude in meters (Section 3.7.3)
+pub const MAX_ALTITUDE: u16 = 3_000;
+
+/// The maximum temperature offset in degrees Celsius recommended by the
+/// datasheet (Section 3.7.1)
+pub const MAX_TEMPERATURE_OFFSET: f32 = 20.0;
+
+/// The accepted ambient pressure values in hPa (Section 3.7.5)
+pub const AMBIENT_PRESSURE_RANGE_HPA: Range<u16> = 700..1201;
+
+// Constant used in several data conversionssuch as in the temperature offset
+const TWO_P16_M1: f32 = u16::MAX as f32; // `2.pow(16) - 1`
+
+// Constant used in the temperature data conversion
+const TEMP_K1: f32 = 175.0f32;
+
+/// Decode the response of [GET_SERIAL_NUMBER] into a 48-bit serial number
+pub fn decode_serial_number(buf: [u8; 9]) -> u64 {
+    u64::from(buf[0]) << 40
+        | u64::from(buf[1]) << 32
+        | u64::from(buf[3]) << 24
+        | u64::from(buf[4]) << 16
+        | u64::from(buf[6]) << 8
+        | u64::from(buf[7])
+}
+
+/// Decode the response of [GET_SENSOR_VARIANT] into the raw word, which
+/// carries both the model and the firmware revision of the sensor
+/// (Section 3.9.7)
+pub fn decode_sensor_variant_info(buf: [u8; 3]) -> SensorVariantInfo {
+    SensorVariantInfo {
+        raw: u16::from_be_bytes([buf[0], buf[1]]),
+    }
+}
+
+/// Decode the response of [GET_SENSOR_VARIANT]. Returns
+/// [Error::WrongDevice] if the variant is not a known SCD4x sensor
+pub fn decode_sensor_variant<E>(buf: [u8; 3]) -> Result<SensorVariant, Error<E>> {
+    decode_sensor_variant_info(buf)
+        .variant()
+        .ok_or(Error::WrongDevice)
+}
+
+/// Decode the response of [READ_MEASUREMENT] (Section 3.6.2)
+pub fn decode_measurement(buf: [u8; 9]) -> Measurement {
+    Measurement {
+        temperature: decode_temp_measurement(buf[3], buf[4]),
+        humidity: decode_humidity_measurement(buf[6], buf[7]),
+        co2: decode_co2_measurement(buf[0], buf[1]),
+    }
+}
+
+fn decode_temp_measurement(msb: u8, lsb: u8) -> f32 {
+    let raw = u16::from_be_bytes([msb, lsb]);
+    raw as f32 * TEMP_K1 / TWO_P16_M1 - 45.0
+}
+
+fn decode_humidity_measurement(msb: u8, lsb: u8) -> f32 {
+    let raw = u16::from_be_bytes([msb, lsb]);
+    raw as f32 * 100.0 / TWO_P16_M1
+}
+
+fn decode_co2_measurement(msb: u8, lsb: u8) -> u16 {
+    u16::from_be_bytes([msb, lsb])
+}
+
+/// Encode a temperature offset in degrees Celsius into the argument of
+/// [SET_TEMPERATURE_OFFSET]. Returns [Error::InvalidInput] for negative or
+/// non-finite offsets
+pub fn encode_temperature_offset<E>(offset: f32) -> Result<u16, Error<E>> {
+    if !offset.is_finite() || offset.is_sign_negative() {
+        return Err(Error::InvalidInput);
+    }
+
+    Ok((offset * TWO_P16_M1 / TEMP_K1) as u16)
+}
+
+/// Decode the response of [GET_TEMPERATURE_OFFSET] into degrees Celsius
+pub fn decode_temperature_offset(buf: [u8; 3]) -> f32 {
+    let offset = u16::from_be_bytes([buf[0], buf[1]]);
+    offset as f32 * TEMP_K1 / TWO_P16_M1
+}
+
+/// Convert the raw argument of [SET_TEMPERATURE_OFFSET] into degrees
+/// Celsius. Unlike [decode_temperature_offset], the result is in the middle
+/// of the tick, so [encode_temperature_offset] maps it back to the same
+/// ticks despite the truncation
+pub fn temperature_offset_from_ticks(ticks: u16) -> f32 {
+    (ticks as f32 + 0.5) * TEMP_K1 / TWO_P16_M1
+}
+
+/// Round an ambient pressure in hPa, e.g. as reported by a barometer, to the
+/// argument of [SET_AMBIENT_PRESSURE]. Returns [Error::InvalidInput] for
+/// non-finite pressures and for pressures outside of
+/// [AMBIENT_PRESSURE_RANGE_HPA] after the rounding
+pub fn encode_ambient_pressure<E>(pressure: f32) -> Result<u16, Error<E>> {
+    if !pressure.is_finite() || pressure.is_sign_negative() {
+        return Err(Error::InvalidInput);
+    }
+
+    // The conversion saturates, so too large values are rejected below
+    let pressure = (pressure + 0.5) as u16;
+    if !AMBIENT_PRESSURE_RANGE_HPA.contains(&pressure) {
+        return Err(Error::InvalidInput);
+    }
+
+    Ok(pressure)
+}
+
+/// Decode the response of [PERFORM_FORCED_RECALIBRATION] into the applied
+/// correction in PPM. Returns `None` if the recalibration failed
+pub fn decode_frc_status(buf: [u8; 3]) -> Option<i16> {
+    // Section 3.8.1 from the datasheet
+    // A return value of 0xFFFF indicates that the FRC has failed
+    // because the sensor was not operated before sending the command.
+    const FRC_FAILED: u16 = 0xFFFF;
+
+    let result = u16::from_be_bytes([buf[0], buf[1]]);
+    if FRC_FAILED == result {
+        return None;
+    }
+
+    let frc_correction = result as i32 - 0x8000;
+    Some(frc_correction as i16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::common::crc8_verify_chunked_3;
+    use crate::wire::crc::crc8;
+    use proptest::prelude::*;
+
+    const F32_TOLERANCE: f32 = 0.005;
+
+    #[test]
+    fn test_decode_serial_number() {
+        let response = [0xF8, 0x96, 0x31, 0x9F, 0x07, 0xC2, 0x3B, 0xBE, 0x89];
+        let serial_number = decode_serial_number(response);
+        assert_eq!(273_325_796_834_238, serial_number);
+    }
+
+    #[test]
+    fn test_decode_sensor_variant() {
+        assert_eq!(
+            Ok(SensorVariant::Scd40),
+            decode_sensor_variant::<()>([0x04, 0x40, crc8(&[0x04, 0x40])])
+        );
+        assert_eq!(
+            Ok(SensorVariant::Scd41),
+            decode_sensor_variant::<()>([0x14, 0x40, crc8(&[0x14, 0x40])])
+        );
+        assert_eq!(
+            Ok(SensorVariant::Scd43),
+            decode_sensor_variant::<()>([0x54, 0x41, crc8(&[0x54, 0x41])])
+        );
+    }
+
+    #[test]
+    fn test_decode_sensor_variant_info() {
+        let info = decode_sensor_variant_info([0x14, 0x41, crc8(&[0x14, 0x41])]);
+        assert_eq!(0x1441, info.raw);
+        assert_eq!(Some(SensorVariant::Scd41), info.variant());
+        assert_eq!(0x0441, info.firmware_revision());
+    }
+
+    #[test]
+    fn test_decode_sensor_variant_unknown() {
+        assert_eq!(
+            Err(Error::WrongDevice),
+            decode_sensor_variant::<()>([0xF0, 0x00, crc8(&[0xF0, 0x00])])
+        );
+    }
+
+    #[test]
+    fn test_prepare_command() {
+        assert_eq!([0x36, 0x82], GET_SERIAL_NUMBER.prepare());
+    }
+
+    #[test]
+    fn test_prepare_command_with_data() {
+        assert_eq!(
+            [0x24, 0x1D, 0x07, 0xE6, 0x48],
+            SET_TEMPERATURE_OFFSET.prepare_with_data(0x07E6)
+        );
+    }
+
+    #[test]
+    fn test_encode_ambient_pressure() {
+        assert_eq!(Ok(1013), encode_ambient_pressure::<()>(1013.25));
+        assert_eq!(Ok(1014), encode_ambient_pressure::<()>(1013.5));
+        assert_eq!(Ok(700), encode_ambient_pressure::<()>(699.5));
+        assert_eq!(Ok(1200), encode_ambient_pressure::<()>(1200.49));
+
+        for pressure in [699.49, 1200.5, -1013.0, 1e9, f32::NAN, f32::INFINITY] {
+            assert_eq!(
+                Err(Error::InvalidInput),
+                encode_ambient_pressure::<()>(pressure)
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_temperature_offset_1() {
+        // Section 3.7.1 of the datasheet
+        let raw = [0x07, 0x0E6, 0x48];
+        let offset = decode_temperature_offset(raw);
+        assert!(offset.is_finite());
+        assert!((5.4 - offset).abs() < F32_TOLERANCE);
+    }
+
+    #[test]
+    fn test_decode_temperature_offset_2() {
+        // Section 3.7.2 of the datasheet
+        let raw = [0x09, 0x012, 0x63];
+        let offset = decode_temperature_offset(raw);
+        assert!(offset.is_finite());
+        assert!((6.2 - offset).abs() < F32_TOLERANCE);
+    }
+
+    #[test]
+    fn test_encode_temperature_offset() {
+        // Section 3.7.1 of the datasheet
+        let word = encode_temperature_offset::<()>(5.4).unwrap();
+        assert_eq!(0x07E6, word);
+    }
+
+    #[test]
+    fn test_encode_temperature_offset_rejects_negative() {
+        assert_eq!(
+            Err(Error::InvalidInput),
+            encode_temperature_offset::<()>(-1.0)
+        );
+    }
+
+    #[test]
+    fn test_encode_temperature_offset_rejects_nan() {
+        assert_eq!(
+            Err(Error::InvalidInput),
+            encode_temperature_offset::<()>(f32::NAN)
+        );
+    }
+
+    #[test]
+    fn test_decode_temp_measurement() {
+        const EXPECTED: f32 = 25.0;
+
+        let decoded = decode_temp_measurement(0x66, 0x67);
+        assert!(decoded.is_finite());
+        assert!(
+            (EXPECTED - decoded).abs() < F32_TOLERANCE,
+            "Expected: {}; Decoded: {}",
+            EXPECTED,
+            decoded
+        );
+    }
+
+    #[test]
+    fn test_decode_co2_measurement() {
+        let decoded = decode_co2_measurement(0x01, 0xF4);
+        assert_eq!(500, decoded)
+    }
+
+    #[test]
+    fn test_decode_humidity_measurement() {
+        const EXPECTED: f32 = 37.0;
+
+        let decoded = decode_humidity_measurement(0x5E, 0xB9);
+        assert!(decoded.is_finite());
+        assert!(
+            (EXPECTED - decoded).abs() < F32_TOLERANCE,
+            "Expected: {}; Decoded: {}",
+            EXPECTED,
+            decoded
+        );
+    }
+
+    #[test]
+    fn test_decode_measurement() {
+        let m = decode_measurement([0x01, 0xF4, 0x33, 0x66, 0x67, 0xA2, 0x5E, 0xB9, 0x3C]);
+        assert_eq!(500, m.co2);
+        assert!((25.0 - m.temperature).abs() < F32_TOLERANCE);
+        assert!((37.0 - m.humidity).abs() < F32_TOLERANCE);
+    }
+
+    #[test]
+    fn test_decode_frc_status() {
+        let status = decode_frc_status([0x7F, 0xCE, 0x7B]);
+        assert_eq!(Some(-50), status);
+    }
+
+    #[test]
+    fn test_decode_frc_status_failed() {
+        let status = decode_frc_status([0xFF, 0xFF, crc8(&[0xFF, 0xFF])]);
+        assert_eq!(None, status);
+    }
+
+    /// Simulate the sensor echoing back a word written by the driver
+    fn echo(payload: [u8; 5]) -> [u8; 3] {
+        [payload[2], payload[3], payload[4]]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_encode_decode_temperature_offset(offset in 0.0f32..175.0) {
+            let encoded = encode_temperature_offset::<()>(offset).unwrap();
+            let response = echo(SET_TEMPERATURE_OFFSET.prepare_with_data(encoded));
+            let decoded = decode_temperature_offset(response);
+
+            // The encoding truncates towards zero, so the decoded value
+            // is at most one LSB below the original offset
+            prop_assert!(decoded <= offset + F32_TOLERANCE);
+            prop_assert!(offset - decoded < TEMP_K1 / TWO_P16_M1 + F32_TOLERANCE);
+        }
+
+        #[test]
+        fn prop_temperature_offset_from_ticks(ticks in any::<u16>()) {
+            let offset = temperature_offset_from_ticks(ticks);
+            prop_assert_eq!(Ok(ticks), encode_temperature_offset::<()>(offset));
+        }
+
+        #[test]
+        fn prop_encode_temperature_offset_rejects_negative(offset in f32::MIN..-f32::MIN_POSITIVE) {
+            prop_assert_eq!(Err(Error::InvalidInput), encode_temperature_offset::<()>(offset));
+        }
+
+        #[test]
+        fn prop_encode_decode_sensor_altitude(altitude in 0..=MAX_ALTITUDE) {
+            let response = echo(SET_SENSOR_ALTITUDE.prepare_with_data(altitude));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+            prop_assert_eq!(altitude, u16::from_be_bytes([response[0], response[1]]));
+        }
+
+        #[test]
+        fn prop_encode_decode_ambient_pressure(pressure in AMBIENT_PRESSURE_RANGE_HPA) {
+            let response = echo(SET_AMBIENT_PRESSURE.prepare_with_data(pressure));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+            prop_assert_eq!(pressure, u16::from_be_bytes([response[0], response[1]]));
+        }
+
+        #[test]
+        fn prop_encode_ambient_pressure(pressure in AMBIENT_PRESSURE_RANGE_HPA, fraction in -0.49f32..0.49) {
+            prop_assert_eq!(Ok(pressure), encode_ambient_pressure::<()>(pressure as f32 + fraction));
+        }
+
+        #[test]
+        fn prop_encode_decode_asc_target(ppm in any::<u16>()) {
+            let response = echo(SET_AUTOMATIC_SELF_CALIBRATION_TARGET.prepare_with_data(ppm));
+            prop_assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+            prop_assert_eq!(ppm, u16::from_be_bytes([response[0], response[1]]));
+        }
+
+        #[test]
+        fn prop_decode_measurement(co2 in any::<u16>(), temp in any::<u16>(), hum in any::<u16>()) {
+            let mut buf = [0; 9];
+            for (chunk, word) in buf.chunks_exact_mut(3).zip([co2, temp, hum]) {
+                let [msb, lsb] = word.to_be_bytes();
+                chunk.copy_from_slice(&[msb, lsb, crc8(&[msb, lsb])]);
+            }
+
+            let m = decode_measurement(buf);
+            prop_assert_eq!(co2, m.co2);
+
+            // Section 3.6.2 of the datasheet
+            let expected_temp = -45.0 + TEMP_K1 * temp as f32 / TWO_P16_M1;
+            let expected_hum = 100.0 * hum as f32 / TWO_P16_M1;
+            prop_assert!((expected_temp - m.temperature).abs() < F32_TOLERANCE);
+            prop_assert!((expected_hum - m.humidity).abs() < F32_TOLERANCE);
+            prop_assert!((-45.0..=130.0).contains(&m.temperature));
+            prop_assert!((0.0..=100.0).contains(&m.humidity));
+        }
+
+        #[test]
+        fn prop_decode_frc_status(correction in -0x8000i32..0x7FFF) {
+            let [msb, lsb] = ((correction + 0x8000) as u16).to_be_bytes();
+            let status = decode_frc_status([msb, lsb, crc8(&[msb, lsb])]);
+            prop_assert_eq!(Some(correction as i16), status);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+#![allow(dead_code)]
+
+//! Helpers for building the I2C frames expected by the sensors
+
+const CRC8_POLY: u8 = 0x31;
+const CRC8_INITIAL: u8 = 0xFF;
+
+pub fn crc8(data: &[u8]) -> u8 {
+    let mut crc = CRC8_INITIAL;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            if crc & 0x80 != 0 {
+                crc = (crc << 1) ^ CRC8_POLY;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+
+    crc
+}
+
+/// A single 16-bit word followed by its CRC, as sent by the sensor
+pub fn word(value: u16) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    vec![bytes[0], bytes[1], crc8(&bytes)]
+}
+
+/// Several words, each followed by its CRC
+pub fn words(values: &[u16]) -> Vec<u8> {
+    values.iter().flat_map(|&v| word(v)).collect()
+}
+
+/// A command without arguments
+pub fn command(op_code: u16) -> Vec<u8> {
+    op_code.to_be_bytes().to_vec()
+}
+
+/// A command followed by a single argument word
+pub fn command_with_data(op_code: u16, data: u16) -> Vec<u8> {
+    let mut buf = command(op_code);
+    buf.extend(word(data));
+    buf
+}
+
+/// Drives a future to completion on the current thread.
+///
+/// The mocks never return `Pending`, so busy polling is enough here.
+pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    let mut future = core::pin::pin!(future);
+    let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+    loop {
+        if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Exposes the checked delay mock through the async `DelayNs` trait, so
+/// that the async drivers record the same delay transactions as the sync ones
+pub struct AsyncDelay(embedded_hal_mock::eh1::delay::CheckedDelay);
+
+impl AsyncDelay {
+    pub fn new(delay: embedded_hal_mock::eh1::delay::CheckedDelay) -> Self {
+        Self(delay)
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for AsyncDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        embedded_hal::delay::DelayNs::delay_ns(&mut self.0, ns);
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        embedded_hal::delay::DelayNs::delay_us(&mut self.0, us);
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        embedded_hal::delay::DelayNs::delay_ms(&mut self.0, ms);
+    }
+}
+
+/// A sensor replaying canned CO2 concentrations. A new measurement becomes
+/// ready after the configured number of `data_ready` polls
+pub struct CannedSensor {
+    pub co2: std::collections::VecDeque<u16>,
+    pub starts: u32,
+    pub polls_until_ready: u32,
+    not_ready: u32,
+}
+
+impl CannedSensor {
+    pub fn new(co2: &[u16]) -> Self {
+        Self::ready_after(co2, 0)
+    }
+
+    pub fn ready_after(co2: &[u16], polls: u32) -> Self {
+        Self {
+            co2: co2.iter().copied().collect(),
+            starts: 0,
+            polls_until_ready: polls,
+            not_ready: polls,
+        }
+    }
+
+    fn start(&mut self) {
+        self.starts += 1;
+        self.not_ready = self.polls_until_ready;
+    }
+
+    fn ready(&mut self) -> bool {
+        if self.not_ready > 0 {
+            self.not_ready -= 1;
+            return false;
+        }
+
+        !self.co2.is_empty()
+    }
+
+    fn next(&mut self) -> libscd::measurement::Measurement {
+        self.not_ready = self.polls_until_ready;
+        libscd::measurement::Measurement {
+            temperature: 21.0,
+            humidity: 45.0,
+            co2: self.co2.pop_front().unwrap(),
+        }
+    }
+}
+
+#[cfg(feature = "sync")]
+impl libscd::synchronous::sensor::Co2Sensor for CannedSensor {
+    type Error = ();
+
+    fn start_measurement(&mut self) -> Result<(), libscd::error::Error<()>> {
+        self.start();
+        Ok(())
+    }
+
+    fn stop_measurement(&mut self) -> Result<(), libscd::error::Error<()>> {
+        Ok(())
+    }
+
+    fn data_ready(&mut self) -> Result<bool, libscd::error::Error<()>> {
+        Ok(self.ready())
+    }
+
+    fn read_measurement(
+        &mut self,
+    ) -> Result<libscd::measurement::Measurement, libscd::error::Error<()>> {
+        Ok(self.next())
+    }
+}
+
+#[cfg(feature = "async")]
+impl libscd::asynchronous::sensor::Co2Sensor for CannedSensor {
+    type Error = ();
+
+    async fn start_measurement(&mut self) -> Result<(), libscd::error::Error<()>> {
+        self.start();
+        Ok(())
+    }
+
+    async fn stop_measurement(&mut self) -> Result<(), libscd::error::Error<()>> {
+        Ok(())
+    }
+
+    async fn data_ready(&mut self) -> Result<bool, libscd::error::Error<()>> {
+        Ok(self.ready())
+    }
+
+    async fn read_measurement(
+        &mut self,
+    ) -> Result<libscd::measurement::Measurement, libscd::error::Error<()>> {
+        Ok(self.next())
+    }
+}
+
+/// Wraps an I2C bus and fails its transfers on demand, e.g. to inject a bus
+/// error in the middle of a procedure of a driver
+#[derive(Clone)]
+pub struct FlakyBus<B> {
+    bus: B,
+    countdown: std::rc::Rc<std::cell::Cell<Option<u32>>>,
+}
+
+impl<B> FlakyBus<B> {
+    pub fn new(bus: B) -> Self {
+        Self {
+            bus,
+            countdown: Default::default(),
+        }
+    }
+
+    /// Fail a single transfer after the next `transfers` succeed
+    pub fn fail_after(&self, transfers: u32) {
+        self.countdown.set(Some(transfers));
+    }
+
+    fn take_failure(&self) -> bool {
+        let countdown = self.countdown.get();
+        self.countdown.set(countdown.and_then(|n| n.checked_sub(1)));
+        countdown == Some(0)
+    }
+}
+
+impl<B> embedded_hal::i2c::ErrorType for FlakyBus<B> {
+    type Error = embedded_hal::i2c::ErrorKind;
+}
+
+impl<B> embedded_hal::i2c::I2c for FlakyBus<B>
+where
+    B: embedded_hal::i2c::I2c<Error = embedded_hal::i2c::ErrorKind>,
+{
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [embedded_hal::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if self.take_failure() {
+            return Err(embedded_hal::i2c::ErrorKind::Bus);
+        }
+
+        self.bus.transaction(address, operations)
+    }
+}
+
+impl<B> embedded_hal_async::i2c::I2c for FlakyBus<B>
+where
+    B: embedded_hal_async::i2c::I2c<Error = embedded_hal::i2c::ErrorKind>,
+{
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [embedded_hal::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if self.take_failure() {
+            return Err(embedded_hal::i2c::ErrorKind::Bus);
+        }
+
+        self.bus.transaction(address, operations).await
+    }
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "custom-commands")]
+
+mod common;
+
+use common::{command, command_with_data, words};
+use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use libscd::commands::{SensorCommand, MAX_RESPONSE_WORDS};
+use libscd::error::Error;
+
+// A command reading two words, e.g. one added by a newer firmware
+struct ReadPair {
+    op_code: u16,
+    argument: Option<u16>,
+    words: usize,
+}
+
+impl ReadPair {
+    fn new(op_code: u16) -> Self {
+        Self {
+            op_code,
+            argument: None,
+            words: 2,
+        }
+    }
+}
+
+impl SensorCommand for ReadPair {
+    type Response = (u16, u16);
+
+    fn op_code(&self) -> u16 {
+        self.op_code
+    }
+
+    fn exec_time_us(&self) -> u32 {
+        10_000
+    }
+
+    fn allowed_while_running(&self) -> bool {
+        self.argument.is_none()
+    }
+
+    fn argument(&self) -> Option<u16> {
+        self.argument
+    }
+
+    fn response_words(&self) -> usize {
+        self.words
+    }
+
+    fn decode(&self, words: &[u16]) -> Self::Response {
+        (words[0], words[1])
+    }
+}
+
+fn delay(ms: u32) -> DelayTransaction {
+    DelayTransaction::delay_ms(ms)
+}
+
+#[cfg(all(feature = "sync", feature = "scd41"))]
+mod scd4x_sync {
+    use super::*;
+    use libscd::synchronous::scd4x::{Scd41, I2C_ADDRESS};
+
+    #[test]
+    fn execute_custom_command() {
+        let i2c = [
+            I2cTransaction::write(I2C_ADDRESS, command_with_data(0x3700, 7)),
+            I2cTransaction::read(I2C_ADDRESS, words(&[0x0102, 0x0304])),
+            I2cTransaction::write(I2C_ADDRESS, command(0x21b1)),
+            I2cTransaction::write(I2C_ADDRESS, command(0x3701)),
+            I2cTransaction::read(I2C_ADDRESS, words(&[1, 2])),
+        ];
+        let delay = CheckedDelay::new(&[delay(10), delay(0), delay(10)]);
+        let mut sensor = Scd41::new(I2cMock::new(&i2c), delay.clone());
+
+        let mut cmd = ReadPair::new(0x3700);
+        cmd.argument = Some(7);
+        assert_eq!(Ok((0x0102, 0x0304)), sensor.execute(&cmd));
+
+        sensor.start_periodic_measurement().unwrap();
+        assert_eq!(Err(Error::NotAllowed), sensor.execute(&cmd));
+        assert_eq!(Ok((1, 2)), sensor.execute(&ReadPair::new(0x3701)));
+
+        let mut cmd = ReadPair::new(0x3702);
+        cmd.words = MAX_RESPONSE_WORDS + 1;
+        assert_eq!(Err(Error::InvalidInput), sensor.execute(&cmd));
+
+        sensor.release().done();
+        delay.clone().done();
+    }
+
+    #[test]
+    fn execute_custom_command_with_bad_crc() {
+        let mut response = words(&[0x0102, 0x0304]);
+        response[5] ^= 0xFF;
+        let i2c = [
+            I2cTransaction::write(I2C_ADDRESS, command(0x3701)),
+            I2cTransaction::read(I2C_ADDRESS, response),
+        ];
+        let delay = CheckedDelay::new(&[delay(10)]);
+        let mut sensor = Scd41::new(I2cMock::new(&i2c), delay.clone());
+
+        assert!(matches!(
+            sensor.execute(&ReadPair::new(0x3701)),
+            Err(Error::CRC(e)) if e.word == 1
+        ));
+
+        sensor.release().done();
+        delay.clone().done();
+    }
+}
+
+#[cfg(all(feature = "async", feature = "scd30"))]
+mod scd30_async {
+    use super::*;
+    use common::{block_on, AsyncDelay};
+    use libscd::asynchronous::scd30::{Scd30, I2C_ADDRESS};
+
+    #[test]
+    fn execute_custom_command() {
+        let i2c = [
+            I2cTransaction::write(I2C_ADDRESS, command(0xD100)),
+            I2cTransaction::read(I2C_ADDRESS, words(&[0x0342, 0x0001])),
+        ];
+        // The 5 ms delay after each command, followed by the rest of the
+        // execution time
+        let delay = CheckedDelay::new(&[delay(5), delay(5)]);
+        let mut sensor = Scd30::new(I2cMock::new(&i2c), AsyncDelay::new(delay.clone()));
+
+        let response = block_on(sensor.execute(&ReadPair::new(0xD100)));
+        assert_eq!(Ok((0x0342, 0x0001)), response);
+
+        sensor.release().done();
+        delay.clone().done();
+    }
+}
@@ -0,0 +1,357 @@
+//! Worked examples from the Sensirion datasheets, used as regression
+//! fixtures for the wire format and the conversion math.
+
+use libscd::wire::common::crc8_verify_chunked_3;
+use libscd::wire::crc::crc8;
+
+// SCD30 interface description, Section 1.1.3 and SCD4x datasheet, Section 3.11
+#[test]
+fn crc_example() {
+    assert_eq!(0x92, crc8(&[0xBE, 0xEF]));
+    assert_eq!(Ok(()), crc8_verify_chunked_3(&[0xBE, 0xEF, 0x92]));
+}
+
+#[cfg(feature = "scd30")]
+mod scd30 {
+    use libscd::wire::common::crc8_verify_chunked_3;
+    use libscd::wire::scd30::*;
+
+    struct Frame {
+        section: &'static str,
+        command: Command,
+        data: Option<u16>,
+        expected: &'static [u8],
+    }
+
+    const FRAMES: &[Frame] = &[
+        Frame {
+            section: "1.4.1",
+            command: START_CONTINUOUS_MEASUREMENT,
+            data: Some(0x0000),
+            expected: &[0x00, 0x10, 0x00, 0x00, 0x81],
+        },
+        Frame {
+            section: "1.4.2",
+            command: STOP_CONTINUOUS_MEASUREMENT,
+            data: None,
+            expected: &[0x01, 0x04],
+        },
+        Frame {
+            section: "1.4.3",
+            command: GET_SET_MEASUREMENT_INTERVAL,
+            data: Some(0x0002),
+            expected: &[0x46, 0x00, 0x00, 0x02, 0xE3],
+        },
+        Frame {
+            section: "1.4.4",
+            command: GET_DATA_READY_STATUS,
+            data: None,
+            expected: &[0x02, 0x02],
+        },
+        Frame {
+            section: "1.4.5",
+            command: READ_MEASUREMENT,
+            data: None,
+            expected: &[0x03, 0x00],
+        },
+        Frame {
+            section: "1.4.6",
+            command: MANAGE_AUTOMATIC_SELF_CALIBRATION,
+            data: Some(0x0001),
+            expected: &[0x53, 0x06, 0x00, 0x01, 0xB0],
+        },
+        Frame {
+            section: "1.4.6",
+            command: SET_FORCED_RECALIBRATION_VALUE,
+            data: Some(450),
+            expected: &[0x52, 0x04, 0x01, 0xC2, 0x50],
+        },
+        Frame {
+            section: "1.4.7",
+            command: GET_SET_TEMPERATURE_OFFSET,
+            data: Some(500),
+            expected: &[0x54, 0x03, 0x01, 0xF4, 0x33],
+        },
+        Frame {
+            section: "1.4.8",
+            command: GET_SET_ALTITUDE_COMPENSATION,
+            data: Some(1000),
+            expected: &[0x51, 0x02, 0x03, 0xE8, 0xD4],
+        },
+        Frame {
+            section: "1.4.9",
+            command: READ_FIRMWARE_VERSION,
+            data: None,
+            expected: &[0xD1, 0x00],
+        },
+        Frame {
+            section: "1.4.10",
+            command: SOFT_RESET,
+            data: None,
+            expected: &[0xD3, 0x04],
+        },
+    ];
+
+    #[test]
+    fn command_frames() {
+        for f in FRAMES {
+            match f.data {
+                Some(data) => assert_eq!(
+                    f.expected,
+                    f.command.prepare_with_data(data),
+                    "Section {}",
+                    f.section
+                ),
+                None => assert_eq!(f.expected, f.command.prepare(), "Section {}", f.section),
+            }
+        }
+    }
+
+    #[test]
+    fn read_measurement_response() {
+        // Section 1.5
+        let response = [
+            0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, // CO2: 439 PPM
+            0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, // Temperature: 27.2 C
+            0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74, // Humidity: 48.8 %
+        ];
+        assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+
+        let m = decode_measurement_data(response);
+        assert_eq!(439, m.co2);
+        assert!((27.2 - m.temperature).abs() < 0.05);
+        assert!((48.8 - m.humidity).abs() < 0.05);
+    }
+
+    #[test]
+    fn word_responses() {
+        // (section, response, expected value)
+        const RESPONSES: &[(&str, [u8; 3], u16)] = &[
+            ("1.4.3", [0x00, 0x02, 0xE3], 2),
+            ("1.4.4", [0x00, 0x01, 0xB0], 1),
+            ("1.4.6", [0x00, 0x00, 0x81], 0),
+            ("1.4.6", [0x01, 0xC2, 0x50], 450),
+            ("1.4.7", [0x01, 0xF4, 0x33], 500),
+            ("1.4.8", [0x03, 0xE8, 0xD4], 1000),
+            ("1.4.9", [0x03, 0x42, 0xF3], 0x0342),
+        ];
+
+        for (section, response, expected) in RESPONSES {
+            assert_eq!(
+                Ok(()),
+                crc8_verify_chunked_3(response),
+                "Section {}",
+                section
+            );
+            assert_eq!(
+                *expected,
+                u16::from_be_bytes([response[0], response[1]]),
+                "Section {}",
+                section
+            );
+        }
+    }
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+mod scd4x {
+    use libscd::wire::common::crc8_verify_chunked_3;
+    use libscd::wire::scd4x::*;
+
+    struct Frame {
+        section: &'static str,
+        command: Command,
+        data: Option<u16>,
+        expected: &'static [u8],
+    }
+
+    const FRAMES: &[Frame] = &[
+        Frame {
+            section: "3.6.1",
+            command: START_PERIODIC_MEASUREMENT,
+            data: None,
+            expected: &[0x21, 0xB1],
+        },
+        Frame {
+            section: "3.6.2",
+            command: READ_MEASUREMENT,
+            data: None,
+            expected: &[0xEC, 0x05],
+        },
+        Frame {
+            section: "3.6.3",
+            command: STOP_PERIODIC_MEASUREMENT,
+            data: None,
+            expected: &[0x3F, 0x86],
+        },
+        Frame {
+            section: "3.7.1",
+            command: SET_TEMPERATURE_OFFSET,
+            data: Some(0x07E6),
+            expected: &[0x24, 0x1D, 0x07, 0xE6, 0x48],
+        },
+        Frame {
+            section: "3.7.2",
+            command: GET_TEMPERATURE_OFFSET,
+            data: None,
+            expected: &[0x23, 0x18],
+        },
+        Frame {
+            section: "3.7.4",
+            command: GET_SENSOR_ALTITUDE,
+            data: None,
+            expected: &[0x23, 0x22],
+        },
+        Frame {
+            section: "3.7.5",
+            command: SET_AMBIENT_PRESSURE,
+            data: Some(987),
+            expected: &[0xE0, 0x00, 0x03, 0xDB, 0x42],
+        },
+        Frame {
+            section: "3.8.1",
+            command: PERFORM_FORCED_RECALIBRATION,
+            data: Some(480),
+            expected: &[0x36, 0x2F, 0x01, 0xE0, 0xB4],
+        },
+        Frame {
+            section: "3.8.2",
+            command: SET_AUTOMATIC_SELF_CALIBRATION_ENABLED,
+            data: Some(1),
+            expected: &[0x24, 0x16, 0x00, 0x01, 0xB0],
+        },
+        Frame {
+            section: "3.8.3",
+            command: GET_AUTOMATIC_SELF_CALIBRATION_ENABLED,
+            data: None,
+            expected: &[0x23, 0x13],
+        },
+        Frame {
+            section: "3.9.1",
+            command: PERSIST_SETTINGS,
+            data: None,
+            expected: &[0x36, 0x15],
+        },
+        Frame {
+            section: "3.9.2",
+            command: GET_SERIAL_NUMBER,
+            data: None,
+            expected: &[0x36, 0x82],
+        },
+        Frame {
+            section: "3.9.3",
+            command: PERFORM_SELF_TEST,
+            data: None,
+            expected: &[0x36, 0x39],
+        },
+        Frame {
+            section: "3.9.4",
+            command: PERFORM_FACTORY_RESET,
+            data: None,
+            expected: &[0x36, 0x32],
+        },
+        Frame {
+            section: "3.9.5",
+            command: REINIT,
+            data: None,
+            expected: &[0x36, 0x46],
+        },
+    ];
+
+    #[test]
+    fn command_frames() {
+        for f in FRAMES {
+            match f.data {
+                Some(data) => assert_eq!(
+                    f.expected,
+                    f.command.prepare_with_data(data),
+                    "Section {}",
+                    f.section
+                ),
+                None => assert_eq!(f.expected, f.command.prepare(), "Section {}", f.section),
+            }
+        }
+    }
+
+    #[test]
+    fn read_measurement_response() {
+        // Section 3.6.2
+        let response = [
+            0x01, 0xF4, 0x33, // CO2: 500 PPM
+            0x66, 0x67, 0xA2, // Temperature: 25 C
+            0x5E, 0xB9, 0x3C, // Humidity: 37 %
+        ];
+        assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+
+        let m = decode_measurement(response);
+        assert_eq!(500, m.co2);
+        assert!((25.0 - m.temperature).abs() < 0.01);
+        assert!((37.0 - m.humidity).abs() < 0.01);
+    }
+
+    #[test]
+    fn temperature_offset() {
+        // Section 3.7.1
+        assert_eq!(Ok(0x07E6), encode_temperature_offset::<()>(5.4));
+
+        // (section, response, expected offset)
+        const RESPONSES: &[(&str, [u8; 3], f32)] = &[
+            ("3.7.1", [0x07, 0xE6, 0x48], 5.4),
+            ("3.7.2", [0x09, 0x12, 0x63], 6.2),
+        ];
+
+        for (section, response, expected) in RESPONSES {
+            assert_eq!(
+                Ok(()),
+                crc8_verify_chunked_3(response),
+                "Section {}",
+                section
+            );
+
+            let offset = decode_temperature_offset(*response);
+            assert!((expected - offset).abs() < 0.01, "Section {}", section);
+        }
+    }
+
+    #[test]
+    fn word_responses() {
+        // (section, response, expected value)
+        const RESPONSES: &[(&str, [u8; 3], u16)] = &[
+            ("3.7.4", [0x04, 0x4C, 0x42], 1100),
+            ("3.7.6", [0x03, 0xDB, 0x42], 987),
+            ("3.8.3", [0x00, 0x00, 0x81], 0),
+            ("3.9.3", [0x00, 0x00, 0x81], 0),
+        ];
+
+        for (section, response, expected) in RESPONSES {
+            assert_eq!(
+                Ok(()),
+                crc8_verify_chunked_3(response),
+                "Section {}",
+                section
+            );
+            assert_eq!(
+                *expected,
+                u16::from_be_bytes([response[0], response[1]]),
+                "Section {}",
+                section
+            );
+        }
+    }
+
+    #[test]
+    fn forced_recalibration_response() {
+        // Section 3.8.1: a correction of -50 PPM
+        let response = [0x7F, 0xCE, 0x7B];
+        assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+        assert_eq!(Some(-50), decode_frc_status(response));
+    }
+
+    #[test]
+    fn serial_number_response() {
+        // Section 3.9.2
+        let response = [0xF8, 0x96, 0x31, 0x9F, 0x07, 0xC2, 0x3B, 0xBE, 0x89];
+        assert_eq!(Ok(()), crc8_verify_chunked_3(&response));
+        assert_eq!(273_325_796_834_238, decode_serial_number(response));
+    }
+}
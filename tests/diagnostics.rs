@@ -0,0 +1,154 @@
+#![cfg(feature = "simulator")]
+
+mod common;
+
+use libscd::diagnostics::MeasurementMode;
+use libscd::error::ErrorKind;
+use libscd::variant::SensorVariant;
+
+#[cfg(all(feature = "sync", feature = "scd41"))]
+mod scd4x_sync {
+    use super::*;
+    use embedded_hal::delay::DelayNs;
+    use libscd::simulator::scd4x::FakeScd4x;
+    use libscd::synchronous::scd4x::Scd41;
+
+    #[test]
+    fn idle_sensor_is_queried() {
+        let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+        sim.set_serial_number(0xBEEF);
+        let mut driver = Scd41::new(sim.clone(), sim.delay());
+        driver.set_ambient_pressure(987).unwrap();
+
+        let diagnostics = driver.diagnostics();
+        assert_eq!(Some(SensorVariant::Scd41), diagnostics.variant);
+        assert_eq!(Some(0xBEEF), diagnostics.serial_number);
+        assert!(diagnostics.firmware_revision.is_some());
+        assert_eq!(None, diagnostics.firmware_version);
+        assert_eq!(MeasurementMode::Idle, diagnostics.mode);
+        assert_eq!(None, diagnostics.last_error);
+
+        let settings = diagnostics.settings.unwrap();
+        assert_eq!(Some(987), settings.ambient_pressure);
+        assert_eq!(Some(400), settings.automatic_self_calibration_target);
+
+        let text = diagnostics.to_string();
+        assert!(text.contains("variant: SCD41"));
+        assert!(text.contains("serial number: 0x00000000beef"));
+        assert!(text.contains("ambient pressure: 987 hPa"));
+        assert!(text.ends_with("last error: -"));
+    }
+
+    #[test]
+    fn measurement_is_not_interrupted() {
+        let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+        let mut delay = sim.delay();
+        let mut driver = Scd41::new(sim.clone(), sim.delay());
+        driver.start_low_power_periodic_measurement().unwrap();
+        delay.delay_ms(30_000);
+        driver.read_measurement().unwrap();
+
+        let diagnostics = driver.diagnostics();
+        assert_eq!(MeasurementMode::LowPowerPeriodic, diagnostics.mode);
+        assert_eq!(None, diagnostics.serial_number);
+        assert_eq!(None, diagnostics.settings);
+        assert_eq!(1, diagnostics.counters.measurements);
+        assert!(driver.suspend().is_measuring());
+        assert!(driver.diagnostics().to_string().contains("settings: -"));
+    }
+
+    #[test]
+    fn bus_errors_are_counted() {
+        let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+        let mut driver = Scd41::new(sim.clone(), sim.delay());
+
+        sim.detach();
+        assert!(driver.serial_number().is_err());
+        assert!(driver.data_ready().is_err());
+        sim.attach();
+
+        let diagnostics = driver.diagnostics();
+        assert_eq!(2, diagnostics.counters.bus_errors);
+        assert_eq!(0, diagnostics.counters.crc_errors);
+        assert_eq!(Some(ErrorKind::Bus), diagnostics.last_error);
+        assert_eq!(Some(SensorVariant::Scd41), diagnostics.variant);
+
+        // The counters survive a deep sleep of the MCU
+        let state = driver.suspend();
+        let driver = Scd41::resume(sim.clone(), sim.delay(), state);
+        assert_eq!(2, driver.suspend().counters().bus_errors);
+    }
+
+    #[test]
+    fn wake_up_nack_is_not_counted() {
+        let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+        let mut driver = Scd41::new(sim.clone(), sim.delay());
+
+        driver.power_down().unwrap();
+        assert!(driver.wake_up().is_err());
+
+        let diagnostics = driver.diagnostics();
+        assert_eq!(0, diagnostics.counters.bus_errors);
+        assert_eq!(None, diagnostics.last_error);
+    }
+}
+
+#[cfg(all(feature = "sync", feature = "scd30"))]
+mod scd30_sync {
+    use super::*;
+    use embedded_hal::delay::DelayNs;
+    use libscd::simulator::scd30::FakeScd30;
+    use libscd::synchronous::scd30::Scd30;
+
+    #[test]
+    fn running_sensor_is_queried() {
+        let sim = FakeScd30::new();
+        let mut delay = sim.delay();
+        let mut driver = Scd30::new(sim.clone(), sim.delay());
+        driver.start_continuous_measurement(1013).unwrap();
+        delay.delay_ms(2_000);
+        driver.read_measurement().unwrap();
+
+        let diagnostics = driver.diagnostics();
+        assert_eq!(Some(SensorVariant::Scd30), diagnostics.variant);
+        assert!(diagnostics.firmware_version.is_some());
+        assert_eq!(None, diagnostics.serial_number);
+        assert_eq!(MeasurementMode::Periodic, diagnostics.mode);
+        assert_eq!(None, diagnostics.operating_ms);
+        assert_eq!(1, diagnostics.counters.measurements);
+
+        let settings = diagnostics.settings.unwrap();
+        assert_eq!(Some(1013), settings.ambient_pressure);
+        assert_eq!(Some(2), settings.measurement_interval);
+        assert!(diagnostics
+            .to_string()
+            .contains("measurement interval: 2 s"));
+    }
+}
+
+#[cfg(all(feature = "async", feature = "scd40"))]
+mod scd4x_async {
+    use super::*;
+    use common::block_on;
+    use libscd::asynchronous::scd4x::Scd40;
+    use libscd::simulator::scd4x::FakeScd4x;
+
+    #[test]
+    fn idle_sensor_is_queried() {
+        block_on(async {
+            let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+            let mut driver = Scd40::new(sim.clone(), sim.delay());
+
+            let diagnostics = driver.diagnostics().await;
+            assert_eq!(Some(SensorVariant::Scd40), diagnostics.variant);
+            assert!(diagnostics.serial_number.is_some());
+            assert!(diagnostics.settings.is_some());
+            assert_eq!(Some(0), diagnostics.operating_ms);
+
+            driver.start_periodic_measurement().await.unwrap();
+            let diagnostics = driver.diagnostics().await;
+            assert_eq!(MeasurementMode::Periodic, diagnostics.mode);
+            assert_eq!(None, diagnostics.variant);
+        });
+    }
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "embassy")]
+
+use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+
+#[test]
+#[cfg(all(feature = "scd30", feature = "sync"))]
+fn scd30_sync() {
+    let sensor = libscd::synchronous::scd30::EmbassyScd30::new_embassy(I2cMock::new(&[]));
+    sensor.release().done();
+}
+
+#[test]
+#[cfg(all(feature = "scd40", feature = "sync"))]
+fn scd40_sync() {
+    let sensor = libscd::synchronous::scd4x::EmbassyScd40::new_embassy(I2cMock::new(&[]));
+    sensor.release().done();
+}
+
+#[test]
+#[cfg(all(feature = "scd41", feature = "sync"))]
+fn scd41_sync() {
+    let sensor = libscd::synchronous::scd4x::EmbassyScd41::new_embassy(I2cMock::new(&[]));
+    sensor.release().done();
+}
+
+#[test]
+#[cfg(all(feature = "scd30", feature = "async"))]
+fn scd30_async() {
+    let sensor = libscd::asynchronous::scd30::EmbassyScd30::new_embassy(I2cMock::new(&[]));
+    sensor.release().done();
+}
+
+#[test]
+#[cfg(all(feature = "scd40", feature = "async"))]
+fn scd40_async() {
+    let sensor = libscd::asynchronous::scd4x::EmbassyScd40::new_embassy(I2cMock::new(&[]));
+    sensor.release().done();
+}
+
+#[test]
+#[cfg(all(feature = "scd41", feature = "async"))]
+fn scd41_async() {
+    let sensor = libscd::asynchronous::scd4x::EmbassyScd41::new_embassy(I2cMock::new(&[]));
+    sensor.release().done();
+}
@@ -0,0 +1,81 @@
+#![cfg(any(feature = "sync", feature = "async"))]
+
+mod common;
+
+use common::CannedSensor;
+use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+
+fn sensors() -> [CannedSensor; 3] {
+    [
+        CannedSensor::ready_after(&[410, 420], 0),
+        CannedSensor::ready_after(&[510, 520], 2),
+        // Never ready within the timeout
+        CannedSensor::ready_after(&[610], 10),
+    ]
+}
+
+fn delays(count: usize) -> Vec<DelayTransaction> {
+    vec![DelayTransaction::delay_ms(100); count]
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn sensor_group_sync() {
+    use libscd::synchronous::group::SensorGroup;
+
+    let mut delay = CheckedDelay::new(&delays(3));
+    let mut group = SensorGroup::new(sensors());
+    group.start().unwrap();
+
+    let batch = group.sample(&mut delay, 300).unwrap();
+    assert!(!batch.is_complete());
+    assert_eq!(
+        vec![410, 510],
+        batch.iter().map(|m| m.co2).collect::<Vec<_>>()
+    );
+    assert!(batch.measurements[2].is_none());
+    assert_eq!(200, batch.spread_ms);
+    delay.done();
+
+    let sensors = group.release();
+    assert!(sensors.iter().all(|sensor| sensor.starts == 1));
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn sensor_group_of_borrowed_sensors_sync() {
+    use libscd::synchronous::group::SensorGroup;
+    use libscd::synchronous::sensor::Co2Sensor;
+
+    let mut first = CannedSensor::new(&[400]);
+    let mut second = CannedSensor::new(&[500]);
+    let mut delay = CheckedDelay::new(&[]);
+
+    let sensors: [&mut dyn Co2Sensor<Error = ()>; 2] = [&mut first, &mut second];
+    let mut group = SensorGroup::new(sensors);
+    let batch = group.sample(&mut delay, 1_000).unwrap();
+    assert!(batch.is_complete());
+    assert_eq!(0, batch.spread_ms);
+    delay.done();
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn sensor_group_async() {
+    use common::{block_on, AsyncDelay};
+    use libscd::asynchronous::group::SensorGroup;
+
+    let delay = CheckedDelay::new(&delays(3));
+    let mut async_delay = AsyncDelay::new(delay.clone());
+    let mut group = SensorGroup::new(sensors());
+    block_on(group.start()).unwrap();
+
+    let batch = block_on(group.sample(&mut async_delay, 300)).unwrap();
+    assert_eq!(
+        vec![410, 510],
+        batch.iter().map(|m| m.co2).collect::<Vec<_>>()
+    );
+    assert_eq!(200, batch.spread_ms);
+    let mut delay = delay;
+    delay.done();
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "simulator")]
+
+mod common;
+
+use libscd::error::Error;
+use libscd::hotplug::HotPlugEvent;
+
+#[cfg(all(feature = "sync", feature = "scd40"))]
+mod scd4x_sync {
+    use super::*;
+    use embedded_hal::delay::DelayNs;
+    use libscd::simulator::scd4x::{FakeScd4x, Mode};
+    use libscd::synchronous::hotplug::HotPlugSensor;
+    use libscd::synchronous::scd4x::Scd40;
+    use libscd::synchronous::sensor::Co2Sensor;
+    use libscd::variant::SensorVariant;
+
+    #[test]
+    fn reattached_sensor_is_restored() {
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        let mut delay = sim.delay();
+        let mut driver = Scd40::new(sim.clone(), sim.delay());
+        driver.set_volatile_settings_cache(true);
+        driver.set_ambient_pressure(987).unwrap();
+        driver.start_periodic_measurement().unwrap();
+
+        let mut sensor = HotPlugSensor::new(driver);
+        assert_eq!(None, sensor.poll().unwrap());
+
+        sim.detach();
+        for _ in 0..3 {
+            assert!(matches!(sensor.data_ready(), Err(Error::I2C(_))));
+        }
+        assert!(!sensor.is_attached());
+        assert_eq!(Err(Error::NotAllowed), sensor.data_ready());
+        assert_eq!(Some(HotPlugEvent::Detached), sensor.poll().unwrap());
+        assert_eq!(None, sensor.poll().unwrap());
+
+        // A replacement module with the default configuration
+        sim.set_serial_number(0x1234);
+        sim.attach();
+        assert_eq!(Mode::Idle, sim.mode());
+
+        assert_eq!(Some(HotPlugEvent::Attached), sensor.poll().unwrap());
+        assert!(sensor.is_attached());
+        assert_eq!(None, sensor.poll().unwrap());
+        assert_eq!(Mode::Periodic, sim.mode());
+        assert_eq!(987, sim.ambient_pressure());
+
+        delay.delay_ms(5_000);
+        assert!(sensor.data_ready().unwrap());
+        sensor.read_measurement().unwrap();
+    }
+}
+
+#[cfg(all(feature = "sync", feature = "scd30"))]
+mod scd30_sync {
+    use super::*;
+    use embedded_hal::delay::DelayNs;
+    use libscd::simulator::scd30::FakeScd30;
+    use libscd::synchronous::hotplug::HotPlugSensor;
+    use libscd::synchronous::scd30::Scd30;
+    use libscd::synchronous::sensor::Co2Sensor;
+
+    #[test]
+    fn reattached_sensor_is_restored() {
+        let sim = FakeScd30::new();
+        let mut delay = sim.delay();
+        let mut driver = Scd30::new(sim.clone(), sim.delay());
+        driver.start_continuous_measurement(1013).unwrap();
+
+        let mut sensor = HotPlugSensor::new(driver);
+        sensor.set_detach_threshold(1);
+
+        sim.detach();
+        assert!(matches!(sensor.data_ready(), Err(Error::I2C(_))));
+        assert_eq!(Some(HotPlugEvent::Detached), sensor.poll().unwrap());
+
+        // The sensor does not respond while it's booting
+        sim.attach();
+        assert_eq!(None, sensor.poll().unwrap());
+        assert!(!sensor.is_attached());
+
+        delay.delay_ms(2_000);
+        assert_eq!(Some(HotPlugEvent::Attached), sensor.poll().unwrap());
+        assert!(sim.is_measuring());
+        assert_eq!(1013, sim.ambient_pressure());
+
+        delay.delay_ms(2_000);
+        assert!(sensor.data_ready().unwrap());
+    }
+}
+
+#[cfg(all(feature = "async", feature = "scd41"))]
+mod scd4x_async {
+    use super::*;
+    use common::block_on;
+    use libscd::asynchronous::hotplug::HotPlugSensor;
+    use libscd::asynchronous::scd4x::Scd41;
+    use libscd::asynchronous::sensor::Co2Sensor;
+    use libscd::simulator::scd4x::{FakeScd4x, Mode};
+    use libscd::variant::SensorVariant;
+
+    #[test]
+    fn reattached_sensor_is_restored() {
+        block_on(async {
+            let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+            let mut driver = Scd41::new(sim.clone(), sim.delay());
+            driver.set_volatile_settings_cache(true);
+            driver.set_sensor_altitude(500).await.unwrap();
+            driver.start_periodic_measurement().await.unwrap();
+
+            let mut sensor = HotPlugSensor::new(driver);
+            sensor.set_detach_threshold(1);
+
+            sim.detach();
+            assert!(matches!(sensor.data_ready().await, Err(Error::I2C(_))));
+            assert_eq!(Some(HotPlugEvent::Detached), sensor.poll().await.unwrap());
+            assert_eq!(None, sensor.poll().await.unwrap());
+
+            sim.attach();
+            assert_eq!(Some(HotPlugEvent::Attached), sensor.poll().await.unwrap());
+            assert_eq!(Mode::Periodic, sim.mode());
+
+            let driver = sensor.sensor();
+            driver.stop_periodic_measurement().await.unwrap();
+            assert_eq!(500, driver.get_sensor_altitude().await.unwrap());
+        });
+    }
+}
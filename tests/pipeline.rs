@@ -0,0 +1,66 @@
+#![cfg(any(feature = "sync", feature = "async"))]
+
+mod common;
+
+use common::CannedSensor;
+use libscd::pipeline::{MeasurementProcessor, MedianFilter, PlausibilityCheck, WarmUpDiscard};
+
+fn pipeline() -> impl MeasurementProcessor {
+    WarmUpDiscard::new(1)
+        .then(MedianFilter::<3>::new())
+        .then(PlausibilityCheck {
+            max_co2: 1_000,
+            ..PlausibilityCheck::default()
+        })
+}
+
+const CO2: [u16; 7] = [5_000, 400, 420, 9_000, 440, 400, 410];
+
+#[test]
+#[cfg(feature = "sync")]
+fn processed_sensor_sync() {
+    use libscd::synchronous::pipeline::ProcessedSensor;
+
+    let mut sensor = ProcessedSensor::new(CannedSensor::new(&CO2), pipeline());
+    sensor.start_measurement().unwrap();
+
+    let mut co2 = vec![];
+    while sensor.data_ready().unwrap() {
+        co2.push(sensor.read_measurement().unwrap().map(|m| m.co2));
+    }
+    assert_eq!(
+        vec![None, Some(400), Some(410), Some(420), Some(440)],
+        co2[..5]
+    );
+
+    // Restarting the measurement starts the warm-up over
+    sensor.sensor().co2.extend([400, 400]);
+    sensor.start_measurement().unwrap();
+    assert_eq!(None, sensor.read_measurement().unwrap().map(|m| m.co2));
+    assert_eq!(Some(400), sensor.read_measurement().unwrap().map(|m| m.co2));
+
+    let (sensor, _) = sensor.release();
+    assert_eq!(2, sensor.starts);
+}
+
+#[test]
+#[cfg(feature = "async")]
+fn processed_sensor_async() {
+    use common::block_on;
+    use libscd::asynchronous::pipeline::ProcessedSensor;
+
+    let mut sensor = ProcessedSensor::new(CannedSensor::new(&CO2), pipeline());
+    block_on(sensor.start_measurement()).unwrap();
+
+    let mut co2 = vec![];
+    while block_on(sensor.data_ready()).unwrap() {
+        co2.push(block_on(sensor.read_measurement()).unwrap().map(|m| m.co2));
+    }
+    assert_eq!(
+        vec![None, Some(400), Some(410), Some(420), Some(440)],
+        co2[..5]
+    );
+
+    let (sensor, _) = sensor.release();
+    assert_eq!(1, sensor.starts);
+}
@@ -0,0 +1,775 @@
+#![cfg(all(feature = "async", feature = "scd30"))]
+
+mod common;
+
+use common::{block_on, command, command_with_data, word, AsyncDelay};
+use core::num::NonZeroU16;
+use core::time::Duration;
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use libscd::asynchronous::scd30::{Scd30, I2C_ADDRESS};
+use libscd::asynchronous::transport::I2cTransport;
+use libscd::calibration::{MeasurementCorrection, Polynomial};
+use libscd::config::Compensation;
+use libscd::error::Error;
+
+const WRITE_DELAY: u32 = 5;
+const BOOT_DELAY: u32 = 2_000;
+
+fn write(bytes: Vec<u8>) -> I2cTransaction {
+    I2cTransaction::write(I2C_ADDRESS, bytes)
+}
+
+fn read(bytes: Vec<u8>) -> I2cTransaction {
+    I2cTransaction::read(I2C_ADDRESS, bytes)
+}
+
+fn delay(ms: u32) -> DelayTransaction {
+    DelayTransaction::delay_ms(ms)
+}
+
+struct Fixture {
+    sensor: Scd30<I2cTransport<I2cMock, AsyncDelay>>,
+    delay: CheckedDelay,
+}
+
+impl Fixture {
+    fn new(i2c: &[I2cTransaction], delays: &[DelayTransaction]) -> Self {
+        let delay = CheckedDelay::new(delays);
+        Self {
+            sensor: Scd30::new(I2cMock::new(i2c), AsyncDelay::new(delay.clone())),
+            delay,
+        }
+    }
+
+    fn done(mut self) {
+        self.sensor.release().done();
+        self.delay.done();
+    }
+}
+
+#[test]
+fn start_continuous_measurement() {
+    let mut f = Fixture::new(
+        &[write(command_with_data(0x0010, 1013))],
+        &[delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.start_continuous_measurement(1013)).unwrap();
+    f.done();
+}
+
+#[test]
+fn start_continuous_measurement_without_pressure_compensation() {
+    let mut f = Fixture::new(
+        &[write(command_with_data(0x0010, 0))],
+        &[delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.start_continuous_measurement(0)).unwrap();
+    f.done();
+}
+
+#[test]
+fn start_continuous_measurement_with_pressure() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x0010, 950)),
+            write(command_with_data(0x0010, 0)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    block_on(
+        f.sensor
+            .start_continuous_measurement_with_pressure(NonZeroU16::new(950)),
+    )
+    .unwrap();
+    block_on(f.sensor.start_continuous_measurement_with_pressure(None)).unwrap();
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(
+            f.sensor
+                .start_continuous_measurement_with_pressure(NonZeroU16::new(1401))
+        )
+    );
+    f.done();
+}
+
+#[test]
+fn start_continuous_measurement_rejects_invalid_pressure() {
+    let mut f = Fixture::new(&[], &[]);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.start_continuous_measurement(699))
+    );
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.start_continuous_measurement(1401))
+    );
+    f.done();
+}
+
+#[test]
+fn stop_continuous_measurement() {
+    let mut f = Fixture::new(&[write(command(0x0104))], &[delay(WRITE_DELAY)]);
+    block_on(f.sensor.stop_continuous_measurement()).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_measurement_interval() {
+    let mut f = Fixture::new(
+        &[write(command_with_data(0x4600, 60))],
+        &[delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.set_measurement_interval(60)).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_measurement_interval_rejects_invalid_input() {
+    let mut f = Fixture::new(&[], &[]);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_measurement_interval(1))
+    );
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_measurement_interval(1801))
+    );
+    f.done();
+}
+
+#[test]
+fn get_measurement_interval() {
+    let mut f = Fixture::new(
+        &[write(command(0x4600)), read(word(2))],
+        &[delay(WRITE_DELAY)],
+    );
+    assert_eq!(2, block_on(f.sensor.get_measurement_interval()).unwrap());
+    f.done();
+}
+
+#[test]
+fn measurement_interval_duration() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x4600, 60)),
+            write(command(0x4600)),
+            read(word(60)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    block_on(
+        f.sensor
+            .set_measurement_interval_duration(Duration::from_secs(60)),
+    )
+    .unwrap();
+    assert_eq!(
+        Duration::from_secs(60),
+        block_on(f.sensor.get_measurement_interval_duration()).unwrap()
+    );
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(
+            f.sensor
+                .set_measurement_interval_duration(Duration::from_millis(60_500))
+        )
+    );
+    f.done();
+}
+
+#[test]
+fn data_ready() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x0202)),
+            read(word(1)),
+            write(command(0x0202)),
+            read(word(0)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    assert!(block_on(f.sensor.data_ready()).unwrap());
+    assert!(!block_on(f.sensor.data_ready()).unwrap());
+    f.done();
+}
+
+#[test]
+fn read_measurement() {
+    // Section 1.5 of the interface description
+    let response = vec![
+        0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, // CO2
+        0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, // Temperature
+        0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74, // Humidity
+    ];
+
+    let mut f = Fixture::new(
+        &[write(command(0x0300)), read(response)],
+        &[delay(WRITE_DELAY)],
+    );
+    let m = block_on(f.sensor.read_measurement()).unwrap();
+    assert_eq!(439, m.co2);
+    assert!((27.2 - m.temperature).abs() < 0.05);
+    assert!((48.8 - m.humidity).abs() < 0.05);
+    f.done();
+}
+
+#[test]
+fn read_measurement_with_correction() {
+    let response = vec![
+        0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, // CO2
+        0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, // Temperature
+        0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74, // Humidity
+    ];
+
+    let mut f = Fixture::new(
+        &[write(command(0x0300)), read(response)],
+        &[delay(WRITE_DELAY)],
+    );
+    f.sensor
+        .set_measurement_correction(Some(MeasurementCorrection {
+            co2: Polynomial::new([-10.0, 1.0, 0.0001, 0.0]),
+            humidity: Polynomial::linear(2.0, 1.0),
+            ..MeasurementCorrection::default()
+        }));
+
+    let m = block_on(f.sensor.read_measurement()).unwrap();
+    assert_eq!(448, m.co2);
+    assert!((27.2 - m.temperature).abs() < 0.05);
+    assert!((50.8 - m.humidity).abs() < 0.05);
+    f.done();
+}
+
+#[test]
+fn read_measurement_of_zeros() {
+    let mut f = Fixture::new(
+        &[write(command(0x0300)), read(word(0).repeat(6))],
+        &[delay(WRITE_DELAY)],
+    );
+    assert!(matches!(
+        block_on(f.sensor.read_measurement()),
+        Err(Error::NoData)
+    ));
+    f.done();
+}
+
+#[test]
+fn tune_temperature_offset_skips_frames_of_zeros() {
+    let response = vec![
+        0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, // CO2
+        0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, // Temperature
+        0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74, // Humidity
+    ];
+
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x0010, 0)),
+            write(command(0x5403)),
+            read(word(100)),
+            write(command(0x0202)),
+            read(word(1)),
+            write(command(0x0300)),
+            read(word(0).repeat(6)),
+            write(command(0x0202)),
+            read(word(1)),
+            write(command(0x0300)),
+            read(response),
+            write(command_with_data(0x5403, 324)),
+        ],
+        &[
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(250),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+        ],
+    );
+    block_on(f.sensor.start_continuous_measurement(0)).unwrap();
+
+    let report = block_on(f.sensor.tune_temperature_offset(&mut 25.0, 1)).unwrap();
+    assert!((3.24 - report.new_offset).abs() < 0.01);
+    f.done();
+}
+
+#[test]
+fn read_measurement_with_buffer() {
+    let response = vec![
+        0x43, 0xDB, 0xCB, 0x8C, 0x2E, 0x8F, // CO2
+        0x41, 0xD9, 0x70, 0xE7, 0xFF, 0xF5, // Temperature
+        0x42, 0x43, 0xBF, 0x3A, 0x1B, 0x74, // Humidity
+    ];
+
+    let mut f = Fixture::new(
+        &[write(command(0x0300)), read(response.clone())],
+        &[delay(WRITE_DELAY)],
+    );
+    let mut buf = [0; 18];
+    let m = block_on(f.sensor.read_measurement_with_buffer(&mut buf)).unwrap();
+    assert_eq!(439, m.co2);
+    assert_eq!(response, buf);
+    f.done();
+}
+
+#[test]
+fn read_measurement_with_invalid_crc() {
+    let mut response = vec![0; 18];
+    response[2] = 0xFF;
+
+    let mut f = Fixture::new(
+        &[write(command(0x0300)), read(response)],
+        &[delay(WRITE_DELAY)],
+    );
+    assert!(matches!(
+        block_on(f.sensor.read_measurement()),
+        Err(Error::CRC(crc)) if crc.word == 0 && crc.received == 0xFF
+    ));
+    f.done();
+}
+
+#[cfg(feature = "danger-skip-crc")]
+#[test]
+fn read_measurement_without_crc_verification() {
+    // 400 ppm CO2 and zero temperature and humidity
+    let mut response = vec![0; 18];
+    response[0] = 0x43;
+    response[1] = 0xC8;
+    response[2] = 0xFF;
+
+    let mut f = Fixture::new(
+        &[write(command(0x0300)), read(response)],
+        &[delay(WRITE_DELAY)],
+    );
+    f.sensor.dangerously_skip_crc_verification(true);
+    assert_eq!(400, block_on(f.sensor.read_measurement()).unwrap().co2);
+    f.done();
+}
+
+#[test]
+fn enable_automatic_self_calibration() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x5306, 1)),
+            write(command_with_data(0x5306, 0)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.enable_automatic_self_calibration(true)).unwrap();
+    block_on(f.sensor.enable_automatic_self_calibration(false)).unwrap();
+    f.done();
+}
+
+#[test]
+fn get_automatic_self_calibration() {
+    let mut f = Fixture::new(
+        &[write(command(0x5306)), read(word(1))],
+        &[delay(WRITE_DELAY)],
+    );
+    assert!(block_on(f.sensor.get_automatic_self_calibration()).unwrap());
+    f.done();
+}
+
+#[test]
+fn set_forced_recalibration_value() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x0010, 0)),
+            write(command_with_data(0x5204, 450)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.start_continuous_measurement(0)).unwrap();
+    block_on(f.sensor.set_forced_recalibration_value(450)).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_forced_recalibration_value_requires_running_measurement() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x0010, 0)),
+            write(command(0x0104)),
+            write(command(0x0202)),
+            read(word(1)),
+            write(command_with_data(0x5204, 450)),
+        ],
+        &(0..4).map(|_| delay(WRITE_DELAY)).collect::<Vec<_>>(),
+    );
+    assert!(!f.sensor.is_measuring());
+    assert_eq!(
+        Err(Error::NotAllowed),
+        block_on(f.sensor.set_forced_recalibration_value(450))
+    );
+
+    block_on(f.sensor.start_continuous_measurement(0)).unwrap();
+    assert!(f.sensor.is_measuring());
+    block_on(f.sensor.stop_continuous_measurement()).unwrap();
+    assert_eq!(
+        Err(Error::NotAllowed),
+        block_on(f.sensor.set_forced_recalibration_value(450))
+    );
+
+    // The SCD30 resumes the measurement after a power-cycle
+    assert!(block_on(f.sensor.data_ready()).unwrap());
+    assert!(f.sensor.is_measuring());
+    block_on(f.sensor.set_forced_recalibration_value(450)).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_forced_recalibration_value_without_state_check() {
+    let mut f = Fixture::new(
+        &[write(command_with_data(0x5204, 450))],
+        &[delay(WRITE_DELAY)],
+    );
+    f.sensor.set_state_check(false);
+    block_on(f.sensor.set_forced_recalibration_value(450)).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_forced_recalibration_value_rejects_invalid_input() {
+    let mut f = Fixture::new(&[], &[]);
+    f.sensor.set_state_check(false);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_forced_recalibration_value(399))
+    );
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_forced_recalibration_value(2001))
+    );
+    f.done();
+}
+
+#[test]
+fn set_temperature_offset() {
+    let mut f = Fixture::new(
+        &[write(command_with_data(0x5403, 500))],
+        &[delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.set_temperature_offset(500)).unwrap();
+    f.done();
+}
+
+#[test]
+fn get_temperature_offset() {
+    let mut f = Fixture::new(
+        &[write(command(0x5403)), read(word(500))],
+        &[delay(WRITE_DELAY)],
+    );
+    assert_eq!(500, block_on(f.sensor.get_temperature_offset()).unwrap());
+    f.done();
+}
+
+#[test]
+fn adjust_temperature_offset() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x5403)),
+            read(word(500)),
+            write(command_with_data(0x5403, 350)),
+            write(command(0x5403)),
+            read(word(350)),
+            write(command_with_data(0x5403, 0)),
+        ],
+        &(0..4).map(|_| delay(WRITE_DELAY)).collect::<Vec<_>>(),
+    );
+    assert_eq!(Ok(3.5), block_on(f.sensor.adjust_temperature_offset(-1.5)));
+
+    // The offset cannot be negative
+    assert_eq!(Ok(0.0), block_on(f.sensor.adjust_temperature_offset(-10.0)));
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.adjust_temperature_offset(f32::NAN))
+    );
+    f.done();
+}
+
+#[test]
+fn set_altitude_compensation() {
+    let mut f = Fixture::new(
+        &[write(command_with_data(0x5102, 600))],
+        &[delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.set_altitude_compensation(600)).unwrap();
+    f.done();
+}
+
+#[test]
+fn get_altitude_compensation() {
+    let mut f = Fixture::new(
+        &[write(command(0x5102)), read(word(600))],
+        &[delay(WRITE_DELAY)],
+    );
+    assert_eq!(600, block_on(f.sensor.get_altitude_compensation()).unwrap());
+    f.done();
+}
+
+#[test]
+fn compensation_conflict_is_accepted_by_default() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x5102, 600)),
+            write(command_with_data(0x0010, 950)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    assert_eq!(Compensation::None, f.sensor.active_compensation());
+    block_on(f.sensor.set_altitude_compensation(600)).unwrap();
+    assert_eq!(Compensation::Altitude(600), f.sensor.active_compensation());
+    block_on(f.sensor.start_continuous_measurement(950)).unwrap();
+    assert_eq!(
+        Compensation::AmbientPressure(950),
+        f.sensor.active_compensation()
+    );
+    f.done();
+}
+
+#[test]
+fn compensation_conflict_check_rejects_both_compensations() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x5102, 600)),
+            write(command_with_data(0x0010, 0)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    f.sensor.set_compensation_conflict_check(true);
+    block_on(f.sensor.set_altitude_compensation(600)).unwrap();
+    assert_eq!(
+        Err(Error::CompensationConflict),
+        block_on(f.sensor.start_continuous_measurement(950))
+    );
+
+    // Disabling the pressure compensation does not conflict
+    block_on(f.sensor.start_continuous_measurement(0)).unwrap();
+    assert_eq!(Compensation::Altitude(600), f.sensor.active_compensation());
+    f.done();
+}
+
+#[test]
+fn back_to_back_configuration_writes_are_delayed() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x5403, 500)),
+            write(command_with_data(0x5102, 600)),
+            write(command_with_data(0x4600, 5)),
+        ],
+        &[delay(WRITE_DELAY), delay(WRITE_DELAY), delay(WRITE_DELAY)],
+    );
+    block_on(f.sensor.set_temperature_offset(500)).unwrap();
+    block_on(f.sensor.set_altitude_compensation(600)).unwrap();
+    block_on(f.sensor.set_measurement_interval(5)).unwrap();
+    f.done();
+}
+
+#[test]
+fn validate_configuration() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x5403)),
+            read(word(250)),
+            write(command(0x5102)),
+            read(word(0)),
+            write(command(0x5306)),
+            read(word(1)),
+            write(command(0x4600)),
+            read(word(2)),
+        ],
+        &[
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+        ],
+    );
+
+    let report = block_on(f.sensor.validate_configuration(true)).unwrap();
+    assert!((2.5 - report.snapshot.temperature_offset).abs() < 0.001);
+    assert_eq!(None, report.snapshot.ambient_pressure);
+    assert_eq!(Some(2), report.snapshot.measurement_interval);
+    assert!(report.is_consistent());
+    f.done();
+}
+
+#[test]
+fn read_firmware_version() {
+    let mut f = Fixture::new(
+        &[write(command(0xD100)), read(word(0x0342))],
+        &[delay(WRITE_DELAY)],
+    );
+    assert_eq!(
+        (3, 0x42),
+        block_on(f.sensor.read_firmware_version()).unwrap()
+    );
+    f.done();
+}
+
+#[test]
+fn soft_reset() {
+    let mut f = Fixture::new(
+        &[write(command(0xD304))],
+        &[delay(WRITE_DELAY), delay(BOOT_DELAY)],
+    );
+    block_on(f.sensor.soft_reset()).unwrap();
+    f.done();
+}
+
+#[test]
+fn soft_reset_and_restore() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x5403, 500)),
+            write(command_with_data(0x5102, 600)),
+            write(command_with_data(0x0010, 950)),
+            write(command(0xD304)),
+            // The temperature offset was lost, the altitude is unchanged
+            write(command(0x5403)),
+            read(word(0)),
+            write(command_with_data(0x5403, 500)),
+            write(command(0x5102)),
+            read(word(600)),
+            write(command_with_data(0x0010, 950)),
+        ],
+        &[
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(BOOT_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+        ],
+    );
+    block_on(f.sensor.set_temperature_offset(500)).unwrap();
+    block_on(f.sensor.set_altitude_compensation(600)).unwrap();
+    block_on(f.sensor.start_continuous_measurement(950)).unwrap();
+
+    let settings = *f.sensor.settings();
+    assert_eq!(Some(500), settings.temperature_offset);
+    assert_eq!(Some(600), settings.altitude);
+    assert_eq!(Some(950), settings.ambient_pressure);
+    assert_eq!(None, settings.measurement_interval);
+
+    block_on(f.sensor.soft_reset_and_restore()).unwrap();
+    assert!(f.sensor.is_measuring());
+    f.done();
+}
+
+#[test]
+fn recovers_after_repeated_crc_failures() {
+    let corrupted = vec![0x00, 0x01, 0x00];
+
+    let mut f = Fixture::new(
+        &[
+            write(command(0x4600)),
+            read(corrupted.clone()),
+            write(command(0x4600)),
+            read(corrupted),
+            write(command(0xD304)),
+        ],
+        &[
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(BOOT_DELAY),
+        ],
+    );
+    f.sensor.set_crc_recovery_threshold(2);
+
+    assert!(matches!(
+        block_on(f.sensor.get_measurement_interval()),
+        Err(Error::CRC(_))
+    ));
+    assert_eq!(
+        Err(Error::Recovered),
+        block_on(f.sensor.get_measurement_interval())
+    );
+    f.done();
+}
+
+#[test]
+fn restores_settings_after_crc_recovery() {
+    let corrupted = vec![0x00, 0x01, 0x00];
+
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x4600, 60)),
+            write(command_with_data(0x5403, 150)),
+            write(command_with_data(0x0010, 1013)),
+            write(command(0x4600)),
+            read(corrupted),
+            write(command(0xD304)),
+            write(command_with_data(0x4600, 60)),
+            write(command_with_data(0x5403, 150)),
+            write(command_with_data(0x0010, 1013)),
+        ],
+        &[
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(BOOT_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+            delay(WRITE_DELAY),
+        ],
+    );
+    block_on(f.sensor.set_measurement_interval(60)).unwrap();
+    block_on(f.sensor.set_temperature_offset(150)).unwrap();
+    block_on(f.sensor.start_continuous_measurement(1013)).unwrap();
+    f.sensor.set_crc_recovery_threshold(1);
+
+    assert_eq!(
+        Err(Error::Recovered),
+        block_on(f.sensor.get_measurement_interval())
+    );
+    assert!(f.sensor.is_measuring());
+    f.done();
+}
+
+#[test]
+fn new_after_power_on() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let i2c = [
+        write(command(0xD100)).with_error(nack),
+        write(command(0xD100)),
+        read(word(0x0342)),
+    ];
+    let delays = [delay(BOOT_DELAY), delay(100), delay(WRITE_DELAY)];
+
+    let mut delay = CheckedDelay::new(&delays);
+    let sensor = block_on(Scd30::new_after_power_on(
+        I2cMock::new(&i2c),
+        AsyncDelay::new(delay.clone()),
+    ))
+    .unwrap();
+    sensor.release().done();
+    delay.done();
+}
+
+#[test]
+fn new_after_power_on_gives_up() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let i2c = vec![write(command(0xD100)).with_error(nack); 5];
+    let mut delays = vec![delay(BOOT_DELAY)];
+    delays.extend((0..4).map(|_| delay(100)));
+
+    let mut mock = I2cMock::new(&i2c);
+    let mut delay = CheckedDelay::new(&delays);
+    assert!(matches!(
+        block_on(Scd30::new_after_power_on(
+            mock.clone(),
+            AsyncDelay::new(delay.clone())
+        )),
+        Err(Error::I2C(ErrorKind::NoAcknowledge(_)))
+    ));
+    mock.done();
+    delay.done();
+}
@@ -0,0 +1,1317 @@
+#![cfg(all(feature = "async", any(feature = "scd40", feature = "scd41")))]
+
+mod common;
+
+use common::{block_on, command, command_with_data, word, words, AsyncDelay};
+use core::time::Duration;
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use libscd::asynchronous::scd4x::I2C_ADDRESS;
+use libscd::asynchronous::transport::I2cTransport;
+use libscd::calibration::{MeasurementCorrection, Polynomial};
+use libscd::config::Compensation;
+use libscd::error::Error;
+use libscd::variant::SensorVariant;
+
+#[cfg(feature = "scd40")]
+type Sensor = libscd::asynchronous::scd4x::Scd40<I2cTransport<I2cMock, AsyncDelay>>;
+
+#[cfg(not(feature = "scd40"))]
+type Sensor = libscd::asynchronous::scd4x::Scd41<I2cTransport<I2cMock, AsyncDelay>>;
+
+fn write(bytes: Vec<u8>) -> I2cTransaction {
+    I2cTransaction::write(I2C_ADDRESS, bytes)
+}
+
+fn read(bytes: Vec<u8>) -> I2cTransaction {
+    I2cTransaction::read(I2C_ADDRESS, bytes)
+}
+
+fn delay(ms: u32) -> DelayTransaction {
+    DelayTransaction::delay_ms(ms)
+}
+
+struct Fixture {
+    sensor: Sensor,
+    delay: CheckedDelay,
+}
+
+impl Fixture {
+    fn new(i2c: &[I2cTransaction], delays: &[DelayTransaction]) -> Self {
+        let delay = CheckedDelay::new(delays);
+        Self {
+            sensor: Sensor::new(I2cMock::new(i2c), AsyncDelay::new(delay.clone())),
+            delay,
+        }
+    }
+
+    fn done(mut self) {
+        self.sensor.release().done();
+        self.delay.done();
+    }
+}
+
+#[test]
+fn start_and_stop_periodic_measurement() {
+    let mut f = Fixture::new(
+        &[write(command(0x21b1)), write(command(0x3f86))],
+        &[delay(0), delay(500)],
+    );
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+    block_on(f.sensor.stop_periodic_measurement()).unwrap();
+    f.done();
+}
+
+#[test]
+fn force_stop() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+    let mut f = Fixture::new(
+        &[
+            write(command(0x21b1)),
+            write(command(0x3f86)).with_error(nack),
+            write(command_with_data(0x2427, 100)),
+        ],
+        &[delay(0), delay(500), delay(1)],
+    );
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+
+    // The missing acknowledgement is tolerated
+    block_on(f.sensor.force_stop());
+    block_on(f.sensor.set_sensor_altitude(100)).unwrap();
+    f.done();
+}
+
+#[test]
+fn ensure_idle() {
+    let mut f = Fixture::new(
+        &[write(command(0x21b1)), write(command(0x3f86))],
+        &[delay(0), delay(500)],
+    );
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+    assert!(block_on(f.sensor.ensure_idle()).unwrap());
+    assert!(!block_on(f.sensor.ensure_idle()).unwrap());
+    f.done();
+}
+
+#[cfg(feature = "scd40")]
+#[test]
+fn reset_to_known_state() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+    let mut f = Fixture::new(
+        &[
+            write(command(0x3f86)).with_error(nack),
+            write(command(0x3646)),
+        ],
+        &[delay(500), delay(30)],
+    );
+    block_on(f.sensor.reset_to_known_state()).unwrap();
+    f.done();
+}
+
+#[test]
+fn start_low_power_periodic_measurement() {
+    let mut f = Fixture::new(&[write(command(0x21ac))], &[delay(0)]);
+    block_on(f.sensor.start_low_power_periodic_measurement()).unwrap();
+    f.done();
+}
+
+#[test]
+fn commands_are_rejected_while_measuring() {
+    let mut f = Fixture::new(&[write(command(0x21b1))], &[delay(0)]);
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+
+    assert_eq!(
+        Err(Error::NotAllowed),
+        block_on(f.sensor.set_sensor_altitude(100))
+    );
+    assert_eq!(Err(Error::NotAllowed), block_on(f.sensor.serial_number()));
+    assert_eq!(
+        Err(Error::NotAllowed),
+        block_on(f.sensor.perform_self_test())
+    );
+    f.done();
+}
+
+#[test]
+fn data_ready() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0xe4b8)),
+            read(word(0x8006)),
+            write(command(0xe4b8)),
+            read(word(0x8000)),
+        ],
+        &[delay(1), delay(1)],
+    );
+    assert!(block_on(f.sensor.data_ready()).unwrap());
+    assert!(!block_on(f.sensor.data_ready()).unwrap());
+    f.done();
+}
+
+#[test]
+fn read_measurement() {
+    // Section 3.6.2 of the datasheet
+    let mut f = Fixture::new(
+        &[
+            write(command(0xec05)),
+            read(words(&[0x01f4, 0x6667, 0x5eb9])),
+        ],
+        &[delay(1)],
+    );
+    let m = block_on(f.sensor.read_measurement()).unwrap();
+    assert_eq!(500, m.co2);
+    assert!((25.0 - m.temperature).abs() < 0.01);
+    assert!((37.0 - m.humidity).abs() < 0.01);
+    f.done();
+}
+
+#[test]
+fn read_measurement_with_correction() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0xec05)),
+            read(words(&[0x01f4, 0x6667, 0x5eb9])),
+        ],
+        &[delay(1)],
+    );
+    f.sensor
+        .set_measurement_correction(Some(MeasurementCorrection {
+            co2: Polynomial::linear(20.0, 1.1),
+            temperature: Polynomial::linear(-1.0, 1.0),
+            ..MeasurementCorrection::default()
+        }));
+
+    let m = block_on(f.sensor.read_measurement()).unwrap();
+    assert_eq!(570, m.co2);
+    assert!((24.0 - m.temperature).abs() < 0.01);
+    assert!((37.0 - m.humidity).abs() < 0.01);
+    f.done();
+}
+
+#[test]
+fn read_measurement_of_zeros() {
+    let mut f = Fixture::new(
+        &[write(command(0xec05)), read(words(&[0, 0, 0]))],
+        &[delay(1)],
+    );
+    assert!(matches!(
+        block_on(f.sensor.read_measurement()),
+        Err(Error::NoData)
+    ));
+    f.done();
+}
+
+#[test]
+fn wait_for_first_measurement() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x21ac)),
+            // The warm-up sample is discarded
+            write(command(0xe4b8)),
+            read(word(0x8006)),
+            write(command(0xec05)),
+            read(words(&[0x0320, 0x6667, 0x5eb9])),
+            write(command(0xe4b8)),
+            read(word(0x8000)),
+            write(command(0xe4b8)),
+            read(word(0x8006)),
+            write(command(0xec05)),
+            read(words(&[0x01f4, 0x6667, 0x5eb9])),
+        ],
+        &[
+            delay(0),
+            delay(30_000),
+            delay(1),
+            delay(1),
+            delay(1),
+            delay(250),
+            delay(1),
+            delay(1),
+        ],
+    );
+    assert!(matches!(
+        block_on(f.sensor.wait_for_first_measurement()),
+        Err(Error::NotAllowed)
+    ));
+
+    block_on(f.sensor.start_low_power_periodic_measurement()).unwrap();
+    f.sensor.set_warm_up_samples(1);
+    assert_eq!(
+        500,
+        block_on(f.sensor.wait_for_first_measurement()).unwrap().co2
+    );
+    f.done();
+}
+
+#[test]
+fn expected_measurement_interval() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x21b1)),
+            write(command(0x3f86)),
+            write(command(0x21ac)),
+        ],
+        &[delay(0), delay(500), delay(0)],
+    );
+    assert_eq!(None, f.sensor.expected_measurement_interval());
+
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+    assert_eq!(
+        Some(Duration::from_secs(5)),
+        f.sensor.expected_measurement_interval()
+    );
+
+    block_on(f.sensor.stop_periodic_measurement()).unwrap();
+    assert_eq!(None, f.sensor.expected_measurement_interval());
+
+    block_on(f.sensor.start_low_power_periodic_measurement()).unwrap();
+    assert_eq!(
+        Some(Duration::from_secs(30)),
+        f.sensor.expected_measurement_interval()
+    );
+    f.done();
+}
+
+#[test]
+fn operating_time() {
+    const MINUTE: u64 = 60 * 1_000;
+
+    let mut f = Fixture::new(
+        &[write(command(0x21b1)), write(command(0x3f86))],
+        &[delay(0), delay(500)],
+    );
+    f.sensor.set_operating_time(Duration::from_secs(50 * 60));
+
+    // Not measuring
+    assert!(!f.sensor.update_operating_time(0));
+
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+    assert!(!f.sensor.update_operating_time(10 * MINUTE));
+    assert!(!f.sensor.update_operating_time(15 * MINUTE));
+    assert!(f.sensor.update_operating_time(30 * MINUTE));
+    assert_eq!(Duration::from_secs(70 * 60), f.sensor.operating_time());
+
+    // The time after the last update is lost
+    block_on(f.sensor.stop_periodic_measurement()).unwrap();
+    assert!(!f.sensor.update_operating_time(90 * MINUTE));
+    assert_eq!(Duration::from_secs(70 * 60), f.sensor.operating_time());
+    f.done();
+}
+
+#[test]
+fn suspend_and_resume() {
+    let mut f = Fixture::new(&[write(command(0x21ac))], &[delay(0)]);
+    f.sensor.set_volatile_settings_cache(true);
+    block_on(f.sensor.start_low_power_periodic_measurement()).unwrap();
+
+    let state = f.sensor.suspend();
+    assert!(state.is_measuring());
+    assert_eq!(I2C_ADDRESS, state.address());
+    f.done();
+
+    // The sensor kept measuring during the deep sleep of the MCU
+    let delay = CheckedDelay::new(&[delay(1)]);
+    let i2c = I2cMock::new(&[
+        write(command(0xec05)),
+        read(words(&[0x01f4, 0x6667, 0x5eb9])),
+    ]);
+    let mut f = Fixture {
+        sensor: Sensor::resume(i2c, AsyncDelay::new(delay.clone()), state),
+        delay,
+    };
+    assert_eq!(
+        Some(Duration::from_secs(30)),
+        f.sensor.expected_measurement_interval()
+    );
+    assert!(f.sensor.volatile_settings().is_some());
+    assert_eq!(Err(Error::NotAllowed), block_on(f.sensor.serial_number()));
+    assert_eq!(500, block_on(f.sensor.read_measurement()).unwrap().co2);
+    assert!(f.sensor.suspend().is_measuring());
+    f.done();
+}
+
+#[test]
+fn read_measurement_with_buffer() {
+    let response = words(&[0x01f4, 0x6667, 0x5eb9]);
+
+    let mut f = Fixture::new(
+        &[write(command(0xec05)), read(response.clone())],
+        &[delay(1)],
+    );
+    let mut buf = [0; 9];
+    let m = block_on(f.sensor.read_measurement_with_buffer(&mut buf)).unwrap();
+    assert_eq!(500, m.co2);
+    assert_eq!(response, buf);
+    f.done();
+}
+
+#[test]
+fn read_measurement_with_invalid_crc() {
+    let mut response = words(&[0x01f4, 0x6667, 0x5eb9]);
+    response[5] ^= 0xFF;
+
+    let mut f = Fixture::new(&[write(command(0xec05)), read(response)], &[delay(1)]);
+    assert!(matches!(
+        block_on(f.sensor.read_measurement()),
+        Err(Error::CRC(crc)) if crc.word == 1
+    ));
+    f.done();
+}
+
+#[cfg(feature = "danger-skip-crc")]
+#[test]
+fn read_measurement_without_crc_verification() {
+    let mut response = words(&[0x01f4, 0x6667, 0x5eb9]);
+    response[5] ^= 0xFF;
+
+    let mut f = Fixture::new(&[write(command(0xec05)), read(response)], &[delay(1)]);
+    f.sensor.dangerously_skip_crc_verification(true);
+    assert_eq!(500, block_on(f.sensor.read_measurement()).unwrap().co2);
+    f.done();
+}
+
+#[test]
+fn set_temperature_offset() {
+    // Section 3.7.1 of the datasheet
+    let mut f = Fixture::new(&[write(command_with_data(0x241d, 0x07e6))], &[delay(1)]);
+    block_on(f.sensor.set_temperature_offset(5.4)).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_temperature_offset_rejects_invalid_input() {
+    let mut f = Fixture::new(&[], &[]);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_temperature_offset(-1.0))
+    );
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_temperature_offset(f32::NAN))
+    );
+
+    // Outside of the range recommended by the datasheet
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_temperature_offset(20.1))
+    );
+    f.done();
+}
+
+#[test]
+fn get_temperature_offset() {
+    // Section 3.7.2 of the datasheet
+    let mut f = Fixture::new(&[write(command(0x2318)), read(word(0x0912))], &[delay(1)]);
+    let offset = block_on(f.sensor.get_temperature_offset()).unwrap();
+    assert!((6.2 - offset).abs() < 0.01);
+    f.done();
+}
+
+#[test]
+fn set_temperature_offset_unchecked() {
+    let mut f = Fixture::new(&[write(command_with_data(0x241d, 0x2492))], &[delay(1)]);
+    block_on(f.sensor.set_temperature_offset_unchecked(25.0)).unwrap();
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_temperature_offset_unchecked(-1.0))
+    );
+    f.done();
+}
+
+#[test]
+fn temperature_offset_ticks() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x241d, 0x07e7)),
+            write(command(0x2318)),
+            read(word(0x07e7)),
+        ],
+        &[delay(1), delay(1)],
+    );
+    block_on(f.sensor.set_temperature_offset_ticks(0x07e7)).unwrap();
+    assert_eq!(
+        Ok(0x07e7),
+        block_on(f.sensor.get_temperature_offset_ticks())
+    );
+    f.done();
+}
+
+#[test]
+fn adjust_temperature_offset() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x2318)),
+            read(word(0x0912)),
+            write(command_with_data(0x241d, 1947)),
+        ],
+        &[delay(1), delay(1)],
+    );
+    let offset = block_on(f.sensor.adjust_temperature_offset(-1.0)).unwrap();
+    assert!((5.2 - offset).abs() < 0.01);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.adjust_temperature_offset(f32::INFINITY))
+    );
+    f.done();
+}
+
+#[test]
+fn set_sensor_altitude() {
+    let mut f = Fixture::new(&[write(command_with_data(0x2427, 0x07d0))], &[delay(1)]);
+    block_on(f.sensor.set_sensor_altitude(2_000)).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_sensor_altitude_rejects_invalid_input() {
+    let mut f = Fixture::new(&[], &[]);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_sensor_altitude(3_001))
+    );
+    f.done();
+}
+
+#[test]
+fn get_sensor_altitude() {
+    let mut f = Fixture::new(&[write(command(0x2322)), read(word(0x044c))], &[delay(1)]);
+    assert_eq!(1_100, block_on(f.sensor.get_sensor_altitude()).unwrap());
+    f.done();
+}
+
+#[test]
+fn set_ambient_pressure() {
+    let mut f = Fixture::new(&[write(command_with_data(0xe000, 0x03db))], &[delay(1)]);
+    block_on(f.sensor.set_ambient_pressure(987)).unwrap();
+    f.done();
+}
+
+#[test]
+fn set_ambient_pressure_rejects_invalid_input() {
+    let mut f = Fixture::new(&[], &[]);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_ambient_pressure(699))
+    );
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_ambient_pressure(1201))
+    );
+    f.done();
+}
+
+#[test]
+fn set_ambient_pressure_f32() {
+    let mut f = Fixture::new(&[write(command_with_data(0xe000, 1_013))], &[delay(1)]);
+    block_on(f.sensor.set_ambient_pressure_f32(1_013.25)).unwrap();
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.set_ambient_pressure_f32(f32::NAN))
+    );
+    f.done();
+}
+
+#[test]
+fn get_ambient_pressure() {
+    let mut f = Fixture::new(&[write(command(0xe000)), read(word(0x03db))], &[delay(1)]);
+    assert_eq!(987, block_on(f.sensor.get_ambient_pressure()).unwrap());
+    f.done();
+}
+
+#[test]
+fn compensation_conflict_is_accepted_by_default() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x2427, 0x07d0)),
+            write(command_with_data(0xe000, 0x03db)),
+        ],
+        &[delay(1), delay(1)],
+    );
+    assert_eq!(Compensation::None, f.sensor.active_compensation());
+    block_on(f.sensor.set_sensor_altitude(2_000)).unwrap();
+    assert_eq!(
+        Compensation::Altitude(2_000),
+        f.sensor.active_compensation()
+    );
+    block_on(f.sensor.set_ambient_pressure(987)).unwrap();
+    assert_eq!(
+        Compensation::AmbientPressure(987),
+        f.sensor.active_compensation()
+    );
+    f.done();
+}
+
+#[test]
+fn compensation_conflict_check_rejects_both_compensations() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0xe000, 0x03db)),
+            write(command(0x3646)),
+            write(command_with_data(0x2427, 0x07d0)),
+        ],
+        &[delay(1), delay(30), delay(1)],
+    );
+    f.sensor.set_compensation_conflict_check(true);
+    block_on(f.sensor.set_ambient_pressure(987)).unwrap();
+    assert_eq!(
+        Err(Error::CompensationConflict),
+        block_on(f.sensor.set_sensor_altitude(2_000))
+    );
+
+    // The ambient pressure does not survive a reinit
+    block_on(f.sensor.reinit()).unwrap();
+    block_on(f.sensor.set_sensor_altitude(2_000)).unwrap();
+    assert_eq!(
+        Compensation::Altitude(2_000),
+        f.sensor.active_compensation()
+    );
+    f.done();
+}
+
+#[test]
+fn enable_automatic_self_calibration() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x2416, 1)),
+            write(command_with_data(0x2416, 0)),
+        ],
+        &[delay(1), delay(1)],
+    );
+    block_on(f.sensor.enable_automatic_self_calibration(true)).unwrap();
+    block_on(f.sensor.enable_automatic_self_calibration(false)).unwrap();
+    f.done();
+}
+
+#[test]
+fn get_automatic_self_calibration() {
+    let mut f = Fixture::new(&[write(command(0x2313)), read(word(0))], &[delay(1)]);
+    assert!(!block_on(f.sensor.get_automatic_self_calibration()).unwrap());
+    f.done();
+}
+
+#[test]
+fn set_automatic_self_calibration_target() {
+    let mut f = Fixture::new(&[write(command_with_data(0x243a, 420))], &[delay(1)]);
+    block_on(f.sensor.set_automatic_self_calibration_target(420)).unwrap();
+    f.done();
+}
+
+#[test]
+fn get_automatic_self_calibration_target() {
+    let mut f = Fixture::new(&[write(command(0x233f)), read(word(420))], &[delay(1)]);
+    assert_eq!(
+        420,
+        block_on(f.sensor.get_automatic_self_calibration_target()).unwrap()
+    );
+    f.done();
+}
+
+#[test]
+fn perform_forced_recalibration() {
+    // Section 3.8.1 of the datasheet
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0x362f, 0x01e0)),
+            read(word(0x7fce)),
+            write(command_with_data(0x362f, 0x01e0)),
+            read(word(0xffff)),
+        ],
+        &[delay(400), delay(400)],
+    );
+    f.sensor.set_frc_runtime_check(false);
+    assert_eq!(
+        Some(-50),
+        block_on(f.sensor.perform_forced_recalibration(480)).unwrap()
+    );
+    assert_eq!(
+        None,
+        block_on(f.sensor.perform_forced_recalibration(480)).unwrap()
+    );
+    f.done();
+}
+
+#[test]
+fn frc_procedure_restores_low_power_mode_on_failure() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data);
+    let mut i2c = vec![write(command(0x21ac))];
+    let mut delays = vec![delay(0)];
+    for _ in 0..36 {
+        i2c.extend([
+            write(command(0xe4b8)),
+            read(word(0x0001)),
+            write(command(0xec05)),
+            read(words(&[0x01f4, 0x6667, 0x5eb9])),
+        ]);
+        delays.extend([delay(1), delay(1)]);
+    }
+    i2c.extend([
+        write(command(0x3f86)),
+        write(command_with_data(0x362f, 0x01e0)).with_error(nack),
+        write(command(0x21ac)),
+    ]);
+    delays.extend([delay(500), delay(0)]);
+
+    let mut f = Fixture::new(&i2c, &delays);
+    block_on(f.sensor.start_low_power_periodic_measurement()).unwrap();
+    assert_eq!(
+        Err(Error::I2C(nack)),
+        block_on(f.sensor.run_frc_procedure(480))
+    );
+    assert_eq!(
+        Some(Duration::from_secs(30)),
+        f.sensor.expected_measurement_interval()
+    );
+    f.done();
+}
+
+#[test]
+fn perform_forced_recalibration_requires_runtime() {
+    let mut i2c = vec![write(command(0x21b1))];
+    let mut delays = vec![delay(0)];
+    for _ in 0..36 {
+        i2c.extend([
+            write(command(0xec05)),
+            read(words(&[0x01f4, 0x6667, 0x5eb9])),
+        ]);
+        delays.push(delay(1));
+    }
+    i2c.extend([
+        write(command(0x3f86)),
+        write(command_with_data(0x362f, 0x01e0)),
+        read(word(0x7fce)),
+    ]);
+    delays.extend([delay(500), delay(400)]);
+
+    let mut f = Fixture::new(&i2c, &delays);
+    assert_eq!(
+        Err(Error::InsufficientRuntime),
+        block_on(f.sensor.perform_forced_recalibration(480))
+    );
+
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+    for _ in 0..36 {
+        block_on(f.sensor.read_measurement()).unwrap();
+    }
+    block_on(f.sensor.stop_periodic_measurement()).unwrap();
+
+    assert_eq!(
+        Some(-50),
+        block_on(f.sensor.perform_forced_recalibration(480)).unwrap()
+    );
+    f.done();
+}
+
+#[test]
+fn validate_configuration() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0xe000, 0x03db)),
+            write(command(0x2318)),
+            read(word(0)),
+            write(command(0x2322)),
+            read(word(0x07d0)),
+            write(command(0x2313)),
+            read(word(1)),
+            write(command(0x233f)),
+            read(word(600)),
+        ],
+        &[delay(1), delay(1), delay(1), delay(1), delay(1)],
+    );
+    block_on(f.sensor.set_ambient_pressure(987)).unwrap();
+
+    let report = block_on(f.sensor.validate_configuration(true)).unwrap();
+    assert_eq!(2_000, report.snapshot.altitude);
+    assert_eq!(Some(987), report.snapshot.ambient_pressure);
+    assert_eq!(Some(600), report.snapshot.automatic_self_calibration_target);
+    assert!(report.missing_temperature_offset);
+    assert!(report.asc_target_off_baseline);
+    assert!(report.compensation_conflict);
+    assert!(!report.asc_interval_mismatch);
+    f.done();
+}
+
+#[test]
+fn persist_settings() {
+    let mut f = Fixture::new(&[write(command(0x3615))], &[delay(800)]);
+    block_on(f.sensor.persists_settings()).unwrap();
+    f.done();
+}
+
+#[test]
+fn apply_settings() {
+    use libscd::config::Scd4xSetting;
+
+    let mut f = Fixture::new(
+        &[
+            write(command(0x21b1)),
+            write(command(0x3f86)),
+            write(command_with_data(0x241d, 0x07e6)),
+            write(command_with_data(0x2427, 500)),
+            write(command_with_data(0x2416, 0)),
+            write(command(0x3615)),
+            write(command(0x21b1)),
+        ],
+        &[
+            delay(0),
+            delay(500),
+            delay(1),
+            delay(1),
+            delay(1),
+            delay(800),
+            delay(0),
+        ],
+    );
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+    block_on(f.sensor.apply_settings(
+        &[
+            Scd4xSetting::TemperatureOffset(5.4),
+            Scd4xSetting::SensorAltitude(500),
+            Scd4xSetting::AutomaticSelfCalibration(false),
+        ],
+        true,
+    ))
+    .unwrap();
+    f.done();
+}
+
+#[test]
+fn apply_settings_rejects_invalid_input() {
+    use libscd::config::Scd4xSetting;
+
+    // Nothing is sent if any of the settings is invalid
+    let mut f = Fixture::new(&[], &[]);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.apply_settings(
+            &[
+                Scd4xSetting::SensorAltitude(500),
+                Scd4xSetting::AmbientPressure(600),
+            ],
+            false,
+        ))
+    );
+    f.done();
+}
+
+#[test]
+fn defer_setting() {
+    use libscd::config::Scd4xSetting;
+
+    let mut f = Fixture::new(
+        &[
+            write(command(0x21b1)),
+            write(command_with_data(0xe000, 1_000)),
+            write(command(0x3f86)),
+            write(command_with_data(0x2427, 600)),
+        ],
+        &[delay(0), delay(1), delay(500), delay(1)],
+    );
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+
+    // The sensor altitude cannot be set while measuring, so it's queued
+    assert_eq!(
+        Ok(false),
+        block_on(f.sensor.defer_setting(Scd4xSetting::SensorAltitude(500)))
+    );
+    assert_eq!(
+        Ok(true),
+        block_on(f.sensor.defer_setting(Scd4xSetting::AmbientPressure(1_000)))
+    );
+    assert_eq!(
+        Ok(false),
+        block_on(f.sensor.defer_setting(Scd4xSetting::SensorAltitude(600)))
+    );
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.defer_setting(Scd4xSetting::SensorAltitude(5_000)))
+    );
+    assert_eq!(Some(600), f.sensor.pending_settings().sensor_altitude);
+
+    // Stopping the measurement writes the queued settings
+    block_on(f.sensor.stop_periodic_measurement()).unwrap();
+    assert!(f.sensor.pending_settings().is_empty());
+    f.done();
+}
+
+#[test]
+fn serial_number() {
+    // Section 3.9.2 of the datasheet
+    let mut f = Fixture::new(
+        &[
+            write(command(0x3682)),
+            read(vec![0xf8, 0x96, 0x31, 0x9f, 0x07, 0xc2, 0x3b, 0xbe, 0x89]),
+        ],
+        &[delay(1)],
+    );
+    assert_eq!(
+        273_325_796_834_238,
+        block_on(f.sensor.serial_number()).unwrap()
+    );
+    f.done();
+}
+
+#[test]
+fn get_sensor_variant() {
+    let mut f = Fixture::new(&[write(command(0x202f)), read(word(0x1440))], &[delay(1)]);
+    assert_eq!(
+        SensorVariant::Scd41,
+        block_on(f.sensor.get_sensor_variant()).unwrap()
+    );
+    f.done();
+}
+
+#[test]
+fn get_sensor_variant_info() {
+    let mut f = Fixture::new(&[write(command(0x202f)), read(word(0x5441))], &[delay(1)]);
+    let info = block_on(f.sensor.get_sensor_variant_info()).unwrap();
+    assert_eq!(Some(SensorVariant::Scd43), info.variant());
+    assert_eq!(0x441, info.firmware_revision());
+    f.done();
+}
+
+#[test]
+fn perform_self_test() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x3639)),
+            read(word(0)),
+            write(command(0x3639)),
+            read(word(0x0010)),
+        ],
+        &[delay(10_000), delay(10_000)],
+    );
+    assert!(block_on(f.sensor.perform_self_test()).unwrap());
+    assert!(!block_on(f.sensor.perform_self_test()).unwrap());
+    f.done();
+}
+
+#[test]
+fn perform_self_test_with_progress() {
+    use core::ops::ControlFlow;
+    use libscd::progress::Progress;
+
+    let mut f = Fixture::new(
+        &[write(command(0x3639)), read(word(0))],
+        &(0..100).map(|_| delay(100)).collect::<Vec<_>>(),
+    );
+
+    let mut reports = Vec::new();
+    let passed = block_on(
+        f.sensor
+            .perform_self_test_with_progress(|progress: Progress| {
+                reports.push(progress);
+                ControlFlow::Continue(())
+            }),
+    )
+    .unwrap();
+    assert!(passed);
+    assert_eq!(100, reports.len());
+    assert_eq!(1, reports[0].percent());
+    assert_eq!(9_900, reports[0].remaining_ms());
+    assert_eq!(100, reports[99].percent());
+    f.done();
+}
+
+#[test]
+fn perform_self_test_with_progress_aborted() {
+    use core::ops::ControlFlow;
+    use libscd::progress::Progress;
+
+    let mut f = Fixture::new(
+        &[
+            write(command(0x3639)),
+            write(command_with_data(0x2427, 0x07d0)),
+        ],
+        &[delay(100), delay(100), delay(100), delay(9_700), delay(1)],
+    );
+
+    assert_eq!(
+        Err(Error::Aborted),
+        block_on(
+            f.sensor
+                .perform_self_test_with_progress(|progress: Progress| {
+                    match progress.elapsed_ms < 300 {
+                        true => ControlFlow::Continue(()),
+                        false => ControlFlow::Break(()),
+                    }
+                })
+        )
+    );
+
+    // The next command waits until the sensor completes the self-test
+    block_on(f.sensor.set_sensor_altitude(2_000)).unwrap();
+    f.done();
+}
+
+#[test]
+fn run_scheduled_self_test() {
+    use libscd::calibration::SelfTestScheduler;
+
+    let mut f = Fixture::new(
+        &[
+            write(command(0x21b1)),
+            write(command(0x3f86)),
+            write(command(0x3639)),
+            read(word(0x0010)),
+            write(command(0x21b1)),
+        ],
+        &[delay(0), delay(500), delay(10_000), delay(0)],
+    );
+    let mut scheduler = SelfTestScheduler::new(SelfTestScheduler::WEEKLY);
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+
+    // Measurements are needed
+    assert_eq!(
+        None,
+        block_on(
+            f.sensor
+                .run_scheduled_self_test(&mut scheduler, 1_000, false)
+        )
+        .unwrap()
+    );
+
+    let record = block_on(
+        f.sensor
+            .run_scheduled_self_test(&mut scheduler, 2_000, true),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(2_000, record.timestamp_ms);
+    assert!(!record.passed());
+    assert_eq!(1, scheduler.failures());
+
+    // Rate limited
+    assert_eq!(
+        None,
+        block_on(
+            f.sensor
+                .run_scheduled_self_test(&mut scheduler, 3_000, true)
+        )
+        .unwrap()
+    );
+    f.done();
+}
+
+#[test]
+fn perform_self_test_strict() {
+    let mut f = Fixture::new(
+        &[
+            write(command(0x3639)),
+            read(word(0)),
+            write(command(0x3639)),
+            read(word(0x0010)),
+        ],
+        &[delay(10_000), delay(10_000)],
+    );
+    assert_eq!(Ok(()), block_on(f.sensor.perform_self_test_strict()));
+    assert_eq!(
+        Err(Error::SelfTestFailed(0x0010)),
+        block_on(f.sensor.perform_self_test_strict())
+    );
+    f.done();
+}
+
+#[test]
+fn perform_factory_reset() {
+    let mut f = Fixture::new(&[write(command(0x3632))], &[delay(1_200)]);
+    block_on(f.sensor.perform_factory_reset()).unwrap();
+    f.done();
+}
+
+#[test]
+fn reinit() {
+    let mut f = Fixture::new(&[write(command(0x3646))], &[delay(30)]);
+    block_on(f.sensor.reinit()).unwrap();
+    f.done();
+}
+
+#[test]
+fn recovers_after_repeated_crc_failures() {
+    let corrupted = vec![0x00, 0x01, 0x00];
+
+    let mut f = Fixture::new(
+        &[
+            write(command(0x2322)),
+            read(corrupted.clone()),
+            write(command(0x2322)),
+            read(corrupted),
+            write(command(0x3f86)),
+            write(command(0x3646)),
+        ],
+        &[delay(1), delay(1), delay(500), delay(30)],
+    );
+    f.sensor.set_crc_recovery_threshold(2);
+
+    assert!(matches!(
+        block_on(f.sensor.get_sensor_altitude()),
+        Err(Error::CRC(_))
+    ));
+    assert_eq!(
+        Err(Error::Recovered),
+        block_on(f.sensor.get_sensor_altitude())
+    );
+    f.done();
+}
+
+#[test]
+fn recovery_resumes_low_power_periodic_measurement() {
+    let corrupted = vec![0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00];
+
+    let mut f = Fixture::new(
+        &[
+            write(command(0x21ac)),
+            write(command(0xec05)),
+            read(corrupted),
+            write(command(0x3f86)),
+            write(command(0x3646)),
+            write(command(0x21ac)),
+        ],
+        &[delay(0), delay(1), delay(500), delay(30), delay(0)],
+    );
+    f.sensor.set_crc_recovery_threshold(1);
+    block_on(f.sensor.start_low_power_periodic_measurement()).unwrap();
+
+    assert!(matches!(
+        block_on(f.sensor.read_measurement()),
+        Err(Error::Recovered)
+    ));
+    assert_eq!(
+        Some(Duration::from_secs(30)),
+        f.sensor.expected_measurement_interval()
+    );
+    f.done();
+}
+
+#[cfg(feature = "scd41")]
+mod scd41 {
+    use super::*;
+    use libscd::asynchronous::scd4x::Scd41;
+
+    struct Fixture {
+        sensor: Scd41<I2cTransport<I2cMock, AsyncDelay>>,
+        delay: CheckedDelay,
+    }
+
+    impl Fixture {
+        fn new(i2c: &[I2cTransaction], delays: &[DelayTransaction]) -> Self {
+            let delay = CheckedDelay::new(delays);
+            Self {
+                sensor: Scd41::new(I2cMock::new(i2c), AsyncDelay::new(delay.clone())),
+                delay,
+            }
+        }
+
+        fn done(mut self) {
+            self.sensor.release().done();
+            self.delay.done();
+        }
+    }
+
+    #[test]
+    fn measure_single_shot() {
+        let mut f = Fixture::new(&[write(command(0x219d))], &[delay(5_000)]);
+        block_on(f.sensor.measure_single_shot()).unwrap();
+        f.done();
+    }
+
+    #[test]
+    fn measure_single_shot_rht_only() {
+        let mut f = Fixture::new(&[write(command(0x2196))], &[delay(50)]);
+        block_on(f.sensor.measure_single_shot_rht_only()).unwrap();
+        f.done();
+    }
+
+    #[test]
+    fn expected_measurement_interval_after_single_shot() {
+        let mut f = Fixture::new(
+            &[write(command(0x219d)), write(command(0x2196))],
+            &[delay(5_000), delay(50)],
+        );
+        block_on(f.sensor.measure_single_shot()).unwrap();
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            f.sensor.expected_measurement_interval()
+        );
+
+        block_on(f.sensor.measure_single_shot_rht_only()).unwrap();
+        assert_eq!(
+            Some(Duration::from_millis(50)),
+            f.sensor.expected_measurement_interval()
+        );
+        f.done();
+    }
+
+    #[test]
+    fn power_down_and_wake_up() {
+        let mut f = Fixture::new(
+            &[write(command(0x36e0)), write(command(0x36f6))],
+            &[delay(1), delay(30)],
+        );
+        block_on(f.sensor.power_down()).unwrap();
+        block_on(f.sensor.wake_up()).unwrap();
+        f.done();
+    }
+
+    #[test]
+    fn reset_to_known_state() {
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        let mut f = Fixture::new(
+            &[
+                write(command(0x36f6)).with_error(nack),
+                write(command(0x3f86)),
+                write(command(0x3646)),
+            ],
+            &[delay(30), delay(500), delay(30)],
+        );
+        block_on(f.sensor.reset_to_known_state()).unwrap();
+        f.done();
+    }
+
+    #[test]
+    fn automatic_self_calibration_initial_period() {
+        let mut f = Fixture::new(
+            &[
+                write(command_with_data(0x2445, 44)),
+                write(command(0x2340)),
+                read(word(44)),
+            ],
+            &[delay(1), delay(1)],
+        );
+        block_on(f.sensor.set_automatic_self_calibration_initial_period(44)).unwrap();
+        assert_eq!(
+            44,
+            block_on(f.sensor.get_automatic_self_calibration_initial_period()).unwrap()
+        );
+        f.done();
+    }
+
+    #[test]
+    fn automatic_self_calibration_standard_period() {
+        let mut f = Fixture::new(
+            &[
+                write(command_with_data(0x244e, 156)),
+                write(command(0x234b)),
+                read(word(156)),
+            ],
+            &[delay(1), delay(1)],
+        );
+        block_on(f.sensor.set_automatic_self_calibration_standard_period(156)).unwrap();
+        assert_eq!(
+            156,
+            block_on(f.sensor.get_automatic_self_calibration_standard_period()).unwrap()
+        );
+        f.done();
+    }
+}
+
+#[test]
+fn field_calibration_rejects_unstable_environment() {
+    use libscd::calibration::FieldCalibrationConfig;
+
+    let config = FieldCalibrationConfig {
+        samples: 36,
+        max_stddev_ppm: 20.0,
+    };
+
+    let mut i2c = vec![write(command(0x21b1))];
+    let mut delays = vec![delay(0)];
+    for sample in 0..config.samples {
+        let co2 = if sample % 2 == 0 { 500 } else { 600 };
+        i2c.extend([
+            write(command(0xe4b8)),
+            read(word(0x8006)),
+            write(command(0xec05)),
+            read(words(&[co2, 0x6667, 0x5eb9])),
+        ]);
+        delays.extend([delay(1), delay(1)]);
+    }
+    i2c.push(write(command(0x3f86)));
+    delays.push(delay(500));
+
+    // No forced recalibration is performed
+    let mut f = Fixture::new(&i2c, &delays);
+    let report = block_on(f.sensor.run_field_calibration(550, config)).unwrap();
+    assert_eq!(None, report.correction);
+    assert!((550.0 - report.mean_ppm).abs() < 0.01);
+    assert!((50.0 - report.stddev_ppm).abs() < 0.01);
+    f.done();
+}
+
+#[test]
+fn field_calibration_rejects_too_few_samples() {
+    use libscd::calibration::FieldCalibrationConfig;
+
+    let config = FieldCalibrationConfig {
+        samples: 35,
+        ..Default::default()
+    };
+
+    let mut f = Fixture::new(&[], &[]);
+    assert_eq!(
+        Err(Error::InvalidInput),
+        block_on(f.sensor.run_field_calibration(550, config))
+    );
+    f.done();
+}
+
+#[test]
+fn new_checked() {
+    #[cfg(feature = "scd40")]
+    let (expected, other) = (0x0000, 0x1000);
+    #[cfg(not(feature = "scd40"))]
+    let (expected, other) = (0x1000, 0x0000);
+
+    let i2c = I2cMock::new(&[
+        write(command(0x202f)),
+        read(word(expected)),
+        write(command(0x202f)),
+        read(word(other)),
+    ]);
+    let mut delay = CheckedDelay::new(&[delay(1), delay(1)]);
+
+    let sensor = block_on(Sensor::new_checked(
+        i2c.clone(),
+        AsyncDelay::new(delay.clone()),
+    ))
+    .unwrap();
+    assert!(matches!(
+        block_on(Sensor::new_checked(
+            i2c.clone(),
+            AsyncDelay::new(delay.clone())
+        )),
+        Err(Error::WrongDevice)
+    ));
+
+    sensor.release().done();
+    delay.done();
+}
+
+#[test]
+fn transport_errors_trigger_reset_detection() {
+    let mut f = Fixture::new(
+        &[
+            write(command_with_data(0xe000, 950)),
+            write(command(0x21b1)),
+            write(command(0xe4b8)).with_error(ErrorKind::Bus),
+            // The sensor accepts the serial number command: it has restarted
+            write(command(0x3682)),
+            read(words(&[0x1234, 0x5678, 0x9abc])),
+            write(command_with_data(0xe000, 950)),
+            write(command(0x21b1)),
+            write(command(0xe4b8)),
+            read(word(0)),
+        ],
+        &[delay(1), delay(0), delay(1), delay(1), delay(0), delay(1)],
+    );
+    f.sensor.set_volatile_settings_cache(true);
+    block_on(f.sensor.set_ambient_pressure(950)).unwrap();
+    block_on(f.sensor.start_periodic_measurement()).unwrap();
+
+    assert_eq!(
+        Err(Error::I2C(ErrorKind::Bus)),
+        block_on(f.sensor.data_ready())
+    );
+    assert!(!block_on(f.sensor.data_ready()).unwrap());
+    f.done();
+}
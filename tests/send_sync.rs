@@ -0,0 +1,148 @@
+mod common;
+
+use embedded_hal::i2c::ErrorKind;
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+use libscd::config::{DriverState, VolatileSettings};
+use libscd::error::Error;
+use libscd::measurement::{Measurement, TimestampedMeasurement};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn data_types_are_send_and_sync() {
+    assert_send::<Measurement>();
+    assert_sync::<Measurement>();
+    assert_send::<TimestampedMeasurement>();
+    assert_sync::<TimestampedMeasurement>();
+    assert_send::<Error<ErrorKind>>();
+    assert_sync::<Error<ErrorKind>>();
+}
+
+#[test]
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+fn driver_state_is_send_and_sync() {
+    assert_send::<DriverState>();
+    assert_sync::<DriverState>();
+    assert_send::<VolatileSettings>();
+    assert_sync::<VolatileSettings>();
+}
+
+#[cfg(feature = "sync")]
+mod sync {
+    use super::*;
+    use embedded_hal_mock::eh1::delay::CheckedDelay;
+    use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+    use libscd::synchronous::any::AnySensor;
+    use libscd::synchronous::transport::I2cTransport;
+
+    type Transport = I2cTransport<I2cMock, CheckedDelay>;
+
+    #[test]
+    fn drivers_are_send_and_sync() {
+        assert_send::<Transport>();
+        assert_sync::<Transport>();
+        assert_send::<AnySensor<Transport>>();
+        assert_sync::<AnySensor<Transport>>();
+    }
+
+    #[test]
+    #[cfg(feature = "scd30")]
+    fn scd30_is_send_and_sync() {
+        use libscd::synchronous::scd30::Scd30;
+
+        assert_send::<Scd30<Transport>>();
+        assert_sync::<Scd30<Transport>>();
+    }
+
+    #[test]
+    #[cfg(feature = "scd40")]
+    fn scd40_is_send_and_sync() {
+        use libscd::synchronous::scd4x::Scd40;
+
+        assert_send::<Scd40<Transport>>();
+        assert_sync::<Scd40<Transport>>();
+    }
+
+    #[test]
+    #[cfg(feature = "scd41")]
+    fn scd41_is_send_and_sync() {
+        use libscd::synchronous::scd4x::Scd41;
+
+        assert_send::<Scd41<Transport>>();
+        assert_sync::<Scd41<Transport>>();
+    }
+}
+
+#[cfg(feature = "async")]
+mod asynchronous {
+    use super::*;
+    use crate::common::AsyncDelay;
+    use core::future::Future;
+    use embedded_hal_mock::eh1::delay::CheckedDelay;
+    use embedded_hal_mock::eh1::i2c::Mock as I2cMock;
+    use libscd::asynchronous::any::AnySensor;
+    use libscd::asynchronous::transport::I2cTransport;
+
+    type Transport = I2cTransport<I2cMock, AsyncDelay>;
+
+    // The futures returned by the drivers must be `Send` as well, so that
+    // they can be awaited in tasks spawned on another executor
+    fn assert_send_future<F: Future + Send>(_: F) {}
+
+    #[test]
+    fn drivers_are_send_and_sync() {
+        assert_send::<Transport>();
+        assert_sync::<Transport>();
+        assert_send::<AnySensor<Transport>>();
+        assert_sync::<AnySensor<Transport>>();
+    }
+
+    #[test]
+    #[cfg(feature = "scd30")]
+    fn scd30_is_send_and_sync() {
+        use libscd::asynchronous::scd30::Scd30;
+
+        assert_send::<Scd30<Transport>>();
+        assert_sync::<Scd30<Transport>>();
+
+        let mut delay = CheckedDelay::new(&[]);
+
+        let mut sensor = Scd30::new(I2cMock::new(&[]), AsyncDelay::new(delay.clone()));
+        assert_send_future(sensor.read_measurement());
+        sensor.release().done();
+        delay.done();
+    }
+
+    #[test]
+    #[cfg(feature = "scd40")]
+    fn scd40_is_send_and_sync() {
+        use libscd::asynchronous::scd4x::Scd40;
+
+        assert_send::<Scd40<Transport>>();
+        assert_sync::<Scd40<Transport>>();
+
+        let mut delay = CheckedDelay::new(&[]);
+
+        let mut sensor = Scd40::new(I2cMock::new(&[]), AsyncDelay::new(delay.clone()));
+        assert_send_future(sensor.read_measurement());
+        sensor.release().done();
+        delay.done();
+    }
+
+    #[test]
+    #[cfg(feature = "scd41")]
+    fn scd41_is_send_and_sync() {
+        use libscd::asynchronous::scd4x::Scd41;
+
+        assert_send::<Scd41<Transport>>();
+        assert_sync::<Scd41<Transport>>();
+
+        let mut delay = CheckedDelay::new(&[]);
+
+        let mut sensor = Scd41::new(I2cMock::new(&[]), AsyncDelay::new(delay.clone()));
+        assert_send_future(sensor.measure_single_shot());
+        sensor.release().done();
+        delay.done();
+    }
+}
@@ -0,0 +1,756 @@
+#![cfg(all(feature = "simulator", feature = "sync"))]
+
+mod common;
+
+use embedded_hal::delay::DelayNs;
+use libscd::measurement::Measurement;
+use libscd::simulator::Environment;
+use libscd::synchronous::sensor::Co2Sensor;
+
+const ENVIRONMENT: Environment = Environment {
+    co2: 800,
+    temperature: 22.5,
+    humidity: 40.0,
+};
+
+// Written once against the trait and used with all sensors
+fn measure_with<S: Co2Sensor>(sensor: &mut S, delay: &mut impl DelayNs) -> Measurement
+where
+    S::Error: core::fmt::Debug,
+{
+    sensor.start_measurement().unwrap();
+    while !sensor.data_ready().unwrap() {
+        delay.delay_ms(100);
+    }
+
+    let measurement = sensor.read_measurement().unwrap();
+    sensor.stop_measurement().unwrap();
+    measurement
+}
+
+#[cfg(feature = "scd30")]
+mod scd30 {
+    use super::*;
+    use libscd::simulator::scd30::{FakeScd30, FIRMWARE_VERSION};
+    use libscd::synchronous::scd30::Scd30;
+    use libscd::variant::FirmwareVersion;
+
+    #[test]
+    fn continuous_measurement() {
+        let sim = FakeScd30::new();
+        sim.set_environment(ENVIRONMENT);
+        let mut delay = sim.delay();
+        let mut sensor = Scd30::new(sim.clone(), sim.delay());
+
+        sensor.start_continuous_measurement(1013).unwrap();
+        assert!(sim.is_measuring());
+        assert_eq!(1013, sim.ambient_pressure());
+        assert!(!sensor.data_ready().unwrap());
+
+        delay.delay_ms(2_000);
+        assert!(sensor.data_ready().unwrap());
+
+        let m = sensor.read_measurement().unwrap();
+        assert_eq!(800, m.co2);
+        assert!((22.5_f32 - m.temperature).abs() < 0.01);
+        assert!((40.0_f32 - m.humidity).abs() < 0.01);
+        assert!(!sensor.data_ready().unwrap());
+
+        sensor.stop_continuous_measurement().unwrap();
+        assert!(!sim.is_measuring());
+    }
+
+    #[test]
+    fn settings() {
+        let sim = FakeScd30::new();
+        let mut sensor = Scd30::new(sim.clone(), sim.delay());
+
+        sensor.set_measurement_interval(10).unwrap();
+        assert_eq!(10, sensor.get_measurement_interval().unwrap());
+
+        sensor.set_temperature_offset(150).unwrap();
+        assert_eq!(150, sensor.get_temperature_offset().unwrap());
+
+        sensor.enable_automatic_self_calibration(true).unwrap();
+        assert!(sensor.get_automatic_self_calibration().unwrap());
+
+        sensor.soft_reset().unwrap();
+        assert_eq!(10, sensor.get_measurement_interval().unwrap());
+        assert_eq!(FIRMWARE_VERSION, sensor.read_firmware_version().unwrap());
+
+        let version = sensor.get_firmware_version().unwrap();
+        assert_eq!(FirmwareVersion::from(FIRMWARE_VERSION), version);
+        assert!(version.is_at_least(3, 66));
+        assert!(!version.is_at_least(3, 67));
+        assert_eq!("3.66", format!("{version}"));
+    }
+
+    #[test]
+    fn generic_sensor() {
+        let sim = FakeScd30::new();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd30::new(sim.clone(), sim.delay());
+
+        let m = measure_with(&mut sensor, &mut sim.delay());
+        assert_eq!(800, m.co2);
+        assert!(!sim.is_measuring());
+    }
+
+    #[cfg(feature = "embedded-sensors")]
+    #[test]
+    fn embedded_sensors_traits() {
+        use embedded_sensors_hal::humidity::RelativeHumiditySensor;
+        use embedded_sensors_hal::sensor::{Error, ErrorKind};
+        use embedded_sensors_hal::temperature::TemperatureSensor;
+
+        let sim = FakeScd30::new();
+        sim.set_environment(ENVIRONMENT);
+        let mut delay = sim.delay();
+        let mut sensor = Scd30::new(sim.clone(), sim.delay());
+
+        sensor.start_continuous_measurement(0).unwrap();
+        delay.delay_ms(2_000);
+        let temperature = sensor.temperature().unwrap();
+        assert!((22.5_f32 - temperature).abs() < 0.01);
+
+        delay.delay_ms(2_000);
+        let humidity = sensor.relative_humidity().unwrap();
+        assert!((40.0_f32 - humidity).abs() < 0.01);
+
+        let err = Scd30::new(sim.clone(), sim.delay())
+            .set_measurement_interval(1)
+            .unwrap_err();
+        assert_eq!(ErrorKind::InvalidInput, Error::kind(&err));
+    }
+
+    #[test]
+    fn any_sensor() {
+        use libscd::synchronous::any::AnySensor;
+        use libscd::synchronous::probe::probe_bus;
+        use libscd::variant::SensorVariant;
+
+        let sim = FakeScd30::new();
+        sim.set_environment(ENVIRONMENT);
+        let report = probe_bus(&mut sim.clone(), &mut sim.delay()).unwrap();
+
+        let mut sensor = AnySensor::from_probe(&report, sim.clone(), sim.delay()).unwrap();
+        assert_eq!(SensorVariant::Scd30, sensor.variant());
+        assert_eq!(800, measure_with(&mut sensor, &mut sim.delay()).co2);
+    }
+
+    #[test]
+    fn probe_bus() {
+        use libscd::synchronous::probe::probe_bus;
+
+        let sim = FakeScd30::new();
+        let report = probe_bus(&mut sim.clone(), &mut sim.delay()).unwrap();
+        assert!(report.scd30);
+        assert!(!report.scd4x);
+    }
+
+    #[test]
+    fn temperature_offset_tuning() {
+        let sim = FakeScd30::new();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd30::new(sim.clone(), sim.delay());
+
+        sensor.set_temperature_offset(100).unwrap();
+        sensor.start_continuous_measurement(0).unwrap();
+
+        let report = sensor.tune_temperature_offset(&mut 20.0, 3).unwrap();
+        assert!((1.0_f32 - report.previous_offset).abs() < 0.01);
+        assert!((21.5_f32 - report.sensor_temperature).abs() < 0.01);
+        assert!((2.5_f32 - report.new_offset).abs() < 0.01);
+        assert_eq!(250, sensor.get_temperature_offset().unwrap());
+
+        while !sensor.data_ready().unwrap() {}
+        let m = sensor.read_measurement().unwrap();
+        assert!((20.0_f32 - m.temperature).abs() < 0.05);
+
+        assert_eq!(
+            Err(libscd::error::Error::CalibrationFailed),
+            sensor.tune_temperature_offset(&mut 40.0, 1)
+        );
+    }
+}
+
+#[cfg(any(feature = "scd40", feature = "scd41"))]
+mod scd4x {
+    use super::*;
+    use libscd::error::Error;
+    use libscd::simulator::scd4x::{FakeScd4x, Mode};
+    use libscd::variant::SensorVariant;
+
+    // A constant reference failing the transfer of the next sample
+    #[cfg(feature = "scd40")]
+    struct FailingReference(common::FlakyBus<FakeScd4x>);
+
+    #[cfg(feature = "scd40")]
+    impl libscd::calibration::ReferenceTemperature for FailingReference {
+        fn temperature(&mut self) -> f32 {
+            self.0.fail_after(0);
+            20.0
+        }
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn periodic_measurement() {
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut delay = sim.delay();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        sensor.start_periodic_measurement().unwrap();
+        assert_eq!(Mode::Periodic, sim.mode());
+        assert!(!sensor.data_ready().unwrap());
+
+        delay.delay_ms(5_000);
+        assert!(sensor.data_ready().unwrap());
+
+        let m = sensor.read_measurement().unwrap();
+        assert_eq!(800, m.co2);
+        assert!((22.5_f32 - m.temperature).abs() < 0.01);
+        assert!((40.0_f32 - m.humidity).abs() < 0.01);
+
+        // Only a subset of the commands is allowed while measuring
+        sensor.set_ambient_pressure(987).unwrap();
+        assert_eq!(987, sim.ambient_pressure());
+        assert_eq!(Err(Error::NotAllowed), sensor.serial_number());
+
+        sensor.stop_periodic_measurement().unwrap();
+        assert_eq!(Mode::Idle, sim.mode());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn generic_sensor() {
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        let m = measure_with(&mut sensor, &mut sim.delay());
+        assert_eq!(800, m.co2);
+        assert_eq!(Mode::Idle, sim.mode());
+    }
+
+    #[cfg(feature = "scd41")]
+    #[test]
+    fn any_sensor() {
+        use libscd::synchronous::any::AnySensor;
+        use libscd::synchronous::probe::probe_bus;
+        use libscd::variant::ProbeReport;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let report = probe_bus(&mut sim.clone(), &mut sim.delay()).unwrap();
+
+        let mut sensor = AnySensor::from_probe(&report, sim.clone(), sim.delay()).unwrap();
+        assert_eq!(SensorVariant::Scd41, sensor.variant());
+        assert_eq!(800, measure_with(&mut sensor, &mut sim.delay()).co2);
+
+        let empty = ProbeReport::default();
+        assert!(AnySensor::from_probe(&empty, sim.clone(), sim.delay()).is_none());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn probe_bus() {
+        use libscd::synchronous::probe::probe_bus;
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        let report = probe_bus(&mut sim.clone(), &mut sim.delay()).unwrap();
+        assert!(!report.scd30);
+        assert!(report.scd4x);
+        assert_eq!(Some(SensorVariant::Scd40), report.scd4x_variant);
+
+        // The variant cannot be read while the sensor is measuring
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+        sensor.start_periodic_measurement().unwrap();
+        let report = probe_bus(&mut sim.clone(), &mut sim.delay()).unwrap();
+        assert!(report.scd4x);
+        assert_eq!(None, report.scd4x_variant);
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn volatile_settings_are_restored_after_reset() {
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+        sensor.set_volatile_settings_cache(true);
+
+        sensor.set_sensor_altitude(500).unwrap();
+        sensor.set_ambient_pressure(950).unwrap();
+        sensor.start_periodic_measurement().unwrap();
+        assert!(!sensor.check_sensor_reset().unwrap());
+
+        // Detected by the sensor accepting commands while it should be measuring
+        sim.power_cycle();
+        assert!(sensor.check_sensor_reset().unwrap());
+        assert_eq!(Mode::Periodic, sim.mode());
+        assert_eq!(950, sim.ambient_pressure());
+
+        // Detected by the lost settings while idle
+        sensor.stop_periodic_measurement().unwrap();
+        assert!(!sensor.check_sensor_reset().unwrap());
+        sim.power_cycle();
+        assert!(sensor.check_sensor_reset().unwrap());
+        assert_eq!(500, sensor.get_sensor_altitude().unwrap());
+        assert_eq!(950, sim.ambient_pressure());
+
+        // The ambient pressure is never persisted
+        sensor.persists_settings().unwrap();
+        let volatile = sensor.volatile_settings().unwrap();
+        assert_eq!(Some(950), volatile.ambient_pressure);
+        assert_eq!(None, volatile.sensor_altitude);
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn settings_are_lost_unless_persisted() {
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        sensor.set_sensor_altitude(500).unwrap();
+        sim.power_cycle();
+        assert_eq!(0, sensor.get_sensor_altitude().unwrap());
+
+        sensor.set_sensor_altitude(500).unwrap();
+        sensor.persists_settings().unwrap();
+        sim.power_cycle();
+        assert_eq!(500, sensor.get_sensor_altitude().unwrap());
+
+        sensor.perform_factory_reset().unwrap();
+        assert_eq!(0, sensor.get_sensor_altitude().unwrap());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn altitude_from_source() {
+        use libscd::calibration::AltitudeSource;
+        use libscd::synchronous::scd4x::Scd40;
+
+        struct Gps(Option<i32>);
+
+        impl AltitudeSource for Gps {
+            fn altitude(&mut self) -> Option<i32> {
+                self.0
+            }
+        }
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        assert_eq!(
+            None,
+            sensor.apply_altitude_from(&mut Gps(None), false).unwrap()
+        );
+        for invalid in [-10, 3_001] {
+            assert_eq!(
+                Err(Error::InvalidInput),
+                sensor.apply_altitude_from(&mut Gps(Some(invalid)), false)
+            );
+        }
+
+        assert_eq!(
+            Some(750),
+            sensor
+                .apply_altitude_from(&mut Gps(Some(750)), false)
+                .unwrap()
+        );
+        assert_eq!(750, sensor.get_sensor_altitude().unwrap());
+
+        assert_eq!(
+            Some(500),
+            sensor.apply_altitude_from(&mut 500, true).unwrap()
+        );
+        sim.power_cycle();
+        assert_eq!(500, sensor.get_sensor_altitude().unwrap());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn forced_recalibration() {
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut delay = sim.delay();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        // The driver refuses the FRC before 3 minutes of operation
+        assert_eq!(
+            Err(Error::InsufficientRuntime),
+            sensor.perform_forced_recalibration(750)
+        );
+
+        // The FRC fails if the sensor has not been operated before
+        sensor.set_frc_runtime_check(false);
+        assert_eq!(None, sensor.perform_forced_recalibration(750).unwrap());
+        sensor.set_frc_runtime_check(true);
+
+        sensor.start_periodic_measurement().unwrap();
+        for _ in 0..35 {
+            delay.delay_ms(5_000);
+            sensor.read_measurement().unwrap();
+        }
+        sensor.stop_periodic_measurement().unwrap();
+        assert_eq!(
+            Err(Error::InsufficientRuntime),
+            sensor.perform_forced_recalibration(750)
+        );
+
+        sensor.start_periodic_measurement().unwrap();
+        for _ in 0..36 {
+            delay.delay_ms(5_000);
+            sensor.read_measurement().unwrap();
+        }
+        sensor.stop_periodic_measurement().unwrap();
+        assert_eq!(Some(-50), sensor.perform_forced_recalibration(750).unwrap());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn forced_recalibration_procedure() {
+        use libscd::calibration::{CalibrationRecord, FrcReport};
+        use libscd::synchronous::scd4x::Scd40;
+        use std::time::Duration;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        let report = sensor.run_frc_procedure(750).unwrap();
+        assert_eq!(
+            FrcReport {
+                reference_ppm: 750,
+                correction: -50,
+                co2_before: 800,
+                co2_after: 750,
+            },
+            report
+        );
+
+        assert!(sim.clock().now() >= Duration::from_secs(180));
+        assert_eq!(Mode::Idle, sim.mode());
+
+        let record = sensor.calibration_record(&report, 1_700_000_000).unwrap();
+        assert_eq!(
+            CalibrationRecord {
+                reference_ppm: 750,
+                correction: -50,
+                asc_enabled: true,
+                timestamp: 1_700_000_000,
+                serial_number: sensor.serial_number().unwrap(),
+            },
+            record
+        );
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn forced_recalibration_procedure_in_low_power_mode() {
+        use libscd::synchronous::scd4x::Scd40;
+        use std::time::Duration;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+        sensor.start_low_power_periodic_measurement().unwrap();
+
+        let report = sensor.run_frc_procedure(750).unwrap();
+        assert_eq!(-50, report.correction);
+        assert_eq!(Mode::LowPowerPeriodic, sim.mode());
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            sensor.expected_measurement_interval()
+        );
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn forced_recalibration_with_policy() {
+        use libscd::calibration::CalibrationPolicy;
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut delay = sim.delay();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        sensor.start_periodic_measurement().unwrap();
+        for _ in 0..36 {
+            delay.delay_ms(5_000);
+            sensor.read_measurement().unwrap();
+        }
+        sensor.stop_periodic_measurement().unwrap();
+
+        // The ASC is enabled by default
+        let keep_asc = CalibrationPolicy {
+            disable_asc: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(Error::NotAllowed),
+            sensor.perform_forced_recalibration_with_policy(750, keep_asc)
+        );
+
+        let policy = CalibrationPolicy::default();
+        assert_eq!(
+            Some(-50),
+            sensor
+                .perform_forced_recalibration_with_policy(750, policy)
+                .unwrap()
+        );
+        assert!(sensor.get_automatic_self_calibration().unwrap());
+
+        let policy = CalibrationPolicy {
+            restore_asc: false,
+            persist: true,
+            ..Default::default()
+        };
+        sensor
+            .perform_forced_recalibration_with_policy(750, policy)
+            .unwrap();
+        sim.power_cycle();
+        assert!(!sensor.get_automatic_self_calibration().unwrap());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn field_calibration() {
+        use libscd::calibration::FieldCalibrationConfig;
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        let report = sensor
+            .run_field_calibration(750, FieldCalibrationConfig::default())
+            .unwrap();
+        assert_eq!(Some(-50), report.correction);
+        assert!((800.0_f32 - report.mean_ppm).abs() < 0.01);
+        assert!(report.stddev_ppm < 0.01);
+        assert_eq!(Mode::Idle, sim.mode());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn field_calibration_failure_restores_the_mode() {
+        use libscd::calibration::FieldCalibrationConfig;
+        use libscd::synchronous::scd4x::Scd40;
+        use std::time::Duration;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let bus = common::FlakyBus::new(sim.clone());
+        let mut sensor = Scd40::new(bus.clone(), sim.delay());
+
+        // Fails while the samples are collected
+        bus.fail_after(100);
+        assert!(matches!(
+            sensor.run_field_calibration(750, FieldCalibrationConfig::default()),
+            Err(Error::I2C(_))
+        ));
+        assert_eq!(Mode::Idle, sim.mode());
+        assert_eq!(None, sensor.expected_measurement_interval());
+
+        sensor.start_low_power_periodic_measurement().unwrap();
+        bus.fail_after(100);
+        assert!(matches!(
+            sensor.run_field_calibration(750, FieldCalibrationConfig::default()),
+            Err(Error::I2C(_))
+        ));
+        assert_eq!(Mode::LowPowerPeriodic, sim.mode());
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            sensor.expected_measurement_interval()
+        );
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn asc_schedule() {
+        use libscd::calibration::{AscScheduler, AscWindow, TimeOfWeek, TimeSource};
+        use libscd::synchronous::scd4x::Scd40;
+
+        struct Clock(TimeOfWeek);
+
+        impl TimeSource for Clock {
+            fn time_of_week(&mut self) -> TimeOfWeek {
+                self.0
+            }
+        }
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        let windows = [AscWindow {
+            days: AscWindow::WEEKEND,
+            start_minutes: 0,
+            end_minutes: 24 * 60,
+        }];
+        let mut scheduler = AscScheduler::new(&windows);
+        let mut clock = Clock(TimeOfWeek {
+            weekday: 2,
+            minutes: 12 * 60,
+        });
+
+        sensor.start_periodic_measurement().unwrap();
+        assert!(!sensor
+            .update_asc_schedule(&mut scheduler, &mut clock)
+            .unwrap());
+        assert!(!sensor
+            .update_asc_schedule(&mut scheduler, &mut clock)
+            .unwrap());
+        assert_eq!(Some(false), scheduler.applied());
+        assert_eq!(Mode::Periodic, sim.mode());
+
+        sensor.stop_periodic_measurement().unwrap();
+        assert!(!sensor.get_automatic_self_calibration().unwrap());
+
+        clock.0.weekday = 6;
+        assert!(sensor
+            .update_asc_schedule(&mut scheduler, &mut clock)
+            .unwrap());
+        assert!(sensor.get_automatic_self_calibration().unwrap());
+        assert_eq!(Mode::Idle, sim.mode());
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn temperature_offset_tuning() {
+        use libscd::synchronous::scd4x::Scd40;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut delay = sim.delay();
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+
+        assert_eq!(
+            Err(Error::InvalidInput),
+            sensor.tune_temperature_offset(&mut 20.0, 0, false)
+        );
+
+        let previous = sensor.get_temperature_offset().unwrap();
+        let report = sensor.tune_temperature_offset(&mut 20.0, 3, false).unwrap();
+        assert!((22.5_f32 - report.sensor_temperature).abs() < 0.01);
+        assert!((20.0_f32 - report.reference_temperature).abs() < 0.01);
+        assert!((previous + 2.5 - report.new_offset).abs() < 0.01);
+        assert_eq!(Mode::Idle, sim.mode());
+
+        sensor.start_periodic_measurement().unwrap();
+        delay.delay_ms(5_000);
+        let m = sensor.read_measurement().unwrap();
+        assert!((20.0_f32 - m.temperature).abs() < 0.05);
+        sensor.stop_periodic_measurement().unwrap();
+
+        // A reference above the sensor temperature cannot be compensated
+        assert_eq!(
+            Err(Error::CalibrationFailed),
+            sensor.tune_temperature_offset(&mut 40.0, 1, false)
+        );
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn temperature_offset_tuning_in_low_power_mode() {
+        use libscd::synchronous::scd4x::Scd40;
+        use std::time::Duration;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd40::new(sim.clone(), sim.delay());
+        sensor.start_low_power_periodic_measurement().unwrap();
+
+        let report = sensor.tune_temperature_offset(&mut 20.0, 3, false).unwrap();
+        assert!((22.5_f32 - report.sensor_temperature).abs() < 0.01);
+        assert_eq!(Mode::LowPowerPeriodic, sim.mode());
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            sensor.expected_measurement_interval()
+        );
+    }
+
+    #[cfg(feature = "scd40")]
+    #[test]
+    fn temperature_offset_tuning_failure_restores_the_mode() {
+        use libscd::synchronous::scd4x::Scd40;
+        use std::time::Duration;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let bus = common::FlakyBus::new(sim.clone());
+        let mut reference = FailingReference(bus.clone());
+        let mut sensor = Scd40::new(bus, sim.delay());
+
+        sensor.start_low_power_periodic_measurement().unwrap();
+        assert!(matches!(
+            sensor.tune_temperature_offset(&mut reference, 3, false),
+            Err(Error::I2C(_))
+        ));
+        assert_eq!(Mode::LowPowerPeriodic, sim.mode());
+        assert_eq!(
+            Some(Duration::from_secs(30)),
+            sensor.expected_measurement_interval()
+        );
+
+        sensor.stop_periodic_measurement().unwrap();
+        assert!(matches!(
+            sensor.tune_temperature_offset(&mut reference, 3, false),
+            Err(Error::I2C(_))
+        ));
+        assert_eq!(Mode::Idle, sim.mode());
+    }
+
+    #[cfg(feature = "scd41")]
+    #[test]
+    fn single_shot_measurement() {
+        use libscd::synchronous::scd4x::Scd41;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+        sim.set_environment(ENVIRONMENT);
+        let mut sensor = Scd41::new(sim.clone(), sim.delay());
+
+        assert_eq!(SensorVariant::Scd41, sensor.get_sensor_variant().unwrap());
+
+        sensor.measure_single_shot().unwrap();
+        assert!(sensor.data_ready().unwrap());
+        assert_eq!(800, sensor.read_measurement().unwrap().co2);
+
+        sensor.measure_single_shot_rht_only().unwrap();
+        let m = sensor.read_measurement().unwrap();
+        assert_eq!(0, m.co2);
+        assert!((22.5_f32 - m.temperature).abs() < 0.01);
+    }
+
+    #[cfg(feature = "scd41")]
+    #[test]
+    fn sleep_and_wake_up() {
+        use libscd::synchronous::scd4x::Scd41;
+
+        let sim = FakeScd4x::new(SensorVariant::Scd41).unwrap();
+        let mut delay = sim.delay();
+        let mut sensor = Scd41::new(sim.clone(), sim.delay());
+
+        sensor.power_down().unwrap();
+        assert_eq!(Mode::Sleep, sim.mode());
+        assert!(sensor.serial_number().is_err());
+
+        // The sensor does not acknowledge the wake-up command
+        assert!(sensor.wake_up().is_err());
+        delay.delay_ms(30);
+        assert_eq!(Mode::Idle, sim.mode());
+        assert!(sensor.serial_number().is_ok());
+    }
+}
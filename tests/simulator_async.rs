@@ -0,0 +1,119 @@
+#![cfg(all(feature = "simulator", feature = "async"))]
+
+mod common;
+
+use libscd::simulator::Environment;
+
+const ENVIRONMENT: Environment = Environment {
+    co2: 800,
+    temperature: 22.5,
+    humidity: 40.0,
+};
+
+#[cfg(feature = "scd40")]
+mod scd4x {
+    use super::*;
+    use common::{block_on, FlakyBus};
+    use core::time::Duration;
+    use libscd::asynchronous::scd4x::Scd40;
+    use libscd::calibration::{FieldCalibrationConfig, ReferenceTemperature};
+    use libscd::error::Error;
+    use libscd::simulator::scd4x::{FakeScd4x, Mode};
+    use libscd::variant::SensorVariant;
+
+    // A constant reference failing the transfer of the next sample
+    struct FailingReference(FlakyBus<FakeScd4x>);
+
+    impl ReferenceTemperature for FailingReference {
+        fn temperature(&mut self) -> f32 {
+            self.0.fail_after(0);
+            20.0
+        }
+    }
+
+    #[test]
+    fn temperature_offset_tuning_in_low_power_mode() {
+        block_on(async {
+            let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+            sim.set_environment(ENVIRONMENT);
+            let mut sensor = Scd40::new(sim.clone(), sim.delay());
+            sensor.start_low_power_periodic_measurement().await.unwrap();
+
+            let report = sensor
+                .tune_temperature_offset(&mut 20.0, 3, false)
+                .await
+                .unwrap();
+            assert!((22.5_f32 - report.sensor_temperature).abs() < 0.01);
+            assert_eq!(Mode::LowPowerPeriodic, sim.mode());
+            assert_eq!(
+                Some(Duration::from_secs(30)),
+                sensor.expected_measurement_interval()
+            );
+        });
+    }
+
+    #[test]
+    fn temperature_offset_tuning_failure_restores_the_mode() {
+        block_on(async {
+            let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+            sim.set_environment(ENVIRONMENT);
+            let bus = FlakyBus::new(sim.clone());
+            let mut reference = FailingReference(bus.clone());
+            let mut sensor = Scd40::new(bus, sim.delay());
+
+            sensor.start_low_power_periodic_measurement().await.unwrap();
+            assert!(matches!(
+                sensor
+                    .tune_temperature_offset(&mut reference, 3, false)
+                    .await,
+                Err(Error::I2C(_))
+            ));
+            assert_eq!(Mode::LowPowerPeriodic, sim.mode());
+            assert_eq!(
+                Some(Duration::from_secs(30)),
+                sensor.expected_measurement_interval()
+            );
+
+            sensor.stop_periodic_measurement().await.unwrap();
+            assert!(matches!(
+                sensor
+                    .tune_temperature_offset(&mut reference, 3, false)
+                    .await,
+                Err(Error::I2C(_))
+            ));
+            assert_eq!(Mode::Idle, sim.mode());
+        });
+    }
+
+    #[test]
+    fn field_calibration_failure_restores_the_mode() {
+        block_on(async {
+            let sim = FakeScd4x::new(SensorVariant::Scd40).unwrap();
+            sim.set_environment(ENVIRONMENT);
+            let bus = FlakyBus::new(sim.clone());
+            let mut sensor = Scd40::new(bus.clone(), sim.delay());
+            let config = FieldCalibrationConfig::default();
+
+            // Fails while the samples are collected
+            bus.fail_after(100);
+            assert!(matches!(
+                sensor.run_field_calibration(750, config).await,
+                Err(Error::I2C(_))
+            ));
+            assert_eq!(Mode::Idle, sim.mode());
+            assert_eq!(None, sensor.expected_measurement_interval());
+
+            sensor.start_low_power_periodic_measurement().await.unwrap();
+            bus.fail_after(100);
+            assert!(matches!(
+                sensor.run_field_calibration(750, config).await,
+                Err(Error::I2C(_))
+            ));
+            assert_eq!(Mode::LowPowerPeriodic, sim.mode());
+            assert_eq!(
+                Some(Duration::from_secs(30)),
+                sensor.expected_measurement_interval()
+            );
+        });
+    }
+}
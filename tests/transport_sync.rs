@@ -0,0 +1,132 @@
+#![cfg(all(feature = "sync", feature = "scd40"))]
+
+mod common;
+
+use common::{command, command_with_data, word};
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+use libscd::error::Error;
+use libscd::synchronous::scd4x::{Scd40, I2C_ADDRESS};
+use libscd::synchronous::transport::{I2cTransport, Transport};
+use std::collections::VecDeque;
+
+/// A transport recording the written frames and replaying canned responses
+#[derive(Default)]
+struct RecordingTransport {
+    written: Vec<Vec<u8>>,
+    responses: VecDeque<Vec<u8>>,
+    delay_ms: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct NoResponse;
+
+impl Transport for RecordingTransport {
+    type Error = NoResponse;
+
+    fn write_frame(&mut self, frame: &[u8]) -> Result<(), Self::Error> {
+        self.written.push(frame.to_vec());
+        Ok(())
+    }
+
+    fn read_frame(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let response = self.responses.pop_front().ok_or(NoResponse)?;
+        buf.copy_from_slice(&response);
+        Ok(())
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.delay_ms += ms;
+    }
+}
+
+#[test]
+fn drives_the_sensor_over_a_custom_transport() {
+    let transport = RecordingTransport {
+        responses: VecDeque::from([word(650)]),
+        ..Default::default()
+    };
+
+    let mut sensor = Scd40::with_transport(transport);
+    sensor.set_sensor_altitude(650).unwrap();
+    assert_eq!(650, sensor.get_sensor_altitude().unwrap());
+
+    let transport = sensor.release_transport();
+    assert_eq!(
+        vec![command_with_data(0x2427, 650), command(0x2322)],
+        transport.written
+    );
+    assert_eq!(2, transport.delay_ms);
+}
+
+#[test]
+fn reports_transport_errors() {
+    let mut sensor = Scd40::with_transport(RecordingTransport::default());
+    assert_eq!(Err(Error::I2C(NoResponse)), sensor.get_sensor_altitude());
+}
+
+#[test]
+fn verifies_the_crc_of_the_responses() {
+    let transport = RecordingTransport {
+        responses: VecDeque::from([vec![0x02, 0x8A, 0x00]]),
+        ..Default::default()
+    };
+
+    let mut sensor = Scd40::with_transport(transport);
+    assert!(matches!(
+        sensor.get_sensor_altitude(),
+        Err(Error::CRC(crc)) if crc.word == 0 && crc.received == 0x00
+    ));
+}
+
+#[test]
+fn recovers_the_bus_before_retrying() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let frame = command_with_data(0x2427, 650);
+    let i2c = I2cMock::new(&[
+        I2cTransaction::write(I2C_ADDRESS, frame.clone()).with_error(ErrorKind::Bus),
+        I2cTransaction::write(I2C_ADDRESS, frame.clone()),
+        I2cTransaction::write(I2C_ADDRESS, frame).with_error(nack),
+    ]);
+    let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(1)]);
+
+    let mut recoveries = Vec::new();
+    let transport = I2cTransport::new(i2c, delay.clone(), I2C_ADDRESS).with_bus_recovery(|kind| {
+        recoveries.push(kind);
+        true
+    });
+
+    let mut sensor = Scd40::with_transport(transport);
+    sensor.set_sensor_altitude(650).unwrap();
+
+    // A missing acknowledgement does not trigger the recovery
+    assert_eq!(Err(Error::I2C(nack)), sensor.set_sensor_altitude(650));
+
+    sensor.release_transport().release().0.done();
+    delay.done();
+    assert_eq!(vec![ErrorKind::Bus], recoveries);
+}
+
+#[test]
+fn feeds_the_watchdog_during_long_delays() {
+    use libscd::synchronous::transport::ChunkedDelay;
+
+    let i2c = I2cMock::new(&[
+        I2cTransaction::write(I2C_ADDRESS, command(0x3639)),
+        I2cTransaction::read(I2C_ADDRESS, word(0)),
+    ]);
+    let expected = (0..4)
+        .map(|_| DelayTransaction::delay_ms(2_500))
+        .collect::<Vec<_>>();
+    let mut delay = CheckedDelay::new(&expected);
+
+    let mut feeds = 0;
+    let chunked = ChunkedDelay::new(delay.clone(), 2_500, || feeds += 1);
+    let mut sensor = Scd40::new(i2c, chunked);
+    assert!(sensor.perform_self_test().unwrap());
+
+    sensor.release().done();
+    delay.done();
+    assert_eq!(4, feeds);
+}